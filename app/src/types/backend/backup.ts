@@ -0,0 +1,25 @@
+export type BackupCommand = "create_backup" | "restore_backup";
+
+export interface BackupFile {
+	path: string;
+	size: number;
+	/** BLAKE3 hash of the contents, in hex. */
+	hash: string;
+}
+
+export interface BackupManifest {
+	version: number;
+	schema_version: number;
+	created_at: string;
+	files: BackupFile[];
+}
+
+export interface CreateBackupParameters {
+	[key: string]: unknown;
+	path: string;
+}
+
+export interface RestoreBackupParameters {
+	[key: string]: unknown;
+	path: string;
+}
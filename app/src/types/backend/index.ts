@@ -1,10 +1,45 @@
+import type { BackupCommand } from "@/types/backend/backup";
+import type { LabelCommand } from "@/types/backend/label";
 import type { LibraryCommand, LibraryEventType } from "@/types/backend/library";
+import type { LyricsCommand } from "@/types/backend/lyrics";
+import type { PersonCommand } from "@/types/backend/person";
+import type { PlaybackCommand, PlaybackEventType } from "@/types/backend/playback";
+import type { PlaylistCommand } from "@/types/backend/playlist";
+import type { PluginCommand } from "@/types/backend/plugin";
+import type { PortableCommand } from "@/types/backend/portable";
 import type { ReleaseCommand } from "@/types/backend/release";
+import type { ScrobbleCommand, ScrobbleEventType } from "@/types/backend/scrobble";
+import type { SettingsCommand, SettingsEventType } from "@/types/backend/settings";
+import type { TagCommand } from "@/types/backend/tag";
+import type { ThemeCommand, ThemeEventType } from "@/types/backend/theme";
 import type { TrackCommand } from "@/types/backend/track";
+import type { WorkCommand } from "@/types/backend/work";
 
 export type GeneralCommand = "setup";
-export type BackendCommands = GeneralCommand | LibraryCommand | ReleaseCommand | TrackCommand;
-export type BackendEvents = LibraryEventType;
+export type BackendCommands =
+	| GeneralCommand
+	| BackupCommand
+	| LabelCommand
+	| LibraryCommand
+	| LyricsCommand
+	| PersonCommand
+	| PlaybackCommand
+	| PlaylistCommand
+	| PluginCommand
+	| PortableCommand
+	| ReleaseCommand
+	| ScrobbleCommand
+	| SettingsCommand
+	| TagCommand
+	| ThemeCommand
+	| TrackCommand
+	| WorkCommand;
+export type BackendEvents =
+	| LibraryEventType
+	| PlaybackEventType
+	| ScrobbleEventType
+	| SettingsEventType
+	| ThemeEventType;
 
 export interface BackendBaseError {
 	short: string;
@@ -25,3 +60,19 @@ export interface BackendEntity<T> {
 	id: number;
 	attributes: T;
 }
+
+export interface BackendCursor {
+	value: string;
+	id: number;
+}
+
+export interface BackendPageRequest {
+	cursor: BackendCursor | null;
+	direction?: "ascending" | "descending";
+	limit: number;
+}
+
+export interface BackendPage<T> {
+	items: BackendEntity<T>[];
+	next_cursor: BackendCursor | null;
+}
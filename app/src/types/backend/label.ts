@@ -0,0 +1,44 @@
+import type { DisplayCover } from "@/types/backend/cover";
+import type { CountryCode } from "@/types/backend/generic";
+import type { Person } from "@/types/backend/person";
+import type { Release } from "@/types/backend/release";
+
+export type LabelCommand = "get_labels" | "get_label" | "set_label_metadata";
+
+export interface Label {
+	name: string;
+	name_sort: null | string;
+	country: null | CountryCode;
+	mbz_id: null | string;
+
+	date_added: string;
+	date_modified: string;
+}
+
+export type LabelEntry = Label & {
+	id: number;
+	release_count: number;
+};
+
+export interface DisplayLabel {
+	label: Label;
+
+	release_ids: number[];
+
+	releases: Record<number, Release>;
+	artists: Record<number, Person>;
+	covers: Record<number, DisplayCover>;
+}
+
+export interface GetLabelParameters {
+	[key: string]: unknown;
+	labelId: number;
+}
+
+export interface SetLabelMetadataParameters {
+	[key: string]: unknown;
+	labelId: number;
+	nameSort: null | string;
+	country: null | CountryCode;
+	mbzId: null | string;
+}
@@ -1,6 +1,14 @@
 import type { BackendEventPayload, BackendPathedError } from "@/types/backend";
 
-export type LibraryCommand = "get_scan_locations" | "initialize_library";
+export type LibraryCommand =
+	| "get_libraries"
+	| "get_active_library"
+	| "set_active_library"
+	| "create_library"
+	| "update_library"
+	| "delete_library"
+	| "get_scan_locations"
+	| "initialize_library";
 export type LibraryEventType = "scan";
 
 export type LibraryEventPayload = BackendEventPayload<LibraryEvent, BackendPathedError>;
@@ -16,7 +24,45 @@ export interface LibraryEventData {
 	path: string;
 }
 
+export interface Library {
+	name: string;
+	scan_locations: ScanLocation[];
+
+	date_added: string;
+	date_modified: string;
+}
+
+export interface ScanLocation {
+	path: string;
+	options: ScanOptions;
+}
+
+export interface ScanOptions {
+	include: string[];
+	exclude: string[];
+	ignore_hidden: boolean;
+	ignore_files: boolean;
+	max_depth: null | number;
+	follow_symlinks: boolean;
+	min_file_size: null | number;
+}
+
+export interface LibraryEntry extends Library {
+	id: number;
+	active: boolean;
+}
+
 export interface LibraryCreateParameters {
 	[key: string]: unknown;
-	scanLocations: string[];
+	/** Plain paths use the default scan options. */
+	scanLocations: (string | ScanLocation)[];
+	/** Defaults to the active library. */
+	libraryId?: number;
+}
+
+export interface LibraryUpsertParameters {
+	[key: string]: unknown;
+	libraryId?: number;
+	name: string;
+	scanLocations: (string | ScanLocation)[];
 }
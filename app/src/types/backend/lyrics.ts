@@ -0,0 +1,36 @@
+export type LyricsCommand = "get_lyrics" | "set_lyrics";
+
+export interface Lyrics {
+	track_id: number;
+	language: null | string;
+	lines: LyricsLine[];
+	/** Set when the lyrics were edited but couldn't be written into a `.lrc` file. */
+	edited: boolean;
+
+	date_added: string;
+	date_modified: string;
+}
+
+export interface LyricsLine {
+	/** Start of the line in milliseconds, null for plain lyrics. */
+	start: null | number;
+	text: string;
+	words: null | LyricsWord[];
+}
+
+export interface LyricsWord {
+	start: number;
+	text: string;
+}
+
+export interface GetLyricsParameters {
+	[key: string]: unknown;
+	trackId: number;
+}
+
+export interface SetLyricsParameters {
+	[key: string]: unknown;
+	trackId: number;
+	/** LRC or plain text, where a blank string removes the lyrics. */
+	contents: string;
+}
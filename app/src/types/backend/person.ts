@@ -1,8 +1,76 @@
-export type PersonType = "artist" | "composer" | "producer" | "unknown";
+import type { DisplayCover } from "@/types/backend/cover";
+import type { Release } from "@/types/backend/release";
+import type { Track } from "@/types/backend/track";
+
+export type PersonCommand = "get_artist" | "set_artist_image" | "merge_people" | "split_person";
+
+export type PersonType =
+	| "artist"
+	| "composer"
+	| "producer"
+	| "lyricist"
+	| "arranger"
+	| "conductor"
+	| "performer"
+	| "remixer"
+	| "mixer"
+	| "engineer"
+	| "dj_mixer"
+	| "other"
+	| "unknown";
 
 export interface Person {
 	type: PersonType;
 	name: string;
 	name_sort: null | string;
 	mbz_id: null | string;
+	cover_ids: null | number[];
+	has_manual_cover: boolean;
+
+	date_added: string;
+	date_modified: string;
+}
+
+export interface DisplayArtist {
+	person: Person;
+
+	release_ids: number[];
+	track_ids: number[];
+	credited_track_ids: Partial<Record<PersonType, number[]>>;
+
+	releases: Record<number, Release>;
+	tracks: Record<number, Track>;
+	artists: Record<number, Person>;
+	covers: Record<number, DisplayCover>;
+}
+
+export interface GetArtistParameters {
+	[key: string]: unknown;
+	personId: number;
+}
+
+export interface SetArtistImageParameters {
+	[key: string]: unknown;
+	personId: number;
+	path: string;
+}
+
+export interface MergePeopleParameters {
+	[key: string]: unknown;
+	sourceId: number;
+	targetId: number;
+}
+
+export interface PersonSplit {
+	name: string;
+	name_sort?: string;
+	mbz_id?: string;
+}
+
+export interface SplitPersonParameters {
+	[key: string]: unknown;
+	personId: number;
+	person: PersonSplit;
+	trackIds: number[];
+	releaseIds: number[];
 }
@@ -0,0 +1,59 @@
+import type { BackendBaseError, BackendEventPayload } from "@/types/backend";
+
+export type PlaybackCommand = "get_playback" | "set_playback";
+export type PlaybackEventType = "player_control";
+
+export type PlaybackStatus = "playing" | "paused" | "stopped";
+
+/** Reported to the backend whenever the state of the player changes. */
+export interface PlaybackReport {
+	track_id: null | number;
+	status: PlaybackStatus;
+	/** Position in milliseconds. */
+	position: number;
+	volume: number;
+	can_go_next: boolean;
+	can_go_previous: boolean;
+	/** Whether the position jumped since the last report. */
+	seeked?: boolean;
+	/** Length of the track in milliseconds, used to decide when it counts as a listen. */
+	duration?: null | number;
+}
+
+export interface PlaybackTrack {
+	id: number;
+	title: string;
+	artists: string[];
+	album: null | string;
+	album_artists: string[];
+	track_number: null | number;
+	disc_number: null | number;
+	length: null | number;
+	cover_path: null | string;
+	path: string;
+	mbz_id: null | string;
+	release_mbz_id: null | string;
+}
+
+export interface Playback {
+	status: PlaybackStatus;
+	track: null | PlaybackTrack;
+	position: number;
+	volume: number;
+	can_go_next: boolean;
+	can_go_previous: boolean;
+}
+
+/** Requests made through the system media controls, which the player must carry out. */
+export type PlayerControl =
+	| { type: "play" | "pause" | "play_pause" | "stop" | "next" | "previous" }
+	| { type: "seek"; data: { offset: number } }
+	| { type: "set_position"; data: { track_id: number; position: number } }
+	| { type: "set_volume"; data: { volume: number } };
+
+export type PlayerEventPayload = BackendEventPayload<PlayerControl, BackendBaseError>;
+
+export interface SetPlaybackParameters {
+	[key: string]: unknown;
+	report: PlaybackReport;
+}
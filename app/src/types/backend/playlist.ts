@@ -0,0 +1,57 @@
+export type PlaylistCommand =
+	| "get_playlists"
+	| "get_playlist_tracks"
+	| "import_playlist"
+	| "export_playlist"
+	| "export_tracks";
+
+export interface Playlist {
+	name: string;
+	description: null | string;
+	track_ids: number[];
+
+	date_added: string;
+	date_modified: string;
+}
+
+export interface PlaylistEntry extends Playlist {
+	id: number;
+}
+
+export interface UnresolvedItem {
+	/** Position of the entry in the file, starting from 1. */
+	position: number;
+	location: string;
+	title: null | string;
+	artist: null | string;
+}
+
+export interface PlaylistImport {
+	id: number;
+	playlist: Playlist;
+	unresolved: UnresolvedItem[];
+}
+
+export interface GetPlaylistTracksParameters {
+	[key: string]: unknown;
+	playlistId: number;
+}
+
+export interface ImportPlaylistParameters {
+	[key: string]: unknown;
+	/** Path of an `.m3u`, `.m3u8`, `.pls` or `.xspf` file. */
+	path: string;
+}
+
+export interface ExportPlaylistParameters {
+	[key: string]: unknown;
+	playlistId: number;
+	/** The format is picked from the extension of the path. */
+	path: string;
+}
+
+export interface ExportTracksParameters {
+	[key: string]: unknown;
+	trackIds: number[];
+	path: string;
+}
@@ -0,0 +1,31 @@
+import type { BackendBaseError } from "@/types/backend";
+
+export type PluginCommand = "get_plugins" | "invoke_plugin_command";
+
+export type PluginCapability = "library_read" | "library_events" | "playback_events" | "metadata_provider" | "commands";
+
+export interface PluginManifest {
+	id: string;
+	name: string;
+	version: string;
+	capabilities: PluginCapability[];
+	commands: string[];
+}
+
+export interface PluginStatus {
+	type: "active" | "failed";
+	data?: BackendBaseError;
+}
+
+export interface PluginEntry {
+	id: string;
+	manifest: null | PluginManifest;
+	status: PluginStatus;
+}
+
+export interface InvokePluginCommandParameters {
+	[key: string]: unknown;
+	pluginId: string;
+	command: string;
+	payload: unknown;
+}
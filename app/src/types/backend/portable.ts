@@ -0,0 +1,32 @@
+export type PortableCommand = "export_library_data" | "import_library_data";
+
+export interface PathMapping {
+	from: string;
+	to: string;
+}
+
+export interface PortableImport {
+	tracks: number;
+	releases: number;
+	people: number;
+	labels: number;
+	tags: number;
+	/** Ratings set on the matched tracks that had none. */
+	ratings: number;
+	/** Plays added to the history, skipping the ones already in it. */
+	plays: number;
+	playlist_ids: number[];
+	/** Paths of the tracks that couldn't be matched, as written in the export. */
+	unmatched_paths: string[];
+}
+
+export interface ExportLibraryDataParameters {
+	[key: string]: unknown;
+	path: string;
+}
+
+export interface ImportLibraryDataParameters {
+	[key: string]: unknown;
+	path: string;
+	pathMappings: PathMapping[];
+}
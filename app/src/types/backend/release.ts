@@ -1,9 +1,9 @@
-import type { BackendEntity } from "@/types/backend";
+import type { BackendEntity, BackendPage, BackendPageRequest } from "@/types/backend";
 import type { DisplayCover } from "@/types/backend/cover";
 import type { CountryCode, InlinedArtist, ScriptCode } from "@/types/backend/generic";
 import type { Person } from "@/types/backend/person";
 
-export type ReleaseCommand = "get_releases" | "get_display_releases";
+export type ReleaseCommand = "get_releases" | "get_display_releases" | "query_releases";
 
 export type ReleaseEntity = BackendEntity<Release>;
 
@@ -19,6 +19,7 @@ export interface Release {
 	country: null | CountryCode;
 	script: null | ScriptCode;
 	total_tracks: null | number;
+	total_discs: null | number;
 	catalog_number: null | string;
 
 	artists: InlinedArtist[];
@@ -33,6 +34,10 @@ export interface Release {
 	type_secondary: null | ReleaseTypeSecondary;
 
 	mbz_id: null | string;
+	directory: null | string;
+
+	date_added: string;
+	date_modified: string;
 }
 
 export interface DisplayReleases {
@@ -45,3 +50,29 @@ export interface ReleasesGetParameters {
 	[key: string]: unknown;
 	libraryId: number;
 }
+
+export type DisplayReleasePage = BackendPage<Release> & {
+	artists: Record<number, Person>;
+	covers: Record<number, DisplayCover>;
+};
+
+export type ReleaseSort = "name" | "artist" | "date" | "added" | "play_count";
+
+export interface ReleaseFilter {
+	/** Defaults to the active library. */
+	library_id?: number;
+	type?: ReleaseType;
+	type_secondary?: ReleaseTypeSecondary;
+	genre_id?: number;
+	label_id?: number;
+	year_from?: number;
+	year_to?: number;
+	country?: CountryCode;
+}
+
+export interface ReleasesQueryParameters {
+	[key: string]: unknown;
+	page: BackendPageRequest;
+	sort: ReleaseSort;
+	filter?: ReleaseFilter;
+}
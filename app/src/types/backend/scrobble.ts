@@ -0,0 +1,20 @@
+import type { BackendBaseError, BackendEventPayload } from "@/types/backend";
+
+export type ScrobbleCommand = "authenticate_lastfm";
+export type ScrobbleEventType = "scrobble_rejected";
+
+export type ScrobbleEventPayload = BackendEventPayload<null, ScrobbleRejection>;
+
+export type ScrobbleService = "listen_brainz" | "last_fm";
+
+/** A listen refused by a service, like when its credentials are no longer valid. */
+export interface ScrobbleRejection {
+	service: ScrobbleService;
+	error: BackendBaseError;
+}
+
+export interface AuthenticateLastFmParameters {
+	[key: string]: unknown;
+	username: string;
+	password: string;
+}
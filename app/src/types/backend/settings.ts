@@ -0,0 +1,56 @@
+import type { BackendEventPayload, BackendPathedError } from "@/types/backend";
+
+export type SettingsCommand = "get_settings" | "set_settings";
+export type SettingsEventType = "settings_changed";
+
+export type SettingsEventPayload = BackendEventPayload<Settings, BackendPathedError>;
+
+export type ReplayGainMode = "off" | "track" | "release";
+export type TranscodeFormat = "mp3" | "opus" | "aac";
+
+export interface Settings {
+	appearance: {
+		theme: null | { name: string };
+	};
+	scanner: {
+		artist_images: boolean;
+		folder_names: boolean;
+		audio_extensions: string[];
+		sniff_unknown: boolean;
+	};
+	playback: {
+		volume: number;
+		gapless: boolean;
+		replay_gain: ReplayGainMode;
+	};
+	server: {
+		enabled: boolean;
+		port: number;
+		username: string;
+		password: string;
+		transcode_format: TranscodeFormat;
+		/** Bitrate in kbps, between 32 and 320. */
+		transcode_bitrate: number;
+		ffmpeg_path: string;
+	};
+	scrobbling: {
+		listenbrainz: {
+			enabled: boolean;
+			token: string;
+			endpoint: string;
+		};
+		lastfm: {
+			enabled: boolean;
+			api_key: string;
+			api_secret: string;
+			/** Set through `authenticate_lastfm`. */
+			session_key: null | string;
+			endpoint: string;
+		};
+	};
+}
+
+export interface SetSettingsParameters {
+	[key: string]: unknown;
+	settings: Settings;
+}
@@ -0,0 +1,19 @@
+export type TagCommand = "get_genres" | "set_genre_parent" | "add_genre_alias" | "merge_genres";
+
+export type TagType = "Genre" | "Other";
+
+export interface Tag {
+	name: string;
+	type: TagType;
+	parent_id: null | number;
+	aliases: null | string[];
+
+	date_added: string;
+	date_modified: string;
+}
+
+export type GenreEntry = Tag & {
+	id: number;
+	release_count: number;
+	track_count: number;
+};
@@ -0,0 +1,32 @@
+import type { BackendBaseError, BackendEventPayload, BackendPathedError } from "@/types/backend";
+
+export type ThemeCommand = "get_themes" | "get_theme";
+export type ThemeEventType = "theme_changed";
+
+export type ThemeEventPayload = BackendEventPayload<ThemeEvent, BackendPathedError>;
+
+export type ThemeMode = "light" | "dark";
+
+export interface Theme {
+	name: string;
+	mode: ThemeMode;
+	colors: Record<string, string>;
+}
+
+export interface ThemeEntry {
+	id: string;
+	path: string;
+	name: null | string;
+	mode: null | ThemeMode;
+	error: null | BackendBaseError;
+}
+
+export interface ThemeEvent {
+	id: string;
+	theme: Theme;
+}
+
+export interface GetThemeParameters {
+	[key: string]: unknown;
+	themeId?: string;
+}
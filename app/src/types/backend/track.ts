@@ -1,7 +1,15 @@
+import type { BackendPage, BackendPageRequest } from "@/types/backend";
 import type { InlinedArtist } from "@/types/backend/generic";
-import type { Person } from "@/types/backend/person";
+import type { Person, PersonType } from "@/types/backend/person";
 
-export type TrackCommand = "get_track_list_for_release";
+export type TrackCommand = "get_track_list_for_release" | "query_tracks" | "set_track_rating";
+
+export interface Credit {
+	person_id: number;
+	type: PersonType;
+	/** The instrument of a performer, or the name of the role when the type is "other". */
+	detail: string | null;
+}
 
 export interface Track {
 	title: string;
@@ -14,8 +22,7 @@ export interface Track {
 	artist_sort: string | null;
 
 	release_id: number;
-	composer_ids: number[] | null;
-	producer_ids: number[] | null;
+	credits: Credit[];
 	cover_ids: number[] | null;
 
 	genre_ids: number[] | null;
@@ -23,6 +30,26 @@ export interface Track {
 
 	mbz_id: string | null;
 	path: string;
+	segment: TrackSegment | null;
+	work_id: number | null;
+	movement: Movement | null;
+	/** From 1 to 5. */
+	rating: number | null;
+
+	date_added: string;
+	date_modified: string;
+}
+
+export interface Movement {
+	number: number | null;
+	total: number | null;
+	name: string | null;
+}
+
+/** Offsets of a track in milliseconds, within a file shared with other tracks. */
+export interface TrackSegment {
+	start: number;
+	end: number | null;
 }
 
 export interface DisplayTrackList {
@@ -34,3 +61,31 @@ export interface GetTrackListParameters {
 	[key: string]: unknown;
 	releaseId: number;
 }
+
+export type DisplayTrackPage = BackendPage<Track> & {
+	artists: Record<number, Person>;
+};
+
+export type TrackSort = "title" | "artist" | "date" | "added" | "play_count";
+
+export interface TrackFilter {
+	/** Defaults to the active library. */
+	library_id?: number;
+	release_id?: number;
+	artist_id?: number;
+	genre_id?: number;
+}
+
+export interface TracksQueryParameters {
+	[key: string]: unknown;
+	page: BackendPageRequest;
+	sort: TrackSort;
+	filter?: TrackFilter;
+}
+
+export interface SetTrackRatingParameters {
+	[key: string]: unknown;
+	trackId: number;
+	/** From 1 to 5, where null removes the rating. */
+	rating: number | null;
+}
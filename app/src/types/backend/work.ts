@@ -0,0 +1,42 @@
+import type { DisplayCover } from "@/types/backend/cover";
+import type { Person } from "@/types/backend/person";
+import type { Release } from "@/types/backend/release";
+import type { Track } from "@/types/backend/track";
+
+export type WorkCommand = "get_works" | "get_work";
+
+export interface Work {
+	name: string;
+	composer_ids: number[];
+	catalogue_number: null | string;
+	key: null | string;
+	mbz_id: null | string;
+
+	date_added: string;
+	date_modified: string;
+}
+
+export type WorkEntry = Work & {
+	id: number;
+	recording_count: number;
+};
+
+export interface WorkRecording {
+	release_id: number;
+	track_ids: number[];
+}
+
+export interface DisplayWork {
+	work: Work;
+	recordings: WorkRecording[];
+
+	releases: Record<number, Release>;
+	tracks: Record<number, Track>;
+	artists: Record<number, Person>;
+	covers: Record<number, DisplayCover>;
+}
+
+export interface GetWorkParameters {
+	[key: string]: unknown;
+	workId: number;
+}
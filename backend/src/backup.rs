@@ -0,0 +1,398 @@
+use std::{
+	collections::HashSet,
+	fs::File,
+	path::{Path, PathBuf},
+};
+
+use {
+	chrono::{DateTime, Utc},
+	serde::{Deserialize, Serialize},
+};
+
+use crate::errors::{pre::invalid_backup, Result};
+
+/// Version of the archive layout, bumped whenever the entries of an archive are moved or renamed.
+///
+/// This is separate from the schema version of the database, which is migrated on startup.
+pub const ARCHIVE_VERSION: u32 = 1;
+
+const MANIFEST_NAME: &str = "manifest.json";
+const DATABASE_PREFIX: &str = "database";
+const COVERS_PREFIX: &str = "covers";
+const SETTINGS_NAME: &str = "settings.toml";
+
+/// Locations of the data kept in a backup.
+#[derive(Debug, Clone)]
+pub struct BackupSources {
+	pub database_dir: PathBuf,
+	pub cover_dir: PathBuf,
+	pub settings_path: PathBuf,
+}
+
+/// Describes the contents of a backup archive, written as its last entry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupManifest {
+	pub version: u32,
+	/// Schema version of the database at the time of the backup.
+	pub schema_version: u64,
+	pub created_at: DateTime<Utc>,
+	pub files: Vec<BackupFile>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupFile {
+	/// Path of the entry in the archive, always separated by `/`.
+	pub path: String,
+	pub size: u64,
+	/// BLAKE3 hash of the contents, in hex.
+	pub hash: String,
+}
+
+/// Writes the database, cover store and settings into a single tar archive.
+///
+/// The database must not be written into while this runs, which is why it should be called with the database locked.
+pub fn create(sources: &BackupSources, schema_version: u64, archive_path: &Path) -> Result<BackupManifest> {
+	let mut entries = collect_files(&sources.database_dir, DATABASE_PREFIX)?;
+	entries.extend(collect_files(&sources.cover_dir, COVERS_PREFIX)?);
+	if sources.settings_path.is_file() {
+		entries.push((sources.settings_path.clone(), SETTINGS_NAME.to_string()));
+	}
+
+	let mut builder = tar::Builder::new(File::create(archive_path)?);
+	let mut files = Vec::with_capacity(entries.len());
+
+	for (path, name) in entries {
+		let (size, hash) = hash_file(&path)?;
+		builder.append_path_with_name(&path, &name)?;
+		files.push(BackupFile { path: name, size, hash });
+	}
+
+	let manifest = BackupManifest {
+		version: ARCHIVE_VERSION,
+		schema_version,
+		created_at: Utc::now(),
+		files,
+	};
+
+	let contents = serde_json::to_vec_pretty(&manifest)?;
+	let mut header = tar::Header::new_gnu();
+	header.set_size(contents.len() as u64);
+	header.set_mode(0o644);
+	header.set_mtime(manifest.created_at.timestamp().max(0) as u64);
+	builder.append_data(&mut header, MANIFEST_NAME, contents.as_slice())?;
+	builder.into_inner()?;
+
+	Ok(manifest)
+}
+
+/// Unpacks an archive into an empty staging directory, checking every file against the manifest.
+///
+/// Nothing outside of `staging_dir` is touched, so a corrupt or incomplete archive can't damage the existing data.
+/// The staging directory is removed again if the archive turns out to be invalid.
+pub fn extract(archive_path: &Path, staging_dir: &Path) -> Result<BackupManifest> {
+	let result = unpack(archive_path, staging_dir);
+	if result.is_err() && staging_dir.exists() {
+		std::fs::remove_dir_all(staging_dir)?;
+	}
+
+	result
+}
+
+fn unpack(archive_path: &Path, staging_dir: &Path) -> Result<BackupManifest> {
+	if staging_dir.exists() {
+		std::fs::remove_dir_all(staging_dir)?;
+	}
+	std::fs::create_dir_all(staging_dir)?;
+
+	let mut archive = tar::Archive::new(File::open(archive_path)?);
+	let mut names = HashSet::new();
+
+	for entry in archive.entries()? {
+		let mut entry = entry?;
+		let name = entry.path()?.to_string_lossy().replace('\\', "/");
+
+		if !entry.unpack_in(staging_dir)? {
+			return Err(invalid_backup(&format!(
+				"The entry '{name}' points outside of the archive."
+			)));
+		}
+
+		if entry.header().entry_type().is_file() {
+			names.insert(name);
+		}
+	}
+
+	if !names.remove(MANIFEST_NAME) {
+		return Err(invalid_backup("The archive doesn't have a manifest."));
+	}
+
+	let manifest = serde_json::from_slice::<BackupManifest>(&std::fs::read(staging_dir.join(MANIFEST_NAME))?)?;
+	if manifest.version > ARCHIVE_VERSION {
+		let m = format!(
+			"The archive is at version {}, which is newer than this app.",
+			manifest.version
+		);
+		return Err(invalid_backup(&m));
+	}
+
+	for file in &manifest.files {
+		if !names.remove(&file.path) {
+			return Err(invalid_backup(&format!("The file '{}' is missing.", file.path)));
+		}
+
+		if hash_file(&staging_dir.join(&file.path))? != (file.size, file.hash.clone()) {
+			return Err(invalid_backup(&format!("The file '{}' is corrupt.", file.path)));
+		}
+	}
+
+	if let Some(name) = names.iter().next() {
+		return Err(invalid_backup(&format!(
+			"The file '{name}' isn't listed in the manifest."
+		)));
+	}
+
+	Ok(manifest)
+}
+
+/// Moves an extracted backup in place of the current data.
+///
+/// The replaced data is kept next to it with a `.pre-restore` suffix, until the next restore. If any step fails,
+/// the data replaced so far is moved back, so the current data is left as it was.
+pub fn apply(staging_dir: &Path, sources: &BackupSources) -> Result<()> {
+	let mut replaced = Vec::<Replaced>::new();
+
+	if let Err(e) = replace_all(staging_dir, sources, &mut replaced) {
+		for x in replaced.iter().rev() {
+			x.roll_back()?;
+		}
+
+		std::fs::remove_dir_all(staging_dir)?;
+		return Err(e);
+	}
+
+	std::fs::remove_dir_all(staging_dir)?;
+
+	Ok(())
+}
+
+/// A path replaced by [apply], along with where its previous contents were moved.
+struct Replaced {
+	target: PathBuf,
+	previous: Option<PathBuf>,
+}
+
+impl Replaced {
+	fn roll_back(&self) -> Result<()> {
+		remove_path(&self.target)?;
+
+		if let Some(previous) = &self.previous {
+			std::fs::rename(previous, &self.target)?;
+		}
+
+		Ok(())
+	}
+}
+
+fn replace_all(staging_dir: &Path, sources: &BackupSources, replaced: &mut Vec<Replaced>) -> Result<()> {
+	let staged = staging_dir.join(DATABASE_PREFIX);
+	replaced.push(move_aside(&sources.database_dir)?);
+	move_or_create_dir(&staged, &sources.database_dir)?;
+
+	let staged = staging_dir.join(COVERS_PREFIX);
+	replaced.push(move_aside(&sources.cover_dir)?);
+	move_or_create_dir(&staged, &sources.cover_dir)?;
+
+	let staged = staging_dir.join(SETTINGS_NAME);
+	if staged.is_file() {
+		replaced.push(move_aside(&sources.settings_path)?);
+		std::fs::copy(staged, &sources.settings_path)?;
+	}
+
+	Ok(())
+}
+
+/// Moves a path out of the way with a `.pre-restore` suffix, replacing the one left by the previous restore.
+fn move_aside(target: &Path) -> Result<Replaced> {
+	let mut name = target.file_name().unwrap_or_default().to_os_string();
+	name.push(".pre-restore");
+
+	let previous = target.with_file_name(name);
+	remove_path(&previous)?;
+
+	if !target.exists() {
+		return Ok(Replaced {
+			target: target.to_path_buf(),
+			previous: None,
+		});
+	}
+
+	std::fs::rename(target, &previous)?;
+
+	Ok(Replaced {
+		target: target.to_path_buf(),
+		previous: Some(previous),
+	})
+}
+
+fn move_or_create_dir(staged: &Path, target: &Path) -> Result<()> {
+	if staged.exists() {
+		std::fs::rename(staged, target)?;
+	} else {
+		std::fs::create_dir_all(target)?;
+	}
+
+	Ok(())
+}
+
+fn remove_path(path: &Path) -> Result<()> {
+	if path.is_dir() {
+		std::fs::remove_dir_all(path)?;
+	} else if path.exists() {
+		std::fs::remove_file(path)?;
+	}
+
+	Ok(())
+}
+
+/// Lists the files in a directory recursively, along with their names in the archive under `prefix`.
+fn collect_files(dir: &Path, prefix: &str) -> Result<Vec<(PathBuf, String)>> {
+	let mut files = Vec::new();
+	if !dir.is_dir() {
+		return Ok(files);
+	}
+
+	for entry in std::fs::read_dir(dir)? {
+		let entry = entry?;
+		let name = format!("{prefix}/{}", entry.file_name().to_string_lossy());
+
+		if entry.file_type()?.is_dir() {
+			files.extend(collect_files(&entry.path(), &name)?);
+		} else {
+			files.push((entry.path(), name));
+		}
+	}
+
+	Ok(files)
+}
+
+fn hash_file(path: &Path) -> Result<(u64, String)> {
+	let mut hasher = blake3::Hasher::new();
+	hasher.update_reader(File::open(path)?)?;
+
+	let size = std::fs::metadata(path)?.len();
+	Ok((size, hasher.finalize().to_hex().to_string()))
+}
+
+#[cfg(test)]
+mod test {
+	use std::path::{Path, PathBuf};
+
+	use super::{apply, create, extract, BackupSources, ARCHIVE_VERSION};
+	use crate::errors::Result;
+
+	fn write_sources(root: &Path) -> Result<BackupSources> {
+		let sources = BackupSources {
+			database_dir: root.join("data/main.bonsaidb"),
+			cover_dir: root.join("data/covers"),
+			settings_path: root.join("config/settings.toml"),
+		};
+
+		std::fs::create_dir_all(sources.database_dir.join("nested"))?;
+		std::fs::create_dir_all(sources.cover_dir.join("thumbs"))?;
+		std::fs::create_dir_all(root.join("config"))?;
+
+		std::fs::write(sources.database_dir.join("nested/file"), "document")?;
+		std::fs::write(sources.cover_dir.join("thumbs/cover.jpg"), "cover")?;
+		std::fs::write(&sources.settings_path, "[playback]")?;
+
+		Ok(sources)
+	}
+
+	fn temp_root(name: &str) -> PathBuf {
+		std::env::temp_dir().join(format!("melody-{name}-{}", ulid::Ulid::new()))
+	}
+
+	#[test]
+	fn test_create_and_restore() -> Result<()> {
+		let root = temp_root("backup");
+		let sources = write_sources(&root)?;
+		let archive_path = root.join("backup.tar");
+
+		let manifest = create(&sources, 2, &archive_path)?;
+		assert_eq!(manifest.version, ARCHIVE_VERSION);
+		assert_eq!(manifest.files.len(), 3);
+
+		std::fs::write(sources.database_dir.join("nested/file"), "changed")?;
+		std::fs::remove_file(&sources.settings_path)?;
+
+		let staging_dir = root.join("data/restore.staging");
+		assert_eq!(extract(&archive_path, &staging_dir)?, manifest);
+		apply(&staging_dir, &sources)?;
+
+		let database_file = std::fs::read_to_string(sources.database_dir.join("nested/file"))?;
+		assert_eq!(database_file, "document");
+		assert_eq!(std::fs::read_to_string(&sources.settings_path)?, "[playback]");
+		assert!(sources.cover_dir.join("thumbs/cover.jpg").is_file());
+		assert!(root.join("data/main.bonsaidb.pre-restore/nested/file").is_file());
+		assert!(!staging_dir.exists());
+
+		std::fs::remove_dir_all(root)?;
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_apply_rollback() -> Result<()> {
+		let root = temp_root("backup-rollback");
+		let sources = write_sources(&root)?;
+		let archive_path = root.join("backup.tar");
+		create(&sources, 2, &archive_path)?;
+
+		std::fs::write(sources.database_dir.join("nested/file"), "changed")?;
+
+		// The settings can't be written into a missing directory, which fails the last step.
+		let sources = BackupSources {
+			settings_path: root.join("missing/settings.toml"),
+			..sources
+		};
+
+		let staging_dir = root.join("data/restore.staging");
+		extract(&archive_path, &staging_dir)?;
+		assert!(apply(&staging_dir, &sources).is_err());
+
+		let database_file = std::fs::read_to_string(sources.database_dir.join("nested/file"))?;
+		assert_eq!(database_file, "changed");
+		assert!(sources.cover_dir.join("thumbs/cover.jpg").is_file());
+		assert!(!root.join("data/main.bonsaidb.pre-restore").exists());
+		assert!(!root.join("data/covers.pre-restore").exists());
+		assert!(!staging_dir.exists());
+
+		std::fs::remove_dir_all(root)?;
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_extract_corrupt() -> Result<()> {
+		let root = temp_root("backup-corrupt");
+		let sources = write_sources(&root)?;
+		let archive_path = root.join("backup.tar");
+		create(&sources, 2, &archive_path)?;
+
+		// Contents are stored as is, so flipping a letter of a file corrupts it without breaking the archive.
+		let archive = std::fs::read(&archive_path)?;
+		let position = archive.windows(8).position(|x| x == b"document").unwrap();
+		let mut corrupt = archive;
+		corrupt[position] = b'D';
+		std::fs::write(&archive_path, corrupt)?;
+
+		let staging_dir = root.join("data/restore.staging");
+		assert!(extract(&archive_path, &staging_dir).is_err());
+		assert!(!staging_dir.exists());
+		assert!(sources.database_dir.join("nested/file").is_file());
+
+		std::fs::remove_dir_all(root)?;
+
+		Ok(())
+	}
+}
@@ -0,0 +1,252 @@
+use std::path::PathBuf;
+
+use {
+	tauri::{AppHandle, Manager, State},
+	tracing::{error, info},
+};
+
+use crate::{
+	backup::{self, BackupManifest, BackupSources},
+	database::{migrations, Database},
+	errors::Result,
+	models::{
+		configuration::Configuration,
+		state::{DatabaseState, DirectoryState, PluginState, ScrobbleState, SubsonicState},
+	},
+};
+
+const RESTORE_STAGING_NAME: &str = "restore.staging";
+
+/// Writes the database, cover store and settings into a single archive at the given path.
+///
+/// The database stays locked until the archive is written, so it's backed up in a consistent state. The Subsonic
+/// server, the scrobbler and the plugins hold their own handles to the database, so they're paused meanwhile.
+#[tauri::command]
+#[tracing::instrument(skip(dir_state, db_state, subsonic_state, scrobble_state, plugin_state), err(Debug))]
+pub async fn create_backup(
+	path: PathBuf,
+	dir_state: State<'_, DirectoryState>,
+	db_state: State<'_, DatabaseState>,
+	subsonic_state: State<'_, SubsonicState>,
+	scrobble_state: State<'_, ScrobbleState>,
+	plugin_state: State<'_, PluginState>,
+) -> Result<BackupManifest> {
+	let sources = get_sources(&dir_state);
+	let cover_dir = sources.cover_dir.clone();
+
+	let subsonic = subsonic_state.stop().await?;
+	let scrobble_pause = scrobble_state.pause().await;
+
+	let db_guard = db_state.get().await;
+	let database = db_guard.as_ref().unwrap().inner_ref();
+
+	let result = async {
+		let schema_version = migrations::get_version(database).await?;
+		// Plugins reach the library while holding their lock, so they're kept out until the files are copied.
+		let plugins = plugin_state.0.clone().lock_owned().await;
+
+		tokio::task::spawn_blocking(move || {
+			let _plugins = plugins;
+			backup::create(&sources, schema_version, &path)
+		})
+		.await?
+	}
+	.await;
+
+	drop(scrobble_pause);
+	if let Some(configuration) = subsonic {
+		if let Err(e) = subsonic_state.apply(&configuration, database.clone(), cover_dir).await {
+			error!("Failed to restart the Subsonic server after the backup: {e:#?}");
+		}
+	}
+
+	let manifest = result?;
+	info!("Created a backup of {} files", manifest.files.len());
+
+	Ok(manifest)
+}
+
+/// Replaces the current data with an archive made by [create_backup], then reloads the window.
+///
+/// The archive is checked in full before anything is replaced. Backups of an older schema version are migrated once the
+/// restored database is opened, while ones of a newer version are refused.
+#[tauri::command]
+#[tracing::instrument(skip(app), err(Debug))]
+pub async fn restore_backup(path: PathBuf, app: AppHandle) -> Result<()> {
+	let dir_state = app.state::<DirectoryState>();
+
+	let sources = get_sources(&dir_state);
+	let plugins_dir = dir_state.get().as_ref().unwrap().plugins_dir.clone();
+	let staging_dir = sources.database_dir.with_file_name(RESTORE_STAGING_NAME);
+
+	let manifest = {
+		let staging_dir = staging_dir.clone();
+		tokio::task::spawn_blocking(move || backup::extract(&path, &staging_dir)).await??
+	};
+
+	if let Err(e) = migrations::is_pending(manifest.schema_version) {
+		std::fs::remove_dir_all(&staging_dir)?;
+		return Err(e);
+	}
+
+	restore(
+		staging_dir,
+		&sources,
+		plugins_dir,
+		&app.state::<DatabaseState>(),
+		&app.state::<SubsonicState>(),
+		&app.state::<ScrobbleState>(),
+		&app.state::<PluginState>(),
+	)
+	.await?;
+
+	// The window still shows the replaced library.
+	info!("Restored a backup from {}, reloading the window", manifest.created_at);
+	if let Some(window) = app.get_window("main") {
+		window.eval("window.location.reload()")?;
+	}
+
+	Ok(())
+}
+
+/// Moves an extracted backup in place of the current data and reopens the database.
+///
+/// The Subsonic server, the scrobbler and the plugins hold their own handles to the database, so they're stopped before
+/// it's replaced. They're started again with the reopened database whether the backup was applied or rolled back.
+async fn restore(
+	staging_dir: PathBuf,
+	sources: &BackupSources,
+	plugins_dir: PathBuf,
+	db_state: &DatabaseState,
+	subsonic_state: &SubsonicState,
+	scrobble_state: &ScrobbleState,
+	plugin_state: &PluginState,
+) -> Result<()> {
+	let subsonic = subsonic_state.stop().await?;
+	let scrobbler = scrobble_state.stop().await;
+	plugin_state.unload().await;
+
+	// The lock is kept until the database is reopened, so no command reaches the replaced database.
+	let mut db_guard = db_state.get().await;
+	db_guard.take();
+
+	let result = {
+		let sources = sources.clone();
+		tokio::task::spawn_blocking(move || backup::apply(&staging_dir, &sources)).await?
+	};
+
+	db_guard.replace(Database::new(&sources.database_dir).await?);
+	let database = db_guard.as_ref().unwrap().inner_ref().clone();
+	drop(db_guard);
+
+	// The restored settings decide whether the server runs, while a rolled back restore keeps the previous ones.
+	let subsonic = match &result {
+		Ok(()) => match Configuration::load(&sources.settings_path) {
+			Ok(x) => Some(x.server),
+			Err(e) => {
+				error!("Failed to read the restored settings: {e:#?}");
+				subsonic
+			}
+		},
+		Err(_) => subsonic,
+	};
+
+	if let Some(configuration) = subsonic {
+		if let Err(e) = subsonic_state
+			.apply(&configuration, database.clone(), sources.cover_dir.clone())
+			.await
+		{
+			error!("Failed to restart the Subsonic server after the restore: {e:#?}");
+		}
+	}
+
+	if let Some(scrobbler) = scrobbler {
+		if let Err(e) = scrobble_state.resume(scrobbler, database.clone()).await {
+			error!("Failed to restart the scrobbler after the restore: {e:#?}");
+		}
+	}
+
+	if let Err(e) = plugin_state.initialize(plugins_dir, database).await {
+		error!("Failed to load the plugins after the restore: {e:#?}");
+	}
+
+	result
+}
+
+fn get_sources(dir_state: &DirectoryState) -> BackupSources {
+	let dir_guard = dir_state.get();
+	let directories = dir_guard.as_ref().unwrap();
+
+	BackupSources {
+		database_dir: directories.database_dir.clone(),
+		cover_dir: directories.cover_dir.clone(),
+		settings_path: directories.settings_path(),
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::sync::{Arc, Mutex};
+
+	use bonsaidb::core::schema::SerializedCollection;
+
+	use crate::{
+		backup::{self, BackupSources},
+		commands::backup::restore,
+		database::{migrations, models::release::Release},
+		errors::Result,
+		models::state::{DatabaseState, PluginState, ScrobbleState, SubsonicState},
+		scrobbler::Scrobbler,
+	};
+
+	#[tokio::test]
+	async fn test_restore() -> Result<()> {
+		let root = std::env::temp_dir().join(format!("melody-restore-{}", ulid::Ulid::new()));
+		let sources = BackupSources {
+			database_dir: root.join("data/main.bonsaidb"),
+			cover_dir: root.join("data/covers"),
+			settings_path: root.join("config/settings.toml"),
+		};
+
+		let plugins_dir = root.join("plugins");
+		std::fs::create_dir_all(&sources.cover_dir)?;
+		std::fs::create_dir_all(root.join("config"))?;
+		std::fs::create_dir_all(&plugins_dir)?;
+
+		let db_state = DatabaseState::default();
+		db_state.initialize(&sources.database_dir).await?;
+		let database = db_state.get().await.as_ref().unwrap().inner_ref().clone();
+
+		Release::default().insert_into_async(&1, &database).await?;
+		let archive_path = root.join("backup.tar");
+		backup::create(&sources, migrations::get_version(&database).await?, &archive_path)?;
+		Release::default().insert_into_async(&2, &database).await?;
+
+		// The scrobbler keeps its own handle to the database, as it does while the application runs.
+		let scrobble_state = ScrobbleState::default();
+		let scrobbler = Scrobbler::new(database, Arc::new(Mutex::new(None)), |_, _| {})?;
+		scrobble_state.0.lock().await.replace(scrobbler);
+
+		let staging_dir = root.join("data/restore.staging");
+		backup::extract(&archive_path, &staging_dir)?;
+		restore(
+			staging_dir,
+			&sources,
+			plugins_dir,
+			&db_state,
+			&SubsonicState::default(),
+			&scrobble_state,
+			&PluginState::default(),
+		)
+		.await?;
+
+		let database = db_state.get().await.as_ref().unwrap().inner_ref().clone();
+		assert!(Release::get_async(&1, &database).await?.is_some());
+		assert!(Release::get_async(&2, &database).await?.is_none());
+		assert!(scrobble_state.0.lock().await.is_some());
+
+		std::fs::remove_dir_all(root)?;
+
+		Ok(())
+	}
+}
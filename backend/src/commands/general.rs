@@ -1,13 +1,22 @@
-use {tauri::Manager, tracing::info};
+use {
+	tauri::Manager,
+	tracing::{error, info},
+};
 
 use rsmpeg::ffi::{av_log_set_level, AV_LOG_FATAL};
 use tauri::AppHandle;
 
 use crate::{
 	errors::Result,
-	models::state::{AppState, DatabaseState, DirectoryState},
+	models::state::{
+		AppState, ConfigurationState, DatabaseState, DirectoryState, PluginState, ScrobbleState, SubsonicState,
+		ThemeState,
+	},
 };
 
+#[cfg(target_os = "linux")]
+use crate::models::state::PlaybackState;
+
 #[tauri::command]
 #[tracing::instrument(skip(app), err)]
 pub async fn setup(app: AppHandle) -> Result<()> {
@@ -15,7 +24,14 @@ pub async fn setup(app: AppHandle) -> Result<()> {
 
 	let app_state = app.state::<AppState>();
 	let dir_state = app.state::<DirectoryState>();
+	let config_state = app.state::<ConfigurationState>();
+	let theme_state = app.state::<ThemeState>();
 	let db_state = app.state::<DatabaseState>();
+	let plugin_state = app.state::<PluginState>();
+	let subsonic_state = app.state::<SubsonicState>();
+	let scrobble_state = app.state::<ScrobbleState>();
+	#[cfg(target_os = "linux")]
+	let playback_state = app.state::<PlaybackState>();
 
 	if let Err(()) = app_state.initialize() {
 		return Ok(());
@@ -24,13 +40,47 @@ pub async fn setup(app: AppHandle) -> Result<()> {
 	let path_resolver = app.path_resolver();
 	dir_state.initialize(path_resolver).await?;
 
-	let database_dir = {
+	let (settings_path, themes_dir, database_dir, plugins_dir, cover_dir) = {
 		let guard = dir_state.get();
 		let directories = guard.as_ref().unwrap();
-		directories.database_dir.clone()
+		(
+			directories.settings_path(),
+			directories.themes_dir.clone(),
+			directories.database_dir.clone(),
+			directories.plugins_dir.clone(),
+			directories.cover_dir.clone(),
+		)
 	};
+
+	let window = app.get_window("main").unwrap();
+	config_state.initialize(&window, &settings_path);
+	config_state.watch(window.clone(), settings_path)?;
+	theme_state.watch(window.clone(), themes_dir)?;
 	db_state.initialize(&database_dir).await?;
 
+	let database = {
+		let guard = db_state.get().await;
+		guard.as_ref().unwrap().inner_ref().clone()
+	};
+
+	// Plugins are optional, so failing to load them shouldn't stop the application from loading.
+	if let Err(e) = plugin_state.initialize(plugins_dir, database.clone()).await {
+		error!("Failed to load the plugins: {e:#?}");
+	}
+
+	scrobble_state
+		.initialize(window.clone(), database.clone(), config_state.0.clone())
+		.await?;
+
+	// The server is optional, so failing to start it shouldn't stop the application from loading.
+	let server_configuration = config_state.get().as_ref().unwrap().server.clone();
+	if let Err(e) = subsonic_state.apply(&server_configuration, database, cover_dir).await {
+		error!("Failed to start the Subsonic server: {e:#?}");
+	}
+
+	#[cfg(target_os = "linux")]
+	playback_state.initialize(window).await;
+
 	unsafe {
 		av_log_set_level(AV_LOG_FATAL.try_into().unwrap());
 	}
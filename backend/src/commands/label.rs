@@ -0,0 +1,146 @@
+use std::collections::{HashMap, HashSet};
+
+use {
+	bonsaidb::core::{
+		document::DocumentId,
+		schema::{SerializedCollection, SerializedView},
+	},
+	tauri::State,
+	tokio::time::Instant,
+	tracing::debug,
+};
+
+use crate::{
+	database::{
+		methods,
+		models::{cover::Cover, label::Label, person::Person, CountryCode},
+		views::release::ReleaseByLabelId,
+	},
+	errors::{pre::database_entry_not_found, Result},
+	models::{
+		state::{DatabaseState, DirectoryState},
+		tauri::{
+			cover::DisplayCover,
+			label::{DisplayLabel, LabelEntry},
+		},
+	},
+};
+
+#[tauri::command]
+#[tracing::instrument(skip(db_state), err(Debug))]
+pub async fn get_labels(db_state: State<'_, DatabaseState>) -> Result<Vec<LabelEntry>> {
+	let start = Instant::now();
+
+	let db_guard = db_state.get().await;
+	let database = db_guard.as_ref().unwrap().inner_ref();
+
+	// Counts are limited to the active library, so they can't be read from the reduced view.
+	let library_id = methods::library::get_active_id(database).await?;
+	let release_ids = methods::library::get_release_ids(database, library_id).await?;
+
+	let mut release_counts = HashMap::<u64, u64>::new();
+	for mapping in &ReleaseByLabelId::entries_async(database).query().await? {
+		if release_ids.contains(&mapping.source.id) {
+			*release_counts.entry(mapping.key).or_default() += 1;
+		}
+	}
+
+	let mut labels = Label::all_async(database)
+		.await?
+		.into_iter()
+		.map(|document| LabelEntry {
+			id: document.header.id,
+			release_count: release_counts.get(&document.header.id).copied().unwrap_or_default(),
+			label: document.contents,
+		})
+		.collect::<Vec<LabelEntry>>();
+
+	labels.sort_by_cached_key(|e| e.label.name_sort.as_ref().unwrap_or(&e.label.name).to_lowercase());
+
+	debug!("Finished building label list query in {:?}", start.elapsed());
+
+	Ok(labels)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(dir_state, db_state), err(Debug))]
+pub async fn get_label(
+	label_id: u64,
+	dir_state: State<'_, DirectoryState>,
+	db_state: State<'_, DatabaseState>,
+) -> Result<DisplayLabel> {
+	let start = Instant::now();
+
+	let resource_cover_dir = {
+		let dir_guard = dir_state.get();
+		let directories = dir_guard.as_ref().unwrap();
+		directories.cover_dir.clone()
+	};
+
+	let db_guard = db_state.get().await;
+	let database = db_guard.as_ref().unwrap().inner_ref();
+
+	let label = Label::get_async(&label_id, database)
+		.await?
+		.ok_or_else(|| database_entry_not_found("labels", label_id))?;
+
+	let mut artist_ids = HashSet::<DocumentId>::new();
+	let mut cover_ids = Vec::<DocumentId>::new();
+	let mut release_ids = Vec::<u64>::new();
+	let mut releases = HashMap::new();
+
+	let library_id = methods::library::get_active_id(database).await?;
+	let label_releases = methods::label::get_releases(database, label_id)
+		.await?
+		.into_iter()
+		.filter(|(_, x)| x.library_id == library_id);
+
+	for (id, release) in label_releases {
+		for artist in &release.artists {
+			artist_ids.insert(DocumentId::from_u64(artist.id));
+		}
+
+		if let Some(covers) = &release.cover_ids {
+			covers.iter().for_each(|e| cover_ids.push(DocumentId::from_u64(*e)));
+		}
+
+		release_ids.push(id);
+		releases.insert(id, release);
+	}
+
+	let mut artists = HashMap::<u64, Person>::with_capacity(artist_ids.len());
+	let mut covers = HashMap::<u64, DisplayCover>::with_capacity(cover_ids.len());
+
+	for i in Person::get_multiple_async(&artist_ids, database).await? {
+		artists.insert(i.header.id, i.contents);
+	}
+
+	for i in Cover::get_multiple_async(&cover_ids, database).await? {
+		covers.insert(i.header.id, DisplayCover::from_cover(i.contents, &resource_cover_dir));
+	}
+
+	debug!("Finished building display label query in {:?}", start.elapsed());
+
+	Ok(DisplayLabel {
+		label: label.contents,
+		release_ids,
+		releases,
+		artists,
+		covers,
+	})
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(db_state), err(Debug))]
+pub async fn set_label_metadata(
+	label_id: u64,
+	name_sort: Option<String>,
+	country: Option<CountryCode>,
+	mbz_id: Option<String>,
+	db_state: State<'_, DatabaseState>,
+) -> Result<()> {
+	let db_guard = db_state.get().await;
+	let database = db_guard.as_ref().unwrap().inner_ref();
+
+	methods::label::set_metadata(database, label_id, name_sort, country, mbz_id).await
+}
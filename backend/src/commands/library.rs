@@ -1,4 +1,4 @@
-use std::{path::PathBuf, sync::mpsc, thread};
+use std::{collections::HashSet, path::PathBuf, sync::mpsc, thread};
 
 use {
 	tokio::time::Instant,
@@ -6,40 +6,207 @@ use {
 };
 
 use crate::{
-	database::{helpers::handle_temp_track_meta, methods},
+	database::{
+		helpers::handle_temp_track_meta,
+		methods,
+		models::{
+			cover::CoverType,
+			library::{Library, ScanLocation},
+		},
+	},
 	errors::{Error, Result},
-	ffmpeg::meta::read_track_meta,
+	metadata::{
+		providers::{EmbeddedTagProvider, FolderNameProvider, LyricsSidecarProvider, PluginProvider, SidecarProvider},
+		MetadataPipeline, MetadataProvider,
+	},
 	models::{
-		state::{DatabaseState, DirectoryState},
-		tauri::library::{LibraryEventData, LibraryEventManager, LibraryEventPayload, LibraryEventType},
-		temp::{TempTrackMeta, TempTrackResource},
+		state::{ConfigurationState, DatabaseState, DirectoryState, PluginState},
+		tauri::library::{LibraryEntry, LibraryEventData, LibraryEventManager, LibraryEventPayload, LibraryEventType},
+		temp::{cover::TempCover, TempTrackMeta, TempTrackResource},
 	},
-	utils::{fs::walkdir_sync, matchers},
+	plugins::PluginEvent,
+	utils::{fs::find_artist_image, matchers::path::AudioMatcher, scan::ScanRules},
 };
 
+#[tauri::command]
+#[tracing::instrument(skip(db_state), err(Debug))]
+pub async fn get_libraries(db_state: tauri::State<'_, DatabaseState>) -> Result<Vec<LibraryEntry>> {
+	let db_lock = db_state.get().await;
+	let database = db_lock.as_ref().unwrap().inner_ref();
+
+	let active_id = methods::library::get_active_id(database).await?;
+	let libraries = methods::library::get_all(database)
+		.await?
+		.into_iter()
+		.map(|(id, library)| LibraryEntry {
+			id,
+			library,
+			active: id == active_id,
+		})
+		.collect();
+
+	Ok(libraries)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(db_state), err(Debug))]
+pub async fn get_active_library(db_state: tauri::State<'_, DatabaseState>) -> Result<LibraryEntry> {
+	let db_lock = db_state.get().await;
+	let database = db_lock.as_ref().unwrap().inner_ref();
+
+	let id = methods::library::get_active_id(database).await?;
+	let library = methods::library::get(database, id).await?;
+
+	Ok(LibraryEntry {
+		id,
+		library,
+		active: true,
+	})
+}
+
+/// Switches the library that releases, tracks and the related listings are scoped to.
+#[tauri::command]
+#[tracing::instrument(skip(db_state), err(Debug))]
+pub async fn set_active_library(library_id: u64, db_state: tauri::State<'_, DatabaseState>) -> Result<()> {
+	let db_lock = db_state.get().await;
+	let database = db_lock.as_ref().unwrap().inner_ref();
+
+	methods::library::set_active_id(database, library_id).await
+}
+
+/// Creates an empty library, which is filled by scanning it with [initialize_library].
+#[tauri::command]
+#[tracing::instrument(skip(db_state), err(Debug))]
+pub async fn create_library(
+	name: String,
+	scan_locations: Vec<ScanLocation>,
+	db_state: tauri::State<'_, DatabaseState>,
+) -> Result<u64> {
+	let db_lock = db_state.get().await;
+	let database = db_lock.as_ref().unwrap().inner_ref();
+
+	methods::library::insert(database, Library::new(name, scan_locations)).await
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(db_state), err(Debug))]
+pub async fn update_library(
+	library_id: u64,
+	name: String,
+	scan_locations: Vec<ScanLocation>,
+	db_state: tauri::State<'_, DatabaseState>,
+) -> Result<()> {
+	let db_lock = db_state.get().await;
+	let database = db_lock.as_ref().unwrap().inner_ref();
+
+	methods::library::update(database, library_id, name, scan_locations).await
+}
+
+/// Deletes a library with everything that was scanned into it. Files on the disk are left untouched.
+#[tauri::command]
+#[tracing::instrument(skip(db_state, dir_state), err(Debug))]
+pub async fn delete_library(
+	library_id: u64,
+	db_state: tauri::State<'_, DatabaseState>,
+	dir_state: tauri::State<'_, DirectoryState>,
+) -> Result<()> {
+	let cover_dir: PathBuf = {
+		let dir_guard = dir_state.get();
+		let directories = dir_guard.as_ref().unwrap();
+		directories.cover_dir.clone()
+	};
+
+	let db_lock = db_state.get().await;
+	let database = db_lock.as_ref().unwrap().inner_ref();
+
+	methods::library::delete(database, &cover_dir, library_id).await
+}
+
+/// Gets the scan locations of the active library, or none if it has no locations yet.
 #[tauri::command]
 #[tracing::instrument(skip(db_state), err(Debug))]
 pub async fn get_scan_locations(db_state: tauri::State<'_, DatabaseState>) -> Result<Option<Vec<String>>> {
 	let db_lock = db_state.get().await;
-	let database = db_lock.as_ref().unwrap();
+	let database = db_lock.as_ref().unwrap().inner_ref();
+
+	let library_id = methods::library::get_active_id(database).await?;
+	let library = methods::library::get(database, library_id).await?;
 
-	methods::library::get_scan_locations(database.inner_ref()).await
+	let paths = library
+		.scan_locations
+		.into_iter()
+		.map(|x| x.path)
+		.collect::<Vec<String>>();
+	Ok(Some(paths).filter(|x| !x.is_empty()))
 }
 
+/// Saves the scan locations of a library and scans them into it.
+///
+/// Scans the active library when `library_id` is missing. Locations are either paths, or paths with their options.
 #[tauri::command]
-#[tracing::instrument(skip(window, dir_state, db_state), err(Debug))]
+#[tracing::instrument(skip(window, dir_state, config_state, db_state, plugin_state), err(Debug))]
 pub async fn initialize_library(
-	scan_locations: Vec<String>,
+	scan_locations: Vec<ScanLocation>,
+	library_id: Option<u64>,
 	window: tauri::Window,
 	dir_state: tauri::State<'_, DirectoryState>,
+	config_state: tauri::State<'_, ConfigurationState>,
 	db_state: tauri::State<'_, DatabaseState>,
+	plugin_state: tauri::State<'_, PluginState>,
 ) -> Result<()> {
 	let start = Instant::now();
+	let locations = scan_locations.iter().map(|x| x.path.clone()).collect::<Vec<String>>();
+	let mut indexed = 0;
 
-	{
+	let rules = scan_locations
+		.iter()
+		.map(|x| Ok((PathBuf::from(&x.path), ScanRules::new(&x.options)?)))
+		.collect::<Result<Vec<(PathBuf, ScanRules)>>>()?;
+
+	let (read_artist_images, read_folder_names, matcher) = {
+		let config_guard = config_state.get();
+		let configuration = config_guard.as_ref().unwrap();
+		(
+			configuration.scanner.artist_images,
+			configuration.scanner.folder_names,
+			AudioMatcher::new(&configuration.scanner),
+		)
+	};
+
+	let pipeline = {
+		let mut providers: Vec<Box<dyn MetadataProvider>> = vec![
+			Box::new(EmbeddedTagProvider),
+			Box::new(SidecarProvider),
+			Box::new(LyricsSidecarProvider),
+		];
+
+		if read_folder_names {
+			let scan_locations = rules.iter().map(|(x, _)| x.clone()).collect();
+			providers.push(Box::new(FolderNameProvider { scan_locations }));
+		}
+
+		let plugin_ids = plugin_state.get().await.as_ref().map(|x| x.metadata_provider_ids());
+		for plugin_id in plugin_ids.unwrap_or_default() {
+			providers.push(Box::new(PluginProvider {
+				plugin_id,
+				host: plugin_state.0.clone(),
+			}));
+		}
+
+		MetadataPipeline::new(providers)
+	};
+
+	let library_id = {
 		let db_lock = db_state.get().await;
-		let database = db_lock.as_ref().unwrap();
-		methods::library::set_scan_locations(database.inner_ref(), &scan_locations).await?;
+		let database = db_lock.as_ref().unwrap().inner_ref();
+
+		let library_id = match library_id {
+			Some(x) => x,
+			None => methods::library::get_active_id(database).await?,
+		};
+
+		methods::library::set_scan_locations(database, library_id, scan_locations).await?;
+		library_id
 	};
 
 	#[derive(Debug)]
@@ -53,19 +220,54 @@ pub async fn initialize_library(
 	let probe_handle = thread::Builder::new()
 		.name("melody_probe".to_string())
 		.spawn::<_, Result<()>>(move || {
-			for location in scan_locations.iter().map(PathBuf::from) {
+			let mut seen_artist_images = HashSet::<PathBuf>::new();
+
+			for (location, rules) in rules {
 				tx.send(ChannelData::Scanning(location.clone()))?;
 
-				let paths = walkdir_sync(&location, matchers::path::audio)?;
+				let paths = rules.walk(&location, |x| matcher.matches(x))?;
 				let total = paths.len() as u64;
 
 				for (i, path) in paths.into_iter().enumerate() {
-					let data = match read_track_meta(&path) {
-						Ok((x, y)) => ChannelData::Finished((i as u64 + 1, total), path.clone(), Box::new(x), y),
-						Err(e) => ChannelData::Err(e, path.clone()),
+					let tracks = match pipeline.read_all(&path) {
+						Ok(x) => x,
+						Err(e) => {
+							tx.send(ChannelData::Err(e, path.clone()))?;
+							continue;
+						}
 					};
 
-					tx.send(data)?;
+					// Files split by a CUE sheet yield a track for every entry, all sharing the same progress.
+					for (x, mut y) in tracks {
+						// Artist images are shared between every track of an artist, so we only read them once.
+						// They go to the artist picked in `handle_temp_track_meta`, if the folder is named after them.
+						let artist_name = x
+							.release_artists
+							.as_ref()
+							.and_then(|x| x.first())
+							.or_else(|| x.artists.as_ref().and_then(|x| x.first()))
+							.map(|x| x.person.name.as_str());
+
+						let image_path = artist_name
+							.filter(|_| read_artist_images)
+							.and_then(|name| find_artist_image(&path, name));
+
+						if let Some(image_path) = image_path {
+							if seen_artist_images.insert(image_path.clone()) {
+								match TempCover::from_path(CoverType::Artist, &image_path) {
+									Ok(cover) => y.artist_cover = Some(cover),
+									Err(e) => tx.send(ChannelData::Err(e, image_path))?,
+								}
+							}
+						}
+
+						tx.send(ChannelData::Finished(
+							(i as u64 + 1, total),
+							path.clone(),
+							Box::new(x),
+							y,
+						))?;
+					}
 				}
 			}
 
@@ -94,7 +296,8 @@ pub async fn initialize_library(
 				let payload = LibraryEventData::new(total, current, path);
 				em.emit(&window, LibraryEventPayload::indexing(payload))?;
 
-				handle_temp_track_meta(database.inner_ref(), &cover_dir, *meta, resources).await?;
+				handle_temp_track_meta(database.inner_ref(), &cover_dir, library_id, *meta, resources).await?;
+				indexed += 1;
 			}
 			ChannelData::Err(e, path) => {
 				error!("Error encountered while reading/indexing: {path:#?}\n{e:#?}");
@@ -107,5 +310,9 @@ pub async fn initialize_library(
 
 	info!("Finished building library in {:?}", start.elapsed());
 
+	plugin_state
+		.dispatch(PluginEvent::LibraryScanned { locations, indexed })
+		.await?;
+
 	Ok(())
 }
@@ -0,0 +1,69 @@
+use std::path::Path;
+
+use {bonsaidb::core::schema::SerializedCollection, tauri::State, tracing::warn};
+
+use crate::{
+	database::{
+		methods,
+		models::{lyrics::Lyrics, track::Track},
+	},
+	errors::{pre::database_entry_not_found, Result},
+	metadata::lyrics::{parse_lyrics, to_lrc},
+	models::state::DatabaseState,
+};
+
+#[tauri::command]
+#[tracing::instrument(skip(db_state), err(Debug))]
+pub async fn get_lyrics(track_id: u64, db_state: State<'_, DatabaseState>) -> Result<Option<Lyrics>> {
+	let db_guard = db_state.get().await;
+	let database = db_guard.as_ref().unwrap().inner_ref();
+
+	methods::lyrics::get(database, track_id).await
+}
+
+/// Replaces the lyrics of a track with the given LRC or plain text, removing them if it's blank.
+///
+/// The lyrics are also written into a `.lrc` file next to the track, so they take precedence in the next scan.
+/// Tracks sharing a file through a CUE sheet, or in a directory that can't be written to, only keep their lyrics in
+/// the database, marked as [Lyrics::edited] so rescans don't replace them.
+#[tauri::command]
+#[tracing::instrument(skip(contents, db_state), err(Debug))]
+pub async fn set_lyrics(track_id: u64, contents: String, db_state: State<'_, DatabaseState>) -> Result<Option<Lyrics>> {
+	let db_guard = db_state.get().await;
+	let database = db_guard.as_ref().unwrap().inner_ref();
+
+	let track = Track::get_async(&track_id, database)
+		.await?
+		.ok_or_else(|| database_entry_not_found("tracks", track_id))?;
+
+	let sidecar_path = Path::new(&track.contents.path).with_extension("lrc");
+	let has_sidecar = track.contents.segment.is_none();
+
+	if contents.trim().is_empty() {
+		methods::lyrics::delete(database, track_id).await?;
+
+		if has_sidecar && sidecar_path.is_file() {
+			if let Err(e) = std::fs::remove_file(&sidecar_path) {
+				warn!("Failed to remove the lyrics file {sidecar_path:?}: {e:#?}");
+			}
+		}
+
+		return Ok(None);
+	}
+
+	let mut lyrics = parse_lyrics(&contents, None).into_lyrics(track_id);
+	lyrics.edited = true;
+	methods::lyrics::set(database, lyrics.clone()).await?;
+
+	if has_sidecar {
+		match std::fs::write(&sidecar_path, to_lrc(&lyrics)) {
+			Ok(()) => {
+				lyrics.edited = false;
+				methods::lyrics::set(database, lyrics).await?;
+			}
+			Err(e) => warn!("Failed to write the lyrics file {sidecar_path:?}, keeping them in the database: {e:#?}"),
+		}
+	}
+
+	methods::lyrics::get(database, track_id).await
+}
@@ -1,4 +1,17 @@
+pub mod backup;
 pub mod general;
+pub mod label;
 pub mod library;
+pub mod lyrics;
+pub mod person;
+pub mod playback;
+pub mod playlist;
+pub mod plugin;
+pub mod portable;
 pub mod release;
+pub mod scrobble;
+pub mod settings;
+pub mod tag;
+pub mod theme;
 pub mod track;
+pub mod work;
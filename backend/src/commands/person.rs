@@ -0,0 +1,205 @@
+use std::{
+	collections::{HashMap, HashSet},
+	path::PathBuf,
+};
+
+use {
+	bonsaidb::core::{
+		document::DocumentId,
+		schema::{SerializedCollection, SerializedView},
+	},
+	tauri::State,
+	tokio::time::Instant,
+	tracing::debug,
+};
+
+use crate::{
+	database::{
+		helpers::initialize_image_resource,
+		methods,
+		models::{
+			cover::{Cover, CoverType},
+			person::{Person, PersonType},
+			release::Release,
+			track::Track,
+		},
+		views::{
+			release::ReleaseByArtistId,
+			track::{TrackByPerson, TrackByPersonKey},
+		},
+	},
+	errors::{pre::database_entry_not_found, Result},
+	models::{
+		state::{DatabaseState, DirectoryState},
+		tauri::{
+			cover::DisplayCover,
+			person::{DisplayArtist, PersonSplit},
+		},
+		temp::cover::TempCover,
+	},
+};
+
+#[tauri::command]
+#[tracing::instrument(skip(dir_state, db_state), err(Debug))]
+pub async fn get_artist(
+	person_id: u64,
+	dir_state: State<'_, DirectoryState>,
+	db_state: State<'_, DatabaseState>,
+) -> Result<DisplayArtist> {
+	let start = Instant::now();
+
+	let resource_cover_dir = {
+		let dir_guard = dir_state.get();
+		let directories = dir_guard.as_ref().unwrap();
+		directories.cover_dir.clone()
+	};
+
+	let db_guard = db_state.get().await;
+	let database = db_guard.as_ref().unwrap().inner_ref();
+
+	let person = Person::get_async(&person_id, database)
+		.await?
+		.ok_or_else(|| database_entry_not_found("people", person_id))?;
+
+	let library_id = methods::library::get_active_id(database).await?;
+	let mut artist_ids = HashSet::<DocumentId>::new();
+	let mut cover_ids = Vec::<DocumentId>::new();
+
+	if let Some(covers) = &person.contents.cover_ids {
+		covers.iter().for_each(|e| cover_ids.push(DocumentId::from_u64(*e)));
+	}
+
+	let release_entries = ReleaseByArtistId::entries_async(database)
+		.with_key(&person_id)
+		.query_with_collection_docs()
+		.await?;
+
+	let mut releases = HashMap::<u64, Release>::with_capacity(release_entries.len());
+	for (_, document) in release_entries.documents {
+		if document.contents.library_id != library_id {
+			continue;
+		}
+
+		for artist in &document.contents.artists {
+			artist_ids.insert(DocumentId::from_u64(artist.id));
+		}
+
+		if let Some(covers) = &document.contents.cover_ids {
+			covers.iter().for_each(|e| cover_ids.push(DocumentId::from_u64(*e)));
+		}
+
+		releases.insert(document.header.id, document.contents);
+	}
+
+	let mut release_ids = releases.keys().copied().collect::<Vec<u64>>();
+	release_ids.sort_by(|a, b| {
+		let (a, b) = (&releases[a], &releases[b]);
+		(a.date, a.year, &a.name).cmp(&(b.date, b.year, &b.name))
+	});
+
+	let mut tracks = HashMap::<u64, Track>::new();
+	let mut credits = HashMap::<PersonType, Vec<u64>>::new();
+
+	for type_ in PersonType::ROLES {
+		let entries = TrackByPerson::entries_async(database)
+			.with_key(&TrackByPersonKey::new(person_id, type_.clone()))
+			.query_with_collection_docs()
+			.await?;
+
+		let mut ids = Vec::new();
+		for (_, document) in entries.documents {
+			if document.contents.library_id != library_id {
+				continue;
+			}
+
+			for artist in &document.contents.artists {
+				artist_ids.insert(DocumentId::from_u64(artist.id));
+			}
+
+			ids.push(document.header.id);
+			tracks.insert(document.header.id, document.contents);
+		}
+
+		if !ids.is_empty() {
+			credits.insert(type_, ids);
+		}
+	}
+
+	let mut artists = HashMap::<u64, Person>::with_capacity(artist_ids.len());
+	let mut covers = HashMap::<u64, DisplayCover>::with_capacity(cover_ids.len());
+
+	for i in Person::get_multiple_async(&artist_ids, database).await? {
+		artists.insert(i.header.id, i.contents);
+	}
+
+	for i in Cover::get_multiple_async(&cover_ids, database).await? {
+		covers.insert(i.header.id, DisplayCover::from_cover(i.contents, &resource_cover_dir));
+	}
+
+	debug!("Finished building display artist query in {:?}", start.elapsed());
+
+	Ok(DisplayArtist {
+		person: person.contents,
+		release_ids,
+		track_ids: credits.remove(&PersonType::Artist).unwrap_or_default(),
+		credited_track_ids: credits,
+		releases,
+		tracks,
+		artists,
+		covers,
+	})
+}
+
+/// Manually sets the image of an artist, replacing the existing one.
+///
+/// The image is kept over an `artist.jpg` like image in the folder of the artist when the library is scanned.
+#[tauri::command]
+#[tracing::instrument(skip(dir_state, db_state), err(Debug))]
+pub async fn set_artist_image(
+	person_id: u64,
+	path: PathBuf,
+	dir_state: State<'_, DirectoryState>,
+	db_state: State<'_, DatabaseState>,
+) -> Result<u64> {
+	let cover_dir = {
+		let dir_guard = dir_state.get();
+		let directories = dir_guard.as_ref().unwrap();
+		directories.cover_dir.clone()
+	};
+
+	let temp = tokio::task::spawn_blocking(move || TempCover::from_path(CoverType::Artist, &path)).await??;
+
+	let db_guard = db_state.get().await;
+	let database = db_guard.as_ref().unwrap().inner_ref();
+
+	let cover_id = initialize_image_resource(database, &cover_dir, temp).await?;
+	methods::person::set_manual_cover(database, person_id, cover_id).await?;
+
+	Ok(cover_id)
+}
+
+/// Merges a person into another one, see [methods::person::merge].
+#[tauri::command]
+#[tracing::instrument(skip(db_state), err(Debug))]
+pub async fn merge_people(source_id: u64, target_id: u64, db_state: State<'_, DatabaseState>) -> Result<()> {
+	let db_guard = db_state.get().await;
+	let database = db_guard.as_ref().unwrap().inner_ref();
+
+	methods::person::merge(database, source_id, target_id).await
+}
+
+/// Moves the given credits of a person into a new person, see [methods::person::split].
+#[tauri::command]
+#[tracing::instrument(skip(db_state), err(Debug))]
+pub async fn split_person(
+	person_id: u64,
+	person: PersonSplit,
+	track_ids: HashSet<u64>,
+	release_ids: HashSet<u64>,
+	db_state: State<'_, DatabaseState>,
+) -> Result<u64> {
+	let db_guard = db_state.get().await;
+	let database = db_guard.as_ref().unwrap().inner_ref();
+
+	methods::person::split(database, person_id, person, track_ids, release_ids).await
+}
@@ -0,0 +1,72 @@
+use tauri::State;
+
+use crate::{
+	errors::Result,
+	models::{
+		playback::{Playback, PlaybackReport, PlaybackTrack},
+		state::{DatabaseState, DirectoryState, PlaybackState, PluginState, ScrobbleState},
+	},
+	plugins::PluginEvent,
+};
+
+#[tauri::command]
+#[tracing::instrument(skip(playback_state), err(Debug))]
+pub async fn get_playback(playback_state: State<'_, PlaybackState>) -> Result<Playback> {
+	Ok(playback_state.get().await.clone())
+}
+
+/// Reports the state of the player, which is mirrored to the system media controls.
+///
+/// Plugins with the `playback_events` capability are notified when the track or the status changes,
+/// and the played tracks are submitted to the enabled scrobbling services.
+#[tauri::command]
+#[tracing::instrument(skip(dir_state, db_state, playback_state, plugin_state, scrobble_state), err(Debug))]
+pub async fn set_playback(
+	report: PlaybackReport,
+	dir_state: State<'_, DirectoryState>,
+	db_state: State<'_, DatabaseState>,
+	playback_state: State<'_, PlaybackState>,
+	plugin_state: State<'_, PluginState>,
+	scrobble_state: State<'_, ScrobbleState>,
+) -> Result<()> {
+	let current_track = playback_state.get().await.track.clone();
+
+	let track = match report.track_id {
+		Some(id) if current_track.as_ref().is_some_and(|x| x.id == id) => current_track,
+		Some(id) => {
+			let cover_dir = {
+				let dir_guard = dir_state.get();
+				dir_guard.as_ref().unwrap().cover_dir.clone()
+			};
+
+			let db_guard = db_state.get().await;
+			let database = db_guard.as_ref().unwrap().inner_ref();
+
+			Some(PlaybackTrack::resolve(database, &cover_dir, id).await?)
+		}
+		None => None,
+	};
+
+	let playback = Playback {
+		status: report.status,
+		track,
+		position: report.position,
+		volume: report.volume.clamp(0.0, 1.0),
+		can_go_next: report.can_go_next,
+		can_go_previous: report.can_go_previous,
+	};
+
+	scrobble_state.report(&playback, report.duration).await?;
+	let previous = playback_state.set(playback, report.seeked).await?;
+
+	if previous.status != report.status || previous.track_id() != report.track_id {
+		let event = PluginEvent::PlaybackChanged {
+			track_id: report.track_id,
+			status: report.status,
+		};
+
+		plugin_state.dispatch(event).await?;
+	}
+
+	Ok(())
+}
@@ -0,0 +1,114 @@
+use std::{
+	collections::{HashMap, HashSet},
+	path::PathBuf,
+};
+
+use {
+	bonsaidb::core::{document::DocumentId, schema::SerializedCollection},
+	tauri::State,
+};
+
+use crate::{
+	database::{
+		methods,
+		models::{person::Person, playlist::Playlist, track::Track},
+	},
+	errors::{pre::database_entry_not_found, Result},
+	models::{
+		state::DatabaseState,
+		tauri::{
+			playlist::{PlaylistEntry, PlaylistImport},
+			track::DisplayTrackList,
+		},
+	},
+	playlists,
+};
+
+#[tauri::command]
+#[tracing::instrument(skip(db_state), err(Debug))]
+pub async fn get_playlists(db_state: State<'_, DatabaseState>) -> Result<Vec<PlaylistEntry>> {
+	let db_guard = db_state.get().await;
+	let database = db_guard.as_ref().unwrap().inner_ref();
+
+	let playlists = methods::playlist::get_all(database)
+		.await?
+		.into_iter()
+		.map(|(id, playlist)| PlaylistEntry { id, playlist })
+		.collect();
+
+	Ok(playlists)
+}
+
+/// Gets the tracks of a playlist in its order, where a track may appear more than once.
+#[tauri::command]
+#[tracing::instrument(skip(db_state), err(Debug))]
+pub async fn get_playlist_tracks(playlist_id: u64, db_state: State<'_, DatabaseState>) -> Result<DisplayTrackList> {
+	let db_guard = db_state.get().await;
+	let database = db_guard.as_ref().unwrap().inner_ref();
+
+	let tracks = methods::playlist::get_tracks(database, playlist_id)
+		.await?
+		.into_iter()
+		.map(|(_, x)| x)
+		.collect::<Vec<Track>>();
+
+	let artist_ids = tracks
+		.iter()
+		.flat_map(|x| x.artists.iter().map(|e| DocumentId::from_u64(e.id)))
+		.collect::<HashSet<DocumentId>>();
+
+	let mut artists = HashMap::<u64, Person>::with_capacity(artist_ids.len());
+	for i in Person::get_multiple_async(&artist_ids, database).await? {
+		artists.insert(i.header.id, i.contents);
+	}
+
+	Ok(DisplayTrackList { tracks, artists })
+}
+
+/// Imports an M3U, M3U8, PLS or XSPF file as a new playlist, reporting the entries missing from the library.
+#[tauri::command]
+#[tracing::instrument(skip(db_state), err(Debug))]
+pub async fn import_playlist(path: PathBuf, db_state: State<'_, DatabaseState>) -> Result<PlaylistImport> {
+	let db_guard = db_state.get().await;
+	let database = db_guard.as_ref().unwrap().inner_ref();
+
+	playlists::import(database, &path).await
+}
+
+/// Exports a playlist into a file, in the format of its extension.
+#[tauri::command]
+#[tracing::instrument(skip(db_state), err(Debug))]
+pub async fn export_playlist(playlist_id: u64, path: PathBuf, db_state: State<'_, DatabaseState>) -> Result<()> {
+	let db_guard = db_state.get().await;
+	let database = db_guard.as_ref().unwrap().inner_ref();
+
+	let playlist = Playlist::get_async(&playlist_id, database)
+		.await?
+		.ok_or_else(|| database_entry_not_found("playlists", playlist_id))?
+		.contents;
+
+	let tracks = methods::playlist::get_tracks(database, playlist_id).await?;
+	playlists::export(database, &path, Some(playlist.name), &tracks).await
+}
+
+/// Exports a list of tracks, like the play queue, into a file in the format of its extension.
+#[tauri::command]
+#[tracing::instrument(skip(track_ids, db_state), err(Debug))]
+pub async fn export_tracks(track_ids: Vec<u64>, path: PathBuf, db_state: State<'_, DatabaseState>) -> Result<()> {
+	let db_guard = db_state.get().await;
+	let database = db_guard.as_ref().unwrap().inner_ref();
+
+	let ids = track_ids.iter().map(|x| DocumentId::from_u64(*x)).collect::<Vec<_>>();
+	let found = Track::get_multiple_async(&ids, database)
+		.await?
+		.into_iter()
+		.map(|x| (x.header.id, x.contents))
+		.collect::<HashMap<u64, Track>>();
+
+	let tracks = track_ids
+		.into_iter()
+		.filter_map(|id| Some((id, found.get(&id)?.clone())))
+		.collect::<Vec<(u64, Track)>>();
+
+	playlists::export(database, &path, None, &tracks).await
+}
@@ -0,0 +1,35 @@
+use {serde_json::Value, tauri::State};
+
+use crate::{
+	errors::{pre::plugins_unavailable, Result},
+	models::{state::PluginState, tauri::plugin::PluginEntry},
+};
+
+/// Lists the loaded plugins, along with the ones that failed to load.
+#[tauri::command]
+#[tracing::instrument(skip(plugin_state), err(Debug))]
+pub async fn get_plugins(plugin_state: State<'_, PluginState>) -> Result<Vec<PluginEntry>> {
+	let plugin_guard = plugin_state.get().await;
+	let host = plugin_guard.as_ref().ok_or_else(plugins_unavailable)?;
+
+	Ok(host.entries())
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(plugin_state), err(Debug))]
+pub async fn invoke_plugin_command(
+	plugin_id: String,
+	command: String,
+	payload: Value,
+	plugin_state: State<'_, PluginState>,
+) -> Result<Value> {
+	let state = plugin_state.0.clone();
+
+	tokio::task::spawn_blocking(move || {
+		let mut plugin_guard = state.blocking_lock();
+		let host = plugin_guard.as_mut().ok_or_else(plugins_unavailable)?;
+
+		host.invoke_command(&plugin_id, &command, payload)
+	})
+	.await?
+}
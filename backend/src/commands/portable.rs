@@ -0,0 +1,51 @@
+use std::path::PathBuf;
+
+use {tauri::State, tracing::info};
+
+use crate::{
+	database::methods,
+	errors::Result,
+	models::{
+		state::DatabaseState,
+		tauri::portable::{PathMapping, PortableImport},
+	},
+	portable,
+};
+
+/// Exports the metadata of the active library into a JSON Lines file.
+#[tauri::command]
+#[tracing::instrument(skip(db_state), err(Debug))]
+pub async fn export_library_data(path: PathBuf, db_state: State<'_, DatabaseState>) -> Result<()> {
+	let db_guard = db_state.get().await;
+	let database = db_guard.as_ref().unwrap().inner_ref();
+
+	let library_id = methods::library::get_active_id(database).await?;
+	let contents = portable::export(database, library_id).await?;
+	tokio::fs::write(path, contents).await?;
+
+	Ok(())
+}
+
+/// Merges an export into the active library, rewriting the paths of its tracks through `path_mappings`.
+#[tauri::command]
+#[tracing::instrument(skip(db_state), err(Debug))]
+pub async fn import_library_data(
+	path: PathBuf,
+	path_mappings: Vec<PathMapping>,
+	db_state: State<'_, DatabaseState>,
+) -> Result<PortableImport> {
+	let db_guard = db_state.get().await;
+	let database = db_guard.as_ref().unwrap().inner_ref();
+
+	let library_id = methods::library::get_active_id(database).await?;
+	let contents = tokio::fs::read_to_string(path).await?;
+	let summary = portable::import(database, library_id, &contents, &path_mappings).await?;
+
+	info!(
+		"Imported library data, matching {} tracks while {} were left unmatched",
+		summary.tracks,
+		summary.unmatched_paths.len()
+	);
+
+	Ok(summary)
+}
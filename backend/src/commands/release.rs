@@ -1,10 +1,7 @@
 use std::collections::{HashMap, HashSet};
 
 use {
-	bonsaidb::core::{
-		document::DocumentId,
-		schema::{SerializedCollection, SerializedView},
-	},
+	bonsaidb::core::{document::DocumentId, schema::SerializedCollection},
 	tauri::State,
 	tokio::time::Instant,
 	tracing::debug,
@@ -12,15 +9,16 @@ use {
 
 use crate::{
 	database::{
+		methods,
 		models::{cover::Cover, person::Person, release::Release},
-		views::release::ReleaseByNameAndArtist,
 	},
 	errors::Result,
 	models::{
 		state::{DatabaseState, DirectoryState},
 		tauri::{
 			cover::DisplayCover,
-			release::{DisplayReleases, ReleaseEntity},
+			release::{DisplayReleasePage, DisplayReleases, ReleaseEntity, ReleaseFilter, ReleaseSort},
+			PageRequest,
 		},
 	},
 };
@@ -31,16 +29,12 @@ pub async fn get_releases(db_state: State<'_, DatabaseState>) -> Result<Vec<Rele
 	let db_guard = db_state.get().await;
 	let database = db_guard.as_ref().unwrap();
 
-	let entries = ReleaseByNameAndArtist::entries_async(database.inner_ref())
-		.query_with_docs()
-		.await?;
-	let mut releases = Vec::with_capacity(entries.len());
-
-	for mapping in &entries {
-		let id = mapping.document.header.id.deserialize::<u64>()?;
-		let release = Release::document_contents(mapping.document)?;
-		releases.push(ReleaseEntity::new(id, release))
-	}
+	let library_id = methods::library::get_active_id(database.inner_ref()).await?;
+	let releases = methods::release::get_all(database.inner_ref(), library_id)
+		.await?
+		.into_iter()
+		.map(|(id, release)| ReleaseEntity::new(id, release))
+		.collect();
 
 	Ok(releases)
 }
@@ -62,7 +56,9 @@ pub async fn get_display_releases(
 	let db_guard = db_state.get().await;
 	let database = db_guard.as_ref().unwrap();
 
-	let entries = Release::all_async(database.inner_ref()).await?;
+	let library_id = methods::library::get_active_id(database.inner_ref()).await?;
+	let release_ids = methods::library::get_release_ids(database.inner_ref(), library_id).await?;
+	let entries = Release::get_multiple_async(&release_ids, database.inner_ref()).await?;
 
 	let mut releases = HashMap::<u64, Release>::with_capacity(entries.len());
 	let mut artist_ids = HashSet::<DocumentId>::new();
@@ -101,3 +97,54 @@ pub async fn get_display_releases(
 		covers,
 	})
 }
+
+#[tauri::command]
+#[tracing::instrument(skip(dir_state, db_state), err(Debug))]
+pub async fn query_releases(
+	page: PageRequest,
+	sort: ReleaseSort,
+	filter: Option<ReleaseFilter>,
+	dir_state: State<'_, DirectoryState>,
+	db_state: State<'_, DatabaseState>,
+) -> Result<DisplayReleasePage> {
+	let start = Instant::now();
+
+	let resource_cover_dir = {
+		let dir_guard = dir_state.get();
+		let directories = dir_guard.as_ref().unwrap();
+		directories.cover_dir.clone()
+	};
+
+	let db_guard = db_state.get().await;
+	let database = db_guard.as_ref().unwrap();
+
+	let page = methods::release::query(database.inner_ref(), page, sort, filter.unwrap_or_default()).await?;
+
+	let mut artist_ids = HashSet::<DocumentId>::new();
+	let mut cover_ids = Vec::<DocumentId>::new();
+
+	for entity in &page.items {
+		for artist in &entity.attributes.artists {
+			artist_ids.insert(DocumentId::from_u64(artist.id));
+		}
+
+		if let Some(covers) = &entity.attributes.cover_ids {
+			covers.iter().for_each(|e| cover_ids.push(DocumentId::from_u64(*e)));
+		}
+	}
+
+	let mut artists = HashMap::<u64, Person>::with_capacity(artist_ids.len());
+	let mut covers = HashMap::<u64, DisplayCover>::with_capacity(cover_ids.len());
+
+	for i in Person::get_multiple_async(&artist_ids, database.inner_ref()).await? {
+		artists.insert(i.header.id, i.contents);
+	}
+
+	for i in Cover::get_multiple_async(&cover_ids, database.inner_ref()).await? {
+		covers.insert(i.header.id, DisplayCover::from_cover(i.contents, &resource_cover_dir));
+	}
+
+	debug!("Finished building release page query in {:?}", start.elapsed());
+
+	Ok(DisplayReleasePage { page, artists, covers })
+}
@@ -0,0 +1,40 @@
+use {reqwest::Client, tauri::State};
+
+use crate::{
+	errors::Result,
+	models::{
+		configuration::Configuration,
+		state::{ConfigurationState, DirectoryState},
+	},
+	scrobbler::services,
+};
+
+/// Signs in to Last.fm with the API key and secret from the settings, storing the session key it returns.
+///
+/// The password is only sent to Last.fm and never saved. Returns the updated settings.
+#[tauri::command]
+#[tracing::instrument(skip(password, dir_state, config_state), err(Debug))]
+pub async fn authenticate_lastfm(
+	username: String,
+	password: String,
+	dir_state: State<'_, DirectoryState>,
+	config_state: State<'_, ConfigurationState>,
+) -> Result<Configuration> {
+	let lastfm = {
+		let config_guard = config_state.get();
+		config_guard.as_ref().unwrap().scrobbling.lastfm.clone()
+	};
+
+	let session_key = services::authenticate_lastfm(&Client::new(), &lastfm, &username, &password).await?;
+
+	let settings_path = {
+		let dir_guard = dir_state.get();
+		dir_guard.as_ref().unwrap().settings_path()
+	};
+
+	let mut settings = config_state.get().clone().unwrap();
+	settings.scrobbling.lastfm.session_key = Some(session_key);
+	config_state.set(&settings_path, settings.clone())?;
+
+	Ok(settings)
+}
@@ -0,0 +1,45 @@
+use tauri::State;
+
+use crate::{
+	errors::Result,
+	models::{
+		configuration::Configuration,
+		state::{ConfigurationState, DatabaseState, DirectoryState, SubsonicState},
+	},
+};
+
+#[tauri::command]
+#[tracing::instrument(skip(config_state), err(Debug))]
+pub async fn get_settings(config_state: State<'_, ConfigurationState>) -> Result<Configuration> {
+	let config_guard = config_state.get();
+	let configuration = config_guard.as_ref().unwrap();
+
+	Ok(configuration.clone())
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(dir_state, config_state, db_state, subsonic_state), err(Debug))]
+pub async fn set_settings(
+	settings: Configuration,
+	dir_state: State<'_, DirectoryState>,
+	config_state: State<'_, ConfigurationState>,
+	db_state: State<'_, DatabaseState>,
+	subsonic_state: State<'_, SubsonicState>,
+) -> Result<()> {
+	let (settings_path, cover_dir) = {
+		let dir_guard = dir_state.get();
+		let directories = dir_guard.as_ref().unwrap();
+		(directories.settings_path(), directories.cover_dir.clone())
+	};
+
+	settings.validate()?;
+
+	let database = {
+		let db_guard = db_state.get().await;
+		db_guard.as_ref().unwrap().inner_ref().clone()
+	};
+
+	// Settings are only saved once they're applied, so a taken port doesn't leave them saved but not running.
+	subsonic_state.apply(&settings.server, database, cover_dir).await?;
+	config_state.set(&settings_path, settings)
+}
@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+
+use {bonsaidb::core::schema::SerializedView, tauri::State, tokio::time::Instant, tracing::debug};
+
+use crate::{
+	database::{
+		methods,
+		models::tag::TagType,
+		views::{release::ReleaseByGenreId, tag::TagByType, track::TrackByGenreId},
+	},
+	errors::Result,
+	models::{state::DatabaseState, tauri::tag::GenreEntry},
+};
+
+#[tauri::command]
+#[tracing::instrument(skip(db_state), err(Debug))]
+pub async fn get_genres(db_state: State<'_, DatabaseState>) -> Result<Vec<GenreEntry>> {
+	let start = Instant::now();
+
+	let db_guard = db_state.get().await;
+	let database = db_guard.as_ref().unwrap().inner_ref();
+
+	let entries = TagByType::entries_async(database)
+		.with_key(&TagType::Genre)
+		.query_with_collection_docs()
+		.await?;
+
+	// Counts are limited to the active library, so they can't be read from the reduced views.
+	let library_id = methods::library::get_active_id(database).await?;
+	let release_ids = methods::library::get_release_ids(database, library_id).await?;
+	let track_ids = methods::library::get_track_ids(database, library_id).await?;
+
+	let mut release_counts = HashMap::<u64, u64>::new();
+	for mapping in &ReleaseByGenreId::entries_async(database).query().await? {
+		if release_ids.contains(&mapping.source.id) {
+			*release_counts.entry(mapping.key).or_default() += 1;
+		}
+	}
+
+	let mut track_counts = HashMap::<u64, u64>::new();
+	for mapping in &TrackByGenreId::entries_async(database).query().await? {
+		if track_ids.contains(&mapping.source.id) {
+			*track_counts.entry(mapping.key).or_default() += 1;
+		}
+	}
+
+	let mut genres = entries
+		.documents
+		.into_iter()
+		.map(|(id, document)| GenreEntry {
+			id,
+			tag: document.contents,
+			release_count: release_counts.get(&id).copied().unwrap_or_default(),
+			track_count: track_counts.get(&id).copied().unwrap_or_default(),
+		})
+		.collect::<Vec<GenreEntry>>();
+
+	genres.sort_by(|a, b| a.tag.name.to_lowercase().cmp(&b.tag.name.to_lowercase()));
+
+	debug!("Finished building genre list query in {:?}", start.elapsed());
+
+	Ok(genres)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(db_state), err(Debug))]
+pub async fn set_genre_parent(genre_id: u64, parent_id: Option<u64>, db_state: State<'_, DatabaseState>) -> Result<()> {
+	let db_guard = db_state.get().await;
+	let database = db_guard.as_ref().unwrap().inner_ref();
+
+	methods::tag::set_parent(database, genre_id, parent_id).await
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(db_state), err(Debug))]
+pub async fn add_genre_alias(genre_id: u64, alias: String, db_state: State<'_, DatabaseState>) -> Result<()> {
+	let db_guard = db_state.get().await;
+	let database = db_guard.as_ref().unwrap().inner_ref();
+
+	methods::tag::add_alias(database, genre_id, alias).await
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(db_state), err(Debug))]
+pub async fn merge_genres(source_id: u64, target_id: u64, db_state: State<'_, DatabaseState>) -> Result<()> {
+	let db_guard = db_state.get().await;
+	let database = db_guard.as_ref().unwrap().inner_ref();
+
+	methods::tag::merge_genres(database, source_id, target_id).await
+}
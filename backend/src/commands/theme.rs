@@ -0,0 +1,53 @@
+use tauri::State;
+
+use crate::{
+	errors::Result,
+	models::{
+		state::{ConfigurationState, DirectoryState},
+		theme::{self, Theme, ThemeEntry},
+	},
+};
+
+/// Lists the themes in the themes directory, including the invalid ones.
+#[tauri::command]
+#[tracing::instrument(skip(dir_state), err(Debug))]
+pub async fn get_themes(dir_state: State<'_, DirectoryState>) -> Result<Vec<ThemeEntry>> {
+	let themes_dir = {
+		let dir_guard = dir_state.get();
+		let directories = dir_guard.as_ref().unwrap();
+		directories.themes_dir.clone()
+	};
+
+	tokio::task::spawn_blocking(move || theme::discover(&themes_dir)).await?
+}
+
+/// Loads the theme with the given id, or the one selected in the settings if none was given.
+///
+/// Returns none if no theme was given nor selected, in which case the frontend should use its bundled theme.
+#[tauri::command]
+#[tracing::instrument(skip(dir_state, config_state), err(Debug))]
+pub async fn get_theme(
+	theme_id: Option<String>,
+	dir_state: State<'_, DirectoryState>,
+	config_state: State<'_, ConfigurationState>,
+) -> Result<Option<Theme>> {
+	let themes_dir = {
+		let dir_guard = dir_state.get();
+		let directories = dir_guard.as_ref().unwrap();
+		directories.themes_dir.clone()
+	};
+
+	let theme_id = theme_id.or_else(|| {
+		let config_guard = config_state.get();
+		let configuration = config_guard.as_ref().unwrap();
+		configuration.appearance.theme.as_ref().map(|x| x.name.clone())
+	});
+
+	let Some(theme_id) = theme_id else {
+		return Ok(None);
+	};
+
+	let theme = tokio::task::spawn_blocking(move || Theme::load(&themes_dir, &theme_id)).await??;
+
+	Ok(Some(theme))
+}
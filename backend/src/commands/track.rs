@@ -12,11 +12,18 @@ use {
 
 use crate::{
 	database::{
+		methods,
 		models::{person::Person, track::Track},
 		views::track::TrackByReleaseId,
 	},
 	errors::Result,
-	models::{state::DatabaseState, tauri::track::DisplayTrackList},
+	models::{
+		state::DatabaseState,
+		tauri::{
+			track::{DisplayTrackList, DisplayTrackPage, TrackFilter, TrackSort},
+			PageRequest,
+		},
+	},
 };
 
 #[tauri::command]
@@ -60,3 +67,48 @@ pub async fn get_track_list_for_release(
 
 	Ok(DisplayTrackList { tracks, artists })
 }
+
+#[tauri::command]
+#[tracing::instrument(skip(db_state), err(Debug))]
+pub async fn query_tracks(
+	page: PageRequest,
+	sort: TrackSort,
+	filter: Option<TrackFilter>,
+	db_state: tauri::State<'_, DatabaseState>,
+) -> Result<DisplayTrackPage> {
+	let start = Instant::now();
+
+	let db_guard = db_state.get().await;
+	let database = db_guard.as_ref().unwrap();
+
+	let page = methods::track::query(database.inner_ref(), page, sort, filter.unwrap_or_default()).await?;
+
+	let artist_ids = page
+		.items
+		.iter()
+		.flat_map(|e| e.attributes.artists.iter().map(|x| DocumentId::from_u64(x.id)))
+		.collect::<HashSet<DocumentId>>();
+
+	let mut artists = HashMap::<u64, Person>::with_capacity(artist_ids.len());
+	for i in Person::get_multiple_async(&artist_ids, database.inner_ref()).await? {
+		artists.insert(i.header.id, i.contents);
+	}
+
+	debug!("Finished building track page query in {:?}", start.elapsed());
+
+	Ok(DisplayTrackPage { page, artists })
+}
+
+/// Rates a track from 1 to 5, or removes its rating when `rating` is missing.
+#[tauri::command]
+#[tracing::instrument(skip(db_state), err(Debug))]
+pub async fn set_track_rating(
+	track_id: u64,
+	rating: Option<u8>,
+	db_state: tauri::State<'_, DatabaseState>,
+) -> Result<()> {
+	let db_guard = db_state.get().await;
+	let database = db_guard.as_ref().unwrap().inner_ref();
+
+	methods::track::set_rating(database, track_id, rating).await
+}
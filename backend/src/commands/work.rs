@@ -0,0 +1,149 @@
+use std::collections::{HashMap, HashSet};
+
+use {
+	bonsaidb::core::{document::DocumentId, schema::SerializedCollection},
+	tauri::State,
+	tokio::time::Instant,
+	tracing::debug,
+};
+
+use crate::{
+	database::{
+		methods,
+		models::{cover::Cover, person::Person, release::Release, work::Work},
+	},
+	errors::{pre::database_entry_not_found, Result},
+	models::{
+		state::{DatabaseState, DirectoryState},
+		tauri::{
+			cover::DisplayCover,
+			work::{DisplayWork, WorkEntry, WorkRecording},
+		},
+	},
+};
+
+#[tauri::command]
+#[tracing::instrument(skip(db_state), err(Debug))]
+pub async fn get_works(db_state: State<'_, DatabaseState>) -> Result<Vec<WorkEntry>> {
+	let start = Instant::now();
+
+	let db_guard = db_state.get().await;
+	let database = db_guard.as_ref().unwrap().inner_ref();
+
+	let library_id = methods::library::get_active_id(database).await?;
+	let counts = methods::work::get_recording_counts(database, library_id).await?;
+
+	let ids = counts.keys().copied().collect::<Vec<u64>>();
+	let mut works = Work::get_multiple_async(&ids, database)
+		.await?
+		.into_iter()
+		.map(|doc| WorkEntry {
+			id: doc.header.id,
+			recording_count: counts[&doc.header.id],
+			work: doc.contents,
+		})
+		.collect::<Vec<WorkEntry>>();
+
+	works.sort_by_cached_key(|e| e.work.name.to_lowercase());
+
+	debug!("Finished building work list query in {:?}", start.elapsed());
+
+	Ok(works)
+}
+
+#[tauri::command]
+#[tracing::instrument(skip(dir_state, db_state), err(Debug))]
+pub async fn get_work(
+	work_id: u64,
+	dir_state: State<'_, DirectoryState>,
+	db_state: State<'_, DatabaseState>,
+) -> Result<DisplayWork> {
+	let start = Instant::now();
+
+	let resource_cover_dir = {
+		let dir_guard = dir_state.get();
+		let directories = dir_guard.as_ref().unwrap();
+		directories.cover_dir.clone()
+	};
+
+	let db_guard = db_state.get().await;
+	let database = db_guard.as_ref().unwrap().inner_ref();
+
+	let work = Work::get_async(&work_id, database)
+		.await?
+		.ok_or_else(|| database_entry_not_found("works", work_id))?;
+
+	let library_id = methods::library::get_active_id(database).await?;
+	let track_ids = methods::library::get_track_ids(database, library_id).await?;
+
+	let mut artist_ids = work
+		.contents
+		.composer_ids
+		.iter()
+		.map(|x| DocumentId::from_u64(*x))
+		.collect::<HashSet<DocumentId>>();
+	let mut cover_ids = Vec::<DocumentId>::new();
+	let mut recordings = Vec::<WorkRecording>::new();
+	let mut tracks = HashMap::new();
+
+	for (release_id, release_tracks) in methods::work::get_recordings(database, work_id, Some(&track_ids)).await? {
+		let mut ids = Vec::with_capacity(release_tracks.len());
+
+		for (id, track) in release_tracks {
+			for artist in &track.artists {
+				artist_ids.insert(DocumentId::from_u64(artist.id));
+			}
+
+			ids.push(id);
+			tracks.insert(id, track);
+		}
+
+		recordings.push(WorkRecording {
+			release_id,
+			track_ids: ids,
+		});
+	}
+
+	let release_ids = recordings.iter().map(|x| x.release_id).collect::<Vec<u64>>();
+	let mut releases = HashMap::<u64, Release>::with_capacity(release_ids.len());
+
+	for i in Release::get_multiple_async(&release_ids, database).await? {
+		for artist in &i.contents.artists {
+			artist_ids.insert(DocumentId::from_u64(artist.id));
+		}
+
+		if let Some(covers) = &i.contents.cover_ids {
+			covers.iter().for_each(|e| cover_ids.push(DocumentId::from_u64(*e)));
+		}
+
+		releases.insert(i.header.id, i.contents);
+	}
+
+	// Recordings are ordered by the release date, rather than the release id they're grouped by.
+	recordings.sort_by(|a, b| {
+		let key = |x: &WorkRecording| releases.get(&x.release_id).map(|y| (y.date, y.year));
+		key(a).cmp(&key(b))
+	});
+
+	let mut artists = HashMap::<u64, Person>::with_capacity(artist_ids.len());
+	let mut covers = HashMap::<u64, DisplayCover>::with_capacity(cover_ids.len());
+
+	for i in Person::get_multiple_async(&artist_ids, database).await? {
+		artists.insert(i.header.id, i.contents);
+	}
+
+	for i in Cover::get_multiple_async(&cover_ids, database).await? {
+		covers.insert(i.header.id, DisplayCover::from_cover(i.contents, &resource_cover_dir));
+	}
+
+	debug!("Finished building display work query in {:?}", start.elapsed());
+
+	Ok(DisplayWork {
+		work: work.contents,
+		recordings,
+		releases,
+		tracks,
+		artists,
+		covers,
+	})
+}
@@ -1,7 +1,21 @@
-pub const SUPPORTED_AUDIO_EXTENSIONS: [&str; 5] = ["wav", "flac", "opus", "ogg", "mp3"];
+/// Extensions read as audio without probing, unless replaced in the scanner settings.
+pub const DEFAULT_AUDIO_EXTENSIONS: [&str; 21] = [
+	"wav", "flac", "opus", "ogg", "oga", "mp3", "m4a", "m4b", "aac", "alac", "wv", "ape", "aif", "aiff", "aifc", "dsf",
+	"dff", "tta", "wma", "mka", "mpc",
+];
+/// Extensions commonly found next to audio files, which are never probed.
+pub const NON_AUDIO_EXTENSIONS: [&str; 22] = [
+	"jpg", "jpeg", "png", "gif", "webp", "bmp", "tif", "tiff", "txt", "nfo", "log", "cue", "lrc", "json", "m3u",
+	"m3u8", "pls", "xspf", "pdf", "md5", "sfv", "accurip",
+];
+pub const ARTIST_IMAGE_FILE_NAMES: [&str; 3] = ["artist.jpg", "artist.jpeg", "artist.png"];
 
 pub const UNKNOWN_PERSON_ID: u64 = 0;
 pub const UNKNOWN_RELEASE_ID: u64 = 0;
+pub const DEFAULT_LIBRARY_ID: u64 = 0;
 
 #[cfg(test)]
 pub const TEST_RELEASE_NAME: &str = "Test Release";
+
+#[cfg(test)]
+pub const TEST_TRACK_PATH: &str = "/music/Test Artist/Test Release/01 Test Track.flac";
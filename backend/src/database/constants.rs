@@ -1,3 +1,7 @@
 pub const DB_MAIN_NAME: &str = "main.bonsaidb";
 pub const KEY_IS_FIRST_RUN: &str = "is_first_run";
+/// Scan locations from before libraries were stored as documents, moved into the default library on startup.
 pub const KEY_SCAN_LOCATIONS: &str = "library_scan_locations";
+pub const KEY_ACTIVE_LIBRARY: &str = "active_library_id";
+/// Version of the documents in the database, upgraded on startup by the [migrations](super::migrations).
+pub const KEY_SCHEMA_VERSION: &str = "schema_version";
@@ -1,22 +1,46 @@
-use std::{fs, path::Path};
+use std::{
+	collections::{HashMap, HashSet},
+	fs,
+	path::Path,
+};
 
 use {
-	bonsaidb::{core::schema::SerializedCollection, local::AsyncDatabase},
+	bonsaidb::{
+		core::{
+			connection::{AsyncConnection, Bound, Range},
+			schema::{SerializedCollection, SerializedView},
+		},
+		local::AsyncDatabase,
+	},
+	chrono::{DateTime, Utc},
 	image::{imageops::FilterType, ImageFormat},
+	serde::Serialize,
 };
 
 use crate::{
+	constants::UNKNOWN_PERSON_ID,
 	database::{
 		methods,
-		models::{cover::Cover, InlinedArtist},
+		models::{
+			cover::Cover,
+			label::Label,
+			person::{Person, PersonType},
+			release::Release,
+			tag::Tag,
+			track::{Credit, Track},
+			InlinedArtist,
+		},
+		views::{count_sort_value, track::TrackByReleaseId, SortKey},
 	},
 	errors::{pre::unsupported_image_type, Result},
 	models::{
 		directories,
+		tauri::{Cursor, Entity, Page, PageRequest, SortDirection},
 		temp::{
 			cover::TempCover, release::TempReleaseIntoArg, track::TempTrackIntoArg, TempTrackMeta, TempTrackResource,
 		},
 	},
+	utils::fs::find_release_dir,
 };
 
 /// Initializes an image resource and inserts the resource into the database, checking if the resource by same hash exists.
@@ -56,14 +80,14 @@ pub async fn initialize_image_resource(database: &AsyncDatabase, cover_dir: &Pat
 pub async fn handle_temp_track_meta(
 	database: &AsyncDatabase,
 	cover_dir: &Path,
+	library_id: u64,
 	meta: TempTrackMeta,
 	resource: TempTrackResource,
 ) -> Result<()> {
-	let temp_track = meta.track.expect("Yeah, no track metadata.");
+	let mut temp_track = meta.track.expect("Yeah, no track metadata.");
 
 	let mut artists = None::<Vec<InlinedArtist>>;
-	let mut composer_ids = None::<Vec<u64>>;
-	let mut producer_ids = None::<Vec<u64>>;
+	let mut credits = Vec::<Credit>::new();
 
 	let mut label_ids = None::<Vec<u64>>;
 	let mut genre_ids = None::<Vec<u64>>;
@@ -84,22 +108,26 @@ pub async fn handle_temp_track_meta(
 		}
 	}
 
-	if let Some(temp_composers) = meta.composers {
-		let x = composer_ids.get_or_insert(Vec::with_capacity(temp_composers.len()));
+	for temp_credit in meta.credits.unwrap_or_default() {
+		let id = methods::person::get_or_insert(database, temp_credit.person.clone()).await?;
+		let credit = temp_credit.into_credit(id);
 
-		for temp_composer in temp_composers {
-			let id = methods::person::get_or_insert(database, temp_composer).await?;
-			x.push(id);
+		if !credits.contains(&credit) {
+			credits.push(credit);
 		}
 	}
 
-	if let Some(temp_producers) = meta.producers {
-		let x = producer_ids.get_or_insert(Vec::with_capacity(temp_producers.len()));
-
-		for temp_producer in temp_producers {
-			let id = methods::person::get_or_insert(database, temp_producer).await?;
-			x.push(id);
-		}
+	// Works are attributed to the composers credited in the track.
+	let mut work_id = None::<u64>;
+	if let Some(temp_work) = meta.work {
+		let composer_ids = credits
+			.iter()
+			.filter(|x| x.type_ == PersonType::Composer)
+			.map(|x| x.person_id)
+			.collect();
+
+		let id = methods::work::get_or_insert(database, temp_work.into_work(composer_ids)).await?;
+		work_id = Some(id);
 	}
 
 	if let Some(temp_labels) = meta.labels {
@@ -138,6 +166,19 @@ pub async fn handle_temp_track_meta(
 		}
 	}
 
+	if let Some(temp) = resource.artist_cover {
+		let artist = release_artists
+			.as_ref()
+			.and_then(|x| x.first())
+			.or_else(|| artists.as_ref().and_then(|x| x.first()));
+
+		if let Some(artist) = artist.filter(|x| x.id != UNKNOWN_PERSON_ID) {
+			let cover_id = initialize_image_resource(database, cover_dir, temp).await?;
+			// The image on the disk takes over a scanned one, so replacing it is picked up on the next scan.
+			methods::person::set_cover(database, artist.id, cover_id, true).await?;
+		}
+	}
+
 	if let Some(release_covers) = resource.release_covers {
 		let x = release_cover_ids.insert(Vec::with_capacity(release_covers.len()));
 
@@ -156,31 +197,366 @@ pub async fn handle_temp_track_meta(
 		}
 	}
 
-	if let Some(temp) = meta.release {
+	if let Some(mut temp) = meta.release {
+		// A track past the first disc is enough to tell the release spans several discs.
+		temp.total_discs = temp.total_discs.or(temp_track.disc_number.filter(|x| *x > 1));
+
+		if temp.artist_sort.is_none() {
+			let x = release_artists.as_deref().unwrap_or_default();
+			temp.artist_sort = methods::person::get_artist_sort(database, x).await?;
+		}
+
 		let release = temp.into_release(TempReleaseIntoArg {
 			artists: release_artists,
 			label_ids,
 			genre_ids: genre_ids.clone(),
 			tag_ids: tag_ids.clone(),
 			cover_ids: release_cover_ids,
+			library_id,
+			directory: find_release_dir(Path::new(&meta.path)).map(|x| x.to_string_lossy().to_string()),
 		});
 
 		let id = methods::release::get_or_insert(database, release).await?;
 		release_id = Some(id);
 	}
 
-	temp_track
-		.into_track(TempTrackIntoArg {
-			artists,
-			release_id,
-			composer_ids,
-			producer_ids,
-			genre_ids,
-			tag_ids,
-			cover_ids: track_cover_ids,
-		})
-		.push_into_async(database)
-		.await?;
+	if temp_track.artist_sort.is_none() {
+		let x = artists.as_deref().unwrap_or_default();
+		temp_track.artist_sort = methods::person::get_artist_sort(database, x).await?;
+	}
+
+	let track = temp_track.into_track(TempTrackIntoArg {
+		artists,
+		release_id,
+		credits,
+		work_id,
+		genre_ids,
+		tag_ids,
+		cover_ids: track_cover_ids,
+		library_id,
+	});
+
+	let track_id = methods::track::insert_or_update(database, track).await?;
+
+	// Lyrics that were only saved in the database would be lost otherwise, so they're kept over the scanned ones.
+	let existing = methods::lyrics::get(database, track_id).await?;
+	if !existing.is_some_and(|x| x.edited) {
+		match meta.lyrics {
+			Some(temp) => {
+				methods::lyrics::set(database, temp.into_lyrics(track_id)).await?;
+			}
+			None => methods::lyrics::delete(database, track_id).await?,
+		}
+	}
+
+	Ok(())
+}
+
+/// Replaces an id in a list of ids, dropping the duplicates that may arise.
+pub fn replace_id(ids: &mut Vec<u64>, from: u64, to: u64) {
+	let mut seen = HashSet::with_capacity(ids.len());
+
+	ids.iter_mut().filter(|x| **x == from).for_each(|x| *x = to);
+	ids.retain(|x| seen.insert(*x));
+}
+
+/// Walks through a view sorted by [SortKey], collecting up to [PageRequest::limit] documents.
+///
+/// Documents are only collected if their id is in `candidates` (when given) and they pass the `filter`.
+/// Entries are read in batches, so filtered out documents don't count towards the limit.
+pub async fn paginate<V, F>(
+	database: &AsyncDatabase,
+	request: PageRequest,
+	candidates: Option<&HashSet<u64>>,
+	filter: F,
+) -> Result<Page<V::Collection>>
+where
+	V: SerializedView<Key = SortKey>,
+	V::Collection: SerializedCollection<Contents = V::Collection, PrimaryKey = u64> + Serialize,
+	F: Fn(&V::Collection) -> bool,
+{
+	const BATCH_SIZE: u32 = 256;
+
+	let limit = request.limit as usize;
+	if limit == 0 {
+		return Ok(Page {
+			items: Vec::new(),
+			next_cursor: request.cursor,
+		});
+	}
+
+	let mut items = Vec::with_capacity(limit);
+	let mut cursor = request.cursor.map(SortKey::from);
+
+	loop {
+		let view = database.view::<V>().limit(BATCH_SIZE);
+		let view = match request.direction {
+			SortDirection::Ascending => view.ascending(),
+			SortDirection::Descending => view.descending(),
+		};
+
+		let view = match (cursor.clone(), request.direction) {
+			(None, _) => view,
+			(Some(key), SortDirection::Ascending) => view.with_key_range(Range {
+				start: Bound::Excluded(key),
+				end: Bound::Unbounded,
+			}),
+			(Some(key), SortDirection::Descending) => view.with_key_range(Range {
+				start: Bound::Unbounded,
+				end: Bound::Excluded(key),
+			}),
+		};
+
+		let mappings = view.query().await?;
+		let batch_len = mappings.len();
+
+		let ids = mappings
+			.iter()
+			.map(|m| m.source.id)
+			.filter(|id| candidates.map_or(true, |x| x.contains(id)))
+			.collect::<Vec<u64>>();
+
+		let mut documents = V::Collection::get_multiple_async(&ids, database)
+			.await?
+			.into_iter()
+			.map(|doc| (doc.header.id, doc.contents))
+			.collect::<HashMap<u64, V::Collection>>();
+
+		for mapping in mappings {
+			let id = mapping.source.id;
+			cursor = Some(mapping.key);
+
+			match documents.remove(&id) {
+				Some(contents) if filter(&contents) => items.push(Entity::new(id, contents)),
+				_ => continue,
+			}
+
+			if items.len() >= limit {
+				return Ok(Page {
+					items,
+					next_cursor: cursor.map(Cursor::from),
+				});
+			}
+		}
+
+		if batch_len < BATCH_SIZE as usize {
+			return Ok(Page {
+				items,
+				next_cursor: None,
+			});
+		}
+	}
+}
+
+/// Builds a [SortKey] from the play count of every id, where the ids without a count were never played.
+pub fn get_play_count_keys(ids: &HashSet<u64>, counts: &HashMap<u64, u64>) -> Vec<SortKey> {
+	ids.iter()
+		.map(|id| (count_sort_value(counts.get(id).copied().unwrap_or(0)), *id))
+		.collect()
+}
+
+/// Collects up to [PageRequest::limit] documents ordered by the given keys, the same way [paginate] walks a view.
+///
+/// Meant for sort values that come from other collections, like play counts, so `keys` holds a [SortKey] for every
+/// candidate document. Documents are only collected if they pass the `filter`.
+pub async fn paginate_by_keys<C, F>(
+	database: &AsyncDatabase,
+	request: PageRequest,
+	mut keys: Vec<SortKey>,
+	filter: F,
+) -> Result<Page<C>>
+where
+	C: SerializedCollection<Contents = C, PrimaryKey = u64> + Serialize,
+	F: Fn(&C) -> bool,
+{
+	const BATCH_SIZE: usize = 256;
+
+	let limit = request.limit as usize;
+	if limit == 0 {
+		return Ok(Page {
+			items: Vec::new(),
+			next_cursor: request.cursor,
+		});
+	}
+
+	keys.sort();
+	if request.direction == SortDirection::Descending {
+		keys.reverse();
+	}
+
+	if let Some(cursor) = request.cursor.map(SortKey::from) {
+		keys.retain(|x| match request.direction {
+			SortDirection::Ascending => *x > cursor,
+			SortDirection::Descending => *x < cursor,
+		});
+	}
+
+	let mut items = Vec::with_capacity(limit);
+
+	for batch in keys.chunks(BATCH_SIZE) {
+		let ids = batch.iter().map(|(_, id)| *id).collect::<Vec<u64>>();
+		let mut documents = C::get_multiple_async(&ids, database)
+			.await?
+			.into_iter()
+			.map(|doc| (doc.header.id, doc.contents))
+			.collect::<HashMap<u64, C>>();
+
+		for key in batch {
+			match documents.remove(&key.1) {
+				Some(contents) if filter(&contents) => items.push(Entity::new(key.1, contents)),
+				_ => continue,
+			}
+
+			if items.len() >= limit {
+				return Ok(Page {
+					items,
+					next_cursor: Some(Cursor::from(key.clone())),
+				});
+			}
+		}
+	}
+
+	Ok(Page {
+		items,
+		next_cursor: None,
+	})
+}
+
+/// Fills in the added and modified dates of the documents saved before they were tracked, which read as the Unix epoch.
+///
+/// Tracks take the modification time of their file and releases the earliest date of their tracks, which is the
+/// closest there is to when they were added. Everything else takes the current time.
+pub async fn backfill_dates(database: &AsyncDatabase) -> Result<()> {
+	let now = Utc::now();
+
+	for mut doc in Track::all_async(database).await? {
+		if doc.contents.date_added != DateTime::<Utc>::default() {
+			continue;
+		}
+
+		let modified = fs::metadata(&doc.contents.path).and_then(|x| x.modified());
+		let date = modified.map_or(now, DateTime::<Utc>::from);
+
+		doc.contents.date_added = date;
+		doc.contents.date_modified = date;
+		doc.update_async(database).await?;
+	}
+
+	for mut doc in Release::all_async(database).await? {
+		if doc.contents.date_added != DateTime::<Utc>::default() {
+			continue;
+		}
+
+		let tracks = TrackByReleaseId::entries_async(database)
+			.with_key(&doc.header.id)
+			.query_with_collection_docs()
+			.await?;
+		let date = tracks.documents.values().map(|x| x.contents.date_added).min();
+
+		doc.contents.date_added = date.unwrap_or(now);
+		doc.contents.date_modified = date.unwrap_or(now);
+		doc.update_async(database).await?;
+	}
+
+	backfill_dates_with::<Cover>(database, now, |x| (&mut x.date_added, &mut x.date_modified)).await?;
+	backfill_dates_with::<Label>(database, now, |x| (&mut x.date_added, &mut x.date_modified)).await?;
+	backfill_dates_with::<Person>(database, now, |x| (&mut x.date_added, &mut x.date_modified)).await?;
+	backfill_dates_with::<Tag>(database, now, |x| (&mut x.date_added, &mut x.date_modified)).await
+}
+
+async fn backfill_dates_with<C>(
+	database: &AsyncDatabase,
+	date: DateTime<Utc>,
+	dates: fn(&mut C) -> (&mut DateTime<Utc>, &mut DateTime<Utc>),
+) -> Result<()>
+where
+	C: SerializedCollection<Contents = C, PrimaryKey = u64>,
+{
+	for mut doc in C::all_async(database).await? {
+		let (added, modified) = dates(&mut doc.contents);
+		if *added != DateTime::<Utc>::default() {
+			continue;
+		}
+
+		*added = date;
+		*modified = date;
+		doc.update_async(database).await?;
+	}
 
 	Ok(())
 }
+
+#[cfg(test)]
+mod test {
+	use std::{
+		fs::{self, File},
+		time::{Duration, SystemTime},
+	};
+
+	use {
+		bonsaidb::core::schema::{SerializedCollection, SerializedView},
+		chrono::DateTime,
+	};
+
+	use crate::{
+		database::{
+			helpers::backfill_dates,
+			models::{release::Release, track::Track},
+			views::{release::ReleaseByDateAdded, track::TrackByDateAdded},
+			Database,
+		},
+		errors::Result,
+	};
+
+	#[tokio::test]
+	async fn test_backfill_dates() -> Result<()> {
+		let db = Database::testing().await?;
+		let dbx = db.0;
+
+		let root = std::env::temp_dir().join(format!("melody-dates-{}", ulid::Ulid::new()));
+		fs::create_dir_all(&root)?;
+
+		let mut release_ids = Vec::new();
+		let mut track_ids = Vec::new();
+		let now = SystemTime::now();
+
+		// Saved in the opposite order of when their files were added.
+		for days in [1, 3, 2] {
+			let release = Release {
+				name: format!("Release {days}"),
+				date_added: DateTime::default(),
+				date_modified: DateTime::default(),
+				..Default::default()
+			};
+			let release_id = release.push_into_async(&dbx).await?.header.id;
+
+			let path = root.join(format!("{days}.flac"));
+			File::create(&path)?.set_modified(now - Duration::from_secs(days * 24 * 60 * 60))?;
+
+			let track = Track {
+				path: path.to_string_lossy().to_string(),
+				release_id,
+				date_added: DateTime::default(),
+				date_modified: DateTime::default(),
+				..Default::default()
+			};
+
+			release_ids.push(release_id);
+			track_ids.push(track.push_into_async(&dbx).await?.header.id);
+		}
+
+		backfill_dates(&dbx).await?;
+
+		let tracks = TrackByDateAdded::entries_async(&dbx).query().await?;
+		let ids = tracks.iter().map(|x| x.source.id).collect::<Vec<u64>>();
+		assert_eq!(ids, vec![track_ids[1], track_ids[2], track_ids[0]]);
+
+		let releases = ReleaseByDateAdded::entries_async(&dbx).query().await?;
+		let ids = releases.iter().map(|x| x.source.id).collect::<Vec<u64>>();
+		assert_eq!(ids, vec![release_ids[1], release_ids[2], release_ids[0]]);
+
+		fs::remove_dir_all(root)?;
+
+		Ok(())
+	}
+}
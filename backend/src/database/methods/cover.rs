@@ -1,14 +1,24 @@
+use std::{collections::HashSet, fs, io::ErrorKind, path::Path};
+
 use {
 	blake3::Hash,
-	bonsaidb::{core::schema::SerializedView, local::AsyncDatabase},
+	bonsaidb::{
+		core::schema::{SerializedCollection, SerializedView},
+		local::AsyncDatabase,
+	},
 };
 
 use crate::{
 	database::{
-		models::cover::CoverType,
-		views::cover::{CoverByTypeAndHash, CoverByTypeAndHashKey},
+		models::cover::{Cover, CoverType},
+		views::{
+			cover::{CoverByTypeAndHash, CoverByTypeAndHashKey},
+			release::ReleaseByCoverId,
+			track::TrackByCoverId,
+		},
 	},
 	errors::Result,
+	models::directories,
 };
 
 pub async fn get_id(database: &AsyncDatabase, type_: CoverType, hash: Hash) -> Result<Option<u64>> {
@@ -21,3 +31,61 @@ pub async fn get_id(database: &AsyncDatabase, type_: CoverType, hash: Hash) -> R
 
 	Ok(matches.first().map(|e| e.source.id))
 }
+
+/// Deletes the track and release covers no longer used by any track or release, along with their files.
+///
+/// Artist covers are left alone as the artists outlive the tracks they're credited in.
+pub async fn delete_unused(database: &AsyncDatabase, cover_dir: &Path, cover_ids: HashSet<u64>) -> Result<()> {
+	for cover_id in cover_ids {
+		let Some(doc) = Cover::get_async(&cover_id, database).await? else {
+			continue;
+		};
+
+		if doc.contents.type_ == CoverType::Artist || is_used(database, cover_id).await? {
+			continue;
+		}
+
+		// Covers of another type with the same image share the file.
+		let mut shared = false;
+		for type_ in [CoverType::Artist, CoverType::Release, CoverType::Track] {
+			if type_ != doc.contents.type_ && get_id(database, type_, doc.contents.hash).await?.is_some() {
+				shared = true;
+			}
+		}
+
+		if !shared {
+			let cover = &doc.contents;
+			let path =
+				directories::get_cover_path(cover_dir, &cover.hash, cover.media_type.as_extension(), cover.has_thumb);
+
+			match fs::remove_file(path) {
+				Err(e) if e.kind() != ErrorKind::NotFound => return Err(e.into()),
+				_ => {}
+			}
+		}
+
+		doc.delete_async(database).await?;
+	}
+
+	Ok(())
+}
+
+async fn is_used(database: &AsyncDatabase, cover_id: u64) -> Result<bool> {
+	let tracks = TrackByCoverId::entries_async(database)
+		.with_key(&cover_id)
+		.limit(1)
+		.query()
+		.await?;
+
+	if !tracks.is_empty() {
+		return Ok(true);
+	}
+
+	let releases = ReleaseByCoverId::entries_async(database)
+		.with_key(&cover_id)
+		.limit(1)
+		.query()
+		.await?;
+
+	Ok(!releases.is_empty())
+}
@@ -1,11 +1,18 @@
-use bonsaidb::{
-	core::schema::{SerializedCollection, SerializedView},
-	local::AsyncDatabase,
+use {
+	bonsaidb::{
+		core::schema::{SerializedCollection, SerializedView},
+		local::AsyncDatabase,
+	},
+	chrono::Utc,
 };
 
 use crate::{
-	database::{models::label::Label, views::label::LabelByName},
-	errors::Result,
+	database::{
+		models::{label::Label, release::Release, CountryCode},
+		views::{label::LabelByName, release::ReleaseByLabelId},
+	},
+	errors::{pre::database_entry_not_found, Result},
+	utils::sort::natural_cmp,
 };
 
 pub async fn get_or_insert(database: &AsyncDatabase, label: Label) -> Result<u64> {
@@ -24,12 +31,65 @@ pub async fn get_or_insert(database: &AsyncDatabase, label: Label) -> Result<u64
 	Ok(id)
 }
 
+/// Gets the releases published under a label, ordered by their catalog number and then their release date.
+///
+/// Releases without a catalog number are placed last.
+pub async fn get_releases(database: &AsyncDatabase, label_id: u64) -> Result<Vec<(u64, Release)>> {
+	let entries = ReleaseByLabelId::entries_async(database)
+		.with_key(&label_id)
+		.query_with_collection_docs()
+		.await?;
+
+	let mut releases = entries
+		.documents
+		.into_iter()
+		.map(|(id, document)| (id, document.contents))
+		.collect::<Vec<(u64, Release)>>();
+
+	releases.sort_by(|(_, a), (_, b)| {
+		let catalog = match (&a.catalog_number, &b.catalog_number) {
+			(Some(x), Some(y)) => natural_cmp(x, y),
+			(x, y) => x.is_none().cmp(&y.is_none()),
+		};
+
+		catalog.then_with(|| (a.date, a.year, &a.name).cmp(&(b.date, b.year, &b.name)))
+	});
+
+	Ok(releases)
+}
+
+/// Updates the user editable metadata of a label.
+pub async fn set_metadata(
+	database: &AsyncDatabase,
+	label_id: u64,
+	name_sort: Option<String>,
+	country: Option<CountryCode>,
+	mbz_id: Option<String>,
+) -> Result<()> {
+	let mut label = Label::get_async(&label_id, database)
+		.await?
+		.ok_or_else(|| database_entry_not_found("labels", label_id))?;
+
+	label.contents.name_sort = name_sort;
+	label.contents.country = country;
+	label.contents.mbz_id = mbz_id;
+	label.contents.date_modified = Utc::now();
+	label.update_async(database).await?;
+
+	Ok(())
+}
+
 #[cfg(test)]
 mod test {
 	use bonsaidb::core::schema::{SerializedCollection, SerializedView};
 
 	use crate::{
-		database::{methods::label::get_or_insert, models::label::Label, views::label::LabelByName, Database},
+		database::{
+			methods::label::{get_or_insert, get_releases},
+			models::{label::Label, release::Release},
+			views::label::LabelByName,
+			Database,
+		},
 		errors::Result,
 	};
 
@@ -39,9 +99,7 @@ mod test {
 		let db = database.0;
 
 		for i in 0..10 {
-			let label = Label {
-				name: format!("Label {i}"),
-			};
+			let label = Label::new(format!("Label {i}"));
 
 			label.push_into_async(&db).await?;
 		}
@@ -60,17 +118,38 @@ mod test {
 		let db = Database::testing().await?;
 		let dbx = db.0;
 
-		let label = Label {
-			name: "Label 1".to_string(),
-		};
+		let label = Label::new("Label 1".to_string());
 		let doc = label.push_into_async(&dbx).await?;
 
-		let label = Label {
-			name: "Label 1".to_string(),
-		};
+		let label = Label::new("Label 1".to_string());
 		let result = get_or_insert(&dbx, label).await?;
 		assert_eq!(result, doc.header.id);
 
 		Ok(())
 	}
+
+	#[tokio::test]
+	async fn test_get_releases() -> Result<()> {
+		let db = Database::testing().await?;
+		let dbx = db.0;
+
+		let label = Label::new("Label 1".to_string()).push_into_async(&dbx).await?;
+
+		for (name, catalog_number) in [("A", Some("LBL-10")), ("B", None), ("C", Some("LBL-9"))] {
+			let release = Release {
+				name: name.to_string(),
+				catalog_number: catalog_number.map(str::to_string),
+				label_ids: Some(vec![label.header.id]),
+				..Default::default()
+			};
+
+			release.push_into_async(&dbx).await?;
+		}
+
+		let releases = get_releases(&dbx, label.header.id).await?;
+		let names = releases.iter().map(|(_, x)| x.name.as_str()).collect::<Vec<&str>>();
+		assert_eq!(names, vec!["C", "A", "B"]);
+
+		Ok(())
+	}
 }
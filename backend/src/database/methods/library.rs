@@ -1,19 +1,344 @@
-use bonsaidb::{core::keyvalue::AsyncKeyValue, local::AsyncDatabase};
+use std::{collections::HashSet, path::Path};
 
-use crate::{database::constants::KEY_SCAN_LOCATIONS, errors::Result};
+use {
+	bonsaidb::{
+		core::{
+			keyvalue::AsyncKeyValue,
+			schema::{SerializedCollection, SerializedView},
+		},
+		local::AsyncDatabase,
+	},
+	chrono::Utc,
+};
 
-pub async fn set_scan_locations(database: &AsyncDatabase, scan_locations: &Vec<String>) -> Result<()> {
-	database.set_key(KEY_SCAN_LOCATIONS, scan_locations).await?;
+use crate::{
+	constants::DEFAULT_LIBRARY_ID,
+	database::{
+		constants::{KEY_ACTIVE_LIBRARY, KEY_SCAN_LOCATIONS},
+		methods,
+		models::{
+			library::{Library, ScanLocation},
+			release::Release,
+			track::Track,
+		},
+		views::{release::ReleaseByLibraryId, track::TrackByLibraryId},
+	},
+	errors::{
+		pre::{database_entry_not_found, default_library_deletion},
+		Result,
+	},
+	utils::scan::ScanRules,
+};
+
+/// Creates the default library if it doesn't exist yet.
+///
+/// Scan locations saved before libraries were stored as documents are moved into it.
+pub async fn ensure_default(database: &AsyncDatabase) -> Result<()> {
+	if Library::get_async(&DEFAULT_LIBRARY_ID, database).await?.is_some() {
+		return Ok(());
+	}
+
+	let scan_locations = database
+		.get_key(KEY_SCAN_LOCATIONS)
+		.await?
+		.map(|rx| rx.deserialize::<Vec<String>>())
+		.transpose()?;
+
+	let scan_locations = scan_locations
+		.unwrap_or_default()
+		.into_iter()
+		.map(ScanLocation::new)
+		.collect();
+
+	let library = Library::new("Main".to_string(), scan_locations);
+	library.insert_into_async(&DEFAULT_LIBRARY_ID, database).await?;
+	database.delete_key(KEY_SCAN_LOCATIONS).await?;
+
+	Ok(())
+}
+
+/// Gets every library, with the default library first.
+pub async fn get_all(database: &AsyncDatabase) -> Result<Vec<(u64, Library)>> {
+	let libraries = Library::all_async(database)
+		.await?
+		.into_iter()
+		.map(|x| (x.header.id, x.contents))
+		.collect();
+
+	Ok(libraries)
+}
+
+pub async fn get(database: &AsyncDatabase, library_id: u64) -> Result<Library> {
+	let library = Library::get_async(&library_id, database)
+		.await?
+		.ok_or_else(|| database_entry_not_found("libraries", library_id))?;
+
+	Ok(library.contents)
+}
+
+pub async fn insert(database: &AsyncDatabase, library: Library) -> Result<u64> {
+	validate_scan_locations(&library.scan_locations)?;
+
+	let doc = library.push_into_async(database).await?;
+	Ok(doc.header.id)
+}
+
+pub async fn update(
+	database: &AsyncDatabase,
+	library_id: u64,
+	name: String,
+	scan_locations: Vec<ScanLocation>,
+) -> Result<()> {
+	validate_scan_locations(&scan_locations)?;
+
+	let mut doc = Library::get_async(&library_id, database)
+		.await?
+		.ok_or_else(|| database_entry_not_found("libraries", library_id))?;
+
+	doc.contents.name = name;
+	doc.contents.scan_locations = scan_locations;
+	doc.contents.date_modified = Utc::now();
+	doc.update_async(database).await?;
+
+	Ok(())
+}
+
+pub async fn set_scan_locations(
+	database: &AsyncDatabase,
+	library_id: u64,
+	scan_locations: Vec<ScanLocation>,
+) -> Result<()> {
+	let library = get(database, library_id).await?;
+	update(database, library_id, library.name, scan_locations).await
+}
+
+/// Deletes a library along with its tracks, their lyrics and its releases.
+///
+/// The deleted tracks are removed from the playlists and the scrobble queue, and the covers nothing else uses are
+/// deleted with their files. The default library can't be deleted, and becomes the active one if the deleted library
+/// was active.
+pub async fn delete(database: &AsyncDatabase, cover_dir: &Path, library_id: u64) -> Result<()> {
+	if library_id == DEFAULT_LIBRARY_ID {
+		return Err(default_library_deletion());
+	}
+
+	let doc = Library::get_async(&library_id, database)
+		.await?
+		.ok_or_else(|| database_entry_not_found("libraries", library_id))?;
+
+	let track_ids = get_track_ids(database, library_id).await?;
+	let mut cover_ids = HashSet::<u64>::new();
+
+	for track_id in &track_ids {
+		methods::lyrics::delete(database, *track_id).await?;
+		if let Some(track) = Track::get_async(track_id, database).await? {
+			cover_ids.extend(track.contents.cover_ids.iter().flatten());
+			track.delete_async(database).await?;
+		}
+	}
+
+	for release_id in get_release_ids(database, library_id).await? {
+		if let Some(release) = Release::get_async(&release_id, database).await? {
+			cover_ids.extend(release.contents.cover_ids.iter().flatten());
+			release.delete_async(database).await?;
+		}
+	}
+
+	methods::playlist::remove_tracks(database, &track_ids).await?;
+	methods::scrobble::delete_by_track_ids(database, &track_ids).await?;
+	methods::play::delete_by_track_ids(database, &track_ids).await?;
+	methods::cover::delete_unused(database, cover_dir, cover_ids).await?;
+
+	doc.delete_async(database).await?;
+
+	if get_active_id(database).await? == library_id {
+		set_active_id(database, DEFAULT_LIBRARY_ID).await?;
+	}
 
 	Ok(())
 }
 
-pub async fn get_scan_locations(database: &AsyncDatabase) -> Result<Option<Vec<String>>> {
+/// Gets the id of the library the queries are scoped to, which is the default library unless another was picked.
+pub async fn get_active_id(database: &AsyncDatabase) -> Result<u64> {
 	let x = database
-		.get_key(KEY_SCAN_LOCATIONS)
+		.get_key(KEY_ACTIVE_LIBRARY)
 		.await?
-		.map(|rx| rx.deserialize::<Vec<String>>())
+		.map(|rx| rx.deserialize::<u64>())
 		.transpose()?;
 
-	Ok(x)
+	Ok(x.unwrap_or(DEFAULT_LIBRARY_ID))
+}
+
+pub async fn set_active_id(database: &AsyncDatabase, library_id: u64) -> Result<()> {
+	if Library::get_async(&library_id, database).await?.is_none() {
+		return Err(database_entry_not_found("libraries", library_id));
+	}
+
+	database.set_key(KEY_ACTIVE_LIBRARY, &library_id).await?;
+
+	Ok(())
+}
+
+pub async fn get_track_ids(database: &AsyncDatabase, library_id: u64) -> Result<HashSet<u64>> {
+	let x = TrackByLibraryId::entries_async(database)
+		.with_key(&library_id)
+		.query()
+		.await?;
+
+	Ok(x.iter().map(|e| e.source.id).collect())
+}
+
+pub async fn get_release_ids(database: &AsyncDatabase, library_id: u64) -> Result<HashSet<u64>> {
+	let x = ReleaseByLibraryId::entries_async(database)
+		.with_key(&library_id)
+		.query()
+		.await?;
+
+	Ok(x.iter().map(|e| e.source.id).collect())
+}
+
+/// Checks the patterns of the scan options, so invalid ones are rejected before they're saved.
+fn validate_scan_locations(scan_locations: &[ScanLocation]) -> Result<()> {
+	for location in scan_locations {
+		ScanRules::new(&location.options)?;
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod test {
+	use std::fs;
+
+	use {
+		bonsaidb::core::{keyvalue::AsyncKeyValue, schema::SerializedCollection},
+		chrono::Utc,
+	};
+
+	use crate::{
+		constants::DEFAULT_LIBRARY_ID,
+		database::{
+			constants::KEY_SCAN_LOCATIONS,
+			methods::{
+				self,
+				library::{delete, ensure_default, get, get_active_id, get_track_ids, insert, set_active_id},
+			},
+			models::{
+				cover::{Cover, CoverMediaType, CoverType},
+				library::{Library, ScanLocation},
+				play::Play,
+				playlist::Playlist,
+				release::Release,
+				scrobble::{Scrobble, ScrobbleService, ScrobbleTrack},
+				track::Track,
+			},
+			Database,
+		},
+		errors::Result,
+		models::directories::get_cover_path,
+	};
+
+	#[tokio::test]
+	async fn test_ensure_default() -> Result<()> {
+		let db = Database::testing().await?;
+		let dbx = db.0;
+
+		let locations = vec!["/music".to_string()];
+		dbx.set_key(KEY_SCAN_LOCATIONS, &locations).await?;
+
+		ensure_default(&dbx).await?;
+		ensure_default(&dbx).await?;
+
+		let library = get(&dbx, DEFAULT_LIBRARY_ID).await?;
+		assert_eq!(library.scan_locations, vec![ScanLocation::new("/music".to_string())]);
+		assert!(dbx.get_key(KEY_SCAN_LOCATIONS).await?.is_none());
+		assert_eq!(Library::all_async(&dbx).await?.len(), 1);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_delete() -> Result<()> {
+		let db = Database::testing().await?;
+		let dbx = db.0;
+
+		ensure_default(&dbx).await?;
+		let library_id = insert(&dbx, Library::new("Other".to_string(), vec![])).await?;
+		set_active_id(&dbx, library_id).await?;
+
+		let cover_dir = std::env::temp_dir().join(format!("melody-library-{}", ulid::Ulid::new()));
+		fs::create_dir_all(&cover_dir)?;
+
+		let hash = blake3::hash(b"cover");
+		let cover_path = get_cover_path(&cover_dir, &hash, "png", false);
+		fs::write(&cover_path, b"cover")?;
+
+		let cover = Cover {
+			type_: CoverType::Release,
+			media_type: CoverMediaType::Png,
+			resolution: (1, 1),
+			comment: None,
+			has_thumb: false,
+			hash,
+			date_added: Utc::now(),
+			date_modified: Utc::now(),
+		};
+		let cover_id = cover.push_into_async(&dbx).await?.header.id;
+
+		let release = Release {
+			library_id,
+			cover_ids: Some(vec![cover_id]),
+			..Default::default()
+		};
+		let release_id = methods::release::get_or_insert(&dbx, release).await?;
+		let default_release_id = methods::release::get_or_insert(&dbx, Release::default()).await?;
+		assert_ne!(release_id, default_release_id);
+
+		let track = Track {
+			library_id,
+			release_id,
+			..Default::default()
+		};
+		let track_id = methods::track::insert_or_update(&dbx, track).await?;
+		let default_track_id = methods::track::insert_or_update(&dbx, Track::default()).await?;
+		assert_ne!(track_id, default_track_id);
+		assert_eq!(get_track_ids(&dbx, library_id).await?.len(), 1);
+
+		let playlist = Playlist::new("Mixed".to_string(), vec![track_id, default_track_id, track_id]);
+		let playlist_id = methods::playlist::insert(&dbx, playlist).await?;
+
+		let scrobble_track = ScrobbleTrack {
+			track_id,
+			title: "Test Track".to_string(),
+			artists: vec![],
+			release: None,
+			release_artists: vec![],
+			track_number: None,
+			duration: None,
+			recording_mbz_id: None,
+			release_mbz_id: None,
+		};
+		let scrobble = Scrobble::new(ScrobbleService::ListenBrainz, scrobble_track, Utc::now());
+		let scrobble_id = methods::scrobble::enqueue(&dbx, scrobble).await?;
+		methods::play::insert(&dbx, Play::new(track_id, Utc::now())).await?;
+
+		assert!(delete(&dbx, &cover_dir, DEFAULT_LIBRARY_ID).await.is_err());
+		delete(&dbx, &cover_dir, library_id).await?;
+
+		assert!(Track::get_async(&track_id, &dbx).await?.is_none());
+		assert!(Release::get_async(&release_id, &dbx).await?.is_none());
+		assert!(Track::get_async(&default_track_id, &dbx).await?.is_some());
+		assert_eq!(get_active_id(&dbx).await?, DEFAULT_LIBRARY_ID);
+
+		let playlist = Playlist::get_async(&playlist_id, &dbx).await?.unwrap();
+		assert_eq!(playlist.contents.track_ids, vec![default_track_id]);
+		assert!(Scrobble::get_async(&scrobble_id, &dbx).await?.is_none());
+		assert!(methods::play::get_by_track_id(&dbx, track_id).await?.is_empty());
+		assert!(Cover::get_async(&cover_id, &dbx).await?.is_none());
+		assert!(!cover_path.exists());
+
+		fs::remove_dir_all(&cover_dir)?;
+
+		Ok(())
+	}
 }
@@ -0,0 +1,104 @@
+use {
+	bonsaidb::{
+		core::{
+			document::CollectionDocument,
+			schema::{SerializedCollection, SerializedView},
+		},
+		local::AsyncDatabase,
+	},
+	chrono::Utc,
+};
+
+use crate::{
+	database::{models::lyrics::Lyrics, views::lyrics::LyricsByTrackId},
+	errors::Result,
+};
+
+async fn get_document(database: &AsyncDatabase, track_id: u64) -> Result<Option<CollectionDocument<Lyrics>>> {
+	let matches = LyricsByTrackId::entries_async(database)
+		.with_key(&track_id)
+		.limit(1)
+		.query_with_collection_docs()
+		.await?;
+
+	Ok(matches.documents.into_values().next())
+}
+
+pub async fn get(database: &AsyncDatabase, track_id: u64) -> Result<Option<Lyrics>> {
+	let document = get_document(database, track_id).await?;
+	Ok(document.map(|x| x.contents))
+}
+
+/// Inserts the lyrics of a track, replacing the existing lyrics while keeping their [Lyrics::date_added].
+pub async fn set(database: &AsyncDatabase, mut lyrics: Lyrics) -> Result<u64> {
+	let id = if let Some(mut doc) = get_document(database, lyrics.track_id).await? {
+		lyrics.date_added = doc.contents.date_added;
+		lyrics.date_modified = Utc::now();
+
+		doc.contents = lyrics;
+		doc.update_async(database).await?;
+		doc.header.id
+	} else {
+		let doc = lyrics.push_into_async(database).await?;
+		doc.header.id
+	};
+
+	Ok(id)
+}
+
+pub async fn delete(database: &AsyncDatabase, track_id: u64) -> Result<()> {
+	if let Some(doc) = get_document(database, track_id).await? {
+		doc.delete_async(database).await?;
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod test {
+	use chrono::Utc;
+
+	use crate::{
+		database::{
+			methods::lyrics::{delete, get, set},
+			models::lyrics::{Lyrics, LyricsLine},
+			Database,
+		},
+		errors::Result,
+	};
+
+	fn lyrics(text: &str) -> Lyrics {
+		Lyrics {
+			track_id: 1,
+			language: None,
+			lines: vec![LyricsLine {
+				start: Some(0),
+				text: text.to_string(),
+				words: None,
+			}],
+			edited: false,
+			date_added: Utc::now(),
+			date_modified: Utc::now(),
+		}
+	}
+
+	#[tokio::test]
+	async fn test_set() -> Result<()> {
+		let db = Database::testing().await?;
+		let dbx = db.0;
+
+		let id = set(&dbx, lyrics("First")).await?;
+		let before = get(&dbx, 1).await?.unwrap();
+
+		assert_eq!(set(&dbx, lyrics("Second")).await?, id);
+
+		let after = get(&dbx, 1).await?.unwrap();
+		assert_eq!(after.lines[0].text, "Second");
+		assert_eq!(after.date_added, before.date_added);
+
+		delete(&dbx, 1).await?;
+		assert!(get(&dbx, 1).await?.is_none());
+
+		Ok(())
+	}
+}
@@ -1,7 +1,12 @@
 pub mod cover;
 pub mod label;
 pub mod library;
+pub mod lyrics;
 pub mod person;
+pub mod play;
+pub mod playlist;
 pub mod release;
+pub mod scrobble;
 pub mod tag;
 pub mod track;
+pub mod work;
@@ -1,19 +1,37 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::HashSet};
 
-use bonsaidb::{
-	core::{
-		document::CollectionDocument,
-		schema::{SerializedCollection, SerializedView},
+use {
+	bonsaidb::{
+		core::{
+			document::CollectionDocument,
+			schema::{SerializedCollection, SerializedView},
+		},
+		local::AsyncDatabase,
 	},
-	local::AsyncDatabase,
+	chrono::Utc,
 };
 
 use crate::{
+	constants::UNKNOWN_PERSON_ID,
 	database::{
-		models::person::Person,
-		views::person::{PersonByNameAndType, PersonByNameAndTypeKey},
+		models::{
+			person::{normalize_person_name, Person, PersonType},
+			release::Release,
+			track::Track,
+			InlinedArtist,
+		},
+		views::{
+			person::{PersonByMbzId, PersonByName},
+			release::ReleaseByArtistId,
+			track::{TrackByPerson, TrackByPersonKey, TrackByWorkId},
+			work::WorkByComposerId,
+		},
+	},
+	errors::{
+		pre::{database_entry_not_found, unknown_person_modification},
+		Error, Result,
 	},
-	errors::{Error, Result},
+	models::tauri::person::PersonSplit,
 };
 
 /// Inserts a document with a unique id.
@@ -36,37 +54,434 @@ pub async fn insert_with_unique_id(
 
 /// Inserts a document or gets an already existing one.
 ///
-/// Uniqueness is based from name and type.
+/// People are resolved through [find], and the MusicBrainz id and sort name of the matched person are filled in
+/// when missing.
 pub async fn get_or_insert(database: &AsyncDatabase, person: Person) -> Result<u64> {
-	let key = PersonByNameAndTypeKey::new(person.name.clone(), person.type_.clone());
-	let matches = PersonByNameAndType::entries_async(database)
-		.with_key(&key)
+	let id = match find(database, &person).await? {
+		Some(mut doc) => {
+			fill_in(database, &mut doc, person).await?;
+			doc.header.id
+		}
+		None => {
+			let person = person.push_into_async(database).await?;
+			person.header.id
+		}
+	};
+
+	Ok(id)
+}
+
+/// Fills in the MusicBrainz id and sort name of a person from another one, when they're missing.
+pub async fn fill_in(database: &AsyncDatabase, doc: &mut CollectionDocument<Person>, person: Person) -> Result<()> {
+	let x = &mut doc.contents;
+
+	if (x.mbz_id.is_none() && person.mbz_id.is_some()) || (x.name_sort.is_none() && person.name_sort.is_some()) {
+		let previous = get_sort_name(x).to_string();
+
+		x.mbz_id = x.mbz_id.take().or(person.mbz_id);
+		x.name_sort = x.name_sort.take().or(person.name_sort);
+		x.date_modified = Utc::now();
+		doc.update_async(database).await?;
+
+		if get_sort_name(&doc.contents) != previous {
+			let track_ids = get_track_ids(database, doc.header.id).await?;
+			let release_ids = get_release_ids(database, doc.header.id).await?;
+			refresh_artist_sort(database, &previous, &track_ids, &release_ids).await?;
+		}
+	}
+
+	Ok(())
+}
+
+/// Finds the existing person that is the same as the given one.
+///
+/// People are resolved by their MusicBrainz id first. Otherwise, people sharing the name are matched regardless of
+/// their type, preferring the same type, unless their MusicBrainz ids or sort names tell them apart.
+pub async fn find(database: &AsyncDatabase, person: &Person) -> Result<Option<CollectionDocument<Person>>> {
+	if let Some(mbz_id) = &person.mbz_id {
+		let matches = PersonByMbzId::entries_async(database)
+			.with_key(&mbz_id.to_lowercase())
+			.query_with_collection_docs()
+			.await?;
+
+		if let Some(x) = matches.documents.into_values().next() {
+			return Ok(Some(x));
+		}
+	}
+
+	let matches = PersonByName::entries_async(database)
+		.with_key(&normalize_person_name(&person.name))
+		.query_with_collection_docs()
+		.await?;
+
+	let mut candidates = matches
+		.documents
+		.into_values()
+		.filter(|x| is_same_person(&x.contents, person))
+		.collect::<Vec<CollectionDocument<Person>>>();
+	candidates.sort_by_key(|x| x.contents.type_ != person.type_);
+
+	Ok(candidates.into_iter().next())
+}
+
+/// Merges people into another one, for the ones that were split while being the same person.
+///
+/// Every track, release and work crediting `source_id` is rewritten to credit `target_id` instead, the missing details
+/// of the target are filled in from the source, and the source is deleted.
+pub async fn merge(database: &AsyncDatabase, source_id: u64, target_id: u64) -> Result<()> {
+	if source_id == target_id {
+		return Ok(());
+	}
+
+	if source_id == UNKNOWN_PERSON_ID || target_id == UNKNOWN_PERSON_ID {
+		return Err(unknown_person_modification());
+	}
+
+	let source = Person::get_async(&source_id, database)
+		.await?
+		.ok_or_else(|| database_entry_not_found("people", source_id))?;
+
+	let mut target = Person::get_async(&target_id, database)
+		.await?
+		.ok_or_else(|| database_entry_not_found("people", target_id))?;
+
+	let track_ids = get_track_ids(database, source_id).await?;
+	let release_ids = get_release_ids(database, source_id).await?;
+	move_credits(database, source_id, target_id, &track_ids, &release_ids).await?;
+	move_composers(database, source_id, target_id, true).await?;
+
+	let source_sort = get_sort_name(&source.contents).to_string();
+	let target_sort = get_sort_name(&target.contents).to_string();
+
+	let x = &mut target.contents;
+	x.name_sort = x.name_sort.take().or(source.contents.name_sort.clone());
+	x.mbz_id = x.mbz_id.take().or(source.contents.mbz_id.clone());
+	if x.cover_ids.is_none() {
+		x.cover_ids = source.contents.cover_ids.clone();
+		x.has_manual_cover = source.contents.has_manual_cover;
+	}
+	x.date_modified = Utc::now();
+	target.update_async(database).await?;
+
+	// The entries of both people now fall back to the target.
+	let track_ids = get_track_ids(database, target_id).await?;
+	let release_ids = get_release_ids(database, target_id).await?;
+	refresh_artist_sort(database, &source_sort, &track_ids, &release_ids).await?;
+	refresh_artist_sort(database, &target_sort, &track_ids, &release_ids).await?;
+
+	source.delete_async(database).await?;
+
+	Ok(())
+}
+
+/// Splits the given tracks and releases of a person into a new person, for namesakes that were merged.
+///
+/// Only the credits of `person_id` are moved, and the ids that don't credit them are skipped. Works follow the credits
+/// of their recordings, see [move_composers].
+/// Returns the id of the new person, which shares the type of the original one.
+pub async fn split(
+	database: &AsyncDatabase,
+	person_id: u64,
+	person: PersonSplit,
+	track_ids: HashSet<u64>,
+	release_ids: HashSet<u64>,
+) -> Result<u64> {
+	if person_id == UNKNOWN_PERSON_ID {
+		return Err(unknown_person_modification());
+	}
+
+	let source = Person::get_async(&person_id, database)
+		.await?
+		.ok_or_else(|| database_entry_not_found("people", person_id))?;
+	let previous = get_sort_name(&source.contents).to_string();
+
+	let new_person = Person {
+		name_sort: person.name_sort,
+		mbz_id: person.mbz_id,
+		..Person::new(person.name, source.contents.type_)
+	};
+	let new_id = new_person.push_into_async(database).await?.header.id;
+
+	move_credits(database, person_id, new_id, &track_ids, &release_ids).await?;
+	move_composers(database, person_id, new_id, false).await?;
+
+	refresh_artist_sort(database, &previous, &track_ids, &release_ids).await?;
+
+	Ok(new_id)
+}
+
+/// Resolves the ids of the tracks crediting a person in any role.
+pub async fn get_track_ids(database: &AsyncDatabase, person_id: u64) -> Result<HashSet<u64>> {
+	let mut ids = HashSet::new();
+
+	for type_ in PersonType::ROLES {
+		let x = TrackByPerson::entries_async(database)
+			.with_key(&TrackByPersonKey::new(person_id, type_))
+			.query()
+			.await?;
+		ids.extend(x.iter().map(|e| e.source.id));
+	}
+
+	Ok(ids)
+}
+
+pub async fn get_release_ids(database: &AsyncDatabase, person_id: u64) -> Result<HashSet<u64>> {
+	let x = ReleaseByArtistId::entries_async(database)
+		.with_key(&person_id)
 		.query()
 		.await?;
 
-	let id = if let Some(person) = matches.first() {
-		person.source.id
-	} else {
-		let person = person.push_into_async(database).await?;
-		person.header.id
+	Ok(x.iter().map(|e| e.source.id).collect())
+}
+
+/// Rewrites the credits of `from` into `to` in the given tracks and releases.
+async fn move_credits(
+	database: &AsyncDatabase,
+	from: u64,
+	to: u64,
+	track_ids: &HashSet<u64>,
+	release_ids: &HashSet<u64>,
+) -> Result<()> {
+	let track_ids = track_ids.iter().copied().collect::<Vec<u64>>();
+	for mut doc in Track::get_multiple_async(&track_ids, database).await? {
+		let x = &mut doc.contents;
+		let mut changed = replace_artist_id(&mut x.artists, from, to);
+
+		if x.credits.iter().any(|y| y.person_id == from) {
+			let mut credits = Vec::with_capacity(x.credits.len());
+			for mut credit in x.credits.drain(..) {
+				if credit.person_id == from {
+					credit.person_id = to;
+				}
+
+				if !credits.contains(&credit) {
+					credits.push(credit);
+				}
+			}
+
+			x.credits = credits;
+			changed = true;
+		}
+
+		if changed {
+			x.date_modified = Utc::now();
+			doc.update_async(database).await?;
+		}
+	}
+
+	let release_ids = release_ids.iter().copied().collect::<Vec<u64>>();
+	for mut doc in Release::get_multiple_async(&release_ids, database).await? {
+		if replace_artist_id(&mut doc.contents.artists, from, to) {
+			doc.contents.date_modified = Utc::now();
+			doc.update_async(database).await?;
+		}
+	}
+
+	Ok(())
+}
+
+/// Rewrites the composers of the works of `from` once their recordings were moved to `to`.
+///
+/// `from` stays a composer while a recording still credits them as one, and `to` becomes one as soon as a recording
+/// does. The works without any recording crediting either of them are handed over to `to` when `replace` is set.
+async fn move_composers(database: &AsyncDatabase, from: u64, to: u64, replace: bool) -> Result<()> {
+	let matches = WorkByComposerId::entries_async(database)
+		.with_key(&from)
+		.query_with_collection_docs()
+		.await?;
+
+	for (work_id, mut doc) in matches.documents {
+		let recordings = TrackByWorkId::entries_async(database)
+			.with_key(&work_id)
+			.query_with_collection_docs()
+			.await?;
+
+		let is_credited = |person_id: u64| {
+			recordings.documents.values().any(|x| {
+				x.contents
+					.credits
+					.iter()
+					.any(|y| y.person_id == person_id && y.type_ == PersonType::Composer)
+			})
+		};
+
+		let (keep_from, add_to) = match (is_credited(from), is_credited(to)) {
+			(false, false) => (!replace, replace),
+			x => x,
+		};
+
+		let x = &mut doc.contents;
+		let composer_ids = x.composer_ids.clone();
+
+		if !keep_from {
+			x.composer_ids.retain(|y| *y != from);
+		}
+
+		if add_to && !x.composer_ids.contains(&to) {
+			x.composer_ids.push(to);
+		}
+
+		if x.composer_ids != composer_ids {
+			x.date_modified = Utc::now();
+			doc.update_async(database).await?;
+		}
+	}
+
+	Ok(())
+}
+
+/// Replaces the id of an artist, dropping the duplicates that may arise while keeping the first credit.
+///
+/// Returns whether the artist was credited.
+/// Gets the value [get_artist_sort] falls back to for a person.
+fn get_sort_name(person: &Person) -> &str {
+	person.name_sort.as_deref().unwrap_or(&person.name)
+}
+
+/// Recomputes the artist sort of the tracks and releases that fell back to `previous`, the sort name of a person
+/// before it changed or before the person stopped being the first artist.
+///
+/// Any other artist sort came from the tags, and is kept as is.
+async fn refresh_artist_sort(
+	database: &AsyncDatabase,
+	previous: &str,
+	track_ids: &HashSet<u64>,
+	release_ids: &HashSet<u64>,
+) -> Result<()> {
+	let track_ids = track_ids.iter().copied().collect::<Vec<u64>>();
+	for mut doc in Track::get_multiple_async(&track_ids, database).await? {
+		if doc.contents.artist_sort.as_deref() != Some(previous) {
+			continue;
+		}
+
+		let artist_sort = get_artist_sort(database, &doc.contents.artists).await?;
+		if doc.contents.artist_sort != artist_sort {
+			doc.contents.artist_sort = artist_sort;
+			doc.update_async(database).await?;
+		}
+	}
+
+	let release_ids = release_ids.iter().copied().collect::<Vec<u64>>();
+	for mut doc in Release::get_multiple_async(&release_ids, database).await? {
+		if doc.contents.artist_sort.as_deref() != Some(previous) {
+			continue;
+		}
+
+		let artist_sort = get_artist_sort(database, &doc.contents.artists).await?;
+		if doc.contents.artist_sort != artist_sort {
+			doc.contents.artist_sort = artist_sort;
+			doc.update_async(database).await?;
+		}
+	}
+
+	Ok(())
+}
+
+fn replace_artist_id(artists: &mut Vec<InlinedArtist>, from: u64, to: u64) -> bool {
+	if !artists.iter().any(|x| x.id == from) {
+		return false;
+	}
+
+	let mut seen = HashSet::with_capacity(artists.len());
+	artists.iter_mut().filter(|x| x.id == from).for_each(|x| x.id = to);
+	artists.retain(|x| seen.insert(x.id));
+
+	true
+}
+
+/// Checks whether a person sharing the name of another one is the same person.
+///
+/// The unknown person only matches another unknown person.
+fn is_same_person(a: &Person, b: &Person) -> bool {
+	if (a.type_ == PersonType::Unknown) != (b.type_ == PersonType::Unknown) {
+		return false;
+	}
+
+	let mbz_id_agrees = match (&a.mbz_id, &b.mbz_id) {
+		(Some(x), Some(y)) => x.eq_ignore_ascii_case(y),
+		_ => true,
 	};
 
-	Ok(id)
+	let name_sort_agrees = match (&a.name_sort, &b.name_sort) {
+		(Some(x), Some(y)) => normalize_person_name(x) == normalize_person_name(y),
+		_ => true,
+	};
+
+	mbz_id_agrees && name_sort_agrees
+}
+
+/// Attaches a cover found by a scan to a person.
+///
+/// If `replace` is false, the cover is only attached when the person doesn't have one already.
+/// A cover set with [set_manual_cover] is never replaced.
+pub async fn set_cover(database: &AsyncDatabase, id: u64, cover_id: u64, replace: bool) -> Result<()> {
+	let mut doc = Person::get_async(&id, database)
+		.await?
+		.ok_or_else(|| database_entry_not_found("people", id))?;
+
+	if doc.contents.cover_ids.is_some() && (!replace || doc.contents.has_manual_cover) {
+		return Ok(());
+	}
+
+	doc.contents.cover_ids = Some(vec![cover_id]);
+	doc.contents.has_manual_cover = false;
+	doc.contents.date_modified = Utc::now();
+	doc.update_async(database).await?;
+
+	Ok(())
+}
+
+/// Attaches a cover picked by the user to a person, replacing the existing one.
+pub async fn set_manual_cover(database: &AsyncDatabase, id: u64, cover_id: u64) -> Result<()> {
+	let mut doc = Person::get_async(&id, database)
+		.await?
+		.ok_or_else(|| database_entry_not_found("people", id))?;
+
+	doc.contents.cover_ids = Some(vec![cover_id]);
+	doc.contents.has_manual_cover = true;
+	doc.contents.date_modified = Utc::now();
+	doc.update_async(database).await?;
+
+	Ok(())
+}
+
+/// Resolves the value an entry is sorted by artist with, when it doesn't have a sort name of its own.
+///
+/// Uses the sort name or the name of the first artist, and none for the unknown person.
+pub async fn get_artist_sort(database: &AsyncDatabase, artists: &[InlinedArtist]) -> Result<Option<String>> {
+	let Some(artist) = artists.first().filter(|x| x.id != UNKNOWN_PERSON_ID) else {
+		return Ok(None);
+	};
+
+	let person = Person::get_async(&artist.id, database).await?;
+	Ok(person.map(|x| x.contents.name_sort.unwrap_or(x.contents.name)))
 }
 
 #[cfg(test)]
 mod test {
 	use bonsaidb::core::schema::{SerializedCollection, SerializedView};
 
+	use std::collections::HashSet;
+
 	use crate::{
 		constants::UNKNOWN_PERSON_ID,
 		database::{
-			methods::person::{get_or_insert, insert_with_unique_id},
-			models::person::{Person, PersonType},
+			methods::{
+				self,
+				person::{get_or_insert, insert_with_unique_id, merge, set_cover, set_manual_cover, split},
+			},
+			models::{
+				person::{Person, PersonType},
+				release::Release,
+				track::{Credit, Track},
+				work::Work,
+				InlinedArtist,
+			},
 			views::person::{PersonByNameAndType, PersonByNameAndTypeKey},
 			Database,
 		},
 		errors::Result,
+		models::tauri::person::PersonSplit,
 	};
 
 	#[tokio::test]
@@ -100,6 +515,225 @@ mod test {
 		Ok(())
 	}
 
+	#[tokio::test]
+	async fn test_get_or_insert_identity() -> Result<()> {
+		let db = Database::testing().await?;
+		let dbx = db.0;
+
+		let artist = get_or_insert(&dbx, Person::new("Jane Doe".to_string(), PersonType::Artist)).await?;
+		let composer = get_or_insert(&dbx, Person::new("jane  doe".to_string(), PersonType::Composer)).await?;
+		assert_eq!(artist, composer);
+
+		let with_mbz_id = Person {
+			mbz_id: Some("a".to_string()),
+			..Person::new("Jane Doe".to_string(), PersonType::Artist)
+		};
+		assert_eq!(get_or_insert(&dbx, with_mbz_id).await?, artist);
+
+		let namesake = Person {
+			mbz_id: Some("b".to_string()),
+			..Person::new("Jane Doe".to_string(), PersonType::Artist)
+		};
+		let namesake = get_or_insert(&dbx, namesake).await?;
+		assert_ne!(namesake, artist);
+
+		let renamed = Person {
+			mbz_id: Some("B".to_string()),
+			..Person::new("J. Doe".to_string(), PersonType::Composer)
+		};
+		assert_eq!(get_or_insert(&dbx, renamed).await?, namesake);
+
+		insert_with_unique_id(&dbx, Person::unknown(), UNKNOWN_PERSON_ID).await?;
+		let unknown = get_or_insert(&dbx, Person::new("Unknown".to_string(), PersonType::Artist)).await?;
+		assert_ne!(unknown, UNKNOWN_PERSON_ID);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_merge_and_split() -> Result<()> {
+		let db = Database::testing().await?;
+		let dbx = db.0;
+
+		let source = Person::new("Source".to_string(), PersonType::Composer);
+		let source = Person {
+			mbz_id: Some("a".to_string()),
+			..source
+		};
+		let source_id = source.push_into_async(&dbx).await?.header.id;
+		let target_id = Person::default().push_into_async(&dbx).await?.header.id;
+
+		let artists = vec![
+			InlinedArtist {
+				id: target_id,
+				..InlinedArtist::unknown()
+			},
+			InlinedArtist {
+				id: source_id,
+				..InlinedArtist::unknown()
+			},
+		];
+
+		let work = Work {
+			composer_ids: vec![source_id],
+			..Work::new("Work".to_string())
+		};
+		let work_id = work.push_into_async(&dbx).await?.header.id;
+		let unrecorded = Work {
+			composer_ids: vec![source_id],
+			..Work::new("Unrecorded".to_string())
+		};
+		let unrecorded_id = unrecorded.push_into_async(&dbx).await?.header.id;
+
+		let track = Track {
+			artists: artists.clone(),
+			credits: vec![
+				Credit::new(source_id, PersonType::Composer, None),
+				Credit::new(target_id, PersonType::Composer, None),
+			],
+			work_id: Some(work_id),
+			..Default::default()
+		};
+		let track_id = track.push_into_async(&dbx).await?.header.id;
+		let other_track = Track {
+			credits: vec![Credit::new(source_id, PersonType::Composer, None)],
+			work_id: Some(work_id),
+			..Default::default()
+		};
+		other_track.push_into_async(&dbx).await?;
+		let release = Release {
+			artists,
+			..Default::default()
+		};
+		let release_id = release.push_into_async(&dbx).await?.header.id;
+
+		merge(&dbx, source_id, target_id).await?;
+
+		let track = Track::get_async(&track_id, &dbx).await?.unwrap().contents;
+		assert_eq!(track.artists.len(), 1);
+		assert_eq!(track.credits, vec![Credit::new(target_id, PersonType::Composer, None)]);
+
+		let release = Release::get_async(&release_id, &dbx).await?.unwrap().contents;
+		assert_eq!(
+			release.artists.iter().map(|x| x.id).collect::<Vec<u64>>(),
+			vec![target_id]
+		);
+
+		assert!(Person::get_async(&source_id, &dbx).await?.is_none());
+		let target = Person::get_async(&target_id, &dbx).await?.unwrap().contents;
+		assert_eq!(target.mbz_id.as_deref(), Some("a"));
+		assert!(merge(&dbx, target_id, UNKNOWN_PERSON_ID).await.is_err());
+
+		let work = Work::get_async(&work_id, &dbx).await?.unwrap().contents;
+		assert_eq!(work.composer_ids, vec![target_id]);
+		let unrecorded = Work::get_async(&unrecorded_id, &dbx).await?.unwrap().contents;
+		assert_eq!(unrecorded.composer_ids, vec![target_id]);
+
+		let person = PersonSplit {
+			name: "Namesake".to_string(),
+			name_sort: None,
+			mbz_id: None,
+		};
+		let split_id = split(&dbx, target_id, person, HashSet::from([track_id]), HashSet::new()).await?;
+
+		let track = Track::get_async(&track_id, &dbx).await?.unwrap().contents;
+		assert_eq!(track.artists[0].id, split_id);
+		assert_eq!(track.credits, vec![Credit::new(split_id, PersonType::Composer, None)]);
+		assert_eq!(
+			methods::person::get_release_ids(&dbx, target_id).await?,
+			HashSet::from([release_id])
+		);
+
+		// The other recording still credits the original composer.
+		let work = Work::get_async(&work_id, &dbx).await?.unwrap().contents;
+		assert_eq!(work.composer_ids, vec![target_id, split_id]);
+		let unrecorded = Work::get_async(&unrecorded_id, &dbx).await?.unwrap().contents;
+		assert_eq!(unrecorded.composer_ids, vec![target_id]);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_refresh_artist_sort() -> Result<()> {
+		let db = Database::testing().await?;
+		let dbx = db.0;
+
+		let source_id = Person::new("Band".to_string(), PersonType::Artist)
+			.push_into_async(&dbx)
+			.await?
+			.header
+			.id;
+		let target = Person {
+			name_sort: Some("Band, The".to_string()),
+			..Person::new("The Band".to_string(), PersonType::Artist)
+		};
+		let target_id = target.push_into_async(&dbx).await?.header.id;
+
+		let artists = vec![InlinedArtist {
+			id: source_id,
+			..InlinedArtist::unknown()
+		}];
+		let fallback = Track {
+			artists: artists.clone(),
+			artist_sort: Some("Band".to_string()),
+			..Default::default()
+		};
+		let fallback_id = fallback.push_into_async(&dbx).await?.header.id;
+		let tagged = Track {
+			artists,
+			artist_sort: Some("Tagged".to_string()),
+			..Default::default()
+		};
+		let tagged_id = tagged.push_into_async(&dbx).await?.header.id;
+
+		merge(&dbx, source_id, target_id).await?;
+
+		let track = Track::get_async(&fallback_id, &dbx).await?.unwrap().contents;
+		assert_eq!(track.artist_sort.as_deref(), Some("Band, The"));
+		let track = Track::get_async(&tagged_id, &dbx).await?.unwrap().contents;
+		assert_eq!(track.artist_sort.as_deref(), Some("Tagged"));
+
+		let person = PersonSplit {
+			name: "The Band".to_string(),
+			name_sort: Some("Band, Another".to_string()),
+			mbz_id: None,
+		};
+		split(&dbx, target_id, person, HashSet::from([fallback_id]), HashSet::new()).await?;
+
+		let track = Track::get_async(&fallback_id, &dbx).await?.unwrap().contents;
+		assert_eq!(track.artist_sort.as_deref(), Some("Band, Another"));
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_set_cover() -> Result<()> {
+		let db = Database::testing().await?;
+		let dbx = db.0;
+
+		let doc = Person::default().push_into_async(&dbx).await?;
+
+		set_cover(&dbx, doc.header.id, 1, false).await?;
+		set_cover(&dbx, doc.header.id, 2, false).await?;
+
+		let person = Person::get_async(&doc.header.id, &dbx).await?.unwrap();
+		assert_eq!(person.contents.cover_ids, Some(vec![1]));
+
+		set_cover(&dbx, doc.header.id, 2, true).await?;
+
+		let person = Person::get_async(&doc.header.id, &dbx).await?.unwrap();
+		assert_eq!(person.contents.cover_ids, Some(vec![2]));
+
+		// A manual image survives the next scan.
+		set_manual_cover(&dbx, doc.header.id, 3).await?;
+		set_cover(&dbx, doc.header.id, 2, true).await?;
+
+		let person = Person::get_async(&doc.header.id, &dbx).await?.unwrap();
+		assert_eq!(person.contents.cover_ids, Some(vec![3]));
+
+		Ok(())
+	}
+
 	#[tokio::test]
 	async fn test_by_name_and_type() -> Result<()> {
 		let db = Database::testing().await?;
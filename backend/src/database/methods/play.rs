@@ -0,0 +1,109 @@
+use std::collections::{HashMap, HashSet};
+
+use bonsaidb::{
+	core::schema::{SerializedCollection, SerializedView},
+	local::AsyncDatabase,
+};
+
+use crate::{
+	database::{
+		models::{play::Play, track::Track},
+		views::play::PlayByTrackId,
+	},
+	errors::Result,
+};
+
+pub async fn insert(database: &AsyncDatabase, play: Play) -> Result<u64> {
+	let doc = play.push_into_async(database).await?;
+	Ok(doc.header.id)
+}
+
+/// Gets the plays of a track, oldest first.
+pub async fn get_by_track_id(database: &AsyncDatabase, track_id: u64) -> Result<Vec<Play>> {
+	let matches = PlayByTrackId::entries_async(database)
+		.with_key(&track_id)
+		.query_with_collection_docs()
+		.await?;
+
+	let mut plays = matches
+		.documents
+		.into_values()
+		.map(|doc| doc.contents)
+		.collect::<Vec<Play>>();
+	plays.sort_by_key(|x| x.played_at);
+
+	Ok(plays)
+}
+
+/// Counts the plays of every track that was played at least once.
+pub async fn get_counts(database: &AsyncDatabase) -> Result<HashMap<u64, u64>> {
+	let groups = PlayByTrackId::entries_async(database).reduce_grouped().await?;
+	Ok(groups.into_iter().map(|x| (x.key, x.value)).collect())
+}
+
+/// Counts the plays of every release, summing the plays of its tracks.
+pub async fn get_release_counts(database: &AsyncDatabase) -> Result<HashMap<u64, u64>> {
+	let counts = get_counts(database).await?;
+	let track_ids = counts.keys().copied().collect::<Vec<u64>>();
+
+	let mut release_counts = HashMap::<u64, u64>::new();
+	for doc in Track::get_multiple_async(&track_ids, database).await? {
+		*release_counts.entry(doc.contents.release_id).or_default() += counts[&doc.header.id];
+	}
+
+	Ok(release_counts)
+}
+
+/// Drops the plays of the given tracks, for tracks deleted from the library.
+pub async fn delete_by_track_ids(database: &AsyncDatabase, track_ids: &HashSet<u64>) -> Result<()> {
+	for doc in Play::all_async(database).await? {
+		if track_ids.contains(&doc.contents.track_id) {
+			doc.delete_async(database).await?;
+		}
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod test {
+	use std::collections::HashSet;
+
+	use chrono::{Duration, Utc};
+
+	use crate::{
+		database::{
+			methods::play::{delete_by_track_ids, get_by_track_id, get_counts, insert},
+			models::play::Play,
+			Database,
+		},
+		errors::Result,
+	};
+
+	#[tokio::test]
+	async fn test_plays() -> Result<()> {
+		let db = Database::testing().await?;
+		let dbx = db.0;
+
+		let now = Utc::now();
+		insert(&dbx, Play::new(1, now)).await?;
+		insert(&dbx, Play::new(1, now - Duration::hours(1))).await?;
+		insert(&dbx, Play::new(2, now)).await?;
+
+		let plays = get_by_track_id(&dbx, 1).await?;
+		assert_eq!(
+			plays.iter().map(|x| x.played_at).collect::<Vec<_>>(),
+			vec![now - Duration::hours(1), now]
+		);
+
+		let counts = get_counts(&dbx).await?;
+		assert_eq!(counts.get(&1), Some(&2));
+		assert_eq!(counts.get(&2), Some(&1));
+
+		delete_by_track_ids(&dbx, &HashSet::from([1])).await?;
+		assert!(get_by_track_id(&dbx, 1).await?.is_empty());
+		assert_eq!(get_counts(&dbx).await?.get(&1), None);
+
+		Ok(())
+	}
+}
@@ -0,0 +1,125 @@
+use std::collections::{HashMap, HashSet};
+
+use {
+	bonsaidb::{
+		core::{document::DocumentId, schema::SerializedCollection},
+		local::AsyncDatabase,
+	},
+	chrono::Utc,
+};
+
+use crate::{
+	database::models::{playlist::Playlist, track::Track},
+	errors::{pre::database_entry_not_found, Result},
+};
+
+/// Gets every playlist, ordered by its name.
+pub async fn get_all(database: &AsyncDatabase) -> Result<Vec<(u64, Playlist)>> {
+	let mut playlists = Playlist::all_async(database)
+		.await?
+		.into_iter()
+		.map(|x| (x.header.id, x.contents))
+		.collect::<Vec<(u64, Playlist)>>();
+
+	playlists.sort_by_cached_key(|(id, x)| (x.name.to_lowercase(), *id));
+
+	Ok(playlists)
+}
+
+pub async fn insert(database: &AsyncDatabase, playlist: Playlist) -> Result<u64> {
+	let doc = playlist.push_into_async(database).await?;
+	Ok(doc.header.id)
+}
+
+/// Gets the tracks of a playlist in its order, skipping the tracks that were removed from the library.
+pub async fn get_tracks(database: &AsyncDatabase, playlist_id: u64) -> Result<Vec<(u64, Track)>> {
+	let playlist = Playlist::get_async(&playlist_id, database)
+		.await?
+		.ok_or_else(|| database_entry_not_found("playlists", playlist_id))?
+		.contents;
+
+	let ids = playlist
+		.track_ids
+		.iter()
+		.map(|x| DocumentId::from_u64(*x))
+		.collect::<Vec<DocumentId>>();
+
+	let tracks = Track::get_multiple_async(&ids, database)
+		.await?
+		.into_iter()
+		.map(|x| (x.header.id, x.contents))
+		.collect::<HashMap<u64, Track>>();
+
+	let ordered = playlist
+		.track_ids
+		.into_iter()
+		.filter_map(|id| Some((id, tracks.get(&id)?.clone())))
+		.collect();
+
+	Ok(ordered)
+}
+
+/// Removes every occurrence of the given tracks from the playlists, for tracks deleted from the library.
+pub async fn remove_tracks(database: &AsyncDatabase, track_ids: &HashSet<u64>) -> Result<()> {
+	for mut doc in Playlist::all_async(database).await? {
+		let count = doc.contents.track_ids.len();
+		doc.contents.track_ids.retain(|x| !track_ids.contains(x));
+
+		if doc.contents.track_ids.len() != count {
+			doc.contents.date_modified = Utc::now();
+			doc.update_async(database).await?;
+		}
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod test {
+	use bonsaidb::core::schema::SerializedCollection;
+
+	use crate::{
+		database::{
+			methods::playlist::{get_all, get_tracks, insert},
+			models::{playlist::Playlist, track::Track},
+			Database,
+		},
+		errors::Result,
+	};
+
+	#[tokio::test]
+	async fn test_get_tracks() -> Result<()> {
+		let db = Database::testing().await?;
+		let dbx = db.0;
+
+		let first = Track::default().push_into_async(&dbx).await?.header.id;
+		let second = Track {
+			title: "Second".to_string(),
+			..Default::default()
+		}
+		.push_into_async(&dbx)
+		.await?
+		.header
+		.id;
+
+		let id = insert(&dbx, Playlist::new("b".to_string(), vec![second, 999, first, second])).await?;
+		insert(&dbx, Playlist::new("A".to_string(), vec![])).await?;
+
+		let names = get_all(&dbx)
+			.await?
+			.into_iter()
+			.map(|(_, x)| x.name)
+			.collect::<Vec<_>>();
+		assert_eq!(names, vec!["A", "b"]);
+
+		// Missing tracks are skipped, while repeated ones are kept.
+		let ids = get_tracks(&dbx, id)
+			.await?
+			.into_iter()
+			.map(|(id, _)| id)
+			.collect::<Vec<_>>();
+		assert_eq!(ids, vec![second, first, second]);
+
+		Ok(())
+	}
+}
@@ -1,30 +1,75 @@
-use bonsaidb::{
-	core::schema::{SerializedCollection, SerializedView},
-	local::AsyncDatabase,
+use std::collections::HashSet;
+
+use {
+	bonsaidb::{
+		core::{
+			connection::{Bound, Range},
+			schema::{SerializedCollection, SerializedView},
+		},
+		local::AsyncDatabase,
+	},
+	chrono::Utc,
 };
 
 use crate::{
 	database::{
+		helpers::{get_play_count_keys, paginate, paginate_by_keys},
+		methods,
 		models::release::Release,
-		views::release::{ReleaseByNameAndArtist, ReleaseByNameAndArtistKey},
+		views::release::{
+			ReleaseByArtistSort, ReleaseByDate, ReleaseByDateAdded, ReleaseByGenreId, ReleaseByLabelId,
+			ReleaseByLibraryId, ReleaseByName, ReleaseByNameAndArtist, ReleaseByNameAndArtistKey, ReleaseByYear,
+		},
 	},
 	errors::Result,
+	models::tauri::{
+		release::{ReleaseFilter, ReleaseSort},
+		Page, PageRequest,
+	},
 };
 
+/// Gets every release of the library, ordered by its name.
+pub async fn get_all(database: &AsyncDatabase, library_id: u64) -> Result<Vec<(u64, Release)>> {
+	let mut releases = ReleaseByLibraryId::entries_async(database)
+		.with_key(&library_id)
+		.query_with_collection_docs()
+		.await?
+		.documents
+		.into_iter()
+		.map(|(id, doc)| (id, doc.contents))
+		.collect::<Vec<(u64, Release)>>();
+
+	releases.sort_by(|(a_id, a), (b_id, b)| a.name.cmp(&b.name).then(a_id.cmp(b_id)));
+
+	Ok(releases)
+}
+
 /// Inserts a release or gets an already existing one.
 ///
-/// Uniqueness is based on name and release artist id.
+/// Candidates share the name, the set of release artists in any order, and the library of the release.
+/// See [is_same_edition] for how editions of the same release are told apart.
 pub async fn get_or_insert(database: &AsyncDatabase, release: Release) -> Result<u64> {
-	let artist_ids = release.artists.iter().map(|x| x.id).collect::<Vec<u64>>();
+	let artist_ids = release.artists.iter().map(|x| x.id).collect::<HashSet<u64>>();
 
-	let key = ReleaseByNameAndArtistKey::new(release.name.clone(), *artist_ids.first().unwrap());
+	let key = ReleaseByNameAndArtistKey::new(release.name.clone(), release.artists[0].id);
 	let matches = ReleaseByNameAndArtist::entries_async(database)
 		.with_key(&key)
-		.query()
+		.query_with_collection_docs()
 		.await?;
 
-	let id = if let Some(release) = matches.first() {
-		release.source.id
+	let existing = matches.documents.into_iter().find(|(_, doc)| {
+		let x = &doc.contents;
+		let y = x.artists.iter().map(|x| x.id).collect::<HashSet<u64>>();
+
+		x.library_id == release.library_id && y == artist_ids && is_same_edition(x, &release)
+	});
+
+	let id = if let Some((id, mut doc)) = existing {
+		// Another track joined the release.
+		doc.contents.total_discs = doc.contents.total_discs.max(release.total_discs);
+		doc.contents.date_modified = Utc::now();
+		doc.update_async(database).await?;
+		id
 	} else {
 		let release = release.push_into_async(database).await?;
 		release.header.id
@@ -33,19 +78,156 @@ pub async fn get_or_insert(database: &AsyncDatabase, release: Release) -> Result
 	Ok(id)
 }
 
+/// Checks whether two releases sharing a name and artists are the same edition.
+///
+/// MusicBrainz ids decide when both releases have them. Otherwise, the releases are kept apart if they disagree on
+/// the catalog number, the date, the total discs or the directory, while missing values are ignored. The total
+/// tracks are only compared between single disc releases, as the discs of a release hold different numbers of tracks.
+fn is_same_edition(a: &Release, b: &Release) -> bool {
+	fn agrees<T: PartialEq>(a: &Option<T>, b: &Option<T>) -> bool {
+		match (a, b) {
+			(Some(a), Some(b)) => a == b,
+			_ => true,
+		}
+	}
+
+	if let (Some(a), Some(b)) = (&a.mbz_id, &b.mbz_id) {
+		return a.eq_ignore_ascii_case(b);
+	}
+
+	let catalog_number = |x: &Release| {
+		x.catalog_number.as_ref().map(|y| {
+			y.chars()
+				.filter(|z| z.is_alphanumeric())
+				.flat_map(char::to_lowercase)
+				.collect::<String>()
+		})
+	};
+
+	agrees(&catalog_number(a), &catalog_number(b))
+		&& agrees(&a.date, &b.date)
+		&& agrees(&a.year, &b.year)
+		&& agrees(&a.total_discs, &b.total_discs)
+		&& (is_multi_disc(a) || is_multi_disc(b) || agrees(&a.total_tracks, &b.total_tracks))
+		&& agrees(&a.directory, &b.directory)
+}
+
+fn is_multi_disc(release: &Release) -> bool {
+	release.total_discs.is_some_and(|x| x > 1)
+}
+
+/// Queries a page of releases sorted by the given key.
+pub async fn query(
+	database: &AsyncDatabase,
+	request: PageRequest,
+	sort: ReleaseSort,
+	filter: ReleaseFilter,
+) -> Result<Page<Release>> {
+	let candidates = get_candidates(database, &filter).await?;
+	let matches = |x: &Release| filter.matches(x);
+
+	match sort {
+		ReleaseSort::Name => paginate::<ReleaseByName, _>(database, request, Some(&candidates), matches).await,
+		ReleaseSort::Artist => paginate::<ReleaseByArtistSort, _>(database, request, Some(&candidates), matches).await,
+		ReleaseSort::Date => paginate::<ReleaseByDate, _>(database, request, Some(&candidates), matches).await,
+		ReleaseSort::Added => paginate::<ReleaseByDateAdded, _>(database, request, Some(&candidates), matches).await,
+		ReleaseSort::PlayCount => {
+			let counts = methods::play::get_release_counts(database).await?;
+			let keys = get_play_count_keys(&candidates, &counts);
+			paginate_by_keys(database, request, keys, matches).await
+		}
+	}
+}
+
+/// Fills in the artist sort of the releases saved before it fell back to the first artist.
+pub async fn fill_artist_sort(database: &AsyncDatabase) -> Result<()> {
+	for mut doc in Release::all_async(database).await? {
+		if doc.contents.artist_sort.is_some() {
+			continue;
+		}
+
+		doc.contents.artist_sort = methods::person::get_artist_sort(database, &doc.contents.artists).await?;
+		if doc.contents.artist_sort.is_some() {
+			doc.update_async(database).await?;
+		}
+	}
+
+	Ok(())
+}
+
+/// Resolves the ids of the releases that match the view backed filters, always scoped to a library.
+async fn get_candidates(database: &AsyncDatabase, filter: &ReleaseFilter) -> Result<HashSet<u64>> {
+	let library_id = match filter.library_id {
+		Some(x) => x,
+		None => methods::library::get_active_id(database).await?,
+	};
+
+	let mut ids = methods::library::get_release_ids(database, library_id).await?;
+	let mut sets = Vec::<HashSet<u64>>::new();
+
+	// Releases of sub-genres are considered to be a part of the parent genre.
+	if let Some(genre_id) = filter.genre_id {
+		let mut ids = HashSet::new();
+
+		for id in methods::tag::get_with_descendant_ids(database, genre_id).await? {
+			let x = ReleaseByGenreId::entries_async(database).with_key(&id).query().await?;
+			ids.extend(x.iter().map(|e| e.source.id));
+		}
+
+		sets.push(ids);
+	}
+
+	if let Some(label_id) = filter.label_id {
+		let x = ReleaseByLabelId::entries_async(database)
+			.with_key(&label_id)
+			.query()
+			.await?;
+		sets.push(x.iter().map(|e| e.source.id).collect());
+	}
+
+	if filter.year_from.is_some() || filter.year_to.is_some() {
+		let range = Range {
+			start: filter.year_from.map_or(Bound::Unbounded, Bound::Included),
+			end: filter.year_to.map_or(Bound::Unbounded, Bound::Included),
+		};
+
+		let x = ReleaseByYear::entries_async(database)
+			.with_key_range(range)
+			.query()
+			.await?;
+		sets.push(x.iter().map(|e| e.source.id).collect());
+	}
+
+	for set in sets {
+		ids.retain(|x| set.contains(x));
+	}
+
+	Ok(ids)
+}
+
 #[cfg(test)]
 mod test {
-	use bonsaidb::core::schema::{SerializedCollection, SerializedView};
+	use {
+		bonsaidb::core::schema::{SerializedCollection, SerializedView},
+		chrono::Utc,
+	};
 
 	use crate::{
-		constants::{TEST_RELEASE_NAME, UNKNOWN_PERSON_ID},
+		constants::{DEFAULT_LIBRARY_ID, TEST_RELEASE_NAME, UNKNOWN_PERSON_ID},
 		database::{
-			methods::release::get_or_insert,
-			models::{release::Release, InlinedArtist},
-			views::release::{ReleaseByNameAndArtist, ReleaseByNameAndArtistKey},
+			methods::{
+				self,
+				release::{fill_artist_sort, get_all, get_or_insert, query},
+			},
+			models::{person::Person, play::Play, release::Release, track::Track, InlinedArtist},
+			views::release::{ReleaseByArtistSort, ReleaseByNameAndArtist, ReleaseByNameAndArtistKey},
 			Database,
 		},
 		errors::Result,
+		models::tauri::{
+			release::{ReleaseFilter, ReleaseSort},
+			PageRequest, SortDirection,
+		},
 	};
 
 	#[tokio::test]
@@ -64,6 +246,324 @@ mod test {
 		Ok(())
 	}
 
+	#[tokio::test]
+	async fn test_get_all() -> Result<()> {
+		let db = Database::testing().await?;
+		let dbx = db.0;
+
+		let artists = vec![
+			InlinedArtist {
+				id: 1,
+				..InlinedArtist::unknown()
+			},
+			InlinedArtist {
+				id: 2,
+				..InlinedArtist::unknown()
+			},
+		];
+
+		let release = Release {
+			name: "B".to_string(),
+			artists,
+			..Default::default()
+		};
+		release.insert_into_async(&1, &dbx).await?;
+
+		let release = Release {
+			name: "A".to_string(),
+			..Default::default()
+		};
+		release.insert_into_async(&2, &dbx).await?;
+
+		let release = Release {
+			name: "C".to_string(),
+			library_id: 2,
+			..Default::default()
+		};
+		release.insert_into_async(&3, &dbx).await?;
+
+		let ids = get_all(&dbx, DEFAULT_LIBRARY_ID)
+			.await?
+			.into_iter()
+			.map(|(id, _)| id)
+			.collect::<Vec<u64>>();
+		assert_eq!(ids, vec![2, 1]);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_get_or_insert_editions() -> Result<()> {
+		let db = Database::testing().await?;
+		let dbx = db.0;
+
+		let artists = vec![
+			InlinedArtist {
+				id: 1,
+				..InlinedArtist::unknown()
+			},
+			InlinedArtist {
+				id: 2,
+				..InlinedArtist::unknown()
+			},
+		];
+
+		let release = Release {
+			artists: artists.clone(),
+			catalog_number: Some("ABC-001".to_string()),
+			directory: Some("/music/Artist/Release".to_string()),
+			..Default::default()
+		};
+		let id = get_or_insert(&dbx, release).await?;
+
+		// The same release with its artists in another order, and the catalog number spelled differently.
+		let reordered = Release {
+			artists: artists.iter().rev().cloned().collect(),
+			catalog_number: Some("abc001".to_string()),
+			directory: Some("/music/Artist/Release".to_string()),
+			..Default::default()
+		};
+		assert_eq!(get_or_insert(&dbx, reordered).await?, id);
+
+		let deluxe = Release {
+			artists: artists.clone(),
+			catalog_number: Some("ABC-002".to_string()),
+			directory: Some("/music/Artist/Release".to_string()),
+			..Default::default()
+		};
+		assert_ne!(get_or_insert(&dbx, deluxe).await?, id);
+
+		let elsewhere = Release {
+			artists: artists.clone(),
+			directory: Some("/music/Artist/Release (Remaster)".to_string()),
+			..Default::default()
+		};
+		assert_ne!(get_or_insert(&dbx, elsewhere).await?, id);
+
+		let release = Release {
+			artists: artists.clone(),
+			mbz_id: Some("a".to_string()),
+			directory: Some("/music/Other/CD1".to_string()),
+			..Default::default()
+		};
+		let id = get_or_insert(&dbx, release).await?;
+
+		let same = Release {
+			artists: artists.clone(),
+			mbz_id: Some("a".to_string()),
+			directory: Some("/music/Other/CD2".to_string()),
+			..Default::default()
+		};
+		assert_eq!(get_or_insert(&dbx, same).await?, id);
+
+		let other = Release {
+			artists,
+			mbz_id: Some("b".to_string()),
+			directory: Some("/music/Other/CD1".to_string()),
+			..Default::default()
+		};
+		assert_ne!(get_or_insert(&dbx, other).await?, id);
+
+		// The discs of a release hold different numbers of tracks.
+		let first_disc = Release {
+			artists: artists.clone(),
+			total_tracks: Some(10),
+			directory: Some("/music/Artist/Double".to_string()),
+			..Default::default()
+		};
+		let id = get_or_insert(&dbx, first_disc).await?;
+
+		let second_disc = Release {
+			artists: artists.clone(),
+			total_tracks: Some(8),
+			total_discs: Some(2),
+			directory: Some("/music/Artist/Double".to_string()),
+			..Default::default()
+		};
+		assert_eq!(get_or_insert(&dbx, second_disc).await?, id);
+
+		let release = Release::get_async(&id, &dbx).await?.unwrap();
+		assert_eq!(release.contents.total_discs, Some(2));
+
+		let first_disc = Release {
+			artists: artists.clone(),
+			total_tracks: Some(10),
+			directory: Some("/music/Artist/Double".to_string()),
+			..Default::default()
+		};
+		assert_eq!(get_or_insert(&dbx, first_disc).await?, id);
+
+		let single = Release {
+			artists: artists.clone(),
+			total_tracks: Some(12),
+			total_discs: Some(1),
+			directory: Some("/music/Artist/Single".to_string()),
+			..Default::default()
+		};
+		let id = get_or_insert(&dbx, single).await?;
+
+		let longer = Release {
+			artists,
+			total_tracks: Some(14),
+			directory: Some("/music/Artist/Single".to_string()),
+			..Default::default()
+		};
+		assert_ne!(get_or_insert(&dbx, longer).await?, id);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_query() -> Result<()> {
+		let db = Database::testing().await?;
+		let dbx = db.0;
+
+		let mut release_ids = Vec::new();
+		for i in 0..5 {
+			let release = Release {
+				name: format!("Release {i}"),
+				year: Some(2000 + i),
+				..Default::default()
+			};
+
+			release_ids.push(release.push_into_async(&dbx).await?.header.id);
+		}
+
+		let request = PageRequest {
+			cursor: None,
+			direction: SortDirection::Ascending,
+			limit: 3,
+		};
+		let first = query(&dbx, request, ReleaseSort::Name, ReleaseFilter::default()).await?;
+
+		assert_eq!(first.items.len(), 3);
+		assert_eq!(first.items[0].attributes.name, "Release 0");
+
+		let request = PageRequest {
+			cursor: first.next_cursor,
+			direction: SortDirection::Ascending,
+			limit: 3,
+		};
+		let second = query(&dbx, request, ReleaseSort::Name, ReleaseFilter::default()).await?;
+
+		assert_eq!(second.items.len(), 2);
+		assert_eq!(second.items[0].attributes.name, "Release 3");
+		assert!(second.next_cursor.is_none());
+
+		let request = PageRequest {
+			cursor: None,
+			direction: SortDirection::Descending,
+			limit: 10,
+		};
+		let filter = ReleaseFilter {
+			year_from: Some(2003),
+			..Default::default()
+		};
+		let filtered = query(&dbx, request, ReleaseSort::Date, filter).await?;
+		let names = filtered
+			.items
+			.iter()
+			.map(|e| e.attributes.name.as_str())
+			.collect::<Vec<&str>>();
+
+		assert_eq!(names, vec!["Release 4", "Release 3"]);
+
+		let request = PageRequest {
+			cursor: None,
+			direction: SortDirection::Ascending,
+			limit: 0,
+		};
+		let empty = query(&dbx, request, ReleaseSort::Name, ReleaseFilter::default()).await?;
+		assert!(empty.items.is_empty());
+
+		for (i, plays) in [(1, 1), (3, 2)] {
+			let track = Track {
+				path: format!("/music/{i}.flac"),
+				release_id: release_ids[i],
+				..Default::default()
+			};
+			let track_id = track.push_into_async(&dbx).await?.header.id;
+
+			for _ in 0..plays {
+				methods::play::insert(&dbx, Play::new(track_id, Utc::now())).await?;
+			}
+		}
+
+		let mut names = Vec::new();
+		let mut cursor = None;
+		loop {
+			let request = PageRequest {
+				cursor,
+				direction: SortDirection::Descending,
+				limit: 2,
+			};
+			let page = query(&dbx, request, ReleaseSort::PlayCount, ReleaseFilter::default()).await?;
+
+			names.extend(page.items.into_iter().map(|e| e.attributes.name));
+			cursor = page.next_cursor;
+			if cursor.is_none() {
+				break;
+			}
+		}
+
+		// Releases that were never played are ordered by their id.
+		assert_eq!(
+			names,
+			vec!["Release 3", "Release 1", "Release 4", "Release 2", "Release 0"]
+		);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_fill_artist_sort() -> Result<()> {
+		let db = Database::testing().await?;
+		let dbx = db.0;
+
+		let person = Person {
+			name: "The Band".to_string(),
+			..Default::default()
+		};
+		let person_id = person.push_into_async(&dbx).await?.header.id;
+
+		let artists = || {
+			vec![InlinedArtist {
+				id: person_id,
+				..InlinedArtist::unknown()
+			}]
+		};
+		let sorted = Release {
+			artists: artists(),
+			artist_sort: Some("Band, The".to_string()),
+			..Default::default()
+		};
+		let sorted_id = sorted.push_into_async(&dbx).await?.header.id;
+
+		let release = Release {
+			artists: artists(),
+			..Default::default()
+		};
+		let id = release.push_into_async(&dbx).await?.header.id;
+		let unknown_id = Release::default().push_into_async(&dbx).await?.header.id;
+
+		fill_artist_sort(&dbx).await?;
+
+		let sorts = ReleaseByArtistSort::entries_async(&dbx)
+			.query()
+			.await?
+			.into_iter()
+			.map(|x| (x.source.id, x.key.0))
+			.collect::<Vec<(u64, String)>>();
+		let expected = vec![
+			(unknown_id, String::new()),
+			(sorted_id, "band, the".to_string()),
+			(id, "the band".to_string()),
+		];
+		assert_eq!(sorts, expected);
+
+		Ok(())
+	}
+
 	#[tokio::test]
 	async fn test_by_name_and_artist() -> Result<()> {
 		let db = Database::testing().await?;
@@ -0,0 +1,157 @@
+use std::collections::HashSet;
+
+use {
+	bonsaidb::{
+		core::{
+			connection::{Bound, Range},
+			schema::{SerializedCollection, SerializedView},
+		},
+		local::AsyncDatabase,
+	},
+	chrono::{DateTime, Duration, Utc},
+};
+
+use crate::{
+	database::{
+		models::scrobble::{Scrobble, ScrobbleService},
+		views::scrobble::{ScrobbleByService, ScrobbleByServiceKey},
+	},
+	errors::Result,
+};
+
+pub async fn enqueue(database: &AsyncDatabase, scrobble: Scrobble) -> Result<u64> {
+	let doc = scrobble.push_into_async(database).await?;
+	Ok(doc.header.id)
+}
+
+/// Gets the queued listens of a service due for a submission attempt at the given time, oldest attempt first.
+pub async fn get_due(
+	database: &AsyncDatabase,
+	service: ScrobbleService,
+	now: DateTime<Utc>,
+	limit: u32,
+) -> Result<Vec<(u64, Scrobble)>> {
+	let range = Range {
+		start: Bound::Included(ScrobbleByServiceKey::new(service, i64::MIN)),
+		end: Bound::Included(ScrobbleByServiceKey::new(service, now.timestamp_millis())),
+	};
+
+	let matches = ScrobbleByService::entries_async(database)
+		.with_key_range(range)
+		.ascending()
+		.limit(limit)
+		.query_with_collection_docs()
+		.await?;
+
+	let mut scrobbles = matches
+		.documents
+		.into_iter()
+		.map(|(id, doc)| (id, doc.contents))
+		.collect::<Vec<_>>();
+	scrobbles.sort_by_key(|(_, x)| x.next_attempt);
+
+	Ok(scrobbles)
+}
+
+/// Records a failed attempt, delaying the next one by the given backoff.
+pub async fn postpone(database: &AsyncDatabase, id: u64, backoff: Duration) -> Result<()> {
+	if let Some(mut doc) = Scrobble::get_async(&id, database).await? {
+		doc.contents.attempts += 1;
+		doc.contents.next_attempt = Utc::now() + backoff;
+		doc.update_async(database).await?;
+	}
+
+	Ok(())
+}
+
+pub async fn delete(database: &AsyncDatabase, id: u64) -> Result<()> {
+	if let Some(doc) = Scrobble::get_async(&id, database).await? {
+		doc.delete_async(database).await?;
+	}
+
+	Ok(())
+}
+
+/// Drops the queued listens of the given tracks, for tracks deleted from the library.
+pub async fn delete_by_track_ids(database: &AsyncDatabase, track_ids: &HashSet<u64>) -> Result<()> {
+	for doc in Scrobble::all_async(database).await? {
+		if track_ids.contains(&doc.contents.track.track_id) {
+			doc.delete_async(database).await?;
+		}
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod test {
+	use chrono::{Duration, Utc};
+
+	use crate::{
+		database::{
+			methods::scrobble::{delete, enqueue, get_due, postpone},
+			models::scrobble::{Scrobble, ScrobbleService, ScrobbleTrack},
+			Database,
+		},
+		errors::Result,
+	};
+
+	fn scrobble(service: ScrobbleService) -> Scrobble {
+		let track = ScrobbleTrack {
+			track_id: 1,
+			title: "Test Track".to_string(),
+			artists: vec!["Test Artist".to_string()],
+			release: None,
+			release_artists: vec![],
+			track_number: None,
+			duration: Some(180_000),
+			recording_mbz_id: None,
+			release_mbz_id: None,
+		};
+
+		Scrobble::new(service, track, Utc::now())
+	}
+
+	#[tokio::test]
+	async fn test_queue() -> Result<()> {
+		let db = Database::testing().await?;
+		let dbx = db.0;
+
+		let first = enqueue(&dbx, scrobble(ScrobbleService::ListenBrainz)).await?;
+		let second = enqueue(&dbx, scrobble(ScrobbleService::ListenBrainz)).await?;
+		let other = enqueue(&dbx, scrobble(ScrobbleService::LastFm)).await?;
+
+		let due = get_due(&dbx, ScrobbleService::ListenBrainz, Utc::now(), 10).await?;
+		assert_eq!(due.len(), 2);
+
+		let due = get_due(&dbx, ScrobbleService::LastFm, Utc::now(), 10).await?;
+		assert_eq!(due.iter().map(|(id, _)| *id).collect::<Vec<_>>(), vec![other]);
+
+		postpone(&dbx, first, Duration::minutes(1)).await?;
+
+		let due = get_due(&dbx, ScrobbleService::ListenBrainz, Utc::now(), 10).await?;
+		assert_eq!(due.iter().map(|(id, _)| *id).collect::<Vec<_>>(), vec![second]);
+
+		let due = get_due(
+			&dbx,
+			ScrobbleService::ListenBrainz,
+			Utc::now() + Duration::minutes(2),
+			10,
+		)
+		.await?;
+		assert_eq!(due.len(), 2);
+		assert_eq!(due[1].1.attempts, 1);
+
+		delete(&dbx, second).await?;
+		let due = get_due(
+			&dbx,
+			ScrobbleService::ListenBrainz,
+			Utc::now() + Duration::minutes(2),
+			10,
+		)
+		.await?;
+		assert_eq!(due.len(), 1);
+
+		Ok(())
+	}
+}
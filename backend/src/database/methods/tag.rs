@@ -1,22 +1,32 @@
-use bonsaidb::{
-	core::schema::{SerializedCollection, SerializedView},
-	local::AsyncDatabase,
+use std::{borrow::Cow, collections::HashSet};
+
+use {
+	bonsaidb::{
+		core::schema::{SerializedCollection, SerializedView},
+		local::AsyncDatabase,
+	},
+	chrono::Utc,
 };
 
 use crate::{
 	database::{
-		models::tag::Tag,
-		views::tag::{TagByNameAndType, TagByNameAndTypeKey},
+		helpers::replace_id,
+		models::tag::{normalize_tag_name, Tag, TagType},
+		views::{
+			release::ReleaseByGenreId,
+			tag::{TagByAlias, TagByNameAndTypeKey, TagByParentId},
+			track::TrackByGenreId,
+		},
 	},
-	errors::Result,
+	errors::{pre::database_entry_not_found, Error, Result},
 };
 
 /// Inserts a tag or gets an already existing one.
 ///
-/// Uniqueness is based on name and tag type.
+/// Uniqueness is based on the normalized name and tag type, matching against the aliases of existing tags.
 pub async fn get_or_insert(database: &AsyncDatabase, tag: Tag) -> Result<u64> {
-	let key = TagByNameAndTypeKey::new(tag.name.clone(), tag.type_.clone());
-	let matches = TagByNameAndType::entries_async(database).with_key(&key).query().await?;
+	let key = TagByNameAndTypeKey::new(normalize_tag_name(&tag.name), tag.type_.clone());
+	let matches = TagByAlias::entries_async(database).with_key(&key).query().await?;
 
 	let id = if let Some(tag) = matches.first() {
 		tag.source.id
@@ -28,35 +38,303 @@ pub async fn get_or_insert(database: &AsyncDatabase, tag: Tag) -> Result<u64> {
 	Ok(id)
 }
 
+/// Resolves the ids of a tag and all of its children, recursively.
+pub async fn get_with_descendant_ids(database: &AsyncDatabase, id: u64) -> Result<HashSet<u64>> {
+	let mut ids = HashSet::from([id]);
+	let mut to_visit = vec![id];
+
+	while let Some(parent_id) = to_visit.pop() {
+		let children = TagByParentId::entries_async(database)
+			.with_key(&parent_id)
+			.query()
+			.await?;
+
+		for child in children {
+			if ids.insert(child.source.id) {
+				to_visit.push(child.source.id);
+			}
+		}
+	}
+
+	Ok(ids)
+}
+
+/// Sets or clears the parent of a tag, refusing to create cycles.
+pub async fn set_parent(database: &AsyncDatabase, id: u64, parent_id: Option<u64>) -> Result<()> {
+	let mut doc = Tag::get_async(&id, database)
+		.await?
+		.ok_or_else(|| database_entry_not_found("tags", id))?;
+
+	if let Some(parent_id) = parent_id {
+		if get_with_descendant_ids(database, id).await?.contains(&parent_id) {
+			let m = format!("Tag '{parent_id}' is either the same as or a descendant of tag '{id}'");
+			return Err(Error::new("Tag hierarchy cycle", Cow::Owned(m)));
+		}
+	}
+
+	doc.contents.parent_id = parent_id;
+	doc.contents.date_modified = Utc::now();
+	doc.update_async(database).await?;
+
+	Ok(())
+}
+
+/// Adds an alias to a tag, so future tags matching the alias resolve to it.
+///
+/// Aliases that already resolve to another tag of the same type are refused.
+pub async fn add_alias(database: &AsyncDatabase, id: u64, alias: String) -> Result<()> {
+	let mut doc = Tag::get_async(&id, database)
+		.await?
+		.ok_or_else(|| database_entry_not_found("tags", id))?;
+
+	let normalized = normalize_tag_name(&alias);
+	let key = TagByNameAndTypeKey::new(normalized.clone(), doc.contents.type_.clone());
+	let matches = TagByAlias::entries_async(database).with_key(&key).query().await?;
+
+	if let Some(other) = matches.iter().find(|x| x.source.id != id) {
+		let m = format!("'{alias}' already resolves to tag '{}'", other.source.id);
+		return Err(Error::new("Tag alias conflict", Cow::Owned(m)));
+	}
+
+	if !matches.is_empty() {
+		return Ok(());
+	}
+
+	doc.contents.aliases.get_or_insert_with(Vec::new).push(alias);
+	doc.contents.date_modified = Utc::now();
+	doc.update_async(database).await?;
+
+	Ok(())
+}
+
+/// Merges a genre into another one.
+///
+/// Every release and track referring to `source_id` is rewritten to refer to `target_id` instead,
+/// the name and aliases of the source are kept as aliases of the target, and the source is deleted.
+/// The children of the source are moved under the target, so the target can't be one of its descendants.
+pub async fn merge_genres(database: &AsyncDatabase, source_id: u64, target_id: u64) -> Result<()> {
+	if source_id == target_id {
+		return Ok(());
+	}
+
+	let source = Tag::get_async(&source_id, database)
+		.await?
+		.ok_or_else(|| database_entry_not_found("tags", source_id))?;
+	let mut target = Tag::get_async(&target_id, database)
+		.await?
+		.ok_or_else(|| database_entry_not_found("tags", target_id))?;
+
+	for (id, tag) in [(source_id, &source.contents), (target_id, &target.contents)] {
+		if tag.type_ != TagType::Genre {
+			let m = format!("Tag '{id}' is not a genre");
+			return Err(Error::new("Invalid tag type", Cow::Owned(m)));
+		}
+	}
+
+	// Nothing is written before the hierarchy is known to stay free of cycles.
+	if get_with_descendant_ids(database, source_id).await?.contains(&target_id) {
+		let m = format!("Tag '{target_id}' is a descendant of tag '{source_id}'");
+		return Err(Error::new("Tag hierarchy cycle", Cow::Owned(m)));
+	}
+
+	let releases = ReleaseByGenreId::entries_async(database)
+		.with_key(&source_id)
+		.query_with_collection_docs()
+		.await?;
+
+	for (_, mut doc) in releases.documents {
+		replace_id(
+			doc.contents.genre_ids.get_or_insert_with(Vec::new),
+			source_id,
+			target_id,
+		);
+		doc.contents.date_modified = Utc::now();
+		doc.update_async(database).await?;
+	}
+
+	let tracks = TrackByGenreId::entries_async(database)
+		.with_key(&source_id)
+		.query_with_collection_docs()
+		.await?;
+
+	for (_, mut doc) in tracks.documents {
+		replace_id(
+			doc.contents.genre_ids.get_or_insert_with(Vec::new),
+			source_id,
+			target_id,
+		);
+		doc.contents.date_modified = Utc::now();
+		doc.update_async(database).await?;
+	}
+
+	let children = TagByParentId::entries_async(database)
+		.with_key(&source_id)
+		.query()
+		.await?;
+
+	for child in children {
+		set_parent(database, child.source.id, Some(target_id)).await?;
+	}
+
+	// The names of the source resolve to itself until it's deleted, so they're added without going through add_alias.
+	let x = &mut target.contents;
+	let mut known = std::iter::once(&x.name)
+		.chain(x.aliases.iter().flatten())
+		.map(|y| normalize_tag_name(y))
+		.collect::<HashSet<String>>();
+
+	let source_aliases = source.contents.aliases.clone().unwrap_or_default();
+	for alias in std::iter::once(source.contents.name.clone()).chain(source_aliases) {
+		if known.insert(normalize_tag_name(&alias)) {
+			x.aliases.get_or_insert_with(Vec::new).push(alias);
+		}
+	}
+
+	x.date_modified = Utc::now();
+	target.update_async(database).await?;
+	source.delete_async(database).await?;
+
+	Ok(())
+}
+
 #[cfg(test)]
 mod test {
 	use bonsaidb::core::schema::{SerializedCollection, SerializedView};
 
 	use crate::{
 		database::{
-			methods::tag::get_or_insert,
-			models::tag::{Tag, TagType},
+			methods::tag::{add_alias, get_or_insert, get_with_descendant_ids, merge_genres, set_parent},
+			models::{
+				release::Release,
+				tag::{normalize_tag_name, Tag, TagType},
+			},
 			views::tag::{TagByNameAndType, TagByNameAndTypeKey},
 			Database,
 		},
 		errors::Result,
 	};
 
+	#[test]
+	fn test_normalize_tag_name() {
+		assert_eq!(normalize_tag_name("Hip-Hop"), "hip hop");
+		assert_eq!(normalize_tag_name("  hip   hop "), "hip hop");
+		assert_eq!(normalize_tag_name("Hip_Hop"), "hip hop");
+	}
+
 	#[tokio::test]
-	async fn test_get_or_insert() -> Result<()> {
+	async fn test_get_or_insert_alias() -> Result<()> {
 		let db = Database::testing().await?;
 		let dbx = db.0;
 
-		let tag = Tag {
-			name: "Tag 1".to_string(),
-			type_: TagType::Genre,
-		};
+		let mut tag = Tag::new("Electronic".to_string(), TagType::Genre);
+		tag.aliases = Some(vec!["Electronica".to_string()]);
 		let doc = tag.push_into_async(&dbx).await?;
 
-		let tag = Tag {
-			name: "Tag 1".to_string(),
-			type_: TagType::Genre,
+		let hyphenated = Tag::new("Hip-Hop".to_string(), TagType::Genre);
+		let hip_hop_id = get_or_insert(&dbx, hyphenated).await?;
+
+		let spaced = Tag::new("hip hop".to_string(), TagType::Genre);
+		assert_eq!(get_or_insert(&dbx, spaced).await?, hip_hop_id);
+
+		let alias = Tag::new("electronica".to_string(), TagType::Genre);
+		assert_eq!(get_or_insert(&dbx, alias).await?, doc.header.id);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_hierarchy() -> Result<()> {
+		let db = Database::testing().await?;
+		let dbx = db.0;
+
+		let root = get_or_insert(&dbx, Tag::new("Hip Hop".to_string(), TagType::Genre)).await?;
+		let child = get_or_insert(&dbx, Tag::new("Trap".to_string(), TagType::Genre)).await?;
+		let grandchild = get_or_insert(&dbx, Tag::new("Drill".to_string(), TagType::Genre)).await?;
+
+		set_parent(&dbx, child, Some(root)).await?;
+		set_parent(&dbx, grandchild, Some(child)).await?;
+
+		let ids = get_with_descendant_ids(&dbx, root).await?;
+		assert_eq!(ids.len(), 3);
+
+		assert!(set_parent(&dbx, root, Some(grandchild)).await.is_err());
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_merge_genres() -> Result<()> {
+		let db = Database::testing().await?;
+		let dbx = db.0;
+
+		let source = get_or_insert(&dbx, Tag::new("Rap".to_string(), TagType::Genre)).await?;
+		let target = get_or_insert(&dbx, Tag::new("Hip Hop".to_string(), TagType::Genre)).await?;
+
+		let release = Release {
+			genre_ids: Some(vec![source, target]),
+			..Default::default()
+		};
+		let doc = release.push_into_async(&dbx).await?;
+
+		merge_genres(&dbx, source, target).await?;
+
+		let release = Release::get_async(&doc.header.id, &dbx).await?.unwrap();
+		assert_eq!(release.contents.genre_ids, Some(vec![target]));
+		assert!(Tag::get_async(&source, &dbx).await?.is_none());
+
+		let rap = Tag::new("rap".to_string(), TagType::Genre);
+		assert_eq!(get_or_insert(&dbx, rap).await?, target);
+
+		// Merging a genre into one of its descendants is refused before anything is rewritten.
+		let child = get_or_insert(&dbx, Tag::new("Trap".to_string(), TagType::Genre)).await?;
+		set_parent(&dbx, child, Some(target)).await?;
+
+		let release = Release {
+			genre_ids: Some(vec![target]),
+			..Default::default()
 		};
+		let doc = release.push_into_async(&dbx).await?;
+
+		assert!(merge_genres(&dbx, target, child).await.is_err());
+		let release = Release::get_async(&doc.header.id, &dbx).await?.unwrap();
+		assert_eq!(release.contents.genre_ids, Some(vec![target]));
+		assert!(Tag::get_async(&target, &dbx).await?.is_some());
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_add_alias() -> Result<()> {
+		let db = Database::testing().await?;
+		let dbx = db.0;
+
+		let electronic = get_or_insert(&dbx, Tag::new("Electronic".to_string(), TagType::Genre)).await?;
+		let techno = get_or_insert(&dbx, Tag::new("Techno".to_string(), TagType::Genre)).await?;
+		let other = get_or_insert(&dbx, Tag::new("Techno".to_string(), TagType::Other)).await?;
+
+		add_alias(&dbx, electronic, "Electronica".to_string()).await?;
+		add_alias(&dbx, electronic, "electronica".to_string()).await?;
+		assert!(add_alias(&dbx, electronic, "techno".to_string()).await.is_err());
+		assert!(add_alias(&dbx, techno, "Electronica".to_string()).await.is_err());
+
+		let tag = Tag::get_async(&electronic, &dbx).await?.unwrap();
+		assert_eq!(tag.contents.aliases, Some(vec!["Electronica".to_string()]));
+
+		// Tags of another type don't collide.
+		add_alias(&dbx, other, "Electronica".to_string()).await?;
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_get_or_insert() -> Result<()> {
+		let db = Database::testing().await?;
+		let dbx = db.0;
+
+		let tag = Tag::new("Tag 1".to_string(), TagType::Genre);
+		let doc = tag.push_into_async(&dbx).await?;
+
+		let tag = Tag::new("Tag 1".to_string(), TagType::Genre);
 		let result = get_or_insert(&dbx, tag).await?;
 		assert_eq!(result, doc.header.id);
 
@@ -68,20 +346,11 @@ mod test {
 		let db = Database::testing().await?;
 		let dbx = db.0;
 
-		let tag_1 = Tag {
-			name: "Tag 1".to_string(),
-			type_: TagType::Genre,
-		};
+		let tag_1 = Tag::new("Tag 1".to_string(), TagType::Genre);
 
-		let tag_2 = Tag {
-			name: "Tag 2".to_string(),
-			type_: TagType::Genre,
-		};
+		let tag_2 = Tag::new("Tag 2".to_string(), TagType::Genre);
 
-		let tag_1_other = Tag {
-			name: "Tag 1".to_string(),
-			type_: TagType::Other,
-		};
+		let tag_1_other = Tag::new("Tag 1".to_string(), TagType::Other);
 
 		tag_1.push_into_async(&dbx).await?;
 		tag_2.push_into_async(&dbx).await?;
@@ -1 +1,318 @@
+use std::collections::HashSet;
 
+use {
+	bonsaidb::{
+		core::schema::{SerializedCollection, SerializedView},
+		local::AsyncDatabase,
+	},
+	chrono::Utc,
+};
+
+use crate::{
+	database::{
+		helpers::{get_play_count_keys, paginate, paginate_by_keys},
+		methods,
+		models::{
+			person::PersonType,
+			track::{Track, MAX_RATING},
+		},
+		views::track::{
+			TrackByArtistSort, TrackByDate, TrackByDateAdded, TrackByGenreId, TrackByPath, TrackByPerson,
+			TrackByPersonKey, TrackByReleaseId, TrackByTitle,
+		},
+	},
+	errors::{
+		pre::{database_entry_not_found, invalid_rating},
+		Result,
+	},
+	models::tauri::{
+		track::{TrackFilter, TrackSort},
+		Page, PageRequest,
+	},
+};
+
+/// Inserts a track, or updates the track of the same library that resides in the same path and segment.
+///
+/// Updating keeps the [Track::date_added] and [Track::rating] of the existing track while bumping its
+/// [Track::date_modified].
+/// Inserting a segment of a file removes the track that previously spanned the whole file, along with the segments
+/// it overlaps, which were left behind by an edited CUE sheet. Inserting a whole file removes every segment of it.
+pub async fn insert_or_update(database: &AsyncDatabase, mut track: Track) -> Result<u64> {
+	let matches = TrackByPath::entries_async(database)
+		.with_key(&track.path)
+		.query_with_collection_docs()
+		.await?;
+
+	let mut existing = None;
+
+	let same_library = matches
+		.documents
+		.into_values()
+		.filter(|x| x.contents.library_id == track.library_id);
+
+	for doc in same_library {
+		let is_stale = match (doc.contents.segment, track.segment) {
+			(x, y) if x.map(|z| z.start) == y.map(|z| z.start) => {
+				existing = Some(doc);
+				continue;
+			}
+			(Some(x), Some(y)) => x.overlaps(&y),
+			_ => true,
+		};
+
+		if is_stale {
+			doc.delete_async(database).await?;
+		}
+	}
+
+	let id = if let Some(mut doc) = existing {
+		track.date_added = doc.contents.date_added;
+		track.date_modified = Utc::now();
+		track.rating = doc.contents.rating;
+
+		doc.contents = track;
+		doc.update_async(database).await?;
+		doc.header.id
+	} else {
+		let track = track.push_into_async(database).await?;
+		track.header.id
+	};
+
+	Ok(id)
+}
+
+/// Rates a track from 1 to [MAX_RATING], or removes its rating.
+pub async fn set_rating(database: &AsyncDatabase, track_id: u64, rating: Option<u8>) -> Result<()> {
+	if let Some(x) = rating.filter(|x| !(1..=MAX_RATING).contains(x)) {
+		return Err(invalid_rating(x));
+	}
+
+	let mut doc = Track::get_async(&track_id, database)
+		.await?
+		.ok_or_else(|| database_entry_not_found("tracks", track_id))?;
+
+	if doc.contents.rating != rating {
+		doc.contents.rating = rating;
+		doc.contents.date_modified = Utc::now();
+		doc.update_async(database).await?;
+	}
+
+	Ok(())
+}
+
+/// Queries a page of tracks sorted by the given key.
+pub async fn query(
+	database: &AsyncDatabase,
+	request: PageRequest,
+	sort: TrackSort,
+	filter: TrackFilter,
+) -> Result<Page<Track>> {
+	let candidates = get_candidates(database, &filter).await?;
+	let matches = |_: &Track| true;
+
+	match sort {
+		TrackSort::Title => paginate::<TrackByTitle, _>(database, request, Some(&candidates), matches).await,
+		TrackSort::Artist => paginate::<TrackByArtistSort, _>(database, request, Some(&candidates), matches).await,
+		TrackSort::Date => paginate::<TrackByDate, _>(database, request, Some(&candidates), matches).await,
+		TrackSort::Added => paginate::<TrackByDateAdded, _>(database, request, Some(&candidates), matches).await,
+		TrackSort::PlayCount => {
+			let counts = methods::play::get_counts(database).await?;
+			let keys = get_play_count_keys(&candidates, &counts);
+			paginate_by_keys(database, request, keys, matches).await
+		}
+	}
+}
+
+/// Fills in the artist sort of the tracks saved before it fell back to the first artist.
+pub async fn fill_artist_sort(database: &AsyncDatabase) -> Result<()> {
+	for mut doc in Track::all_async(database).await? {
+		if doc.contents.artist_sort.is_some() {
+			continue;
+		}
+
+		doc.contents.artist_sort = methods::person::get_artist_sort(database, &doc.contents.artists).await?;
+		if doc.contents.artist_sort.is_some() {
+			doc.update_async(database).await?;
+		}
+	}
+
+	Ok(())
+}
+
+/// Moves the composers and producers of the tracks saved before credits existed into their credits.
+pub async fn convert_legacy_credits(database: &AsyncDatabase) -> Result<()> {
+	for mut doc in Track::all_async(database).await? {
+		if doc.contents.convert_legacy_credits() {
+			doc.update_async(database).await?;
+		}
+	}
+
+	Ok(())
+}
+
+/// Resolves the ids of the tracks that match the view backed filters, always scoped to a library.
+async fn get_candidates(database: &AsyncDatabase, filter: &TrackFilter) -> Result<HashSet<u64>> {
+	let library_id = match filter.library_id {
+		Some(x) => x,
+		None => methods::library::get_active_id(database).await?,
+	};
+
+	let mut ids = methods::library::get_track_ids(database, library_id).await?;
+	let mut sets = Vec::<HashSet<u64>>::new();
+
+	if let Some(release_id) = filter.release_id {
+		let x = TrackByReleaseId::entries_async(database)
+			.with_key(&release_id)
+			.query()
+			.await?;
+		sets.push(x.iter().map(|e| e.source.id).collect());
+	}
+
+	if let Some(artist_id) = filter.artist_id {
+		let key = TrackByPersonKey::new(artist_id, PersonType::Artist);
+		let x = TrackByPerson::entries_async(database).with_key(&key).query().await?;
+		sets.push(x.iter().map(|e| e.source.id).collect());
+	}
+
+	// Tracks of sub-genres are considered to be a part of the parent genre.
+	if let Some(genre_id) = filter.genre_id {
+		let mut ids = HashSet::new();
+
+		for id in methods::tag::get_with_descendant_ids(database, genre_id).await? {
+			let x = TrackByGenreId::entries_async(database).with_key(&id).query().await?;
+			ids.extend(x.iter().map(|e| e.source.id));
+		}
+
+		sets.push(ids);
+	}
+
+	for set in sets {
+		ids.retain(|x| set.contains(x));
+	}
+
+	Ok(ids)
+}
+
+#[cfg(test)]
+mod test {
+	use bonsaidb::core::schema::{SerializedCollection, SerializedView};
+
+	use crate::{
+		constants::TEST_TRACK_PATH,
+		database::{
+			methods::track::{insert_or_update, set_rating},
+			models::{
+				person::PersonType,
+				track::{Credit, Track, TrackSegment},
+			},
+			views::track::TrackByPath,
+			Database,
+		},
+		errors::Result,
+	};
+
+	#[test]
+	fn test_convert_legacy_credits() {
+		let mut value = serde_json::to_value(Track::default()).unwrap();
+		assert!(value.get("composer_ids").is_none());
+
+		value["composer_ids"] = serde_json::json!([1, 2]);
+		value["producer_ids"] = serde_json::json!([1]);
+
+		let mut track = serde_json::from_value::<Track>(value).unwrap();
+		assert!(track.convert_legacy_credits());
+		assert!(!track.convert_legacy_credits());
+
+		let credits = vec![
+			Credit::new(1, PersonType::Composer, None),
+			Credit::new(2, PersonType::Composer, None),
+			Credit::new(1, PersonType::Producer, None),
+		];
+		assert_eq!(track.credits, credits);
+	}
+
+	#[tokio::test]
+	async fn test_insert_or_update() -> Result<()> {
+		let db = Database::testing().await?;
+		let dbx = db.0;
+
+		let id = insert_or_update(&dbx, Track::default()).await?;
+		set_rating(&dbx, id, Some(4)).await?;
+		assert!(set_rating(&dbx, id, Some(6)).await.is_err());
+		let before = Track::get_async(&id, &dbx).await?.unwrap();
+
+		let track = Track {
+			title: "Updated Track".to_string(),
+			..Default::default()
+		};
+		let updated_id = insert_or_update(&dbx, track).await?;
+		assert_eq!(updated_id, id);
+
+		let after = Track::get_async(&id, &dbx).await?.unwrap();
+		assert_eq!(after.contents.title, "Updated Track");
+		assert_eq!(after.contents.rating, Some(4));
+		assert_eq!(after.contents.date_added, before.contents.date_added);
+		assert!(after.contents.date_modified >= before.contents.date_modified);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_insert_or_update_segments() -> Result<()> {
+		let db = Database::testing().await?;
+		let dbx = db.0;
+
+		let whole_id = insert_or_update(&dbx, Track::default()).await?;
+
+		let first = Track {
+			segment: Some(TrackSegment {
+				start: 0,
+				end: Some(1000),
+			}),
+			..Default::default()
+		};
+		let second = Track {
+			segment: Some(TrackSegment { start: 1000, end: None }),
+			..Default::default()
+		};
+
+		let first_id = insert_or_update(&dbx, first).await?;
+		let second_id = insert_or_update(&dbx, second).await?;
+		assert_ne!(first_id, second_id);
+		assert!(Track::get_async(&whole_id, &dbx).await?.is_none());
+
+		let first = Track {
+			title: "Updated Track".to_string(),
+			segment: Some(TrackSegment {
+				start: 0,
+				end: Some(1000),
+			}),
+			..Default::default()
+		};
+		assert_eq!(insert_or_update(&dbx, first).await?, first_id);
+
+		// An edited sheet moving the second track replaces the segments it overlaps.
+		let first = Track {
+			segment: Some(TrackSegment {
+				start: 0,
+				end: Some(1500),
+			}),
+			..Default::default()
+		};
+		assert_eq!(insert_or_update(&dbx, first).await?, first_id);
+		assert!(Track::get_async(&second_id, &dbx).await?.is_none());
+
+		// Removing the sheet leaves only the track spanning the whole file.
+		let whole_id = insert_or_update(&dbx, Track::default()).await?;
+		assert!(Track::get_async(&first_id, &dbx).await?.is_none());
+
+		let paths = TrackByPath::entries_async(&dbx)
+			.with_key(TEST_TRACK_PATH)
+			.query()
+			.await?;
+		assert_eq!(paths.len(), 1);
+		assert_eq!(paths[0].source.id, whole_id);
+
+		Ok(())
+	}
+}
@@ -0,0 +1,261 @@
+use std::collections::{HashMap, HashSet};
+
+use {
+	bonsaidb::{
+		core::{
+			connection::{Bound, Range},
+			schema::{SerializedCollection, SerializedView},
+		},
+		local::AsyncDatabase,
+	},
+	chrono::Utc,
+};
+
+use crate::{
+	database::{
+		models::{
+			track::Track,
+			work::{normalize_work_name, Work},
+		},
+		views::{
+			track::{TrackByLibraryAndWork, TrackByLibraryAndWorkKey, TrackByWorkId},
+			work::{WorkByMbzId, WorkByName},
+		},
+	},
+	errors::Result,
+};
+
+/// Inserts a work or gets an already existing one.
+///
+/// Works are resolved by their MusicBrainz id first. Otherwise, works sharing the name are matched when they share
+/// a composer and their catalogue numbers don't differ. The missing details of the matched work are filled in.
+pub async fn get_or_insert(database: &AsyncDatabase, work: Work) -> Result<u64> {
+	if let Some(mbz_id) = &work.mbz_id {
+		let matches = WorkByMbzId::entries_async(database)
+			.with_key(&mbz_id.to_lowercase())
+			.query()
+			.await?;
+
+		if let Some(x) = matches.first() {
+			return Ok(x.source.id);
+		}
+	}
+
+	let matches = WorkByName::entries_async(database)
+		.with_key(&normalize_work_name(&work.name))
+		.query_with_collection_docs()
+		.await?;
+
+	let existing = matches
+		.documents
+		.into_values()
+		.find(|x| is_same_work(&x.contents, &work));
+
+	let id = if let Some(mut doc) = existing {
+		let x = &mut doc.contents;
+		let composer_count = x.composer_ids.len();
+
+		for id in work.composer_ids {
+			if !x.composer_ids.contains(&id) {
+				x.composer_ids.push(id);
+			}
+		}
+
+		let changed = x.composer_ids.len() != composer_count
+			|| (x.mbz_id.is_none() && work.mbz_id.is_some())
+			|| (x.catalogue_number.is_none() && work.catalogue_number.is_some())
+			|| (x.key.is_none() && work.key.is_some());
+
+		if changed {
+			x.mbz_id = x.mbz_id.take().or(work.mbz_id);
+			x.catalogue_number = x.catalogue_number.take().or(work.catalogue_number);
+			x.key = x.key.take().or(work.key);
+			x.date_modified = Utc::now();
+			doc.update_async(database).await?;
+		}
+
+		doc.header.id
+	} else {
+		let work = work.push_into_async(database).await?;
+		work.header.id
+	};
+
+	Ok(id)
+}
+
+/// Gets the recordings of a work across every release, as the ids of their releases with the tracks in each.
+///
+/// Releases are ordered by their id, and the tracks of a release by their movement, disc and track number.
+/// Only the tracks of the given ids are included when `candidates` is given, like the ones of a library.
+/// Counts the releases recording each work in a library, skipping the works without any recording there.
+pub async fn get_recording_counts(database: &AsyncDatabase, library_id: u64) -> Result<HashMap<u64, u64>> {
+	let range = Range {
+		start: Bound::Included(TrackByLibraryAndWorkKey::new(library_id, u64::MIN, u64::MIN)),
+		end: Bound::Included(TrackByLibraryAndWorkKey::new(library_id, u64::MAX, u64::MAX)),
+	};
+
+	let groups = TrackByLibraryAndWork::entries_async(database)
+		.with_key_range(range)
+		.reduce_grouped()
+		.await?;
+
+	let mut counts = HashMap::<u64, u64>::new();
+	for group in groups {
+		*counts.entry(group.key.work_id).or_default() += 1;
+	}
+
+	Ok(counts)
+}
+
+pub async fn get_recordings(
+	database: &AsyncDatabase,
+	work_id: u64,
+	candidates: Option<&HashSet<u64>>,
+) -> Result<Vec<(u64, Vec<(u64, Track)>)>> {
+	let entries = TrackByWorkId::entries_async(database)
+		.with_key(&work_id)
+		.query_with_collection_docs()
+		.await?;
+
+	let mut tracks = entries
+		.documents
+		.into_iter()
+		.filter(|(id, _)| candidates.map_or(true, |x| x.contains(id)))
+		.map(|(id, doc)| (id, doc.contents))
+		.collect::<Vec<(u64, Track)>>();
+
+	tracks.sort_by_key(|(_, x)| {
+		let movement = x.movement.as_ref().and_then(|y| y.number);
+		(x.release_id, movement, x.disc_number, x.track_number)
+	});
+
+	let mut recordings = Vec::<(u64, Vec<(u64, Track)>)>::new();
+	for (id, track) in tracks {
+		match recordings.last_mut() {
+			Some((release_id, x)) if *release_id == track.release_id => x.push((id, track)),
+			_ => recordings.push((track.release_id, vec![(id, track)])),
+		}
+	}
+
+	Ok(recordings)
+}
+
+/// Checks whether a work sharing the name of another one is the same work.
+///
+/// Works without composers match any work, as the composers of a track aren't always tagged.
+fn is_same_work(a: &Work, b: &Work) -> bool {
+	let composers_agree = a.composer_ids.is_empty()
+		|| b.composer_ids.is_empty()
+		|| a.composer_ids.iter().any(|x| b.composer_ids.contains(x));
+
+	let mbz_id_agrees = match (&a.mbz_id, &b.mbz_id) {
+		(Some(x), Some(y)) => x.eq_ignore_ascii_case(y),
+		_ => true,
+	};
+
+	let catalogue_number_agrees = match (&a.catalogue_number, &b.catalogue_number) {
+		(Some(x), Some(y)) => normalize_work_name(x) == normalize_work_name(y),
+		_ => true,
+	};
+
+	composers_agree && mbz_id_agrees && catalogue_number_agrees
+}
+
+#[cfg(test)]
+mod test {
+	use std::collections::HashMap;
+
+	use bonsaidb::core::schema::SerializedCollection;
+
+	use crate::{
+		constants::DEFAULT_LIBRARY_ID,
+		database::{
+			methods::work::{get_or_insert, get_recording_counts, get_recordings},
+			models::{
+				track::{Movement, Track},
+				work::Work,
+			},
+			Database,
+		},
+		errors::Result,
+		models::temp::work::TempWork,
+	};
+
+	#[tokio::test]
+	async fn test_get_or_insert() -> Result<()> {
+		let db = Database::testing().await?;
+		let dbx = db.0;
+
+		let name = "Cello Suite No. 1 in G major, BWV 1007".to_string();
+		let temp = TempWork {
+			name: name.clone(),
+			mbz_id: None,
+		};
+		let work = temp.into_work(vec![1]);
+		assert_eq!(work.catalogue_number.as_deref(), Some("BWV 1007"));
+		assert_eq!(work.key.as_deref(), Some("G major"));
+
+		let id = get_or_insert(&dbx, work).await?;
+
+		let uncredited = Work::new(name.to_lowercase());
+		assert_eq!(get_or_insert(&dbx, uncredited).await?, id);
+
+		let with_mbz_id = Work {
+			composer_ids: vec![1, 2],
+			mbz_id: Some("a".to_string()),
+			..Work::new(name.clone())
+		};
+		assert_eq!(get_or_insert(&dbx, with_mbz_id).await?, id);
+
+		let work = Work::get_async(&id, &dbx).await?.unwrap().contents;
+		assert_eq!(work.composer_ids, vec![1, 2]);
+		assert_eq!(work.mbz_id.as_deref(), Some("a"));
+
+		let other_composer = Work {
+			composer_ids: vec![3],
+			..Work::new(name)
+		};
+		assert_ne!(get_or_insert(&dbx, other_composer).await?, id);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_get_recordings() -> Result<()> {
+		let db = Database::testing().await?;
+		let dbx = db.0;
+
+		let work_id = Work::new("Work".to_string()).push_into_async(&dbx).await?.header.id;
+
+		for (release_id, number) in [(2, 2), (1, 1), (2, 1)] {
+			let track = Track {
+				release_id,
+				work_id: Some(work_id),
+				movement: Some(Movement {
+					number: Some(number),
+					..Default::default()
+				}),
+				..Default::default()
+			};
+			track.push_into_async(&dbx).await?;
+		}
+		Track::default().push_into_async(&dbx).await?;
+
+		let recordings = get_recordings(&dbx, work_id, None).await?;
+		let recordings = recordings
+			.into_iter()
+			.map(|(release_id, tracks)| {
+				let numbers = tracks.iter().map(|(_, x)| x.movement.as_ref().unwrap().number.unwrap());
+				(release_id, numbers.collect::<Vec<u32>>())
+			})
+			.collect::<Vec<_>>();
+
+		assert_eq!(recordings, vec![(1, vec![1]), (2, vec![1, 2])]);
+
+		let counts = get_recording_counts(&dbx, DEFAULT_LIBRARY_ID).await?;
+		assert_eq!(counts, HashMap::from([(work_id, 2)]));
+		assert!(get_recording_counts(&dbx, DEFAULT_LIBRARY_ID + 1).await?.is_empty());
+
+		Ok(())
+	}
+}
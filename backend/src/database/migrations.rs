@@ -0,0 +1,312 @@
+use std::{
+	fs,
+	path::{Path, PathBuf},
+};
+
+use {
+	bonsaidb::{core::keyvalue::AsyncKeyValue, local::AsyncDatabase},
+	tracing::info,
+};
+
+use crate::{
+	database::{
+		constants::{KEY_IS_FIRST_RUN, KEY_SCHEMA_VERSION},
+		helpers, methods,
+	},
+	errors::{pre::unsupported_schema_version, Result},
+	utils,
+};
+
+/// Version of the documents written by this build.
+///
+/// Bumped along with a new step in [migrate_step] whenever a model changes in a way that old documents can't be read as.
+pub const SCHEMA_VERSION: u64 = 4;
+
+/// Reads the schema version of a database.
+///
+/// Databases that were set up before the version was stored are at version 0, while empty ones are already current.
+pub async fn get_version(database: &AsyncDatabase) -> Result<u64> {
+	let version = database
+		.get_key(KEY_SCHEMA_VERSION)
+		.await?
+		.map(|rx| rx.deserialize::<u64>())
+		.transpose()?;
+
+	match version {
+		Some(x) => Ok(x),
+		None if database.get_key(KEY_IS_FIRST_RUN).await?.is_some() => Ok(0),
+		None => Ok(SCHEMA_VERSION),
+	}
+}
+
+pub async fn set_version(database: &AsyncDatabase, version: u64) -> Result<()> {
+	database.set_key(KEY_SCHEMA_VERSION, &version).await?;
+
+	Ok(())
+}
+
+/// Checks whether a database at the given version has to be migrated, refusing ones written by a newer build.
+pub fn is_pending(version: u64) -> Result<bool> {
+	if version > SCHEMA_VERSION {
+		return Err(unsupported_schema_version(version));
+	}
+
+	Ok(version < SCHEMA_VERSION)
+}
+
+/// Copies the database files next to the database, like `main.bonsaidb.v1.backup`, before they're migrated.
+///
+/// The database must be closed while it's copied. The files are copied under a temporary name first and only renamed
+/// once complete, so an existing backup of the same version is kept as is, since it was taken before a previous
+/// attempt that failed partway.
+pub fn backup(db_path: &Path, version: u64) -> Result<PathBuf> {
+	let mut name = db_path.file_name().unwrap_or_default().to_os_string();
+	name.push(format!(".v{version}.backup"));
+
+	let backup_path = db_path.with_file_name(&name);
+	if backup_path.exists() {
+		return Ok(backup_path);
+	}
+
+	// Leftovers of a copy that was interrupted are incomplete.
+	name.push(".tmp");
+	let temp_path = db_path.with_file_name(name);
+	if temp_path.exists() {
+		fs::remove_dir_all(&temp_path)?;
+	}
+
+	utils::fs::copy_dir(db_path, &temp_path)?;
+	fs::rename(&temp_path, &backup_path)?;
+
+	Ok(backup_path)
+}
+
+/// Upgrades the documents of a database step by step, from the given version up to [SCHEMA_VERSION].
+///
+/// The version is saved after every step, so an interrupted run continues from the last finished one.
+pub async fn run(database: &AsyncDatabase, from: u64) -> Result<()> {
+	for version in from..SCHEMA_VERSION {
+		info!("Migrating the database from version {version} to {}", version + 1);
+
+		migrate_step(database, version).await?;
+		set_version(database, version + 1).await?;
+	}
+
+	Ok(())
+}
+
+async fn migrate_step(database: &AsyncDatabase, from: u64) -> Result<()> {
+	match from {
+		// Scan locations were stored in a key before libraries existed.
+		0 => methods::library::ensure_default(database).await,
+		// Composers and producers were stored in their own fields before credits existed.
+		1 => methods::track::convert_legacy_credits(database).await,
+		// Releases and tracks without an artist sort in their tags were sorted as if they had no artists.
+		2 => {
+			methods::release::fill_artist_sort(database).await?;
+			methods::track::fill_artist_sort(database).await
+		}
+		// Dates were missing before they were tracked, and read as the Unix epoch.
+		3 => helpers::backfill_dates(database).await,
+		_ => unreachable!("No migration step from schema version {from}"),
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use {
+		bonsaidb::{
+			core::{
+				connection::AsyncConnection,
+				keyvalue::AsyncKeyValue,
+				schema::{Collection, SerializedCollection, SerializedView},
+			},
+			local::{config::StorageConfiguration, AsyncDatabase},
+		},
+		chrono::{DateTime, NaiveDate},
+		serde::{Deserialize, Serialize},
+	};
+
+	use crate::{
+		constants::{DEFAULT_LIBRARY_ID, TEST_TRACK_PATH, UNKNOWN_PERSON_ID, UNKNOWN_RELEASE_ID},
+		database::{
+			constants::{KEY_IS_FIRST_RUN, KEY_SCAN_LOCATIONS},
+			methods,
+			migrations::{get_version, is_pending, run, set_version, SCHEMA_VERSION},
+			models::{
+				library::Library,
+				person::{Person, PersonType},
+				track::{Credit, Track},
+				InlinedArtist, LocalSchema,
+			},
+			views::track::{TrackByPerson, TrackByPersonKey},
+			Database,
+		},
+		errors::Result,
+	};
+
+	/// A track as it was stored before the schema was versioned, without credits, a library or dates.
+	#[derive(Debug, Serialize, Deserialize, Collection)]
+	#[collection(name = "tracks")]
+	struct LegacyTrack {
+		title: String,
+		title_sort: Option<String>,
+		track_number: Option<u32>,
+		disc_number: Option<u32>,
+		original_date: Option<NaiveDate>,
+
+		artists: Vec<InlinedArtist>,
+		artist_sort: Option<String>,
+
+		release_id: u64,
+		composer_ids: Option<Vec<u64>>,
+		producer_ids: Option<Vec<u64>>,
+		cover_ids: Option<Vec<u64>>,
+
+		genre_ids: Option<Vec<u64>>,
+		tag_ids: Option<Vec<u64>>,
+
+		mbz_id: Option<String>,
+		path: String,
+	}
+
+	/// Writes the documents of a database from before the schema was versioned.
+	///
+	/// Scan locations are in a key instead of the default library, and the composers of tracks in `composer_ids`.
+	/// The track is written as raw bytes in its old shape, so the current model can't fill in any of its fields.
+	async fn write_v0_fixture(dbx: &AsyncDatabase) -> Result<u64> {
+		methods::person::insert_with_unique_id(dbx, Person::unknown(), UNKNOWN_PERSON_ID).await?;
+		dbx.set_key(KEY_IS_FIRST_RUN, &false).await?;
+		dbx.set_key(KEY_SCAN_LOCATIONS, &vec!["/music".to_string()]).await?;
+
+		let track = LegacyTrack {
+			title: "Test Track".to_string(),
+			title_sort: None,
+			track_number: None,
+			disc_number: None,
+			original_date: None,
+
+			artists: vec![InlinedArtist::unknown()],
+			artist_sort: None,
+
+			release_id: UNKNOWN_RELEASE_ID,
+			composer_ids: Some(vec![UNKNOWN_PERSON_ID]),
+			producer_ids: None,
+			cover_ids: None,
+
+			genre_ids: None,
+			tag_ids: None,
+
+			mbz_id: None,
+			path: TEST_TRACK_PATH.to_string(),
+		};
+
+		let bytes = <LegacyTrack as SerializedCollection>::serialize(&track)?;
+		let header = dbx.collection::<Track>().push_bytes(bytes).await?;
+
+		Ok(header.id.deserialize::<u64>()?)
+	}
+
+	async fn assert_current(dbx: &AsyncDatabase, track_id: u64) -> Result<()> {
+		assert_eq!(get_version(dbx).await?, SCHEMA_VERSION);
+
+		let library = Library::get_async(&DEFAULT_LIBRARY_ID, dbx).await?.unwrap();
+		assert_eq!(library.contents.scan_locations[0].path, "/music");
+		assert!(dbx.get_key(KEY_SCAN_LOCATIONS).await?.is_none());
+
+		let track = Track::get_async(&track_id, dbx).await?.unwrap().contents;
+		assert_eq!(
+			track.credits,
+			vec![Credit::new(UNKNOWN_PERSON_ID, PersonType::Composer, None)]
+		);
+		assert!(track.composer_ids.is_none());
+		assert_eq!(track.library_id, DEFAULT_LIBRARY_ID);
+		assert_ne!(track.date_added, DateTime::default());
+
+		let composed = TrackByPerson::entries_async(dbx)
+			.with_key(&TrackByPersonKey::new(UNKNOWN_PERSON_ID, PersonType::Composer))
+			.query()
+			.await?;
+		assert_eq!(composed.len(), 1);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_get_version() -> Result<()> {
+		let db = Database::testing().await?;
+		let dbx = db.0;
+
+		assert_eq!(get_version(&dbx).await?, SCHEMA_VERSION);
+
+		dbx.set_key(KEY_IS_FIRST_RUN, &false).await?;
+		assert_eq!(get_version(&dbx).await?, 0);
+
+		set_version(&dbx, 1).await?;
+		assert_eq!(get_version(&dbx).await?, 1);
+
+		assert!(is_pending(1)?);
+		assert!(!is_pending(SCHEMA_VERSION)?);
+		assert!(is_pending(SCHEMA_VERSION + 1).is_err());
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_run_from_v0() -> Result<()> {
+		let db = Database::testing().await?;
+		let dbx = db.0;
+
+		let track_id = write_v0_fixture(&dbx).await?;
+		assert_eq!(get_version(&dbx).await?, 0);
+
+		run(&dbx, 0).await?;
+		assert_current(&dbx, track_id).await?;
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_run_from_v1() -> Result<()> {
+		let db = Database::testing().await?;
+		let dbx = db.0;
+
+		// Version 1 databases already have their scan locations in the default library.
+		let track_id = write_v0_fixture(&dbx).await?;
+		methods::library::ensure_default(&dbx).await?;
+		set_version(&dbx, 1).await?;
+
+		run(&dbx, get_version(&dbx).await?).await?;
+		assert_current(&dbx, track_id).await?;
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_open_v0_fixture() -> Result<()> {
+		let root = std::env::temp_dir().join(format!("melody-migrations-{}", ulid::Ulid::new()));
+		let db_path = root.join("main.bonsaidb");
+
+		let track_id = {
+			let db_conf = StorageConfiguration::new(&db_path);
+			let dbx = AsyncDatabase::open::<LocalSchema>(db_conf).await?;
+			write_v0_fixture(&dbx).await?
+		};
+
+		let db = Database::new(&db_path).await?;
+		assert_current(db.inner_ref(), track_id).await?;
+		assert!(root.join("main.bonsaidb.v0.backup").is_dir());
+		assert!(!root.join("main.bonsaidb.v0.backup.tmp").exists());
+
+		// Opening a migrated database doesn't take another backup.
+		drop(db);
+		let db = Database::new(&db_path).await?;
+		assert_current(db.inner_ref(), track_id).await?;
+		assert!(!root.join(format!("main.bonsaidb.v{SCHEMA_VERSION}.backup")).exists());
+
+		drop(db);
+		std::fs::remove_dir_all(root)?;
+
+		Ok(())
+	}
+}
@@ -8,13 +8,14 @@ use {
 			AsyncDatabase as BonsaiDatabase,
 		},
 	},
-	tracing::debug,
+	tracing::{debug, info},
 };
 
 use crate::{
 	constants::UNKNOWN_PERSON_ID,
 	database::{
 		constants::KEY_IS_FIRST_RUN,
+		migrations::SCHEMA_VERSION,
 		models::{person::Person, LocalSchema},
 	},
 	errors::Result,
@@ -23,6 +24,7 @@ use crate::{
 pub mod constants;
 pub mod helpers;
 pub mod methods;
+pub mod migrations;
 pub mod models;
 pub mod views;
 
@@ -31,20 +33,41 @@ pub struct Database(pub BonsaiDatabase);
 impl Database {
 	/// Initialize the database.
 	///
+	/// Databases from an older schema version are backed up next to the database, then migrated.
 	/// This function should run in the context of tauri.
 	#[tracing::instrument]
 	pub async fn new(db_path: &Path) -> Result<Self> {
-		let db_conf = StorageConfiguration::new(db_path);
-		let database = BonsaiDatabase::open::<LocalSchema>(db_conf).await?;
-		debug!("Successfully opened the database at {:?}", db_path);
+		let database = Self::open(db_path).await?;
 
 		if database.get_key(KEY_IS_FIRST_RUN).await?.is_none() {
 			Self::run_first_time_setup(&database).await?;
+			return Ok(Self(database));
 		}
 
+		let version = migrations::get_version(&database).await?;
+		if !migrations::is_pending(version)? {
+			return Ok(Self(database));
+		}
+
+		// The files are only copied once nothing writes into them.
+		drop(database);
+		let backup_path = migrations::backup(db_path, version)?;
+		info!("Backed up the database at schema version {version} to {backup_path:?}");
+
+		let database = Self::open(db_path).await?;
+		migrations::run(&database, version).await?;
+
 		Ok(Self(database))
 	}
 
+	async fn open(db_path: &Path) -> Result<BonsaiDatabase> {
+		let db_conf = StorageConfiguration::new(db_path);
+		let database = BonsaiDatabase::open::<LocalSchema>(db_conf).await?;
+		debug!("Successfully opened the database at {:?}", db_path);
+
+		Ok(database)
+	}
+
 	#[cfg(test)]
 	pub async fn testing() -> Result<Self> {
 		use crate::database::constants::DB_MAIN_NAME;
@@ -62,8 +85,10 @@ impl Database {
 	async fn run_first_time_setup(database: &BonsaiDatabase) -> Result<()> {
 		let unknown_person = Person::unknown();
 		methods::person::insert_with_unique_id(database, unknown_person, UNKNOWN_PERSON_ID).await?;
+		methods::library::ensure_default(database).await?;
 
 		database.set_key(KEY_IS_FIRST_RUN, &false).await?;
+		migrations::set_version(database, SCHEMA_VERSION).await?;
 
 		Ok(())
 	}
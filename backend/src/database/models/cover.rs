@@ -1,6 +1,7 @@
 use {
 	blake3::Hash,
 	bonsaidb::core::{key::Key, schema::Collection},
+	chrono::{DateTime, Utc},
 	serde::{Deserialize, Serialize},
 };
 
@@ -34,6 +35,11 @@ pub struct Cover {
 	pub has_thumb: bool,
 	// TODO: hash get returned as bytes
 	pub hash: Hash,
+
+	#[serde(default)]
+	pub date_added: DateTime<Utc>,
+	#[serde(default)]
+	pub date_modified: DateTime<Utc>,
 }
 
 impl CoverMediaType {
@@ -44,6 +50,14 @@ impl CoverMediaType {
 		}
 	}
 
+	pub fn from_extension(extension: &str) -> Option<Self> {
+		match extension.to_lowercase().as_str() {
+			"png" => Some(Self::Png),
+			"jpg" | "jpeg" => Some(Self::Jpeg),
+			_ => None,
+		}
+	}
+
 	pub fn from_codec_id(value: rsmpeg::ffi::AVCodecID) -> Result<Self> {
 		use rsmpeg::ffi::{AVCodecID_AV_CODEC_ID_MJPEG, AVCodecID_AV_CODEC_ID_PNG};
 
@@ -1,10 +1,36 @@
-use bonsaidb::core::schema::Collection;
-use serde::{Deserialize, Serialize};
+use {
+	bonsaidb::core::schema::Collection,
+	chrono::{DateTime, Utc},
+	serde::{Deserialize, Serialize},
+};
 
-use crate::database::views::label::LabelByName;
+use crate::database::{models::CountryCode, views::label::LabelByName};
 
 #[derive(Debug, Serialize, Deserialize, Collection)]
 #[collection(name = "labels", views = [LabelByName])]
 pub struct Label {
 	pub name: String,
+	pub name_sort: Option<String>,
+	pub country: Option<CountryCode>,
+	pub mbz_id: Option<String>,
+
+	#[serde(default)]
+	pub date_added: DateTime<Utc>,
+	#[serde(default)]
+	pub date_modified: DateTime<Utc>,
+}
+
+impl Label {
+	pub fn new(name: String) -> Self {
+		let now = Utc::now();
+
+		Self {
+			name,
+			name_sort: None,
+			country: None,
+			mbz_id: None,
+			date_added: now,
+			date_modified: now,
+		}
+	}
 }
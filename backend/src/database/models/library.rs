@@ -0,0 +1,104 @@
+use {
+	bonsaidb::core::schema::Collection,
+	chrono::{DateTime, Utc},
+	serde::{Deserialize, Serialize},
+};
+
+/// A set of scan locations, owning the tracks and releases found in them.
+///
+/// People, labels and tags are shared between every library.
+#[derive(Debug, Clone, Serialize, Deserialize, Collection)]
+#[collection(name = "libraries")]
+pub struct Library {
+	pub name: String,
+	pub scan_locations: Vec<ScanLocation>,
+
+	#[serde(default)]
+	pub date_added: DateTime<Utc>,
+	#[serde(default)]
+	pub date_modified: DateTime<Utc>,
+}
+
+/// A directory to scan, along with the rules deciding which of its files are read.
+///
+/// Deserializes from a plain path too, which uses the default options.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "ScanLocationRepr")]
+pub struct ScanLocation {
+	pub path: String,
+	pub options: ScanOptions,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ScanLocationRepr {
+	Path(String),
+	Location {
+		path: String,
+		#[serde(default)]
+		options: ScanOptions,
+	},
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanOptions {
+	/// Glob patterns of the files to read, reading every audio file when empty.
+	pub include: Vec<String>,
+	/// Glob patterns of the files and directories to skip.
+	pub exclude: Vec<String>,
+	/// Skips the files and directories starting with a dot.
+	pub ignore_hidden: bool,
+	/// Skips the directories containing a `.nomedia` file, and the paths listed in `.melodyignore` files.
+	pub ignore_files: bool,
+	/// Levels of directories to descend into, where `0` only reads the files directly in the location.
+	pub max_depth: Option<u32>,
+	pub follow_symlinks: bool,
+	/// Smallest size of a file to read in bytes, which skips short samples and broken files.
+	pub min_file_size: Option<u64>,
+}
+
+impl Default for ScanOptions {
+	fn default() -> Self {
+		Self {
+			include: Vec::new(),
+			exclude: Vec::new(),
+			ignore_hidden: true,
+			ignore_files: true,
+			max_depth: None,
+			follow_symlinks: false,
+			min_file_size: None,
+		}
+	}
+}
+
+impl ScanLocation {
+	pub fn new(path: String) -> Self {
+		Self {
+			path,
+			options: ScanOptions::default(),
+		}
+	}
+}
+
+impl From<ScanLocationRepr> for ScanLocation {
+	fn from(value: ScanLocationRepr) -> Self {
+		match value {
+			ScanLocationRepr::Path(path) => Self::new(path),
+			ScanLocationRepr::Location { path, options } => Self { path, options },
+		}
+	}
+}
+
+impl Library {
+	pub fn new(name: String, scan_locations: Vec<ScanLocation>) -> Self {
+		let now = Utc::now();
+
+		Self {
+			name,
+			scan_locations,
+			date_added: now,
+			date_modified: now,
+		}
+	}
+}
@@ -0,0 +1,40 @@
+use {
+	bonsaidb::core::schema::Collection,
+	chrono::{DateTime, Utc},
+	serde::{Deserialize, Serialize},
+};
+
+use crate::database::views::lyrics::LyricsByTrackId;
+
+/// Lyrics of a track, either synced where every line has a timestamp, or plain.
+#[derive(Debug, Clone, Serialize, Deserialize, Collection)]
+#[collection(name = "lyrics", views = [LyricsByTrackId])]
+pub struct Lyrics {
+	pub track_id: u64,
+	pub language: Option<String>,
+	pub lines: Vec<LyricsLine>,
+	/// Set for lyrics edited in the application that couldn't be written into a `.lrc` file, which rescans keep.
+	#[serde(default)]
+	pub edited: bool,
+
+	#[serde(default)]
+	pub date_added: DateTime<Utc>,
+	#[serde(default)]
+	pub date_modified: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LyricsLine {
+	/// Start of the line in milliseconds, missing for plain lyrics.
+	pub start: Option<u64>,
+	pub text: String,
+	/// Word level timings from enhanced LRC files.
+	pub words: Option<Vec<LyricsWord>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LyricsWord {
+	/// Start of the word in milliseconds.
+	pub start: u64,
+	pub text: String,
+}
@@ -5,23 +5,35 @@ use crate::constants::UNKNOWN_PERSON_ID;
 
 pub mod cover;
 pub mod label;
+pub mod library;
+pub mod lyrics;
 pub mod person;
+pub mod play;
+pub mod playlist;
 pub mod release;
+pub mod scrobble;
 pub mod tag;
 pub mod track;
+pub mod work;
 
 #[derive(Debug, Schema)]
 #[schema(name = "default", collections = [
     label::Label,
+    library::Library,
+    lyrics::Lyrics,
     person::Person,
+    play::Play,
+    playlist::Playlist,
     release::Release,
+    scrobble::Scrobble,
     tag::Tag,
     track::Track,
+    work::Work,
 	cover::Cover,
 ])]
 pub struct LocalSchema;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum CountryCode {
 	Worldwide,
 	Japan,
@@ -63,7 +75,7 @@ impl FromTag for ScriptCode {
 	}
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InlinedArtist {
 	pub id: u64,
 	/// This is different from the [Person::name] field.
@@ -1,54 +1,130 @@
-use bonsaidb::core::{key::Key, schema::Collection};
-use serde::{Deserialize, Serialize};
+use {
+	bonsaidb::core::{key::Key, schema::Collection},
+	chrono::{DateTime, Utc},
+	serde::{Deserialize, Serialize},
+};
 
-use crate::database::views::person::PersonByNameAndType;
+use crate::database::views::person::{PersonByMbzId, PersonByName, PersonByNameAndType};
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Key)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Key)]
 #[serde(rename_all = "snake_case")]
 pub enum PersonType {
 	Artist,
 	Composer,
 	Producer,
+	Lyricist,
+	Arranger,
+	Conductor,
+	/// Performer of an instrument or a vocal part, which is kept in [super::track::Credit::detail].
+	Performer,
+	Remixer,
+	Mixer,
+	Engineer,
+	DjMixer,
+	/// Any other role, named in [super::track::Credit::detail].
+	Other,
 
 	/// Special type strictly for handling unknown people (id 0).
 	Unknown,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Collection)]
-#[collection(name = "people", views = [PersonByNameAndType])]
+#[collection(name = "people", views = [PersonByNameAndType, PersonByName, PersonByMbzId])]
 pub struct Person {
 	pub name: String,
 	pub name_sort: Option<String>,
 	pub mbz_id: Option<String>,
+	pub cover_ids: Option<Vec<u64>>,
+	/// Whether the cover was set with `set_artist_image`, which keeps it from being replaced by scans.
+	#[serde(default)]
+	pub has_manual_cover: bool,
 
 	#[serde(rename = "type")]
 	pub type_: PersonType,
+
+	#[serde(default)]
+	pub date_added: DateTime<Utc>,
+	#[serde(default)]
+	pub date_modified: DateTime<Utc>,
+}
+
+impl PersonType {
+	/// Every role a person can be credited with in a track.
+	pub const ROLES: [PersonType; 12] = [
+		PersonType::Artist,
+		PersonType::Composer,
+		PersonType::Producer,
+		PersonType::Lyricist,
+		PersonType::Arranger,
+		PersonType::Conductor,
+		PersonType::Performer,
+		PersonType::Remixer,
+		PersonType::Mixer,
+		PersonType::Engineer,
+		PersonType::DjMixer,
+		PersonType::Other,
+	];
+
+	/// Resolves a role from an ID3 `TIPL` involvement or a MusicBrainz relationship name, like `mix` or `DJ-mix`.
+	///
+	/// Returns none for the roles without a dedicated type.
+	pub fn from_involvement(role: &str) -> Option<Self> {
+		let role = role.trim().to_lowercase().replace(['-', '_'], " ");
+
+		let x = match role.as_str() {
+			"composer" | "composition" => PersonType::Composer,
+			"producer" | "co producer" | "executive producer" => PersonType::Producer,
+			"lyricist" | "lyrics" | "writer" => PersonType::Lyricist,
+			"arranger" | "arrangement" | "orchestrator" => PersonType::Arranger,
+			"conductor" | "chorus master" => PersonType::Conductor,
+			"performer" => PersonType::Performer,
+			"remixer" | "remix" => PersonType::Remixer,
+			"mix" | "mixer" | "mixing" => PersonType::Mixer,
+			"engineer" | "recording" | "recording engineer" | "audio engineer" | "sound engineer" => {
+				PersonType::Engineer
+			}
+			"dj mix" | "dj mixer" => PersonType::DjMixer,
+			_ => return None,
+		};
+
+		Some(x)
+	}
 }
 
 impl Person {
+	pub fn new(name: String, type_: PersonType) -> Self {
+		let now = Utc::now();
+
+		Self {
+			name,
+			type_,
+			name_sort: None,
+			mbz_id: None,
+			cover_ids: None,
+			has_manual_cover: false,
+			date_added: now,
+			date_modified: now,
+		}
+	}
+
 	/// Create a [Person] that follows the default semantics for an unknown person.
 	///
 	/// NOTE: Make sure this isn't used anywhere outside of testing and the initial database setup.
 	///
 	/// See [super::InlinedArtist::unknown] for the inlined equivalent.
 	pub fn unknown() -> Self {
-		Self {
-			name: "Unknown".to_string(),
-			type_: PersonType::Unknown,
-			name_sort: None,
-			mbz_id: None,
-		}
+		Self::new("Unknown".to_string(), PersonType::Unknown)
 	}
 }
 
+/// Normalizes a name for matching, ignoring the case and the repeated whitespace.
+pub fn normalize_person_name(name: &str) -> String {
+	name.split_whitespace().collect::<Vec<&str>>().join(" ").to_lowercase()
+}
+
 #[cfg(test)]
 impl Default for Person {
 	fn default() -> Self {
-		Self {
-			name: "Person".to_string(),
-			type_: PersonType::Artist,
-			name_sort: None,
-			mbz_id: None,
-		}
+		Self::new("Person".to_string(), PersonType::Artist)
 	}
 }
@@ -0,0 +1,23 @@
+use {
+	bonsaidb::core::schema::Collection,
+	chrono::{DateTime, Utc},
+	serde::{Deserialize, Serialize},
+};
+
+use crate::database::views::play::PlayByTrackId;
+
+/// A track played for long enough to count as a listen, as told by the scrobble tracker.
+///
+/// Plays are kept whether or not a scrobbling service is set up, so the play history stays local.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Collection)]
+#[collection(name = "plays", views = [PlayByTrackId])]
+pub struct Play {
+	pub track_id: u64,
+	pub played_at: DateTime<Utc>,
+}
+
+impl Play {
+	pub fn new(track_id: u64, played_at: DateTime<Utc>) -> Self {
+		Self { track_id, played_at }
+	}
+}
@@ -0,0 +1,33 @@
+use {
+	bonsaidb::core::schema::Collection,
+	chrono::{DateTime, Utc},
+	serde::{Deserialize, Serialize},
+};
+
+/// An ordered list of tracks, where a track may appear more than once.
+#[derive(Debug, Clone, Serialize, Deserialize, Collection)]
+#[collection(name = "playlists")]
+pub struct Playlist {
+	pub name: String,
+	pub description: Option<String>,
+	pub track_ids: Vec<u64>,
+
+	#[serde(default)]
+	pub date_added: DateTime<Utc>,
+	#[serde(default)]
+	pub date_modified: DateTime<Utc>,
+}
+
+impl Playlist {
+	pub fn new(name: String, track_ids: Vec<u64>) -> Self {
+		let now = Utc::now();
+
+		Self {
+			name,
+			description: None,
+			track_ids,
+			date_added: now,
+			date_modified: now,
+		}
+	}
+}
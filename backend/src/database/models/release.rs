@@ -1,14 +1,17 @@
 use {
 	bonsaidb::core::schema::Collection,
-	chrono::NaiveDate,
+	chrono::{DateTime, Datelike, NaiveDate, Utc},
 	serde::{Deserialize, Serialize},
 };
 
-use crate::database::views::release::ReleaseByNameAndArtist;
+use crate::database::views::release::{
+	ReleaseByArtistId, ReleaseByArtistSort, ReleaseByCoverId, ReleaseByDate, ReleaseByDateAdded, ReleaseByGenreId,
+	ReleaseByLabelId, ReleaseByLibraryId, ReleaseByName, ReleaseByNameAndArtist, ReleaseByYear,
+};
 
 use super::{CountryCode, FromTag, InlinedArtist, ScriptCode};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ReleaseType {
 	Album,
@@ -18,7 +21,7 @@ pub enum ReleaseType {
 	Other,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ReleaseTypeSecondary {
 	Compilation,
@@ -29,7 +32,19 @@ pub enum ReleaseTypeSecondary {
 }
 
 #[derive(Debug, Serialize, Deserialize, Collection)]
-#[collection(name = "releases", views = [ReleaseByNameAndArtist])]
+#[collection(name = "releases", views = [
+	ReleaseByNameAndArtist,
+	ReleaseByArtistId,
+	ReleaseByName,
+	ReleaseByArtistSort,
+	ReleaseByDate,
+	ReleaseByYear,
+	ReleaseByGenreId,
+	ReleaseByLabelId,
+	ReleaseByDateAdded,
+	ReleaseByLibraryId,
+	ReleaseByCoverId,
+])]
 pub struct Release {
 	pub name: String,
 	pub name_sort: Option<String>,
@@ -38,11 +53,16 @@ pub struct Release {
 	pub date: Option<NaiveDate>,
 	pub country: Option<CountryCode>,
 	pub script: Option<ScriptCode>,
+	/// Tracks on a disc, which differs between the discs of a release.
 	pub total_tracks: Option<u32>,
+	/// Falls back to the highest disc number seen so far when the tags don't have one.
+	#[serde(default)]
+	pub total_discs: Option<u32>,
 	pub catalog_number: Option<String>,
 
 	/// Either [InlinedArtist::unknown] or populated with artists.
 	pub artists: Vec<InlinedArtist>,
+	/// Falls back to the sort name or the name of the first artist when the tags don't have one.
 	pub artist_sort: Option<String>,
 
 	pub label_ids: Option<Vec<u64>>,
@@ -55,6 +75,35 @@ pub struct Release {
 	pub type_secondary: Option<Vec<ReleaseTypeSecondary>>,
 
 	pub mbz_id: Option<String>,
+	/// The library this release was scanned into. Releases from before libraries existed belong to the default one.
+	#[serde(default)]
+	pub library_id: u64,
+	/// The directory the tracks of this release reside in, above any disc directories like `CD1`.
+	#[serde(default)]
+	pub directory: Option<String>,
+
+	#[serde(default)]
+	pub date_added: DateTime<Utc>,
+	#[serde(default)]
+	pub date_modified: DateTime<Utc>,
+}
+
+impl Release {
+	/// Resolves the year of this release, preferring the year of [Release::date] if it exists.
+	pub fn get_year(&self) -> Option<i32> {
+		self.date.map(|x| x.year()).or(self.year)
+	}
+
+	/// Resolves the value used to sort releases by date.
+	///
+	/// Returns an ISO 8601 date, or just the year when the full date is missing.
+	pub fn get_date_sort(&self) -> String {
+		match (self.date, self.year) {
+			(Some(date), _) => date.format("%Y-%m-%d").to_string(),
+			(None, Some(year)) => format!("{year:04}"),
+			(None, None) => String::new(),
+		}
+	}
 }
 
 impl FromTag for ReleaseType {
@@ -93,7 +142,7 @@ impl FromTag for ReleaseTypeSecondary {
 #[cfg(test)]
 impl Default for Release {
 	fn default() -> Self {
-		use crate::constants::TEST_RELEASE_NAME;
+		use crate::constants::{DEFAULT_LIBRARY_ID, TEST_RELEASE_NAME};
 
 		Self {
 			name: TEST_RELEASE_NAME.to_string(),
@@ -104,6 +153,7 @@ impl Default for Release {
 			country: None,
 			script: None,
 			total_tracks: None,
+			total_discs: None,
 			catalog_number: None,
 
 			artists: vec![InlinedArtist::unknown()],
@@ -118,6 +168,11 @@ impl Default for Release {
 			type_secondary: None,
 
 			mbz_id: None,
+			library_id: DEFAULT_LIBRARY_ID,
+			directory: None,
+
+			date_added: Utc::now(),
+			date_modified: Utc::now(),
 		}
 	}
 }
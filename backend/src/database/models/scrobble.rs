@@ -0,0 +1,53 @@
+use {
+	bonsaidb::core::{key::Key, schema::Collection},
+	chrono::{DateTime, Utc},
+	serde::{Deserialize, Serialize},
+};
+
+use crate::database::views::scrobble::ScrobbleByService;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Key)]
+#[serde(rename_all = "snake_case")]
+pub enum ScrobbleService {
+	ListenBrainz,
+	LastFm,
+}
+
+/// Listen waiting to be submitted to a service, kept until the service accepts it.
+#[derive(Debug, Clone, Serialize, Deserialize, Collection)]
+#[collection(name = "scrobbles", views = [ScrobbleByService])]
+pub struct Scrobble {
+	pub service: ScrobbleService,
+	pub track: ScrobbleTrack,
+	pub listened_at: DateTime<Utc>,
+	/// Failed submissions so far, used to back off from an unreachable service.
+	pub attempts: u32,
+	pub next_attempt: DateTime<Utc>,
+}
+
+/// Metadata of the listened track at the time of the listen, so later edits to the library don't change it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScrobbleTrack {
+	pub track_id: u64,
+	pub title: String,
+	pub artists: Vec<String>,
+	pub release: Option<String>,
+	pub release_artists: Vec<String>,
+	pub track_number: Option<u32>,
+	/// Length in milliseconds.
+	pub duration: Option<u64>,
+	pub recording_mbz_id: Option<String>,
+	pub release_mbz_id: Option<String>,
+}
+
+impl Scrobble {
+	pub fn new(service: ScrobbleService, track: ScrobbleTrack, listened_at: DateTime<Utc>) -> Self {
+		Self {
+			service,
+			track,
+			listened_at,
+			attempts: 0,
+			next_attempt: Utc::now(),
+		}
+	}
+}
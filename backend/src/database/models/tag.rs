@@ -1,7 +1,10 @@
-use bonsaidb::core::{key::Key, schema::Collection};
-use serde::{Deserialize, Serialize};
+use {
+	bonsaidb::core::{key::Key, schema::Collection},
+	chrono::{DateTime, Utc},
+	serde::{Deserialize, Serialize},
+};
 
-use crate::database::views::tag::TagByNameAndType;
+use crate::database::views::tag::{TagByAlias, TagByNameAndType, TagByParentId, TagByType};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Key)]
 pub enum TagType {
@@ -10,10 +13,44 @@ pub enum TagType {
 }
 
 #[derive(Debug, Serialize, Deserialize, Collection)]
-#[collection(name = "tags", views = [TagByNameAndType])]
+#[collection(name = "tags", views = [TagByNameAndType, TagByAlias, TagByParentId, TagByType])]
 pub struct Tag {
 	pub name: String,
 
 	#[serde(rename = "type")]
 	pub type_: TagType,
+
+	/// The broader tag this tag belongs to, e.g. "Hip Hop" for "Trap".
+	pub parent_id: Option<u64>,
+	/// Alternative spellings of this tag that resolve to it while scanning.
+	pub aliases: Option<Vec<String>>,
+
+	#[serde(default)]
+	pub date_added: DateTime<Utc>,
+	#[serde(default)]
+	pub date_modified: DateTime<Utc>,
+}
+
+impl Tag {
+	pub fn new(name: String, type_: TagType) -> Self {
+		let now = Utc::now();
+
+		Self {
+			name,
+			type_,
+			parent_id: None,
+			aliases: None,
+			date_added: now,
+			date_modified: now,
+		}
+	}
+}
+
+/// Normalizes a tag name for comparison, so that "Hip-Hop", "hip hop" and "Hip_Hop" are treated the same.
+pub fn normalize_tag_name(name: &str) -> String {
+	name.to_lowercase()
+		.replace(['-', '_'], " ")
+		.split_whitespace()
+		.collect::<Vec<&str>>()
+		.join(" ")
 }
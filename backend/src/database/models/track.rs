@@ -1,13 +1,35 @@
 use {
 	bonsaidb::core::schema::Collection,
-	chrono::NaiveDate,
+	chrono::{DateTime, NaiveDate, Utc},
 	serde::{Deserialize, Serialize},
 };
 
-use crate::database::{models::InlinedArtist, views::track::TrackByReleaseId};
+use crate::database::{
+	models::{person::PersonType, InlinedArtist},
+	views::track::{
+		TrackByArtistSort, TrackByCoverId, TrackByDate, TrackByDateAdded, TrackByGenreId, TrackByLibraryAndWork,
+		TrackByLibraryId, TrackByPath, TrackByPerson, TrackByReleaseId, TrackByTitle, TrackByWorkId,
+	},
+};
+
+/// Highest [Track::rating] a track can be given.
+pub const MAX_RATING: u8 = 5;
 
-#[derive(Debug, Serialize, Deserialize, Collection)]
-#[collection(name = "tracks", views = [TrackByReleaseId])]
+#[derive(Debug, Clone, Serialize, Deserialize, Collection)]
+#[collection(name = "tracks", views = [
+	TrackByReleaseId,
+	TrackByPerson,
+	TrackByTitle,
+	TrackByArtistSort,
+	TrackByDate,
+	TrackByGenreId,
+	TrackByDateAdded,
+	TrackByPath,
+	TrackByLibraryId,
+	TrackByWorkId,
+	TrackByCoverId,
+	TrackByLibraryAndWork,
+])]
 pub struct Track {
 	pub title: String,
 	pub title_sort: Option<String>,
@@ -17,12 +39,14 @@ pub struct Track {
 
 	/// Either [InlinedArtist::unknown] or populated with artists.
 	pub artists: Vec<InlinedArtist>,
+	/// Falls back to the sort name or the name of the first artist when the tags don't have one.
 	pub artist_sort: Option<String>,
 
 	/// Either [constants::UNKNOWN_RELEASE_ID] or a u64 of sorts.
 	pub release_id: u64,
-	pub composer_ids: Option<Vec<u64>>,
-	pub producer_ids: Option<Vec<u64>>,
+	/// Everyone credited in a role other than the artists, like composers, performers or engineers.
+	#[serde(default)]
+	pub credits: Vec<Credit>,
 	pub cover_ids: Option<Vec<u64>>,
 
 	pub genre_ids: Option<Vec<u64>>,
@@ -30,4 +54,139 @@ pub struct Track {
 
 	pub mbz_id: Option<String>,
 	pub path: String,
+	/// Part of the file this track spans, when the file holds more than one track.
+	#[serde(default)]
+	pub segment: Option<TrackSegment>,
+	/// The library this track was scanned into. Tracks from before libraries existed belong to the default one.
+	#[serde(default)]
+	pub library_id: u64,
+	/// The [work](super::work::Work) this track is a recording of.
+	#[serde(default)]
+	pub work_id: Option<u64>,
+	#[serde(default)]
+	pub movement: Option<Movement>,
+	/// Rating given in the application, from 1 to [MAX_RATING]. Kept through rescans, as it isn't read from the tags.
+	#[serde(default)]
+	pub rating: Option<u8>,
+
+	#[serde(default)]
+	pub date_added: DateTime<Utc>,
+	#[serde(default)]
+	pub date_modified: DateTime<Utc>,
+
+	/// Replaced by [Track::credits], only kept to convert the tracks saved before credits existed.
+	#[serde(default, skip_serializing)]
+	pub composer_ids: Option<Vec<u64>>,
+	/// Replaced by [Track::credits], only kept to convert the tracks saved before credits existed.
+	#[serde(default, skip_serializing)]
+	pub producer_ids: Option<Vec<u64>>,
+}
+
+/// A person credited in a track along with their role.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Credit {
+	pub person_id: u64,
+	#[serde(rename = "type")]
+	pub type_: PersonType,
+	/// The instrument or vocal part of a performer, or the name of the role when the type is [PersonType::Other].
+	pub detail: Option<String>,
+}
+
+impl Credit {
+	pub fn new(person_id: u64, type_: PersonType, detail: Option<String>) -> Self {
+		Self {
+			person_id,
+			type_,
+			detail,
+		}
+	}
+}
+
+impl Track {
+	/// Moves the composers and producers saved before credits existed into [Track::credits].
+	///
+	/// Returns whether there was anything to move.
+	pub fn convert_legacy_credits(&mut self) -> bool {
+		let composer_ids = self.composer_ids.take().unwrap_or_default();
+		let producer_ids = self.producer_ids.take().unwrap_or_default();
+
+		let legacy = composer_ids
+			.into_iter()
+			.map(|x| Credit::new(x, PersonType::Composer, None))
+			.chain(
+				producer_ids
+					.into_iter()
+					.map(|x| Credit::new(x, PersonType::Producer, None)),
+			)
+			.filter(|x| !self.credits.contains(x))
+			.collect::<Vec<Credit>>();
+
+		let converted = !legacy.is_empty();
+		self.credits.extend(legacy);
+
+		converted
+	}
+}
+
+/// Position of a track within its work, for works recorded over several tracks.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Movement {
+	pub number: Option<u32>,
+	pub total: Option<u32>,
+	pub name: Option<String>,
+}
+
+/// Offsets of a track within a file shared with other tracks, like the single file images split by a CUE sheet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrackSegment {
+	/// Start of the track in milliseconds.
+	pub start: u64,
+	/// End of the track in milliseconds, missing for the last track as it plays until the end of the file.
+	pub end: Option<u64>,
+}
+
+impl TrackSegment {
+	/// Checks whether the two segments share any part of the file.
+	pub fn overlaps(&self, other: &TrackSegment) -> bool {
+		self.start < other.end.unwrap_or(u64::MAX) && other.start < self.end.unwrap_or(u64::MAX)
+	}
+}
+
+#[cfg(test)]
+impl Default for Track {
+	fn default() -> Self {
+		use crate::constants::{DEFAULT_LIBRARY_ID, TEST_TRACK_PATH, UNKNOWN_RELEASE_ID};
+
+		Self {
+			title: "Test Track".to_string(),
+			title_sort: None,
+			track_number: None,
+			disc_number: None,
+			original_date: None,
+
+			artists: vec![InlinedArtist::unknown()],
+			artist_sort: None,
+
+			release_id: UNKNOWN_RELEASE_ID,
+			credits: Vec::new(),
+			cover_ids: None,
+
+			genre_ids: None,
+			tag_ids: None,
+
+			mbz_id: None,
+			path: TEST_TRACK_PATH.to_string(),
+			segment: None,
+			library_id: DEFAULT_LIBRARY_ID,
+			work_id: None,
+			movement: None,
+			rating: None,
+
+			date_added: Utc::now(),
+			date_modified: Utc::now(),
+
+			composer_ids: None,
+			producer_ids: None,
+		}
+	}
 }
@@ -0,0 +1,46 @@
+use {
+	bonsaidb::core::schema::Collection,
+	chrono::{DateTime, Utc},
+	serde::{Deserialize, Serialize},
+};
+
+use crate::database::views::work::{WorkByComposerId, WorkByMbzId, WorkByName};
+
+/// A composition, like a symphony or a sonata, recorded by any number of tracks across releases.
+#[derive(Debug, Clone, Serialize, Deserialize, Collection)]
+#[collection(name = "works", views = [WorkByName, WorkByMbzId, WorkByComposerId])]
+pub struct Work {
+	pub name: String,
+	pub composer_ids: Vec<u64>,
+	/// Number of the work in the catalogue of its composer, like `BWV 1007` or `K. 331`.
+	pub catalogue_number: Option<String>,
+	/// Key of the work, like `D minor`.
+	pub key: Option<String>,
+	pub mbz_id: Option<String>,
+
+	#[serde(default)]
+	pub date_added: DateTime<Utc>,
+	#[serde(default)]
+	pub date_modified: DateTime<Utc>,
+}
+
+impl Work {
+	pub fn new(name: String) -> Self {
+		let now = Utc::now();
+
+		Self {
+			name,
+			composer_ids: Vec::new(),
+			catalogue_number: None,
+			key: None,
+			mbz_id: None,
+			date_added: now,
+			date_modified: now,
+		}
+	}
+}
+
+/// Normalizes a name for matching, ignoring the case and the repeated whitespace.
+pub fn normalize_work_name(name: &str) -> String {
+	name.split_whitespace().collect::<Vec<&str>>().join(" ").to_lowercase()
+}
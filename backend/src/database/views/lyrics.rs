@@ -0,0 +1,17 @@
+use bonsaidb::core::{
+	document::{CollectionDocument, Emit},
+	schema::{CollectionMapReduce, View, ViewMapResult, ViewSchema},
+};
+
+use crate::database::models::lyrics::Lyrics;
+
+#[derive(Debug, Clone, View, ViewSchema)]
+#[view(collection = Lyrics, key = u64, value = ())]
+pub struct LyricsByTrackId;
+
+impl CollectionMapReduce for LyricsByTrackId {
+	fn map<'doc>(&self, document: CollectionDocument<Lyrics>) -> ViewMapResult<'doc, Self::View> {
+		let x = document.contents;
+		document.header.emit_key(x.track_id)
+	}
+}
@@ -1,6 +1,27 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+
 pub mod cover;
 pub mod label;
+pub mod lyrics;
 pub mod person;
+pub mod play;
 pub mod release;
+pub mod scrobble;
 pub mod tag;
 pub mod track;
+pub mod work;
+
+/// Key used by views that are meant to be paginated.
+///
+/// Holds the value to sort by followed by the document id, which makes every key unique and usable as a cursor.
+pub type SortKey = (String, u64);
+
+/// Formats a timestamp into a fixed-width string that sorts chronologically when used in a [SortKey].
+pub fn timestamp_sort_value(timestamp: &DateTime<Utc>) -> String {
+	timestamp.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// Formats a count into a fixed-width string that sorts numerically when used in a [SortKey].
+pub fn count_sort_value(count: u64) -> String {
+	format!("{count:020}")
+}
@@ -1,10 +1,12 @@
 use bonsaidb::core::{
 	document::{CollectionDocument, Emit},
 	key::Key,
-	schema::{CollectionMapReduce, ReduceResult, View, ViewMapResult, ViewMappedValue, ViewSchema},
+	schema::{
+		view::map::Mappings, CollectionMapReduce, ReduceResult, View, ViewMapResult, ViewMappedValue, ViewSchema,
+	},
 };
 
-use crate::database::models::person::{Person, PersonType};
+use crate::database::models::person::{normalize_person_name, Person, PersonType};
 
 #[derive(Debug, Clone, PartialEq, Key)]
 pub struct PersonByNameAndTypeKey {
@@ -33,3 +35,28 @@ impl CollectionMapReduce for PersonByNameAndType {
 		Ok(mappings.iter().map(|m| m.value).sum())
 	}
 }
+
+/// Maps people by their normalized name regardless of their type, so the same person can be found across roles.
+#[derive(Debug, Clone, View, ViewSchema)]
+#[view(collection = Person, key = String, value = ())]
+pub struct PersonByName;
+
+impl CollectionMapReduce for PersonByName {
+	fn map<'doc>(&self, document: CollectionDocument<Person>) -> ViewMapResult<'doc, Self::View> {
+		let key = normalize_person_name(&document.contents.name);
+		document.header.emit_key(key)
+	}
+}
+
+#[derive(Debug, Clone, View, ViewSchema)]
+#[view(collection = Person, key = String, value = ())]
+pub struct PersonByMbzId;
+
+impl CollectionMapReduce for PersonByMbzId {
+	fn map<'doc>(&self, document: CollectionDocument<Person>) -> ViewMapResult<'doc, Self::View> {
+		match &document.contents.mbz_id {
+			Some(x) => document.header.emit_key(x.to_lowercase()),
+			None => Ok(Mappings::none()),
+		}
+	}
+}
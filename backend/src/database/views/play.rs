@@ -0,0 +1,22 @@
+use bonsaidb::core::{
+	document::{CollectionDocument, Emit},
+	schema::{CollectionMapReduce, ReduceResult, View, ViewMapResult, ViewMappedValue, ViewSchema},
+};
+
+use crate::database::models::play::Play;
+
+/// Maps the plays to their track, reducing into the number of times each track was played.
+#[derive(Debug, Clone, View, ViewSchema)]
+#[view(collection = Play, key = u64, value = u64)]
+pub struct PlayByTrackId;
+
+impl CollectionMapReduce for PlayByTrackId {
+	fn map<'doc>(&self, document: CollectionDocument<Play>) -> ViewMapResult<'doc, Self::View> {
+		let x = document.contents;
+		document.header.emit_key_and_value(x.track_id, 1)
+	}
+
+	fn reduce(&self, mappings: &[ViewMappedValue<Self>], _rereduce: bool) -> ReduceResult<Self::View> {
+		Ok(mappings.iter().map(|m| m.value).sum())
+	}
+}
@@ -1,10 +1,16 @@
 use bonsaidb::core::{
-	document::{CollectionDocument, Header},
+	document::{CollectionDocument, Emit, Header},
 	key::Key,
-	schema::{view::map::Mappings, CollectionMapReduce, Map as BonsaiMap, View, ViewMapResult, ViewSchema},
+	schema::{
+		view::map::Mappings, CollectionMapReduce, Map as BonsaiMap, ReduceResult, View, ViewMapResult, ViewMappedValue,
+		ViewSchema,
+	},
 };
 
-use crate::database::models::release::Release;
+use crate::database::{
+	models::release::Release,
+	views::{timestamp_sort_value, SortKey},
+};
 
 #[derive(Debug, Clone, PartialEq, Key)]
 pub struct ReleaseByNameAndArtistKey {
@@ -37,3 +43,161 @@ impl CollectionMapReduce for ReleaseByNameAndArtist {
 		Ok(Mappings::List(maps))
 	}
 }
+
+#[derive(Debug, Clone, View, ViewSchema)]
+#[view(collection = Release, key = u64, value = u64)]
+pub struct ReleaseByArtistId;
+
+impl CollectionMapReduce for ReleaseByArtistId {
+	fn map<'doc>(&self, document: CollectionDocument<Release>) -> ViewMapResult<'doc, Self::View> {
+		let x = document.contents;
+
+		let mut maps = Vec::<BonsaiMap<u64, u64>>::with_capacity(x.artists.len());
+		let header = Header::try_from(document.header)?;
+
+		for artist in x.artists {
+			maps.push(BonsaiMap::new(header.clone(), artist.id, 1));
+		}
+
+		Ok(Mappings::List(maps))
+	}
+
+	fn reduce(&self, mappings: &[ViewMappedValue<Self>], _rereduce: bool) -> ReduceResult<Self::View> {
+		Ok(mappings.iter().map(|m| m.value).sum())
+	}
+}
+
+#[derive(Debug, Clone, View, ViewSchema)]
+#[view(collection = Release, key = SortKey, value = ())]
+pub struct ReleaseByName;
+
+impl CollectionMapReduce for ReleaseByName {
+	fn map<'doc>(&self, document: CollectionDocument<Release>) -> ViewMapResult<'doc, Self::View> {
+		let x = document.contents;
+		let name = x.name_sort.unwrap_or(x.name).to_lowercase();
+
+		document.header.emit_key((name, document.header.id))
+	}
+}
+
+#[derive(Debug, Clone, View, ViewSchema)]
+#[view(collection = Release, key = SortKey, value = ())]
+pub struct ReleaseByArtistSort;
+
+impl CollectionMapReduce for ReleaseByArtistSort {
+	fn map<'doc>(&self, document: CollectionDocument<Release>) -> ViewMapResult<'doc, Self::View> {
+		let x = document.contents;
+		let credited_as = x.artists.into_iter().next().and_then(|e| e.credited_as);
+		let artist = x.artist_sort.or(credited_as).unwrap_or_default().to_lowercase();
+
+		document.header.emit_key((artist, document.header.id))
+	}
+}
+
+#[derive(Debug, Clone, View, ViewSchema)]
+#[view(collection = Release, key = SortKey, value = ())]
+pub struct ReleaseByDate;
+
+impl CollectionMapReduce for ReleaseByDate {
+	fn map<'doc>(&self, document: CollectionDocument<Release>) -> ViewMapResult<'doc, Self::View> {
+		let date = document.contents.get_date_sort();
+		document.header.emit_key((date, document.header.id))
+	}
+}
+
+#[derive(Debug, Clone, View, ViewSchema)]
+#[view(collection = Release, key = i32, value = ())]
+pub struct ReleaseByYear;
+
+impl CollectionMapReduce for ReleaseByYear {
+	fn map<'doc>(&self, document: CollectionDocument<Release>) -> ViewMapResult<'doc, Self::View> {
+		match document.contents.get_year() {
+			Some(year) => document.header.emit_key(year),
+			None => Ok(Mappings::none()),
+		}
+	}
+}
+
+#[derive(Debug, Clone, View, ViewSchema)]
+#[view(collection = Release, key = u64, value = u64)]
+pub struct ReleaseByGenreId;
+
+impl CollectionMapReduce for ReleaseByGenreId {
+	fn map<'doc>(&self, document: CollectionDocument<Release>) -> ViewMapResult<'doc, Self::View> {
+		let header = Header::try_from(document.header)?;
+		let genre_ids = document.contents.genre_ids.unwrap_or_default();
+
+		let maps = genre_ids
+			.into_iter()
+			.map(|id| BonsaiMap::new(header.clone(), id, 1))
+			.collect::<Vec<BonsaiMap<u64, u64>>>();
+
+		Ok(Mappings::List(maps))
+	}
+
+	fn reduce(&self, mappings: &[ViewMappedValue<Self>], _rereduce: bool) -> ReduceResult<Self::View> {
+		Ok(mappings.iter().map(|m| m.value).sum())
+	}
+}
+
+#[derive(Debug, Clone, View, ViewSchema)]
+#[view(collection = Release, key = u64, value = u64)]
+pub struct ReleaseByLabelId;
+
+impl CollectionMapReduce for ReleaseByLabelId {
+	fn map<'doc>(&self, document: CollectionDocument<Release>) -> ViewMapResult<'doc, Self::View> {
+		let header = Header::try_from(document.header)?;
+		let label_ids = document.contents.label_ids.unwrap_or_default();
+
+		let maps = label_ids
+			.into_iter()
+			.map(|id| BonsaiMap::new(header.clone(), id, 1))
+			.collect::<Vec<BonsaiMap<u64, u64>>>();
+
+		Ok(Mappings::List(maps))
+	}
+
+	fn reduce(&self, mappings: &[ViewMappedValue<Self>], _rereduce: bool) -> ReduceResult<Self::View> {
+		Ok(mappings.iter().map(|m| m.value).sum())
+	}
+}
+
+#[derive(Debug, Clone, View, ViewSchema)]
+#[view(collection = Release, key = SortKey, value = ())]
+pub struct ReleaseByDateAdded;
+
+impl CollectionMapReduce for ReleaseByDateAdded {
+	fn map<'doc>(&self, document: CollectionDocument<Release>) -> ViewMapResult<'doc, Self::View> {
+		let date = timestamp_sort_value(&document.contents.date_added);
+		document.header.emit_key((date, document.header.id))
+	}
+}
+
+#[derive(Debug, Clone, View, ViewSchema)]
+#[view(collection = Release, key = u64, value = ())]
+pub struct ReleaseByLibraryId;
+
+impl CollectionMapReduce for ReleaseByLibraryId {
+	fn map<'doc>(&self, document: CollectionDocument<Release>) -> ViewMapResult<'doc, Self::View> {
+		document.header.emit_key(document.contents.library_id)
+	}
+}
+
+/// Maps the releases to each of their covers, to tell whether a cover is still used.
+#[derive(Debug, Clone, View, ViewSchema)]
+#[view(collection = Release, key = u64, value = ())]
+pub struct ReleaseByCoverId;
+
+impl CollectionMapReduce for ReleaseByCoverId {
+	fn map<'doc>(&self, document: CollectionDocument<Release>) -> ViewMapResult<'doc, Self::View> {
+		let header = Header::try_from(document.header)?;
+		let cover_ids = document.contents.cover_ids.unwrap_or_default();
+
+		let maps = cover_ids
+			.into_iter()
+			.map(|id| BonsaiMap::new(header.clone(), id, ()))
+			.collect::<Vec<BonsaiMap<u64, ()>>>();
+
+		Ok(Mappings::List(maps))
+	}
+}
@@ -0,0 +1,33 @@
+use bonsaidb::core::{
+	document::{CollectionDocument, Emit},
+	key::Key,
+	schema::{CollectionMapReduce, View, ViewMapResult, ViewSchema},
+};
+
+use crate::database::models::scrobble::{Scrobble, ScrobbleService};
+
+#[derive(Debug, Clone, PartialEq, Key)]
+pub struct ScrobbleByServiceKey {
+	pub service: ScrobbleService,
+	/// Unix timestamp in milliseconds of the next submission attempt.
+	pub next_attempt: i64,
+}
+
+impl ScrobbleByServiceKey {
+	pub fn new(service: ScrobbleService, next_attempt: i64) -> Self {
+		Self { service, next_attempt }
+	}
+}
+
+/// Maps the queued listens to their service and the time of their next submission attempt.
+#[derive(Debug, Clone, View, ViewSchema)]
+#[view(collection = Scrobble, key = ScrobbleByServiceKey, value = ())]
+pub struct ScrobbleByService;
+
+impl CollectionMapReduce for ScrobbleByService {
+	fn map<'doc>(&self, document: CollectionDocument<Scrobble>) -> ViewMapResult<'doc, Self::View> {
+		let x = document.contents;
+		let key = ScrobbleByServiceKey::new(x.service, x.next_attempt.timestamp_millis());
+		document.header.emit_key(key)
+	}
+}
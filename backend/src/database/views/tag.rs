@@ -1,10 +1,13 @@
 use bonsaidb::core::{
-	document::{CollectionDocument, Emit},
+	document::{CollectionDocument, Emit, Header},
 	key::Key,
-	schema::{CollectionMapReduce, ReduceResult, View, ViewMapResult, ViewMappedValue, ViewSchema},
+	schema::{
+		view::map::Mappings, CollectionMapReduce, Map as BonsaiMap, ReduceResult, View, ViewMapResult, ViewMappedValue,
+		ViewSchema,
+	},
 };
 
-use crate::database::models::tag::{Tag, TagType};
+use crate::database::models::tag::{normalize_tag_name, Tag, TagType};
 
 #[derive(Debug, Clone, PartialEq, Key)]
 pub struct TagByNameAndTypeKey {
@@ -33,3 +36,49 @@ impl CollectionMapReduce for TagByNameAndType {
 		Ok(mappings.iter().map(|m| m.value).sum())
 	}
 }
+
+/// Maps the normalized name and aliases of a tag, see [normalize_tag_name].
+#[derive(Debug, Clone, View, ViewSchema)]
+#[view(collection = Tag, key = TagByNameAndTypeKey, value = ())]
+pub struct TagByAlias;
+
+impl CollectionMapReduce for TagByAlias {
+	fn map<'doc>(&self, document: CollectionDocument<Tag>) -> ViewMapResult<'doc, Self::View> {
+		let x = document.contents;
+		let header = Header::try_from(document.header)?;
+
+		let maps = std::iter::once(x.name)
+			.chain(x.aliases.unwrap_or_default())
+			.map(|name| {
+				let key = TagByNameAndTypeKey::new(normalize_tag_name(&name), x.type_.clone());
+				BonsaiMap::new(header.clone(), key, ())
+			})
+			.collect::<Vec<BonsaiMap<TagByNameAndTypeKey, ()>>>();
+
+		Ok(Mappings::List(maps))
+	}
+}
+
+#[derive(Debug, Clone, View, ViewSchema)]
+#[view(collection = Tag, key = u64, value = ())]
+pub struct TagByParentId;
+
+impl CollectionMapReduce for TagByParentId {
+	fn map<'doc>(&self, document: CollectionDocument<Tag>) -> ViewMapResult<'doc, Self::View> {
+		match document.contents.parent_id {
+			Some(parent_id) => document.header.emit_key(parent_id),
+			None => Ok(Mappings::none()),
+		}
+	}
+}
+
+#[derive(Debug, Clone, View, ViewSchema)]
+#[view(collection = Tag, key = TagType, value = ())]
+pub struct TagByType;
+
+impl CollectionMapReduce for TagByType {
+	fn map<'doc>(&self, document: CollectionDocument<Tag>) -> ViewMapResult<'doc, Self::View> {
+		let x = document.contents;
+		document.header.emit_key(x.type_)
+	}
+}
@@ -1,9 +1,16 @@
 use bonsaidb::core::{
-	document::{CollectionDocument, Emit},
-	schema::{CollectionMapReduce, View, ViewMapResult, ViewSchema},
+	document::{CollectionDocument, Emit, Header},
+	key::Key,
+	schema::{
+		view::map::Mappings, CollectionMapReduce, Map as BonsaiMap, ReduceResult, View, ViewMapResult, ViewMappedValue,
+		ViewSchema,
+	},
 };
 
-use crate::database::models::track::Track;
+use crate::database::{
+	models::{person::PersonType, track::Track},
+	views::{timestamp_sort_value, SortKey},
+};
 
 #[derive(Debug, Clone, View, ViewSchema)]
 #[view(collection = Track, key = u64, value = ())]
@@ -16,3 +23,215 @@ impl CollectionMapReduce for TrackByReleaseId {
 		document.header.emit_key(x.release_id)
 	}
 }
+
+#[derive(Debug, Clone, PartialEq, Key)]
+pub struct TrackByPersonKey {
+	pub person_id: u64,
+	/// The role this person was credited with in the track.
+	pub type_: PersonType,
+}
+
+impl TrackByPersonKey {
+	pub fn new(person_id: u64, type_: PersonType) -> Self {
+		Self { person_id, type_ }
+	}
+}
+
+/// Maps every person credited in a track, by each of their roles.
+#[derive(Debug, Clone, View, ViewSchema)]
+#[view(collection = Track, key = TrackByPersonKey, value = ())]
+pub struct TrackByPerson;
+
+impl CollectionMapReduce for TrackByPerson {
+	fn map<'doc>(&self, document: CollectionDocument<Track>) -> ViewMapResult<'doc, Self::View> {
+		let x = document.contents;
+		let header = Header::try_from(document.header)?;
+
+		let credits = x
+			.artists
+			.iter()
+			.map(|e| TrackByPersonKey::new(e.id, PersonType::Artist))
+			.chain(
+				x.credits
+					.into_iter()
+					.map(|e| TrackByPersonKey::new(e.person_id, e.type_)),
+			);
+
+		// A performer playing several instruments is credited more than once, yet mapped once.
+		let mut maps = Vec::<BonsaiMap<TrackByPersonKey, ()>>::new();
+		for key in credits {
+			if !maps.iter().any(|e| e.key == key) {
+				maps.push(BonsaiMap::new(header.clone(), key, ()));
+			}
+		}
+
+		Ok(Mappings::List(maps))
+	}
+}
+
+#[derive(Debug, Clone, View, ViewSchema)]
+#[view(collection = Track, key = SortKey, value = ())]
+pub struct TrackByTitle;
+
+impl CollectionMapReduce for TrackByTitle {
+	fn map<'doc>(&self, document: CollectionDocument<Track>) -> ViewMapResult<'doc, Self::View> {
+		let x = document.contents;
+		let title = x.title_sort.unwrap_or(x.title).to_lowercase();
+
+		document.header.emit_key((title, document.header.id))
+	}
+}
+
+#[derive(Debug, Clone, View, ViewSchema)]
+#[view(collection = Track, key = SortKey, value = ())]
+pub struct TrackByArtistSort;
+
+impl CollectionMapReduce for TrackByArtistSort {
+	fn map<'doc>(&self, document: CollectionDocument<Track>) -> ViewMapResult<'doc, Self::View> {
+		let x = document.contents;
+		let credited_as = x.artists.into_iter().next().and_then(|e| e.credited_as);
+		let artist = x.artist_sort.or(credited_as).unwrap_or_default().to_lowercase();
+
+		document.header.emit_key((artist, document.header.id))
+	}
+}
+
+#[derive(Debug, Clone, View, ViewSchema)]
+#[view(collection = Track, key = SortKey, value = ())]
+pub struct TrackByDate;
+
+impl CollectionMapReduce for TrackByDate {
+	fn map<'doc>(&self, document: CollectionDocument<Track>) -> ViewMapResult<'doc, Self::View> {
+		let x = document.contents;
+		let date = x.original_date.map(|d| d.format("%Y-%m-%d").to_string());
+
+		document.header.emit_key((date.unwrap_or_default(), document.header.id))
+	}
+}
+
+#[derive(Debug, Clone, View, ViewSchema)]
+#[view(collection = Track, key = u64, value = u64)]
+pub struct TrackByGenreId;
+
+impl CollectionMapReduce for TrackByGenreId {
+	fn map<'doc>(&self, document: CollectionDocument<Track>) -> ViewMapResult<'doc, Self::View> {
+		let header = Header::try_from(document.header)?;
+		let genre_ids = document.contents.genre_ids.unwrap_or_default();
+
+		let maps = genre_ids
+			.into_iter()
+			.map(|id| BonsaiMap::new(header.clone(), id, 1))
+			.collect::<Vec<BonsaiMap<u64, u64>>>();
+
+		Ok(Mappings::List(maps))
+	}
+
+	fn reduce(&self, mappings: &[ViewMappedValue<Self>], _rereduce: bool) -> ReduceResult<Self::View> {
+		Ok(mappings.iter().map(|m| m.value).sum())
+	}
+}
+
+#[derive(Debug, Clone, View, ViewSchema)]
+#[view(collection = Track, key = SortKey, value = ())]
+pub struct TrackByDateAdded;
+
+impl CollectionMapReduce for TrackByDateAdded {
+	fn map<'doc>(&self, document: CollectionDocument<Track>) -> ViewMapResult<'doc, Self::View> {
+		let date = timestamp_sort_value(&document.contents.date_added);
+		document.header.emit_key((date, document.header.id))
+	}
+}
+
+#[derive(Debug, Clone, View, ViewSchema)]
+#[view(collection = Track, key = String, value = ())]
+pub struct TrackByPath;
+
+impl CollectionMapReduce for TrackByPath {
+	fn map<'doc>(&self, document: CollectionDocument<Track>) -> ViewMapResult<'doc, Self::View> {
+		let x = document.contents;
+		document.header.emit_key(x.path)
+	}
+}
+
+#[derive(Debug, Clone, View, ViewSchema)]
+#[view(collection = Track, key = u64, value = ())]
+pub struct TrackByLibraryId;
+
+impl CollectionMapReduce for TrackByLibraryId {
+	fn map<'doc>(&self, document: CollectionDocument<Track>) -> ViewMapResult<'doc, Self::View> {
+		document.header.emit_key(document.contents.library_id)
+	}
+}
+
+#[derive(Debug, Clone, View, ViewSchema)]
+#[view(collection = Track, key = u64, value = ())]
+pub struct TrackByWorkId;
+
+impl CollectionMapReduce for TrackByWorkId {
+	fn map<'doc>(&self, document: CollectionDocument<Track>) -> ViewMapResult<'doc, Self::View> {
+		match document.contents.work_id {
+			Some(work_id) => document.header.emit_key(work_id),
+			None => Ok(Mappings::none()),
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Key)]
+pub struct TrackByLibraryAndWorkKey {
+	pub library_id: u64,
+	pub work_id: u64,
+	pub release_id: u64,
+}
+
+impl TrackByLibraryAndWorkKey {
+	pub fn new(library_id: u64, work_id: u64, release_id: u64) -> Self {
+		Self {
+			library_id,
+			work_id,
+			release_id,
+		}
+	}
+}
+
+/// Counts the tracks recording a work in each release of a library, so the recordings of every work are counted in
+/// a single query.
+#[derive(Debug, Clone, View, ViewSchema)]
+#[view(collection = Track, key = TrackByLibraryAndWorkKey, value = u64)]
+pub struct TrackByLibraryAndWork;
+
+impl CollectionMapReduce for TrackByLibraryAndWork {
+	fn map<'doc>(&self, document: CollectionDocument<Track>) -> ViewMapResult<'doc, Self::View> {
+		let x = document.contents;
+
+		match x.work_id {
+			Some(work_id) => {
+				let key = TrackByLibraryAndWorkKey::new(x.library_id, work_id, x.release_id);
+				document.header.emit_key_and_value(key, 1)
+			}
+			None => Ok(Mappings::none()),
+		}
+	}
+
+	fn reduce(&self, mappings: &[ViewMappedValue<Self>], _rereduce: bool) -> ReduceResult<Self::View> {
+		Ok(mappings.iter().map(|m| m.value).sum())
+	}
+}
+
+/// Maps the tracks to each of their covers, to tell whether a cover is still used.
+#[derive(Debug, Clone, View, ViewSchema)]
+#[view(collection = Track, key = u64, value = ())]
+pub struct TrackByCoverId;
+
+impl CollectionMapReduce for TrackByCoverId {
+	fn map<'doc>(&self, document: CollectionDocument<Track>) -> ViewMapResult<'doc, Self::View> {
+		let header = Header::try_from(document.header)?;
+		let cover_ids = document.contents.cover_ids.unwrap_or_default();
+
+		let maps = cover_ids
+			.into_iter()
+			.map(|id| BonsaiMap::new(header.clone(), id, ()))
+			.collect::<Vec<BonsaiMap<u64, ()>>>();
+
+		Ok(Mappings::List(maps))
+	}
+}
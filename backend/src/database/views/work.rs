@@ -0,0 +1,51 @@
+use bonsaidb::core::{
+	document::{CollectionDocument, Emit, Header},
+	schema::{view::map::Mappings, CollectionMapReduce, Map as BonsaiMap, View, ViewMapResult, ViewSchema},
+};
+
+use crate::database::models::work::{normalize_work_name, Work};
+
+/// Maps works by their normalized name.
+#[derive(Debug, Clone, View, ViewSchema)]
+#[view(collection = Work, key = String, value = ())]
+pub struct WorkByName;
+
+impl CollectionMapReduce for WorkByName {
+	fn map<'doc>(&self, document: CollectionDocument<Work>) -> ViewMapResult<'doc, Self::View> {
+		let key = normalize_work_name(&document.contents.name);
+		document.header.emit_key(key)
+	}
+}
+
+#[derive(Debug, Clone, View, ViewSchema)]
+#[view(collection = Work, key = String, value = ())]
+pub struct WorkByMbzId;
+
+impl CollectionMapReduce for WorkByMbzId {
+	fn map<'doc>(&self, document: CollectionDocument<Work>) -> ViewMapResult<'doc, Self::View> {
+		match &document.contents.mbz_id {
+			Some(x) => document.header.emit_key(x.to_lowercase()),
+			None => Ok(Mappings::none()),
+		}
+	}
+}
+
+/// Maps works to each of their composers.
+#[derive(Debug, Clone, View, ViewSchema)]
+#[view(collection = Work, key = u64, value = ())]
+pub struct WorkByComposerId;
+
+impl CollectionMapReduce for WorkByComposerId {
+	fn map<'doc>(&self, document: CollectionDocument<Work>) -> ViewMapResult<'doc, Self::View> {
+		let header = Header::try_from(document.header)?;
+
+		let maps = document
+			.contents
+			.composer_ids
+			.into_iter()
+			.map(|id| BonsaiMap::new(header.clone(), id, ()))
+			.collect::<Vec<BonsaiMap<u64, ()>>>();
+
+		Ok(Mappings::List(maps))
+	}
+}
@@ -328,6 +328,93 @@ impl From<serde_json::Error> for Error {
 	}
 }
 
+impl From<toml::de::Error> for Error {
+	fn from(value: toml::de::Error) -> Self {
+		Self {
+			kind: ErrorKind::Conversion,
+			short: Cow::Borrowed("Serde: TOML deserialization error"),
+			message: Some(Cow::Owned(value.to_string())),
+		}
+	}
+}
+
+impl From<toml::ser::Error> for Error {
+	fn from(value: toml::ser::Error) -> Self {
+		Self {
+			kind: ErrorKind::Conversion,
+			short: Cow::Borrowed("Serde: TOML serialization error"),
+			message: Some(Cow::Owned(value.to_string())),
+		}
+	}
+}
+
+impl From<notify::Error> for Error {
+	fn from(value: notify::Error) -> Self {
+		Self {
+			kind: ErrorKind::Io,
+			short: Cow::Borrowed("Notify: Watcher error"),
+			message: Some(Cow::Owned(value.to_string())),
+		}
+	}
+}
+
+impl From<globset::Error> for Error {
+	fn from(value: globset::Error) -> Self {
+		Self {
+			kind: ErrorKind::Conversion,
+			short: Cow::Borrowed("Glob: Invalid pattern"),
+			message: Some(Cow::Owned(value.to_string())),
+		}
+	}
+}
+
+#[cfg(target_os = "linux")]
+impl From<zbus::Error> for Error {
+	fn from(value: zbus::Error) -> Self {
+		Self {
+			kind: ErrorKind::Io,
+			short: Cow::Borrowed("D-Bus: Connection error"),
+			message: Some(Cow::Owned(value.to_string())),
+		}
+	}
+}
+
+impl From<reqwest::Error> for Error {
+	fn from(value: reqwest::Error) -> Self {
+		let short = if value.is_timeout() || value.is_connect() {
+			"HTTP: Service unreachable"
+		} else {
+			"HTTP: Request error"
+		};
+
+		Self {
+			kind: ErrorKind::Io,
+			short: Cow::Borrowed(short),
+			message: Some(Cow::Owned(value.to_string())),
+		}
+	}
+}
+
+impl From<wasmtime::Error> for Error {
+	fn from(value: wasmtime::Error) -> Self {
+		Self {
+			kind: ErrorKind::Other,
+			short: Cow::Borrowed("Plugin: Runtime error"),
+			message: Some(Cow::Owned(format!("{value:#}"))),
+		}
+	}
+}
+
+impl From<wasmtime::MemoryAccessError> for Error {
+	fn from(value: wasmtime::MemoryAccessError) -> Self {
+		Self {
+			kind: ErrorKind::Other,
+			short: Cow::Borrowed("Plugin: Memory access error"),
+			message: Some(Cow::Owned(value.to_string())),
+		}
+	}
+}
+
 impl<T: Debug> From<std::sync::mpsc::SendError<T>> for Error {
 	fn from(value: std::sync::mpsc::SendError<T>) -> Self {
 		let message = format!("Failed to send data through the mpsc channel, got: {:?}", value.0);
@@ -343,7 +430,10 @@ impl<T: Debug> From<std::sync::mpsc::SendError<T>> for Error {
 pub mod pre {
 	use std::borrow::Cow;
 
-	use crate::errors::{Error, ErrorKind};
+	use crate::{
+		database::models::track::MAX_RATING,
+		errors::{Error, ErrorKind},
+	};
 
 	#[inline]
 	pub fn probe_no_meta() -> Error {
@@ -354,6 +444,17 @@ pub mod pre {
 		}
 	}
 
+	#[inline]
+	pub fn non_utf8_path(path: &std::path::Path) -> Error {
+		let message = format!("The path '{}' isn't valid UTF-8.", path.display());
+
+		Error {
+			kind: ErrorKind::Io,
+			short: Cow::Borrowed("Invalid path"),
+			message: Some(Cow::Owned(message)),
+		}
+	}
+
 	#[inline]
 	pub fn unsupported_media_type(type_: &str) -> Error {
 		let message = format!("Unsupported media type '{type_}' was passed.");
@@ -365,6 +466,17 @@ pub mod pre {
 		}
 	}
 
+	#[inline]
+	pub fn database_entry_not_found(collection: &str, id: u64) -> Error {
+		let message = format!("Couldn't find an entry with the id '{id}' in the '{collection}' collection.");
+
+		Error {
+			kind: ErrorKind::Database,
+			short: Cow::Borrowed("Entry not found"),
+			message: Some(Cow::Owned(message)),
+		}
+	}
+
 	#[inline]
 	pub fn unsupported_image_type(ext: &str) -> Error {
 		let message = format!("Unsupported image file extension type: '{ext}'");
@@ -375,4 +487,224 @@ pub mod pre {
 			message: Some(Cow::Owned(message)),
 		}
 	}
+
+	#[inline]
+	pub fn unsupported_image_size(width: u32, height: u32) -> Error {
+		let message = format!("Images up to 65535x65535 are supported, but the given one is {width}x{height}.");
+
+		Error {
+			kind: ErrorKind::Other,
+			short: Cow::Borrowed("Invalid image size"),
+			message: Some(Cow::Owned(message)),
+		}
+	}
+
+	#[inline]
+	pub fn invalid_theme_id(id: &str) -> Error {
+		let message = format!("'{id}' isn't a valid theme id, as theme ids are file names in the themes directory.");
+
+		Error {
+			kind: ErrorKind::Other,
+			short: Cow::Borrowed("Invalid theme id"),
+			message: Some(Cow::Owned(message)),
+		}
+	}
+
+	#[inline]
+	pub fn invalid_configuration(message: &'static str) -> Error {
+		Error {
+			kind: ErrorKind::Conversion,
+			short: Cow::Borrowed("Invalid configuration"),
+			message: Some(Cow::Borrowed(message)),
+		}
+	}
+
+	#[inline]
+	pub fn invalid_plugin_manifest(message: &'static str) -> Error {
+		Error {
+			kind: ErrorKind::Conversion,
+			short: Cow::Borrowed("Invalid plugin manifest"),
+			message: Some(Cow::Borrowed(message)),
+		}
+	}
+
+	#[inline]
+	pub fn invalid_plugin_module(message: &'static str) -> Error {
+		Error {
+			kind: ErrorKind::Other,
+			short: Cow::Borrowed("Invalid plugin module"),
+			message: Some(Cow::Borrowed(message)),
+		}
+	}
+
+	#[inline]
+	pub fn duplicate_plugin(id: &str) -> Error {
+		let message = format!("A plugin with the id '{id}' is already loaded.");
+
+		Error {
+			kind: ErrorKind::Other,
+			short: Cow::Borrowed("Duplicate plugin"),
+			message: Some(Cow::Owned(message)),
+		}
+	}
+
+	#[inline]
+	pub fn plugin_not_found(id: &str) -> Error {
+		let message = format!("Couldn't find a loaded plugin with the id '{id}'.");
+
+		Error {
+			kind: ErrorKind::Other,
+			short: Cow::Borrowed("Plugin not found"),
+			message: Some(Cow::Owned(message)),
+		}
+	}
+
+	#[inline]
+	pub fn plugin_command_not_found(id: &str, command: &str) -> Error {
+		let message = format!("The plugin '{id}' doesn't declare the command '{command}'.");
+
+		Error {
+			kind: ErrorKind::Other,
+			short: Cow::Borrowed("Plugin command not found"),
+			message: Some(Cow::Owned(message)),
+		}
+	}
+
+	#[inline]
+	pub fn plugins_unavailable() -> Error {
+		Error {
+			kind: ErrorKind::Other,
+			short: Cow::Borrowed("Plugins unavailable"),
+			message: Some(Cow::Borrowed(
+				"The plugins couldn't be loaded when the application started.",
+			)),
+		}
+	}
+
+	#[inline]
+	pub fn invalid_plugin_buffer(id: &str) -> Error {
+		let message = format!("The plugin '{id}' returned a buffer outside of its memory.");
+
+		Error {
+			kind: ErrorKind::Other,
+			short: Cow::Borrowed("Invalid plugin buffer"),
+			message: Some(Cow::Owned(message)),
+		}
+	}
+
+	#[inline]
+	pub fn plugin_failed(id: &str) -> Error {
+		let message = format!("The plugin '{id}' has failed previously and was disabled until the next restart.");
+
+		Error {
+			kind: ErrorKind::Other,
+			short: Cow::Borrowed("Plugin disabled"),
+			message: Some(Cow::Owned(message)),
+		}
+	}
+
+	#[inline]
+	pub fn invalid_cue_sheet(line: usize, message: &str) -> Error {
+		let message = format!("Line {line}: {message}");
+
+		Error {
+			kind: ErrorKind::Conversion,
+			short: Cow::Borrowed("Invalid CUE sheet"),
+			message: Some(Cow::Owned(message)),
+		}
+	}
+
+	#[inline]
+	pub fn scrobble_rejected(service: &str, status: u16, message: &str) -> Error {
+		let message = format!("{service} responded with status {status}: {message}");
+
+		Error {
+			kind: ErrorKind::Other,
+			short: Cow::Borrowed("Scrobble failed"),
+			message: Some(Cow::Owned(message)),
+		}
+	}
+
+	#[inline]
+	pub fn invalid_rating(rating: u8) -> Error {
+		let message = format!("Tracks are rated from 1 to {MAX_RATING}, but {rating} was given.");
+
+		Error {
+			kind: ErrorKind::Other,
+			short: Cow::Borrowed("Invalid rating"),
+			message: Some(Cow::Owned(message)),
+		}
+	}
+
+	#[inline]
+	pub fn unsupported_playlist_format(ext: &str) -> Error {
+		let message = format!("Unsupported playlist file extension: '{ext}'. Expected m3u, m3u8, pls or xspf.");
+
+		Error {
+			kind: ErrorKind::Other,
+			short: Cow::Borrowed("Invalid playlist type"),
+			message: Some(Cow::Owned(message)),
+		}
+	}
+
+	#[inline]
+	pub fn invalid_playlist(position: usize, message: &str) -> Error {
+		let message = format!("Byte {position}: {message}");
+
+		Error {
+			kind: ErrorKind::Conversion,
+			short: Cow::Borrowed("Invalid playlist"),
+			message: Some(Cow::Owned(message)),
+		}
+	}
+
+	#[inline]
+	pub fn default_library_deletion() -> Error {
+		Error {
+			kind: ErrorKind::Other,
+			short: Cow::Borrowed("Default library"),
+			message: Some(Cow::Borrowed("The default library can't be deleted.")),
+		}
+	}
+
+	#[inline]
+	pub fn unknown_person_modification() -> Error {
+		Error {
+			kind: ErrorKind::Other,
+			short: Cow::Borrowed("Unknown person"),
+			message: Some(Cow::Borrowed("The unknown person can't be merged or split.")),
+		}
+	}
+
+	#[inline]
+	pub fn unsupported_schema_version(version: u64) -> Error {
+		let message =
+			format!("The database is at schema version {version}, which is newer than this version of the app.");
+
+		Error {
+			kind: ErrorKind::Database,
+			short: Cow::Borrowed("Unsupported database version"),
+			message: Some(Cow::Owned(message)),
+		}
+	}
+
+	#[inline]
+	pub fn invalid_backup(message: &str) -> Error {
+		Error {
+			kind: ErrorKind::Conversion,
+			short: Cow::Borrowed("Invalid backup"),
+			message: Some(Cow::Owned(message.to_string())),
+		}
+	}
+
+	#[inline]
+	pub fn invalid_library_export(line: usize, message: &str) -> Error {
+		let message = format!("Line {line}: {message}");
+
+		Error {
+			kind: ErrorKind::Conversion,
+			short: Cow::Borrowed("Invalid library export"),
+			message: Some(Cow::Owned(message)),
+		}
+	}
 }
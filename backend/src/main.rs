@@ -7,16 +7,28 @@ use {
 	window_shadows::set_shadow,
 };
 
-use crate::models::state::{AppState, DatabaseState, DirectoryState};
+use crate::models::state::{
+	AppState, ConfigurationState, DatabaseState, DirectoryState, PlaybackState, PluginState, ScrobbleState,
+	SubsonicState, ThemeState,
+};
 
 pub mod macros;
 
+mod backup;
 mod commands;
 mod constants;
 mod database;
 mod errors;
 mod ffmpeg;
+mod metadata;
 mod models;
+#[cfg(target_os = "linux")]
+mod mpris;
+mod playlists;
+mod plugins;
+mod portable;
+mod scrobbler;
+mod subsonic;
 mod utils;
 
 fn main() {
@@ -40,14 +52,62 @@ fn main() {
 		})
 		.manage(AppState::default())
 		.manage(DirectoryState::default())
+		.manage(ConfigurationState::default())
+		.manage(ThemeState::default())
 		.manage(DatabaseState::default())
+		.manage(PluginState::default())
+		.manage(PlaybackState::default())
+		.manage(SubsonicState::default())
+		.manage(ScrobbleState::default())
 		.invoke_handler(tauri::generate_handler![
 			commands::general::setup,
+			commands::backup::create_backup,
+			commands::backup::restore_backup,
+			commands::library::get_libraries,
+			commands::library::get_active_library,
+			commands::library::set_active_library,
+			commands::library::create_library,
+			commands::library::update_library,
+			commands::library::delete_library,
 			commands::library::get_scan_locations,
 			commands::library::initialize_library,
+			commands::label::get_labels,
+			commands::label::get_label,
+			commands::label::set_label_metadata,
+			commands::lyrics::get_lyrics,
+			commands::lyrics::set_lyrics,
+			commands::person::get_artist,
+			commands::person::set_artist_image,
+			commands::person::merge_people,
+			commands::person::split_person,
+			commands::playback::get_playback,
+			commands::playback::set_playback,
+			commands::playlist::get_playlists,
+			commands::playlist::get_playlist_tracks,
+			commands::playlist::import_playlist,
+			commands::playlist::export_playlist,
+			commands::playlist::export_tracks,
+			commands::plugin::get_plugins,
+			commands::plugin::invoke_plugin_command,
+			commands::portable::export_library_data,
+			commands::portable::import_library_data,
 			commands::release::get_releases,
 			commands::release::get_display_releases,
+			commands::release::query_releases,
+			commands::scrobble::authenticate_lastfm,
+			commands::settings::get_settings,
+			commands::settings::set_settings,
+			commands::tag::get_genres,
+			commands::tag::set_genre_parent,
+			commands::tag::add_genre_alias,
+			commands::tag::merge_genres,
+			commands::theme::get_themes,
+			commands::theme::get_theme,
 			commands::track::get_track_list_for_release,
+			commands::track::query_tracks,
+			commands::track::set_track_rating,
+			commands::work::get_works,
+			commands::work::get_work,
 		])
 		.run(tauri::generate_context!())
 		.expect("error while running tauri application");
@@ -0,0 +1,301 @@
+use std::path::Path;
+
+use crate::{
+	database::models::track::TrackSegment,
+	errors::{pre::invalid_cue_sheet, Result},
+	metadata::TagMap,
+};
+
+/// Frames per second of the `MM:SS:FF` timestamps.
+const FRAMES_PER_SECOND: u64 = 75;
+
+/// Tags and the start offset of a track while its sheet is being read.
+type PendingTrack = (TagMap, Option<u64>);
+
+#[derive(Debug)]
+pub struct CueSheet {
+	pub files: Vec<CueFile>,
+}
+
+/// An audio file referenced by a CUE sheet, along with the tracks it holds.
+#[derive(Debug)]
+pub struct CueFile {
+	pub name: String,
+	pub tracks: Vec<CueTrack>,
+}
+
+#[derive(Debug)]
+pub struct CueTrack {
+	/// Tags of the sheet, overridden by the tags of the track.
+	pub tags: TagMap,
+	pub segment: TrackSegment,
+}
+
+impl CueSheet {
+	/// Parses a CUE sheet, mapping the sheet commands into release tags and the track commands into track tags.
+	///
+	/// `REM` comments in the `REM KEY value` form are read as tags, covering `REM DATE` and `REM GENRE`.
+	pub fn parse(contents: &str) -> Result<Self> {
+		let mut sheet_tags = TagMap::default();
+		let mut files = Vec::<(String, Vec<PendingTrack>)>::new();
+		// Track being read, pushed into the last file once the next TRACK or FILE command is reached.
+		let mut track = None::<PendingTrack>;
+
+		let flush = |files: &mut Vec<(String, Vec<PendingTrack>)>, track: Option<PendingTrack>| {
+			if let (Some((_, tracks)), Some(x)) = (files.last_mut(), track) {
+				tracks.push(x);
+			}
+		};
+
+		for (i, line) in contents.trim_start_matches('\u{feff}').lines().enumerate() {
+			let line_no = i + 1;
+			let (command, args) = split_command(line.trim());
+			let value = unquote(args).to_string();
+
+			match command.to_uppercase().as_str() {
+				"FILE" => {
+					flush(&mut files, track.take());
+
+					let name = args.rsplit_once(' ').map_or(args, |(name, _)| name);
+					files.push((unquote(name).to_string(), Vec::new()));
+				}
+				"TRACK" => {
+					if files.is_empty() {
+						return Err(invalid_cue_sheet(line_no, "TRACK appears before any FILE"));
+					}
+
+					let number = args
+						.split_whitespace()
+						.next()
+						.and_then(|x| x.parse::<u32>().ok())
+						.ok_or_else(|| invalid_cue_sheet(line_no, "TRACK is missing its number"))?;
+
+					flush(&mut files, track.take());
+
+					let mut tags = TagMap::default();
+					tags.push("track", number.to_string());
+					track = Some((tags, None));
+				}
+				"INDEX" => {
+					let mut parts = args.split_whitespace();
+					let (Some(index), Some(timestamp)) = (parts.next(), parts.next()) else {
+						return Err(invalid_cue_sheet(line_no, "INDEX is missing its number or timestamp"));
+					};
+
+					if let (Some((_, start)), Ok(1)) = (track.as_mut(), index.parse::<u32>()) {
+						let x = parse_timestamp(timestamp)
+							.ok_or_else(|| invalid_cue_sheet(line_no, "INDEX has an invalid timestamp"))?;
+						*start = Some(x);
+					}
+				}
+				command => match (command, track.as_mut()) {
+					("TITLE", Some((tags, _))) => tags.push("title", value),
+					("PERFORMER", Some((tags, _))) => tags.push("artist", value),
+					("SONGWRITER", Some((tags, _))) => tags.push("composer", value),
+					("ISRC", Some((tags, _))) => tags.push("isrc", value),
+					("REM", Some((tags, _))) => push_comment(tags, args),
+
+					("TITLE", None) => sheet_tags.push("album", value),
+					("PERFORMER", None) => sheet_tags.push("album_artist", value),
+					("SONGWRITER", None) => sheet_tags.push("composer", value),
+					("CATALOG", None) => sheet_tags.push("barcode", value),
+					("REM", None) => push_comment(&mut sheet_tags, args),
+
+					_ => continue,
+				},
+			}
+		}
+
+		flush(&mut files, track.take());
+
+		let files = files
+			.into_iter()
+			.map(|(name, tracks)| {
+				let total = tracks.len();
+				let starts = tracks.iter().map(|(_, x)| *x).collect::<Vec<Option<u64>>>();
+
+				let tracks = tracks
+					.into_iter()
+					.enumerate()
+					.filter_map(|(i, (track_tags, start))| {
+						let start = start?;
+						let end = starts.get(i + 1).copied().flatten();
+
+						let mut tags = sheet_tags.clone();
+						tags.push("total_tracks", total.to_string());
+						tags.merge(track_tags);
+
+						Some(CueTrack {
+							tags,
+							segment: TrackSegment { start, end },
+						})
+					})
+					.collect();
+
+				CueFile { name, tracks }
+			})
+			.collect();
+
+		Ok(Self { files })
+	}
+
+	/// Takes the entry of the audio file, matched by the file stem as sheets often refer to the original `.wav` files.
+	///
+	/// Falls back to the only file if the sheet has a single one.
+	pub fn take_file(mut self, path: &Path) -> Option<CueFile> {
+		let stem_of = |x: &Path| x.file_stem().map(|y| y.to_string_lossy().to_lowercase());
+		let stem = stem_of(path);
+
+		let position = self
+			.files
+			.iter()
+			.position(|x| stem_of(Path::new(&x.name)) == stem)
+			.or_else(|| (self.files.len() == 1).then_some(0))?;
+
+		Some(self.files.swap_remove(position))
+	}
+}
+
+/// Finds the CUE sheet entry of an audio file, returning none if the file is not split by a sheet.
+///
+/// Looks for a `.cue` file sharing the stem of the audio file, then for any other `.cue` file in the same directory
+/// referring to it, and finally for a sheet embedded in the `CUESHEET` tag.
+pub fn find_cue_file(path: &Path, tags: &TagMap) -> Result<Option<CueFile>> {
+	let sibling = path.with_extension("cue");
+	if sibling.is_file() {
+		if let Some(file) = read_cue_sheet(&sibling)?.take_file(path) {
+			return Ok(Some(file));
+		}
+	}
+
+	if let Some(entries) = path.parent().and_then(|x| std::fs::read_dir(x).ok()) {
+		// Unreadable entries are skipped like unrelated sheets.
+		for entry in entries.flatten() {
+			let cue_path = entry.path();
+			let is_cue = cue_path.extension().is_some_and(|x| x.eq_ignore_ascii_case("cue"));
+
+			if !is_cue || cue_path == sibling {
+				continue;
+			}
+
+			// Unrelated sheets shouldn't fail the scan of this file.
+			let Ok(sheet) = read_cue_sheet(&cue_path) else {
+				continue;
+			};
+
+			let stem = path.file_stem();
+			if sheet.files.iter().any(|x| Path::new(&x.name).file_stem() == stem) {
+				return Ok(sheet.take_file(path));
+			}
+		}
+	}
+
+	if let Some(contents) = tags.get("cuesheet").and_then(|x| x.first()) {
+		let mut sheet = CueSheet::parse(contents)?;
+		if !sheet.files.is_empty() {
+			return Ok(Some(sheet.files.swap_remove(0)));
+		}
+	}
+
+	Ok(None)
+}
+
+fn read_cue_sheet(path: &Path) -> Result<CueSheet> {
+	let bytes = std::fs::read(path)?;
+	CueSheet::parse(&String::from_utf8_lossy(&bytes))
+}
+
+fn split_command(line: &str) -> (&str, &str) {
+	match line.split_once(char::is_whitespace) {
+		Some((command, args)) => (command, args.trim()),
+		None => (line, ""),
+	}
+}
+
+fn unquote(value: &str) -> &str {
+	let value = value.trim();
+	value
+		.strip_prefix('"')
+		.and_then(|x| x.strip_suffix('"'))
+		.unwrap_or(value)
+}
+
+/// Reads `REM KEY value` comments as tags, ignoring `REM COMMENT` and the free form ones.
+fn push_comment(tags: &mut TagMap, args: &str) {
+	let (key, value) = split_command(args);
+	let is_key = key.chars().all(|x| x.is_ascii_uppercase() || x == '_');
+
+	if is_key && key != "COMMENT" && !value.is_empty() {
+		tags.push(key, unquote(value).to_string());
+	}
+}
+
+/// Converts a `MM:SS:FF` timestamp into milliseconds.
+fn parse_timestamp(value: &str) -> Option<u64> {
+	let mut parts = value.split(':').map(|x| x.parse::<u64>().ok());
+	let (Some(Some(minutes)), Some(Some(seconds)), Some(Some(frames)), None) =
+		(parts.next(), parts.next(), parts.next(), parts.next())
+	else {
+		return None;
+	};
+
+	Some((minutes * 60 + seconds) * 1000 + frames * 1000 / FRAMES_PER_SECOND)
+}
+
+#[cfg(test)]
+mod test {
+	use std::path::Path;
+
+	use crate::{database::models::track::TrackSegment, errors::Result, metadata::cue::CueSheet};
+
+	const SHEET: &str = r#"REM GENRE "Ambient"
+REM DATE 2020
+REM COMMENT "ExactAudioCopy v1.6"
+PERFORMER "Artist"
+TITLE "Release"
+FILE "Artist - Release.wav" WAVE
+  TRACK 01 AUDIO
+    TITLE "First"
+    INDEX 01 00:00:00
+  TRACK 02 AUDIO
+    TITLE "Second"
+    PERFORMER "Guest"
+    INDEX 00 03:20:00
+    INDEX 01 03:21:37
+"#;
+
+	#[test]
+	fn test_parse() -> Result<()> {
+		let sheet = CueSheet::parse(SHEET)?;
+		let file = sheet.take_file(Path::new("/music/Artist - Release.flac")).unwrap();
+		assert_eq!(file.tracks.len(), 2);
+
+		let first = &file.tracks[0];
+		assert_eq!(
+			first.segment,
+			TrackSegment {
+				start: 0,
+				end: Some(201_493)
+			}
+		);
+		assert!(first.tags.iter().any(|x| x == ("title", "First")));
+		assert!(first.tags.iter().any(|x| x == ("album", "Release")));
+		assert!(first.tags.iter().any(|x| x == ("date", "2020")));
+		assert!(!first.tags.iter().any(|x| x.0 == "comment"));
+
+		let second = &file.tracks[1];
+		assert_eq!(
+			second.segment,
+			TrackSegment {
+				start: 201_493,
+				end: None
+			}
+		);
+		assert!(second.tags.iter().any(|x| x == ("artist", "Guest")));
+		assert!(second.tags.iter().any(|x| x == ("track", "2")));
+
+		assert!(CueSheet::parse("TRACK 01 AUDIO").is_err());
+
+		Ok(())
+	}
+}
@@ -0,0 +1,205 @@
+use std::fmt::Write;
+
+use crate::{
+	database::models::lyrics::{Lyrics, LyricsLine, LyricsWord},
+	models::temp::lyrics::TempLyrics,
+};
+
+/// Parses lyrics in the LRC format, falling back to plain lyrics if no line has a timestamp.
+///
+/// Supports lines with multiple timestamps, enhanced word timestamps in the `<mm:ss.xx>` form,
+/// and the `[offset:]` and `[la:]` tags. Other LRC tags are skipped, while bracketed lines like `[Verse: 1]` are kept
+/// as text. Lines without a timestamp are dropped from synced lyrics.
+pub fn parse_lyrics(contents: &str, language: Option<String>) -> TempLyrics {
+	let mut lyrics = TempLyrics {
+		language,
+		lines: Vec::new(),
+	};
+
+	let mut offset = 0_i64;
+	let mut plain = Vec::<LyricsLine>::new();
+
+	for line in contents.trim_start_matches('\u{feff}').lines() {
+		let mut rest = line.trim();
+		let mut stamps = Vec::<u64>::new();
+		let mut has_tags = false;
+
+		while let Some((tag, remaining)) = rest.strip_prefix('[').and_then(|x| x.split_once(']')) {
+			if let Some(stamp) = parse_timestamp(tag) {
+				stamps.push(stamp);
+			} else if let Some((key, value)) = tag.split_once(':') {
+				match key.trim().to_lowercase().as_str() {
+					"offset" => offset = value.trim().parse::<i64>().unwrap_or_default(),
+					"la" | "lang" => lyrics.language = Some(value.trim().to_string()),
+					"ar" | "al" | "ti" | "au" | "by" | "length" | "re" | "tool" | "ve" | "#" => {}
+					// Lines like `[Verse: 1]` are a part of the lyrics rather than tags.
+					_ => break,
+				}
+			} else {
+				break;
+			}
+
+			has_tags = true;
+			rest = remaining;
+		}
+
+		if stamps.is_empty() {
+			if !has_tags {
+				plain.push(LyricsLine {
+					start: None,
+					text: rest.to_string(),
+					words: None,
+				});
+			}
+
+			continue;
+		}
+
+		let (text, words) = parse_words(rest);
+		for start in stamps {
+			lyrics.lines.push(LyricsLine {
+				start: Some(start),
+				text: text.clone(),
+				words: words.clone(),
+			});
+		}
+	}
+
+	if lyrics.lines.is_empty() {
+		// Leading and trailing blank lines are dropped, while the ones separating the verses are kept.
+		let first = plain.iter().position(|x| !x.text.is_empty()).unwrap_or(plain.len());
+		let last = plain.iter().rposition(|x| !x.text.is_empty()).map_or(first, |x| x + 1);
+		lyrics.lines = plain.drain(first..last).collect();
+
+		return lyrics;
+	}
+
+	// A positive offset shows the lyrics earlier.
+	let apply_offset = |x: u64| x.saturating_add_signed(-offset);
+	for line in &mut lyrics.lines {
+		line.start = line.start.map(apply_offset);
+
+		for word in line.words.iter_mut().flatten() {
+			word.start = apply_offset(word.start);
+		}
+	}
+
+	lyrics.lines.sort_by_key(|x| x.start);
+	lyrics
+}
+
+/// Splits a line into its words if it has enhanced timestamps.
+fn parse_words(line: &str) -> (String, Option<Vec<LyricsWord>>) {
+	let mut words = Vec::<LyricsWord>::new();
+	let mut rest = line;
+
+	while let Some((tag, remaining)) = rest.strip_prefix('<').and_then(|x| x.split_once('>')) {
+		let Some(start) = parse_timestamp(tag) else {
+			break;
+		};
+
+		let end = remaining.find('<').unwrap_or(remaining.len());
+		let text = &remaining[..end];
+		rest = &remaining[end..];
+
+		if !text.is_empty() {
+			words.push(LyricsWord {
+				start,
+				text: text.to_string(),
+			});
+		}
+	}
+
+	if words.is_empty() {
+		return (line.to_string(), None);
+	}
+
+	let text = words.iter().map(|x| x.text.as_str()).collect::<String>();
+	(text.trim().to_string(), Some(words))
+}
+
+/// Writes the lyrics back in the LRC format, or as plain text if they aren't synced.
+pub fn to_lrc(lyrics: &Lyrics) -> String {
+	let mut output = String::new();
+
+	if let Some(language) = &lyrics.language {
+		writeln!(output, "[la:{language}]").unwrap();
+	}
+
+	for line in &lyrics.lines {
+		if let Some(start) = line.start {
+			output.push_str(&format_timestamp(start, '[', ']'));
+		}
+
+		match &line.words {
+			Some(words) => words
+				.iter()
+				.for_each(|x| output.push_str(&(format_timestamp(x.start, '<', '>') + &x.text))),
+			None => output.push_str(&line.text),
+		}
+
+		output.push('\n');
+	}
+
+	output
+}
+
+/// Reads `mm:ss`, `mm:ss.xx`, `mm:ss.xxx` and `mm:ss:xx` timestamps into milliseconds.
+fn parse_timestamp(value: &str) -> Option<u64> {
+	let (minutes, rest) = value.trim().split_once(':')?;
+	let (seconds, fraction) = rest.split_once(['.', ':']).map_or((rest, None), |(x, y)| (x, Some(y)));
+
+	let minutes = minutes.parse::<u64>().ok()?;
+	let seconds = seconds.parse::<u64>().ok()?;
+	let millis = match fraction {
+		None => 0,
+		Some(x) if (1..=3).contains(&x.len()) => x.parse::<u64>().ok()? * 10_u64.pow(3 - x.len() as u32),
+		Some(_) => return None,
+	};
+
+	Some((minutes * 60 + seconds) * 1000 + millis)
+}
+
+fn format_timestamp(millis: u64, open: char, close: char) -> String {
+	let minutes = millis / 60_000;
+	let seconds = millis / 1000 % 60;
+	let centis = millis % 1000 / 10;
+
+	format!("{open}{minutes:02}:{seconds:02}.{centis:02}{close}")
+}
+
+#[cfg(test)]
+mod test {
+	use crate::metadata::lyrics::{parse_lyrics, to_lrc};
+
+	#[test]
+	fn test_parse_lyrics() {
+		let contents =
+			"[ar:Artist]\n[offset:+500]\n[00:12.00][00:45.00]Chorus\n[00:30.50]<00:30.50>Sing <00:31.25>along\n";
+		let lyrics = parse_lyrics(contents, None);
+
+		let starts = lyrics.lines.iter().map(|x| x.start).collect::<Vec<Option<u64>>>();
+		assert_eq!(starts, vec![Some(11_500), Some(30_000), Some(44_500)]);
+
+		let words = lyrics.lines[1].words.as_ref().unwrap();
+		assert_eq!(lyrics.lines[1].text, "Sing along");
+		assert_eq!(words[1].start, 30_750);
+		assert_eq!(words[1].text, "along");
+
+		let plain = parse_lyrics("\nFirst verse\n\nSecond verse\n\n", Some("eng".to_string()));
+		let texts = plain.lines.iter().map(|x| x.text.as_str()).collect::<Vec<&str>>();
+		assert_eq!(texts, vec!["First verse", "", "Second verse"]);
+
+		let plain = parse_lyrics("[ar:Artist]\n[Verse: 1]\nFirst verse\n", None);
+		let texts = plain.lines.iter().map(|x| x.text.as_str()).collect::<Vec<&str>>();
+		assert_eq!(texts, vec!["[Verse: 1]", "First verse"]);
+	}
+
+	#[test]
+	fn test_roundtrip() {
+		let contents = "[la:jpn]\n[00:01.00]First\n[00:02.50]<00:02.50>Second <00:03.00>line\n";
+		let lyrics = parse_lyrics(contents, None).into_lyrics(0);
+
+		assert_eq!(to_lrc(&lyrics), contents);
+	}
+}
@@ -0,0 +1,588 @@
+use chrono::NaiveDate;
+
+use crate::{
+	database::models::{
+		label::Label,
+		person::{Person, PersonType},
+		release::{ReleaseType, ReleaseTypeSecondary},
+		tag::{Tag, TagType},
+		track::Movement,
+		CountryCode, FromTag, ScriptCode,
+	},
+	errors::Result,
+	metadata::{lyrics::parse_lyrics, TagMap},
+	models::temp::{OptionedDate, TempCredit, TempInlinedArtist, TempTrackMeta},
+	utils::matchers,
+};
+
+/// Resolves the canonical name of a tag key, merging the spellings used by different taggers.
+///
+/// MP4 atoms like `©nam` or `trkn`, and the freeform `----:com.apple.iTunes:` names are merged as well,
+/// for the files where they aren't already renamed by ffmpeg.
+pub fn canonical_key(key: &str) -> String {
+	let key = key.trim().to_lowercase();
+	let key = key.strip_prefix("----:com.apple.itunes:").unwrap_or(&key);
+
+	let canonical = match key {
+		"titlesort" => "title_sort",
+		"artistsort" => "artist_sort",
+		"albumsort" => "album_sort",
+		"albumartist" | "album artist" => "album_artist",
+		"albumartistsort" => "album_artist_sort",
+		"releasecountry" => "release_country",
+		"tracknumber" => "track",
+		"discnumber" => "disc",
+		"totaltracks" | "tracktotal" => "total_tracks",
+		"totaldiscs" | "disctotal" => "total_discs",
+		"originaldate" => "original_date",
+		"catalognumber" => "catalog",
+		"publisher" => "label",
+		"unsyncedlyrics" | "uslt" | "sylt" | "©lyr" => "lyrics",
+		"text" | "writer" => "lyricist",
+		"tpe3" => "conductor",
+		"tpe4" | "mixartist" => "remixer",
+		"dj-mixer" | "dj_mixer" => "djmixer",
+		"©wrk" => "work",
+		"movementname" | "mvnm" | "©mvn" => "movement_name",
+		"movementnumber" | "mvin" | "©mvi" => "movement",
+		"movementtotal" | "movementcount" | "©mvc" => "movement_total",
+		"involvedpeople" | "involved_people" => "tipl",
+		"musiciancredits" | "musician_credits" => "tmcl",
+
+		// MP4 atoms
+		"©nam" => "title",
+		"sonm" => "title_sort",
+		"©art" => "artist",
+		"soar" => "artist_sort",
+		"aart" => "album_artist",
+		"soaa" => "album_artist_sort",
+		"©alb" => "album",
+		"soal" => "album_sort",
+		"trkn" => "track",
+		"disk" => "disc",
+		"©day" => "date",
+		"©gen" | "gnre" => "genre",
+		"©wrt" => "composer",
+
+		// Freeform atoms written by MusicBrainz Picard
+		"musicbrainz track id" => "musicbrainz_trackid",
+		"musicbrainz album id" => "musicbrainz_albumid",
+		"musicbrainz artist id" => "musicbrainz_artistid",
+		"musicbrainz album artist id" => "musicbrainz_albumartistid",
+		"musicbrainz work id" => "musicbrainz_workid",
+		"musicbrainz album type" => "releasetype",
+		"musicbrainz album release country" => "release_country",
+		_ => return key.to_string(),
+	};
+
+	canonical.to_string()
+}
+
+/// Maps the merged tags of a track into its metadata.
+pub fn map_tags(tags: &TagMap, path: String) -> Result<TempTrackMeta> {
+	let mut meta = TempTrackMeta {
+		path,
+		..Default::default()
+	};
+
+	let mut primary_release_type_used = false;
+	let mut artist_mbz_ids = Vec::<String>::new();
+	let mut release_artist_mbz_ids = Vec::<String>::new();
+
+	for (key, val) in tags.iter() {
+		let val = val.to_string();
+
+		match key {
+			"title" => {
+				let x = meta.get_or_default_track();
+				x.title = val;
+			}
+			"title_sort" => {
+				let x = meta.get_or_default_track();
+				x.title_sort = Some(val);
+			}
+
+			// The ARTISTS field *should* contain all artists associated with the track, so it takes over if present.
+			"artist" if !tags.contains("artists") => {
+				let x = meta.artists.get_or_insert_with(Vec::new);
+				let y = Person::new(val, PersonType::Artist);
+
+				x.push(TempInlinedArtist::from(y))
+			}
+			"artists" => {
+				let x = meta.artists.get_or_insert_with(Vec::new);
+				let y = Person::new(val, PersonType::Artist);
+
+				x.push(TempInlinedArtist::from(y))
+			}
+			"artist_sort" => {
+				let x = meta.get_or_default_track();
+				x.artist_sort = Some(val);
+			}
+
+			"composer" => push_credit(&mut meta, val, PersonType::Composer, None),
+			"producer" => push_credit(&mut meta, val, PersonType::Producer, None),
+			"lyricist" => push_credit(&mut meta, val, PersonType::Lyricist, None),
+			"arranger" => push_credit(&mut meta, val, PersonType::Arranger, None),
+			"conductor" => push_credit(&mut meta, val, PersonType::Conductor, None),
+			"remixer" => push_credit(&mut meta, val, PersonType::Remixer, None),
+			"mixer" => push_credit(&mut meta, val, PersonType::Mixer, None),
+			"engineer" => push_credit(&mut meta, val, PersonType::Engineer, None),
+			"djmixer" => push_credit(&mut meta, val, PersonType::DjMixer, None),
+			// Vorbis comments name the instrument in parentheses, as in `Name (piano)`.
+			"performer" => {
+				let (name, instrument) = split_performer(&val);
+				push_credit(&mut meta, name, PersonType::Performer, instrument);
+			}
+
+			"album" => {
+				let x = meta.get_or_default_release();
+				x.name = val;
+			}
+			"album_sort" => {
+				let x = meta.get_or_default_release();
+				x.name_sort = Some(val);
+			}
+			"album_artist" => {
+				let x = meta.release_artists.get_or_insert_with(Vec::new);
+				let y = Person::new(val, PersonType::Artist);
+
+				x.push(TempInlinedArtist::from(y))
+			}
+			"album_artist_sort" => {
+				let x = meta.get_or_default_release();
+				x.artist_sort = Some(val);
+			}
+
+			"script" => {
+				let x = meta.get_or_default_release();
+				let y = ScriptCode::from_tag(val.as_str()).unwrap();
+				x.script = Some(y);
+			}
+			"release_country" => {
+				let x = meta.get_or_default_release();
+				let y = CountryCode::from_tag(val.as_str()).unwrap();
+				x.country = Some(y);
+			}
+
+			"track" => {
+				if let Some((track_no, track_total_opt)) = get_no_and_maybe_total(val)? {
+					let y = meta.get_or_default_track();
+					y.track_number = Some(track_no);
+
+					if let Some(track_total) = track_total_opt {
+						let z = meta.get_or_default_release();
+						z.total_tracks.get_or_insert(track_total);
+					}
+				}
+			}
+			"disc" => {
+				if let Some((disc_no, disc_total_opt)) = get_no_and_maybe_total(val)? {
+					let y = meta.get_or_default_track();
+					y.disc_number = Some(disc_no);
+
+					if let Some(disc_total) = disc_total_opt {
+						let z = meta.get_or_default_release();
+						z.total_discs.get_or_insert(disc_total);
+					}
+				}
+			}
+
+			"total_tracks" => {
+				let y = val.parse::<u32>()?;
+				let x = meta.get_or_default_release();
+				x.total_tracks = Some(y);
+			}
+			"total_discs" => {
+				let y = val.parse::<u32>()?;
+				let x = meta.get_or_default_release();
+				x.total_discs = Some(y);
+			}
+
+			"original_date" => {
+				if let Some((Some(year), Some(month), day_opt)) = get_val_date(val)? {
+					let y = meta.get_or_default_track();
+					y.original_date = NaiveDate::from_ymd_opt(year, month, day_opt.unwrap_or(1));
+				}
+			}
+			"date" => match get_val_date(val)? {
+				Some((Some(year), Some(month), day_opt)) => {
+					let y = meta.get_or_default_release();
+					y.date = NaiveDate::from_ymd_opt(year, month, day_opt.unwrap_or(1));
+				}
+				Some((Some(year), None, None)) => {
+					let y = meta.get_or_default_release();
+					if y.year.is_none() {
+						y.year = Some(year);
+					}
+				}
+				_ => {}
+			},
+
+			"work" => {
+				let x = meta.get_or_default_work();
+				x.name = val;
+			}
+			"musicbrainz_workid" => {
+				let x = meta.get_or_default_work();
+				x.mbz_id = Some(val);
+			}
+			// Some taggers write the name of the movement in place of its number, which is skipped over.
+			"movement" => {
+				if let Some((no, total)) = get_no_and_maybe_total(val).ok().flatten() {
+					let x = meta
+						.get_or_default_track()
+						.movement
+						.get_or_insert_with(Movement::default);
+					x.number = Some(no);
+					x.total = total.or(x.total);
+				}
+			}
+			"movement_total" => {
+				if let Ok(y) = val.parse::<u32>() {
+					let x = meta
+						.get_or_default_track()
+						.movement
+						.get_or_insert_with(Movement::default);
+					x.total = Some(y);
+				}
+			}
+			"movement_name" => {
+				let x = meta
+					.get_or_default_track()
+					.movement
+					.get_or_insert_with(Movement::default);
+				x.name = Some(val);
+			}
+
+			"label" => {
+				let x = meta.labels.get_or_insert_with(Vec::new);
+				let y = Label::new(val);
+				x.push(y);
+			}
+			"catalog" => {
+				let x = meta.get_or_default_release();
+				x.catalog_number = Some(val);
+			}
+
+			"genre" => {
+				let x = meta.genres.get_or_insert_with(Vec::new);
+				let y = Tag::new(val, TagType::Genre);
+
+				x.push(y);
+			}
+
+			// ID3 lyrics come with their language as in `lyrics-eng`, and give way to the ones read from a sidecar.
+			// Among the rest, synced lyrics take over the plain ones.
+			x if x == "lyrics" || (x.starts_with("lyrics-") && !tags.contains("lyrics")) => {
+				let language = x.strip_prefix("lyrics-").map(|y| y.to_string());
+				let lyrics = parse_lyrics(&val, language);
+				let is_synced = lyrics.lines.iter().any(|y| y.start.is_some());
+
+				let replaces = match &meta.lyrics {
+					Some(existing) => is_synced && existing.lines.iter().all(|y| y.start.is_none()),
+					None => !lyrics.lines.is_empty(),
+				};
+
+				if replaces {
+					meta.lyrics = Some(lyrics);
+				}
+			}
+
+			"musicbrainz_trackid" => {
+				let x = meta.get_or_default_track();
+				x.mbz_id = Some(val);
+			}
+			"musicbrainz_albumid" => {
+				let x = meta.get_or_default_release();
+				x.mbz_id = Some(val);
+			}
+
+			"musicbrainz_artistid" => artist_mbz_ids.push(val),
+			"musicbrainz_albumartistid" => release_artist_mbz_ids.push(val),
+
+			"releasetype" if !primary_release_type_used => {
+				let x = meta.get_or_default_release();
+
+				match ReleaseType::from_tag(val.as_str()) {
+					Ok(y) => {
+						x.type_ = y;
+						primary_release_type_used = true;
+					}
+					Err(_) => {
+						let y = ReleaseTypeSecondary::from_tag(val.as_str()).unwrap(); // Infallible
+						x.type_secondary.get_or_insert_with(Vec::new).push(y);
+					}
+				}
+			}
+			"releasetype" if primary_release_type_used => {
+				let x = meta.get_or_default_release();
+				let y = ReleaseTypeSecondary::from_tag(val.as_str()).unwrap();
+				x.type_secondary.get_or_insert_with(Vec::new).push(y);
+			}
+
+			_ => continue,
+		}
+	}
+
+	// ID3 involvement lists come in `role, name` pairs, where TMCL names an instrument instead of a role.
+	for (role, name) in get_involvement_pairs(tags.get("tipl")) {
+		match PersonType::from_involvement(&role) {
+			Some(type_) => push_credit(&mut meta, name, type_, None),
+			None => push_credit(&mut meta, name, PersonType::Other, Some(role)),
+		}
+	}
+
+	for (instrument, name) in get_involvement_pairs(tags.get("tmcl")) {
+		push_credit(&mut meta, name, PersonType::Performer, Some(instrument));
+	}
+
+	assign_mbz_ids(meta.artists.as_mut(), artist_mbz_ids);
+	assign_mbz_ids(meta.release_artists.as_mut(), release_artist_mbz_ids);
+
+	if let Some(track) = meta.track.as_mut() {
+		track.path = meta.path.clone();
+	}
+
+	// A work id without the name of the work can't be displayed, so it's dropped.
+	meta.work = meta.work.filter(|x| !x.name.trim().is_empty());
+
+	Ok(meta)
+}
+
+#[inline]
+fn push_credit(meta: &mut TempTrackMeta, name: String, type_: PersonType, detail: Option<String>) {
+	let x = meta.credits.get_or_insert_with(Vec::new);
+	let y = Person::new(name, type_);
+
+	x.push(TempCredit::new(y, detail));
+}
+
+/// Splits a performer into their name and the instrument in the trailing parentheses, if any.
+fn split_performer(val: &str) -> (String, Option<String>) {
+	let parts = val
+		.strip_suffix(')')
+		.and_then(|x| x.rsplit_once('('))
+		.map(|(name, instrument)| (name.trim(), instrument.trim()))
+		.filter(|(name, instrument)| !name.is_empty() && !instrument.is_empty());
+
+	match parts {
+		Some((name, instrument)) => (name.to_string(), Some(instrument.to_string())),
+		None => (val.trim().to_string(), None),
+	}
+}
+
+/// Reads the pairs of an ID3 `TIPL` or `TMCL` frame, given as separate values or joined with null characters.
+fn get_involvement_pairs(values: Option<&Vec<String>>) -> Vec<(String, String)> {
+	let parts = values
+		.into_iter()
+		.flatten()
+		.flat_map(|x| x.split('\0'))
+		.map(str::trim)
+		.filter(|x| !x.is_empty())
+		.collect::<Vec<&str>>();
+
+	parts
+		.chunks_exact(2)
+		.map(|x| (x[0].to_string(), x[1].to_string()))
+		.collect()
+}
+
+#[inline]
+fn get_val_date(x: String) -> Result<OptionedDate> {
+	// MP4 files store dates as timestamps, like `2020-01-01T00:00:00Z`.
+	let x = match x.split_once('T') {
+		Some((date, _)) => date.to_string(),
+		None => x,
+	};
+
+	let date: OptionedDate = if matchers::reg::is_ymd(x.as_str()) {
+		let splits = x.split('-').collect::<Vec<&str>>();
+
+		let year = {
+			let y = splits.first().unwrap();
+			y.parse::<i32>()?
+		};
+		let month = {
+			let y = splits.get(1).unwrap();
+			y.parse::<u32>()?
+		};
+		let day = {
+			let y = splits.get(2).unwrap();
+			y.parse::<u32>()?
+		};
+
+		Some((Some(year), Some(month), Some(day)))
+	} else if matchers::reg::is_ym(x.as_str()) {
+		let splits = x.split('-').collect::<Vec<&str>>();
+
+		let year = {
+			let y = splits.first().unwrap();
+			y.parse::<i32>()?
+		};
+		let month = {
+			let y = splits.get(1).unwrap();
+			y.parse::<u32>()?
+		};
+
+		Some((Some(year), Some(month), None))
+	} else if matchers::reg::is_year(x.as_str()) {
+		let year = x.parse::<i32>()?;
+		Some((Some(year), None, None))
+	} else {
+		None
+	};
+
+	Ok(date)
+}
+
+/// Assigns the artist ids in the order they're listed, which is the order of the artists.
+///
+/// Artists joined in a single tag can't be told apart from their ids, so the ids are dropped unless there's one for
+/// every artist.
+fn assign_mbz_ids(artists: Option<&mut Vec<TempInlinedArtist>>, mbz_ids: Vec<String>) {
+	let Some(artists) = artists.filter(|x| x.len() == mbz_ids.len()) else {
+		return;
+	};
+
+	for (artist, mbz_id) in artists.iter_mut().zip(mbz_ids) {
+		artist.person.mbz_id = Some(mbz_id);
+	}
+}
+
+/// Reads into a value and tries to get an int followed by an optional int separated by a forward slash.
+///
+/// Useful for handling edge cases like track_no and track_total included in the same tag.
+/// A total of `0` is treated as missing, which is how MP4 files without a total are read.
+///
+/// ### Example
+/// ```text
+/// "2" -> (2, None)
+/// "1/2" -> (1, None)
+/// ```
+#[inline]
+fn get_no_and_maybe_total(value: String) -> Result<Option<(u32, Option<u32>)>> {
+	let tuple = if matchers::reg::is_no_and_total(value.as_str()) {
+		let splits = value.split('/').collect::<Vec<&str>>();
+		let no_str = splits.first().unwrap();
+		let total_str = splits.last().unwrap();
+
+		let no = no_str.parse::<u32>()?;
+		let total = total_str.parse::<u32>()?;
+
+		Some((no, Some(total).filter(|x| *x > 0)))
+	} else {
+		Some((value.parse::<u32>()?, None))
+	};
+
+	Ok(tuple)
+}
+
+#[cfg(test)]
+mod test {
+	use crate::{
+		database::models::person::PersonType,
+		errors::Result,
+		metadata::{mapping::map_tags, TagMap},
+	};
+
+	#[test]
+	fn test_map_mp4_tags() -> Result<()> {
+		let mut tags = TagMap::default();
+		tags.push("©nam", "Track".to_string());
+		tags.push("aART", "Artist".to_string());
+		tags.push("trkn", "3/0".to_string());
+		tags.push("disk", "1/2".to_string());
+		tags.push("©day", "2020-04-01T00:00:00Z".to_string());
+		tags.push("----:com.apple.iTunes:MusicBrainz Track Id", "id".to_string());
+		tags.push(
+			"----:com.apple.iTunes:MusicBrainz Album Artist Id",
+			"artist-id".to_string(),
+		);
+
+		let meta = map_tags(&tags, "/music/01 Track.m4a".to_string())?;
+		let track = meta.track.unwrap();
+		let release = meta.release.unwrap();
+
+		assert_eq!(track.title, "Track");
+		assert_eq!(track.track_number, Some(3));
+		assert_eq!(track.disc_number, Some(1));
+		assert_eq!(track.mbz_id.as_deref(), Some("id"));
+		assert_eq!(release.total_tracks, None);
+		assert_eq!(release.total_discs, Some(2));
+		assert_eq!(release.date.map(|x| x.to_string()).as_deref(), Some("2020-04-01"));
+		let release_artist = &meta.release_artists.unwrap()[0].person;
+		assert_eq!(release_artist.name, "Artist");
+		assert_eq!(release_artist.mbz_id.as_deref(), Some("artist-id"));
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_map_artist_mbz_ids() -> Result<()> {
+		let mut tags = TagMap::default();
+		tags.push("TITLE", "Track".to_string());
+		tags.push("ARTISTS", "First".to_string());
+		tags.push("ARTISTS", "Second".to_string());
+		tags.push("MUSICBRAINZ_ARTISTID", "first-id".to_string());
+		tags.push("MUSICBRAINZ_ARTISTID", "second-id".to_string());
+		tags.push("ALBUMARTIST", "First & Second".to_string());
+		tags.push("MUSICBRAINZ_ALBUMARTISTID", "first-id".to_string());
+		tags.push("MUSICBRAINZ_ALBUMARTISTID", "second-id".to_string());
+
+		let meta = map_tags(&tags, "/music/01 Track.flac".to_string())?;
+
+		let artist_ids = meta
+			.artists
+			.unwrap()
+			.into_iter()
+			.map(|x| x.person.mbz_id)
+			.collect::<Vec<_>>();
+		assert_eq!(
+			artist_ids,
+			vec![Some("first-id".to_string()), Some("second-id".to_string())]
+		);
+
+		// A joined release artist can't take the id of either artist.
+		let release_artist = &meta.release_artists.unwrap()[0].person;
+		assert_eq!(release_artist.mbz_id, None);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_map_credits() -> Result<()> {
+		let mut tags = TagMap::default();
+		tags.push("TITLE", "Track".to_string());
+		tags.push("COMPOSER", "Composer".to_string());
+		tags.push("LYRICIST", "Lyricist".to_string());
+		tags.push("PERFORMER", "Pianist (piano)".to_string());
+		tags.push("TIPL", "DJ-mix\0Selector\0mastering\0Engineer".to_string());
+		tags.push("TMCL", "violin".to_string());
+		tags.push("TMCL", "Violinist".to_string());
+
+		let meta = map_tags(&tags, "/music/01 Track.flac".to_string())?;
+		let credits = meta
+			.credits
+			.unwrap()
+			.into_iter()
+			.map(|x| (x.person.name, x.person.type_, x.detail))
+			.collect::<Vec<_>>();
+
+		let expected = vec![
+			("Composer".to_string(), PersonType::Composer, None),
+			("Lyricist".to_string(), PersonType::Lyricist, None),
+			("Pianist".to_string(), PersonType::Performer, Some("piano".to_string())),
+			("Selector".to_string(), PersonType::DjMixer, None),
+			("Engineer".to_string(), PersonType::Other, Some("mastering".to_string())),
+			(
+				"Violinist".to_string(),
+				PersonType::Performer,
+				Some("violin".to_string()),
+			),
+		];
+		assert_eq!(credits, expected);
+
+		Ok(())
+	}
+}
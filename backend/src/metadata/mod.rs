@@ -0,0 +1,271 @@
+use std::{borrow::Cow, collections::BTreeMap, path::Path};
+
+use {serde::Serialize, serde_json::Value, tracing::debug};
+
+use crate::{
+	errors::{
+		pre::{non_utf8_path, probe_no_meta},
+		Error, Result,
+	},
+	models::temp::{TempTrackMeta, TempTrackResource},
+};
+
+use self::{
+	cue::find_cue_file,
+	mapping::{canonical_key, map_tags},
+};
+
+pub mod cue;
+pub mod lyrics;
+pub mod mapping;
+pub mod providers;
+
+/// Precedence of the tags embedded in the audio file.
+///
+/// Providers below this only fill in the gaps, while the ones above override the embedded tags.
+pub const EMBEDDED_PRECEDENCE: u8 = 100;
+
+/// Raw tags of a track, keyed by their canonical name.
+///
+/// Keys are stored in a sorted map, so mapping the same tags always yields the same metadata.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct TagMap(BTreeMap<String, Vec<String>>);
+
+impl TagMap {
+	/// Appends a value to a tag, merging aliases of the key through [canonical_key].
+	pub fn push(&mut self, key: &str, value: String) {
+		self.0.entry(canonical_key(key)).or_default().push(value);
+	}
+
+	#[inline]
+	pub fn get(&self, key: &str) -> Option<&Vec<String>> {
+		self.0.get(key)
+	}
+
+	#[inline]
+	pub fn remove(&mut self, key: &str) {
+		self.0.remove(key);
+	}
+
+	#[inline]
+	pub fn retain(&mut self, f: impl Fn(&str) -> bool) {
+		self.0.retain(|key, _| f(key));
+	}
+
+	#[inline]
+	pub fn contains(&self, key: &str) -> bool {
+		self.0.contains_key(key)
+	}
+
+	#[inline]
+	pub fn is_empty(&self) -> bool {
+		self.0.is_empty()
+	}
+
+	/// Replaces the values of every tag present in the other map.
+	pub fn merge(&mut self, other: TagMap) {
+		self.0.extend(other.0);
+	}
+
+	/// Iterates over every value along with its key, in key order.
+	pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+		self.0
+			.iter()
+			.flat_map(|(key, values)| values.iter().map(move |x| (key.as_str(), x.as_str())))
+	}
+
+	/// Reads a JSON object, where each value is either a scalar or an array of scalars.
+	pub fn from_json(value: Value) -> Result<Self> {
+		let Value::Object(object) = value else {
+			return Err(Error::new(
+				"Invalid tags",
+				Cow::Borrowed("Expected a JSON object of tags"),
+			));
+		};
+
+		let mut tags = Self::default();
+		for (key, value) in object {
+			let values = match value {
+				Value::Array(x) => x,
+				x => vec![x],
+			};
+
+			for value in values {
+				match value {
+					Value::String(x) => tags.push(&key, x),
+					Value::Number(x) => tags.push(&key, x.to_string()),
+					Value::Bool(x) => tags.push(&key, x.to_string()),
+					_ => continue,
+				}
+			}
+		}
+
+		Ok(tags)
+	}
+}
+
+/// A source of track tags, such as the embedded tags, sidecar files or online lookups.
+pub trait MetadataProvider: Send + Sync {
+	/// Name of the provider, used to order the providers with the same precedence.
+	fn name(&self) -> &str;
+
+	/// Providers run in an ascending order of precedence, and the tags of a later provider replace the earlier ones.
+	fn precedence(&self) -> u8;
+
+	/// Reads the tags of the track in the given path.
+	///
+	/// `tags` holds the tags merged from the providers of lower precedence.
+	fn provide(&self, path: &Path, tags: &TagMap, resource: &mut TempTrackResource) -> Result<Option<TagMap>>;
+}
+
+/// Merges the tags of a set of providers into the metadata of a track.
+pub struct MetadataPipeline {
+	providers: Vec<Box<dyn MetadataProvider>>,
+}
+
+impl MetadataPipeline {
+	pub fn new(mut providers: Vec<Box<dyn MetadataProvider>>) -> Self {
+		providers.sort_by(|a, b| a.precedence().cmp(&b.precedence()).then_with(|| a.name().cmp(b.name())));
+
+		Self { providers }
+	}
+
+	/// Reads every track in the given path, splitting the file by its CUE sheet if it has one.
+	///
+	/// Tags of the sheet override the tags of the file, and the resources are only attached to the first track.
+	pub fn read_all(&self, path: &Path) -> Result<Vec<(TempTrackMeta, TempTrackResource)>> {
+		let path_str = path.to_str().ok_or_else(|| non_utf8_path(path))?.to_string();
+		let (mut tags, resource) = self.collect(path)?;
+
+		let Some(cue_file) = find_cue_file(path, &tags)? else {
+			return Ok(vec![(map_tags(&tags, path_str)?, resource)]);
+		};
+
+		// The file level title, ids and lyrics describe the whole image rather than a single track.
+		tags.remove("title");
+		tags.remove("musicbrainz_trackid");
+		tags.retain(|key| key != "lyrics" && !key.starts_with("lyrics-"));
+
+		let mut resource = Some(resource);
+		let mut tracks = Vec::with_capacity(cue_file.tracks.len());
+
+		for cue_track in cue_file.tracks {
+			let mut track_tags = tags.clone();
+			if cue_track.tags.contains("artist") {
+				track_tags.remove("artists");
+			}
+
+			track_tags.merge(cue_track.tags);
+
+			let mut meta = map_tags(&track_tags, path_str.clone())?;
+			meta.get_or_default_track().segment = Some(cue_track.segment);
+
+			tracks.push((meta, resource.take().unwrap_or_default()));
+		}
+
+		Ok(tracks)
+	}
+
+	fn collect(&self, path: &Path) -> Result<(TagMap, TempTrackResource)> {
+		let mut tags = TagMap::default();
+		let mut resource = TempTrackResource::default();
+
+		for provider in &self.providers {
+			if let Some(provided) = provider.provide(path, &tags, &mut resource)? {
+				debug!("Provider '{}' contributed {} tags", provider.name(), provided.0.len());
+				tags.merge(provided);
+			}
+		}
+
+		if tags.is_empty() {
+			return Err(probe_no_meta());
+		}
+
+		Ok((tags, resource))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::path::Path;
+
+	use crate::{
+		errors::Result,
+		metadata::{MetadataPipeline, MetadataProvider, TagMap},
+		models::temp::TempTrackResource,
+	};
+
+	struct StaticProvider(&'static str, u8, &'static [(&'static str, &'static str)]);
+
+	impl MetadataProvider for StaticProvider {
+		fn name(&self) -> &str {
+			self.0
+		}
+
+		fn precedence(&self) -> u8 {
+			self.1
+		}
+
+		fn provide(&self, _: &Path, _: &TagMap, _: &mut TempTrackResource) -> Result<Option<TagMap>> {
+			let mut tags = TagMap::default();
+			self.2.iter().for_each(|(k, v)| tags.push(k, v.to_string()));
+
+			Ok(Some(tags))
+		}
+	}
+
+	#[test]
+	fn test_precedence() -> Result<()> {
+		let pipeline = MetadataPipeline::new(vec![
+			Box::new(StaticProvider("sidecar", 200, &[("TITLE", "Sidecar Title")])),
+			Box::new(StaticProvider(
+				"embedded",
+				100,
+				&[
+					("title", "Embedded Title"),
+					("albumartist", "Artist"),
+					("album", "Release"),
+				],
+			)),
+			Box::new(StaticProvider(
+				"folder",
+				10,
+				&[("album", "Folder Release"), ("track", "3")],
+			)),
+		]);
+
+		let (meta, _) = pipeline.read_all(Path::new("/music/track.flac"))?.remove(0);
+		let track = meta.track.unwrap();
+		let release = meta.release.unwrap();
+
+		assert_eq!(track.title, "Sidecar Title");
+		assert_eq!(track.track_number, Some(3));
+		assert_eq!(release.name, "Release");
+		assert_eq!(meta.release_artists.unwrap().len(), 1);
+
+		Ok(())
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn test_non_utf8_path() {
+		use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+		let pipeline = MetadataPipeline::new(vec![Box::new(StaticProvider("embedded", 100, &[("title", "Title")]))]);
+		let path = Path::new(OsStr::from_bytes(b"/music/\xff.flac"));
+
+		assert!(pipeline.read_all(path).is_err());
+	}
+
+	#[test]
+	fn test_from_json() -> Result<()> {
+		let value = serde_json::json!({ "Artists": ["A", "B"], "TrackNumber": 2, "comment": null });
+		let tags = TagMap::from_json(value)?;
+
+		let pairs = tags.iter().collect::<Vec<(&str, &str)>>();
+		assert_eq!(pairs, vec![("artists", "A"), ("artists", "B"), ("track", "2")]);
+
+		Ok(())
+	}
+}
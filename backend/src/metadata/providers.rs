@@ -0,0 +1,316 @@
+use std::{
+	path::{Component, Path, PathBuf},
+	sync::Arc,
+};
+
+use {
+	once_cell::sync::Lazy,
+	regex::Regex,
+	serde_json::{json, Value},
+	tokio::sync::Mutex as AsyncMutex,
+	tracing::error,
+};
+
+use crate::{
+	errors::{Error, Result},
+	ffmpeg::meta::read_track_meta,
+	metadata::{MetadataProvider, TagMap, EMBEDDED_PRECEDENCE},
+	models::temp::TempTrackResource,
+	plugins::PluginHost,
+};
+
+/// Reads the tags and pictures embedded in the audio file through ffmpeg.
+pub struct EmbeddedTagProvider;
+
+impl MetadataProvider for EmbeddedTagProvider {
+	fn name(&self) -> &str {
+		"embedded"
+	}
+
+	fn precedence(&self) -> u8 {
+		EMBEDDED_PRECEDENCE
+	}
+
+	fn provide(&self, path: &Path, _: &TagMap, resource: &mut TempTrackResource) -> Result<Option<TagMap>> {
+		let (tags, embedded) = read_track_meta(path)?;
+
+		if let Some(covers) = embedded.release_covers {
+			resource.release_covers.get_or_insert_with(Vec::new).extend(covers);
+		}
+
+		Ok(Some(tags).filter(|x| !x.is_empty()))
+	}
+}
+
+/// Reads a JSON file sharing the name of the audio file, which overrides the embedded tags.
+///
+/// ### Example
+/// ```text
+/// "01 Track.flac" -> "01 Track.json"
+/// ```
+pub struct SidecarProvider;
+
+impl MetadataProvider for SidecarProvider {
+	fn name(&self) -> &str {
+		"sidecar"
+	}
+
+	fn precedence(&self) -> u8 {
+		200
+	}
+
+	fn provide(&self, path: &Path, _: &TagMap, _: &mut TempTrackResource) -> Result<Option<TagMap>> {
+		let sidecar_path = path.with_extension("json");
+		if !sidecar_path.is_file() {
+			return Ok(None);
+		}
+
+		let contents = std::fs::read_to_string(&sidecar_path)
+			.map_err(|e| Error::from(e).append_message("Failed to read the sidecar file"))?;
+		let value = serde_json::from_str::<Value>(&contents)?;
+
+		Ok(Some(TagMap::from_json(value)?))
+	}
+}
+
+/// Reads a `.lrc` file sharing the name of the audio file into the `lyrics` tag, overriding the embedded lyrics.
+pub struct LyricsSidecarProvider;
+
+impl MetadataProvider for LyricsSidecarProvider {
+	fn name(&self) -> &str {
+		"lrc"
+	}
+
+	fn precedence(&self) -> u8 {
+		200
+	}
+
+	fn provide(&self, path: &Path, _: &TagMap, _: &mut TempTrackResource) -> Result<Option<TagMap>> {
+		let lrc_path = path.with_extension("lrc");
+		if !lrc_path.is_file() {
+			return Ok(None);
+		}
+
+		let bytes =
+			std::fs::read(&lrc_path).map_err(|e| Error::from(e).append_message("Failed to read the lyrics file"))?;
+
+		let mut tags = TagMap::default();
+		tags.push("lyrics", String::from_utf8_lossy(&bytes).to_string());
+
+		Ok(Some(tags))
+	}
+}
+
+/// Guesses the tags from the `artist/release/track` layout, only filling in the tags missing from other sources.
+///
+/// Recognizes `01 Title`, `01 - Title` and `01. Title` file names, `Release (2020)` and `[2020] Release` release
+/// directories, and `CD1` or `Disc 1` directories placed inside a release directory.
+///
+/// Paths are read relative to the scan location they're in, so the release artist is never taken from the location
+/// itself. Files that didn't get any tags from the other providers are left as is, so they're still reported as
+/// missing their metadata.
+pub struct FolderNameProvider {
+	pub scan_locations: Vec<PathBuf>,
+}
+
+impl MetadataProvider for FolderNameProvider {
+	fn name(&self) -> &str {
+		"folder_name"
+	}
+
+	/// Runs after every other provider, as it has to know which tags are missing.
+	fn precedence(&self) -> u8 {
+		u8::MAX
+	}
+
+	fn provide(&self, path: &Path, tags: &TagMap, _: &mut TempTrackResource) -> Result<Option<TagMap>> {
+		if tags.is_empty() {
+			return Ok(None);
+		}
+
+		let Some(relative) = self.scan_locations.iter().find_map(|x| path.strip_prefix(x).ok()) else {
+			return Ok(None);
+		};
+
+		let mut guessed = parse_folder_names(relative);
+		guessed.retain(|key| !tags.contains(key));
+
+		Ok(Some(guessed).filter(|x| !x.is_empty()))
+	}
+}
+
+/// Reads the tags from a path relative to its scan location, laid out as `[artist/]release/[disc/]track`.
+///
+/// Returns no tags for other layouts, like files placed directly in the scan location or nested any deeper.
+fn parse_folder_names(relative: &Path) -> TagMap {
+	static TRACK: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(\d{1,3})(?:\s*[-.]\s*|\s+)(.+)$").unwrap());
+	static DISC: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)^(?:cd|disc)\s*(\d{1,2})$").unwrap());
+	static RELEASE_SUFFIXED: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(.+?)\s*[(\[](\d{4})[)\]]$").unwrap());
+	static RELEASE_PREFIXED: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[(\[](\d{4})[)\]]\s*(.+)$").unwrap());
+
+	let mut tags = TagMap::default();
+
+	let Some(mut dirs) = relative
+		.parent()
+		.unwrap_or(Path::new(""))
+		.components()
+		.map(|x| match x {
+			Component::Normal(y) => y.to_str(),
+			_ => None,
+		})
+		.collect::<Option<Vec<&str>>>()
+	else {
+		return tags;
+	};
+
+	let disc = dirs
+		.last()
+		.filter(|_| dirs.len() > 1)
+		.and_then(|x| DISC.captures(x))
+		.map(|x| x[1].to_string());
+	if disc.is_some() {
+		dirs.pop();
+	}
+
+	let (artist, release) = match dirs.as_slice() {
+		[release] => (None, *release),
+		[artist, release] => (Some(*artist), *release),
+		_ => return tags,
+	};
+
+	if let Some(stem) = relative.file_stem().and_then(|x| x.to_str()) {
+		if let Some(captures) = TRACK.captures(stem) {
+			tags.push("track", captures[1].parse::<u32>().unwrap().to_string());
+			tags.push("title", captures[2].to_string());
+		} else {
+			tags.push("title", stem.to_string());
+		}
+	}
+
+	if let Some(disc) = disc {
+		tags.push("disc", disc);
+	}
+
+	if let Some(captures) = RELEASE_SUFFIXED.captures(release) {
+		tags.push("album", captures[1].to_string());
+		tags.push("date", captures[2].to_string());
+	} else if let Some(captures) = RELEASE_PREFIXED.captures(release) {
+		tags.push("date", captures[1].to_string());
+		tags.push("album", captures[2].to_string());
+	} else {
+		tags.push("album", release.to_string());
+	}
+
+	if let Some(artist) = artist {
+		tags.push("album_artist", artist.to_string());
+	}
+
+	tags
+}
+
+/// Asks a plugin with the `metadata_provider` capability for tags, overriding the embedded tags.
+///
+/// The plugin receives `{ "path": string, "tags": object }` and returns an object of tags, or nothing.
+/// Plugin failures are logged and otherwise ignored, so a broken plugin never stops a scan.
+pub struct PluginProvider {
+	pub plugin_id: String,
+	pub host: Arc<AsyncMutex<Option<PluginHost>>>,
+}
+
+impl MetadataProvider for PluginProvider {
+	fn name(&self) -> &str {
+		&self.plugin_id
+	}
+
+	fn precedence(&self) -> u8 {
+		150
+	}
+
+	fn provide(&self, path: &Path, tags: &TagMap, _: &mut TempTrackResource) -> Result<Option<TagMap>> {
+		let input = json!({ "path": path, "tags": tags });
+
+		let output = {
+			// Providers run on the probing thread, outside of the async runtime.
+			let mut guard = self.host.blocking_lock();
+			let Some(host) = guard.as_mut() else {
+				return Ok(None);
+			};
+
+			host.provide_metadata(&self.plugin_id, &input)
+		};
+
+		match output.and_then(|x| x.map(TagMap::from_json).transpose()) {
+			Ok(x) => Ok(x),
+			Err(e) => {
+				error!(
+					"Plugin '{}' failed to provide metadata for {path:?}: {e:#?}",
+					self.plugin_id
+				);
+				Ok(None)
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::path::{Path, PathBuf};
+
+	use super::{parse_folder_names, FolderNameProvider};
+
+	use crate::{
+		errors::Result,
+		metadata::{MetadataProvider, TagMap},
+		models::temp::TempTrackResource,
+	};
+
+	#[test]
+	fn test_parse_folder_names() {
+		let tags = parse_folder_names(Path::new("Artist/Release (2020)/CD2/03 - Title.flac"));
+		let pairs = tags.iter().collect::<Vec<(&str, &str)>>();
+
+		assert_eq!(
+			pairs,
+			vec![
+				("album", "Release"),
+				("album_artist", "Artist"),
+				("date", "2020"),
+				("disc", "2"),
+				("title", "Title"),
+				("track", "3"),
+			]
+		);
+
+		let tags = parse_folder_names(Path::new("[2020] Release/Title.flac"));
+		let pairs = tags.iter().collect::<Vec<(&str, &str)>>();
+
+		assert!(pairs.contains(&("album", "Release")));
+		assert!(pairs.contains(&("title", "Title")));
+		assert!(!tags.contains("album_artist"));
+
+		assert!(parse_folder_names(Path::new("Title.flac")).is_empty());
+		assert!(parse_folder_names(Path::new("Genre/Artist/Release/Title.flac")).is_empty());
+	}
+
+	#[test]
+	fn test_provide() -> Result<()> {
+		let provider = FolderNameProvider {
+			scan_locations: vec![PathBuf::from("/music/Artist")],
+		};
+		let path = Path::new("/music/Artist/Release/01 Title.flac");
+		let mut resource = TempTrackResource::default();
+
+		// Untagged files are left for the pipeline to report.
+		assert!(provider.provide(path, &TagMap::default(), &mut resource)?.is_none());
+
+		let mut tags = TagMap::default();
+		tags.push("title", "Tagged".to_string());
+
+		let guessed = provider.provide(path, &tags, &mut resource)?.unwrap();
+		assert!(!guessed.contains("title"));
+		assert!(guessed.contains("album"));
+		assert!(!guessed.contains("album_artist"));
+
+		Ok(())
+	}
+}
@@ -1,11 +1,310 @@
+use std::path::Path;
+
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+use crate::{
+	constants::DEFAULT_AUDIO_EXTENSIONS,
+	errors::{pre::invalid_configuration, Error, Result},
+};
+
+/// User editable settings, stored in `config_directory/settings.toml`.
+///
+/// Every section falls back to its defaults when missing, so a partially written file is still valid.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Configuration {
+	pub appearance: AppearanceConfiguration,
+	pub scanner: ScannerConfiguration,
+	pub playback: PlaybackConfiguration,
+	pub server: ServerConfiguration,
+	pub scrobbling: ScrobblingConfiguration,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppearanceConfiguration {
 	pub theme: Option<Theme>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Theme {
+	/// File stem of the selected theme in `config_directory/themes`.
 	pub name: String,
 }
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScannerConfiguration {
+	/// Whether to read `artist.jpg` like images placed next to release directories.
+	pub artist_images: bool,
+	/// Whether to guess the missing tags of a track from the `artist/release/track` layout of its scan location.
+	pub folder_names: bool,
+	/// Extensions of the files read as audio, compared case-insensitively.
+	pub audio_extensions: Vec<String>,
+	/// Whether to probe the contents of files with other extensions, picking up the ones ffmpeg reads as audio.
+	pub sniff_unknown: bool,
+}
+
+impl Default for ScannerConfiguration {
+	fn default() -> Self {
+		Self {
+			artist_images: true,
+			folder_names: false,
+			audio_extensions: DEFAULT_AUDIO_EXTENSIONS.iter().map(|x| x.to_string()).collect(),
+			sniff_unknown: true,
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlaybackConfiguration {
+	/// Linear volume in the range of `0.0..=1.0`.
+	pub volume: f32,
+	pub gapless: bool,
+	pub replay_gain: ReplayGainMode,
+}
+
+impl Default for PlaybackConfiguration {
+	fn default() -> Self {
+		Self {
+			volume: 1.0,
+			gapless: true,
+			replay_gain: ReplayGainMode::default(),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplayGainMode {
+	#[default]
+	Off,
+	Track,
+	Release,
+}
+
+/// Embedded server that exposes the library through the Subsonic API.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerConfiguration {
+	pub enabled: bool,
+	pub port: u16,
+	pub username: String,
+	/// Kept in plain text, as Subsonic clients authenticate with a salted hash of it.
+	pub password: String,
+	/// Format used for transcodes when the client doesn't ask for one.
+	pub transcode_format: TranscodeFormat,
+	/// Bitrate in kbps used for transcodes when the client doesn't limit it.
+	pub transcode_bitrate: u32,
+	/// Path to the `ffmpeg` executable used for transcoding, resolved through `PATH` by default.
+	pub ffmpeg_path: String,
+}
+
+impl Default for ServerConfiguration {
+	fn default() -> Self {
+		Self {
+			enabled: false,
+			port: 4533,
+			username: "melody".to_string(),
+			password: String::new(),
+			transcode_format: TranscodeFormat::default(),
+			transcode_bitrate: 192,
+			ffmpeg_path: "ffmpeg".to_string(),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscodeFormat {
+	#[default]
+	Mp3,
+	Opus,
+	Aac,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScrobblingConfiguration {
+	pub listenbrainz: ListenBrainzConfiguration,
+	pub lastfm: LastFmConfiguration,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ListenBrainzConfiguration {
+	pub enabled: bool,
+	/// User token from the settings page of ListenBrainz.
+	pub token: String,
+	/// Root of the API, replaceable with a compatible server like Maloja.
+	pub endpoint: String,
+}
+
+impl Default for ListenBrainzConfiguration {
+	fn default() -> Self {
+		Self {
+			enabled: false,
+			token: String::new(),
+			endpoint: "https://api.listenbrainz.org".to_string(),
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LastFmConfiguration {
+	pub enabled: bool,
+	pub api_key: String,
+	pub api_secret: String,
+	/// Obtained through the `authenticate_lastfm` command.
+	pub session_key: Option<String>,
+	pub endpoint: String,
+}
+
+impl Default for LastFmConfiguration {
+	fn default() -> Self {
+		Self {
+			enabled: false,
+			api_key: String::new(),
+			api_secret: String::new(),
+			session_key: None,
+			endpoint: "https://ws.audioscrobbler.com/2.0/".to_string(),
+		}
+	}
+}
+
+impl Configuration {
+	/// Reads the settings file in the given path, writing the defaults if it doesn't exist.
+	pub fn load(path: &Path) -> Result<Self> {
+		if !path.exists() {
+			let configuration = Self::default();
+			configuration.save(path)?;
+
+			return Ok(configuration);
+		}
+
+		let contents = std::fs::read_to_string(path)
+			.map_err(|e| Error::from(e).append_message("Failed to read the settings file"))?;
+
+		Self::parse(&contents)
+	}
+
+	/// Parses and validates the contents of a settings file.
+	pub fn parse(contents: &str) -> Result<Self> {
+		let configuration = toml::from_str::<Self>(contents)?;
+		configuration.validate()?;
+
+		Ok(configuration)
+	}
+
+	/// Validates and writes the settings to the given path.
+	///
+	/// The settings are written next to the file and then renamed over it, so the file is never seen half written.
+	pub fn save(&self, path: &Path) -> Result<()> {
+		self.validate()?;
+
+		let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+		temp_name.push(".tmp");
+		let temp_path = path.with_file_name(temp_name);
+
+		let contents = toml::to_string_pretty(self)?;
+		std::fs::write(&temp_path, contents)
+			.and_then(|_| std::fs::rename(&temp_path, path))
+			.map_err(|e| Error::from(e).append_message("Failed to write the settings file"))?;
+
+		Ok(())
+	}
+
+	/// Checks for values that are well typed, but nonsensical.
+	pub fn validate(&self) -> Result<()> {
+		if !(0.0..=1.0).contains(&self.playback.volume) {
+			return Err(invalid_configuration("playback.volume must be between 0.0 and 1.0"));
+		}
+
+		if self.scanner.audio_extensions.is_empty() {
+			return Err(invalid_configuration("scanner.audio_extensions must not be empty"));
+		}
+
+		if self.server.enabled && (self.server.username.is_empty() || self.server.password.is_empty()) {
+			return Err(invalid_configuration(
+				"server.username and server.password must be set to enable the server",
+			));
+		}
+
+		if !(32..=320).contains(&self.server.transcode_bitrate) {
+			return Err(invalid_configuration(
+				"server.transcode_bitrate must be between 32 and 320",
+			));
+		}
+
+		let listenbrainz = &self.scrobbling.listenbrainz;
+		if listenbrainz.enabled && listenbrainz.token.is_empty() {
+			return Err(invalid_configuration(
+				"scrobbling.listenbrainz.token must be set to enable ListenBrainz",
+			));
+		}
+
+		let lastfm = &self.scrobbling.lastfm;
+		if lastfm.enabled && (lastfm.api_key.is_empty() || lastfm.api_secret.is_empty()) {
+			return Err(invalid_configuration(
+				"scrobbling.lastfm.api_key and scrobbling.lastfm.api_secret must be set to enable Last.fm",
+			));
+		}
+
+		if let Some(theme) = &self.appearance.theme {
+			if theme.name.trim().is_empty() {
+				return Err(invalid_configuration("appearance.theme.name must not be empty"));
+			}
+		}
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::{Configuration, ReplayGainMode};
+
+	#[test]
+	fn test_parse() {
+		let configuration = Configuration::parse("").unwrap();
+		assert_eq!(configuration, Configuration::default());
+
+		let configuration = Configuration::parse("[playback]\nreplay_gain = \"track\"").unwrap();
+		assert_eq!(configuration.playback.replay_gain, ReplayGainMode::Track);
+		assert_eq!(configuration.playback.volume, 1.0);
+		assert!(configuration.scanner.artist_images);
+
+		assert!(Configuration::parse("[playback]\nvolume = 2.0").is_err());
+		assert!(Configuration::parse("[playback]\nvolume = \"loud\"").is_err());
+		assert!(Configuration::parse("[server]\nenabled = true").is_err());
+		assert!(Configuration::parse("[server]\nenabled = true\npassword = \"secret\"").is_ok());
+		assert!(Configuration::parse("[scrobbling.listenbrainz]\nenabled = true").is_err());
+		assert!(Configuration::parse("[scanner]\naudio_extensions = []").is_err());
+	}
+
+	#[test]
+	fn test_roundtrip() {
+		let configuration = Configuration::default();
+		let contents = toml::to_string_pretty(&configuration).unwrap();
+
+		assert_eq!(Configuration::parse(&contents).unwrap(), configuration);
+	}
+
+	#[test]
+	fn test_save() {
+		let root = std::env::temp_dir().join(format!("melody-configuration-{}", ulid::Ulid::new()));
+		std::fs::create_dir_all(&root).unwrap();
+		let path = root.join("settings.toml");
+
+		let mut configuration = Configuration::default();
+		configuration.playback.replay_gain = ReplayGainMode::Track;
+		configuration.save(&path).unwrap();
+
+		assert_eq!(Configuration::load(&path).unwrap(), configuration);
+		assert!(!root.join("settings.toml.tmp").exists());
+
+		std::fs::remove_dir_all(root).unwrap();
+	}
+}
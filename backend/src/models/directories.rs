@@ -8,28 +8,52 @@ use crate::{
 };
 
 pub struct Directories {
+	pub config_dir: PathBuf,
+	pub themes_dir: PathBuf,
 	pub database_dir: PathBuf,
 	pub cover_dir: PathBuf,
+	pub plugins_dir: PathBuf,
 }
 
 impl Directories {
 	const COVER_FOLDER_NAME: &'static str = "covers";
 	const THUMB_FOLDER_NAME: &'static str = "thumbs";
+	const PLUGINS_FOLDER_NAME: &'static str = "plugins";
+	const THEMES_FOLDER_NAME: &'static str = "themes";
+	const SETTINGS_FILE_NAME: &'static str = "settings.toml";
 
 	#[inline]
-	fn new(database_dir: PathBuf, cover_dir: PathBuf) -> Self {
+	fn new(
+		config_dir: PathBuf,
+		themes_dir: PathBuf,
+		database_dir: PathBuf,
+		cover_dir: PathBuf,
+		plugins_dir: PathBuf,
+	) -> Self {
 		Self {
+			config_dir,
+			themes_dir,
 			database_dir,
 			cover_dir,
+			plugins_dir,
 		}
 	}
 
 	pub async fn initialize(path_resolver: PathResolver) -> Result<Self> {
+		let config_dir = path_resolver.app_config_dir().expect("App config dir was not found");
 		let data_dir = path_resolver.app_data_dir().expect("App data dir was not found");
 
 		tokio::task::spawn_blocking::<_, Result<Directories>>(move || {
+			let themes_dir = config_dir.join(Self::THEMES_FOLDER_NAME);
 			let database_dir = data_dir.join(DB_MAIN_NAME);
 			let cover_dir = data_dir.join(Self::COVER_FOLDER_NAME);
+			let plugins_dir = data_dir.join(Self::PLUGINS_FOLDER_NAME);
+
+			std::fs::create_dir_all(&config_dir)
+				.map_err(|e| Error::from(e).append_message("Failed to create 'config' directory"))?;
+
+			std::fs::create_dir_all(&themes_dir)
+				.map_err(|e| Error::from(e).append_message("Failed to create 'themes' directory"))?;
 
 			std::fs::create_dir_all(&data_dir)
 				.map_err(|e| Error::from(e).append_message("Failed to create 'data' directory"))?;
@@ -40,10 +64,24 @@ impl Directories {
 			std::fs::create_dir_all(cover_dir.join(Self::THUMB_FOLDER_NAME))
 				.map_err(|e| Error::from(e).append_message("Failed to create 'covers/thumbs' directory"))?;
 
-			Ok(Directories::new(database_dir, cover_dir))
+			std::fs::create_dir_all(&plugins_dir)
+				.map_err(|e| Error::from(e).append_message("Failed to create 'plugins' directory"))?;
+
+			Ok(Directories::new(
+				config_dir,
+				themes_dir,
+				database_dir,
+				cover_dir,
+				plugins_dir,
+			))
 		})
 		.await?
 	}
+
+	#[inline]
+	pub fn settings_path(&self) -> PathBuf {
+		self.config_dir.join(Self::SETTINGS_FILE_NAME)
+	}
 }
 
 pub fn get_cover_path(cover_dir: &Path, hash: &Hash, extension: &str, is_thumb: bool) -> PathBuf {
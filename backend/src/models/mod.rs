@@ -1,5 +1,7 @@
 pub mod configuration;
 pub mod directories;
+pub mod playback;
 pub mod state;
 pub mod tauri;
 pub mod temp;
+pub mod theme;
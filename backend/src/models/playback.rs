@@ -0,0 +1,169 @@
+use std::path::{Path, PathBuf};
+
+use {
+	bonsaidb::{core::schema::SerializedCollection, local::AsyncDatabase},
+	serde::{Deserialize, Serialize},
+};
+
+use crate::{
+	database::models::{cover::Cover, person::Person, release::Release, track::Track, InlinedArtist},
+	errors::{pre::database_entry_not_found, Result},
+	models::tauri::cover::DisplayCover,
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaybackStatus {
+	Playing,
+	Paused,
+	#[default]
+	Stopped,
+}
+
+/// State of the player as reported by the frontend, which owns the audio output.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlaybackReport {
+	pub track_id: Option<u64>,
+	pub status: PlaybackStatus,
+	/// Position in the track in milliseconds.
+	pub position: u64,
+	pub volume: f64,
+	pub can_go_next: bool,
+	pub can_go_previous: bool,
+	/// Whether the position jumped since the last report, instead of advancing on its own.
+	#[serde(default)]
+	pub seeked: bool,
+	/// Length of the track in milliseconds, as decoded by the player.
+	#[serde(default)]
+	pub duration: Option<u64>,
+}
+
+/// Resolved state of the player, mirrored to the system media controls.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Playback {
+	pub status: PlaybackStatus,
+	pub track: Option<PlaybackTrack>,
+	pub position: u64,
+	pub volume: f64,
+	pub can_go_next: bool,
+	pub can_go_previous: bool,
+}
+
+/// Display metadata of the track being played.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlaybackTrack {
+	pub id: u64,
+	pub title: String,
+	pub artists: Vec<String>,
+	pub album: Option<String>,
+	pub album_artists: Vec<String>,
+	pub track_number: Option<u32>,
+	pub disc_number: Option<u32>,
+	/// Length in milliseconds, only known for the tracks split from a CUE sheet.
+	pub length: Option<u64>,
+	pub cover_path: Option<PathBuf>,
+	pub path: String,
+	pub mbz_id: Option<String>,
+	pub release_mbz_id: Option<String>,
+}
+
+/// Requests made through the system media controls, carried out by the frontend.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub enum PlayerControl {
+	Play,
+	Pause,
+	PlayPause,
+	Stop,
+	Next,
+	Previous,
+	/// Moves the position by the given milliseconds.
+	Seek {
+		offset: i64,
+	},
+	SetPosition {
+		track_id: u64,
+		position: u64,
+	},
+	SetVolume {
+		volume: f64,
+	},
+	/// Brings the window to the front, handled by the backend.
+	Raise,
+}
+
+impl Playback {
+	#[inline]
+	pub fn track_id(&self) -> Option<u64> {
+		self.track.as_ref().map(|x| x.id)
+	}
+}
+
+impl PlaybackTrack {
+	/// Builds the metadata of a track from its release, artists and the first available cover.
+	pub async fn resolve(database: &AsyncDatabase, cover_dir: &Path, track_id: u64) -> Result<Self> {
+		let track = Track::get_async(&track_id, database)
+			.await?
+			.ok_or_else(|| database_entry_not_found("tracks", track_id))?
+			.contents;
+
+		let release = Release::get_async(&track.release_id, database)
+			.await?
+			.map(|x| x.contents);
+
+		let artists = get_artist_names(database, &track.artists).await?;
+		let album_artists = match &release {
+			Some(x) => get_artist_names(database, &x.artists).await?,
+			None => Vec::new(),
+		};
+
+		let cover_id = track
+			.cover_ids
+			.iter()
+			.chain(release.as_ref().and_then(|x| x.cover_ids.as_ref()))
+			.flatten()
+			.next();
+
+		let cover_path = match cover_id {
+			Some(id) => Cover::get_async(id, database)
+				.await?
+				.map(|x| DisplayCover::from_cover(x.contents, cover_dir).path),
+			None => None,
+		};
+
+		let length = track.segment.and_then(|x| Some(x.end? - x.start));
+
+		let release_mbz_id = release.as_ref().and_then(|x| x.mbz_id.clone());
+
+		Ok(Self {
+			id: track_id,
+			title: track.title,
+			artists,
+			album: release.map(|x| x.name),
+			album_artists,
+			track_number: track.track_number,
+			disc_number: track.disc_number,
+			length,
+			cover_path,
+			path: track.path,
+			mbz_id: track.mbz_id,
+			release_mbz_id,
+		})
+	}
+}
+
+async fn get_artist_names(database: &AsyncDatabase, artists: &[InlinedArtist]) -> Result<Vec<String>> {
+	let mut names = Vec::with_capacity(artists.len());
+
+	for artist in artists {
+		let name = match &artist.credited_as {
+			Some(x) => Some(x.clone()),
+			None => Person::get_async(&artist.id, database).await?.map(|x| x.contents.name),
+		};
+
+		names.extend(name);
+	}
+
+	Ok(names)
+}
@@ -1,18 +1,46 @@
 use std::{
-	path::Path,
+	path::{Path, PathBuf},
 	sync::{Arc, Mutex as BlockingMutex, MutexGuard as BlockingMutexGuard},
 };
 
 use {
+	bonsaidb::local::AsyncDatabase as BonsaiDatabase,
+	notify::RecommendedWatcher,
 	tauri::PathResolver,
-	tokio::sync::{Mutex as AsyncMutex, MutexGuard as AsyncMutexGuard},
-	tracing::debug,
+	tokio::{
+		runtime::Handle,
+		sync::{Mutex as AsyncMutex, MutexGuard as AsyncMutexGuard, OwnedMutexGuard},
+	},
+	tracing::{debug, error, info},
 };
 
 use crate::{
 	database::Database,
-	errors::Result,
-	models::{configuration::Configuration, directories::Directories},
+	errors::{Error, Result},
+	models::{
+		configuration::{Configuration, ServerConfiguration},
+		directories::Directories,
+		playback::Playback,
+		tauri::{
+			configuration::{SettingsEventManager, SettingsEventPayload, SettingsEventType},
+			scrobble::{ScrobbleEventManager, ScrobbleEventPayload, ScrobbleEventType},
+			theme::{ThemeEventManager, ThemeEventPayload, ThemeEventType},
+		},
+		theme::Theme,
+	},
+	plugins::{runtime::LibraryAccess, PluginEvent, PluginHost},
+	scrobbler::{Scrobbler, StoppedScrobbler},
+	subsonic::SubsonicServer,
+	utils::fs::watch_dir,
+};
+
+#[cfg(target_os = "linux")]
+use crate::{
+	models::{
+		playback::PlayerControl,
+		tauri::playback::{PlayerEventManager, PlayerEventPayload, PlayerEventType},
+	},
+	mpris::MprisServer,
 };
 
 #[derive(Default)]
@@ -23,8 +51,36 @@ pub struct AppState {
 #[derive(Default)]
 pub struct DirectoryState(pub BlockingMutex<Option<Directories>>);
 
+/// Holds the loaded settings, along with the watcher that keeps them in sync with the settings file.
+#[derive(Default)]
+pub struct ConfigurationState(
+	pub Arc<BlockingMutex<Option<Configuration>>>,
+	BlockingMutex<Option<RecommendedWatcher>>,
+);
+
+/// Holds the watcher that reports edits made to the theme files.
+#[derive(Default)]
+pub struct ThemeState(BlockingMutex<Option<RecommendedWatcher>>);
+
+/// Holds the loaded plugins. Calls into them block, so they're made from blocking threads with
+/// [AsyncMutex::blocking_lock], never from the async runtime.
 #[derive(Default)]
-pub struct ConfigurationState(pub Arc<BlockingMutex<Option<Configuration>>>);
+pub struct PluginState(pub Arc<AsyncMutex<Option<PluginHost>>>);
+
+/// Holds the playback reported by the frontend, along with the MPRIS server mirroring it on Linux.
+#[derive(Default)]
+pub struct PlaybackState(
+	AsyncMutex<Playback>,
+	#[cfg(target_os = "linux")] AsyncMutex<Option<MprisServer>>,
+);
+
+/// Holds the running Subsonic server, restarted whenever its settings change.
+#[derive(Default)]
+pub struct SubsonicState(AsyncMutex<Option<SubsonicServer>>);
+
+/// Holds the scrobbler following the playback, along with the worker submitting the queued listens.
+#[derive(Default)]
+pub struct ScrobbleState(pub AsyncMutex<Option<Scrobbler>>);
 
 #[derive(Default)]
 pub struct DatabaseState(pub Arc<AsyncMutex<Option<Database>>>);
@@ -56,12 +112,282 @@ impl DirectoryState {
 }
 
 impl ConfigurationState {
+	/// Loads the settings file, falling back to the defaults if it can't be read or is invalid.
+	///
+	/// The error is emitted as [SettingsEventType::Changed] while the file is left as is,
+	/// so [ConfigurationState::watch] applies the settings once the file is fixed.
+	pub fn initialize(&self, window: &tauri::Window, settings_path: &Path) {
+		let configuration = match Configuration::load(settings_path) {
+			Ok(x) => x,
+			Err(e) => {
+				error!("Failed to load the settings file, falling back to the defaults: {e:#?}");
+
+				let em = SettingsEventManager::new(SettingsEventType::Changed);
+				if let Err(e) = em.emit(window, SettingsEventPayload::error(e, settings_path.to_path_buf())) {
+					error!("Failed to emit the settings event: {e:#?}");
+				}
+
+				Configuration::default()
+			}
+		};
+
+		self.get().replace(configuration);
+	}
+
+	/// Watches the settings file, applying the changes made outside of the application.
+	///
+	/// Emits [SettingsEventType::Changed] with the new settings, or with the error if the edited file was invalid.
+	/// In the latter case, the previous settings are kept.
+	pub fn watch(&self, window: tauri::Window, settings_path: PathBuf) -> Result<()> {
+		let state = self.0.clone();
+		let config_dir = settings_path.parent().unwrap().to_path_buf();
+		let em = SettingsEventManager::new(SettingsEventType::Changed);
+
+		let watcher = watch_dir(&config_dir, move |path| {
+			if path.file_name() != settings_path.file_name() {
+				return;
+			}
+
+			// The file is read under the lock, so it isn't compared against settings that are still being written.
+			let mut guard = state.lock().unwrap();
+			let result = std::fs::read_to_string(&settings_path)
+				.map_err(Error::from)
+				.and_then(|x| Configuration::parse(&x));
+
+			let payload = match result {
+				Ok(configuration) => {
+					// Writes done through the application are already applied.
+					if guard.as_ref() == Some(&configuration) {
+						return;
+					}
+
+					info!("Settings file was modified, applying changes");
+					guard.replace(configuration.clone());
+					SettingsEventPayload::changed(configuration)
+				}
+				Err(e) => {
+					error!("Failed to reload the settings file: {e:#?}");
+					SettingsEventPayload::error(e, settings_path.clone())
+				}
+			};
+
+			drop(guard);
+			if let Err(e) = em.emit(&window, payload) {
+				error!("Failed to emit the settings event: {e:#?}");
+			}
+		})?;
+
+		self.1.lock().unwrap().replace(watcher);
+
+		Ok(())
+	}
+
+	/// Validates and writes the given settings to the settings file before applying them.
+	pub fn set(&self, settings_path: &Path, configuration: Configuration) -> Result<()> {
+		// The lock is held while the file is replaced, so the watcher sees the new settings as already applied.
+		let mut guard = self.get();
+		configuration.save(settings_path)?;
+		guard.replace(configuration);
+
+		Ok(())
+	}
+
 	#[inline(always)]
 	pub fn get(&self) -> BlockingMutexGuard<'_, Option<Configuration>> {
 		self.0.lock().unwrap()
 	}
 }
 
+impl ThemeState {
+	/// Watches the themes directory, emitting [ThemeEventType::Changed] with the reloaded theme or its validation error.
+	pub fn watch(&self, window: tauri::Window, themes_dir: PathBuf) -> Result<()> {
+		let em = ThemeEventManager::new(ThemeEventType::Changed);
+
+		let watcher = watch_dir(&themes_dir, move |path| {
+			let Some(id) = path
+				.file_stem()
+				.and_then(|x| x.to_str())
+				.filter(|_| path.extension().is_some_and(|x| x == "json"))
+			else {
+				return;
+			};
+
+			let payload = match Theme::load(&themes_dir, id) {
+				Ok(theme) => {
+					info!("Theme '{id}' was modified, reloading");
+					ThemeEventPayload::changed(id.to_string(), theme)
+				}
+				Err(e) => {
+					error!("Failed to reload the theme '{id}': {e:#?}");
+					ThemeEventPayload::error(e, path.to_path_buf())
+				}
+			};
+
+			if let Err(e) = em.emit(&window, payload) {
+				error!("Failed to emit the theme event: {e:#?}");
+			}
+		})?;
+
+		self.0.lock().unwrap().replace(watcher);
+
+		Ok(())
+	}
+}
+
+impl PlaybackState {
+	/// Registers the MPRIS interface, forwarding the requests made through it to the window.
+	///
+	/// Media controls are optional, so a missing session bus is only logged.
+	#[cfg(target_os = "linux")]
+	pub async fn initialize(&self, window: tauri::Window) {
+		let em = PlayerEventManager::new(PlayerEventType::Control);
+		let on_control = move |control| {
+			let result = match control {
+				PlayerControl::Raise => window.show().and_then(|_| window.set_focus()).map_err(Error::from),
+				x => em.emit(&window, PlayerEventPayload::control(x)),
+			};
+
+			if let Err(e) = result {
+				error!("Failed to handle the player control: {e:#?}");
+			}
+		};
+
+		match MprisServer::new(on_control).await {
+			Ok(server) => {
+				self.1.lock().await.replace(server);
+			}
+			Err(e) => error!("Failed to register the MPRIS interface: {e:#?}"),
+		}
+	}
+
+	/// Replaces the playback, returning the previous one.
+	#[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+	pub async fn set(&self, playback: Playback, seeked: bool) -> Result<Playback> {
+		let previous = std::mem::replace(&mut *self.0.lock().await, playback.clone());
+
+		#[cfg(target_os = "linux")]
+		if let Some(server) = self.1.lock().await.as_ref() {
+			server.update(playback, seeked).await?;
+		}
+
+		Ok(previous)
+	}
+
+	#[inline(always)]
+	pub async fn get(&self) -> AsyncMutexGuard<'_, Playback> {
+		self.0.lock().await
+	}
+}
+
+impl SubsonicState {
+	/// Starts, stops or restarts the server to match the given settings.
+	///
+	/// A new port is bound before the running server stops, so the server keeps running if the port is taken.
+	pub async fn apply(
+		&self,
+		configuration: &ServerConfiguration,
+		database: BonsaiDatabase,
+		cover_dir: PathBuf,
+	) -> Result<()> {
+		let mut guard = self.0.lock().await;
+
+		if guard.as_ref().map(|x| &x.configuration) == Some(configuration) {
+			return Ok(());
+		}
+
+		let port_changed = guard.as_ref().map(|x| x.configuration.port) != Some(configuration.port);
+		let listener = (configuration.enabled && port_changed)
+			.then(|| SubsonicServer::bind(configuration.port))
+			.transpose()?;
+
+		if let Some(server) = guard.take() {
+			server.stop().await?;
+		}
+
+		if configuration.enabled {
+			let listener = match listener {
+				Some(x) => x,
+				None => SubsonicServer::bind(configuration.port)?,
+			};
+
+			let server = SubsonicServer::start(configuration.clone(), listener, database, cover_dir)?;
+			guard.replace(server);
+		}
+
+		Ok(())
+	}
+
+	/// Stops the server, returning the settings it ran with so it can be started again with [SubsonicState::apply].
+	pub async fn stop(&self) -> Result<Option<ServerConfiguration>> {
+		let Some(server) = self.0.lock().await.take() else {
+			return Ok(None);
+		};
+
+		let configuration = server.configuration.clone();
+		server.stop().await?;
+
+		Ok(Some(configuration))
+	}
+}
+
+impl ScrobbleState {
+	/// Starts the scrobbler, emitting [ScrobbleEventType::Rejected] for the listens refused by a service.
+	pub async fn initialize(
+		&self,
+		window: tauri::Window,
+		database: BonsaiDatabase,
+		configuration: Arc<BlockingMutex<Option<Configuration>>>,
+	) -> Result<()> {
+		let em = ScrobbleEventManager::new(ScrobbleEventType::Rejected);
+		let on_rejected = move |service, error: &Error| {
+			if let Err(e) = em.emit(&window, ScrobbleEventPayload::rejected(service, error.clone())) {
+				error!("Failed to emit the scrobble event: {e:#?}");
+			}
+		};
+
+		let scrobbler = Scrobbler::new(database, configuration, on_rejected)?;
+		self.0.lock().await.replace(scrobbler);
+
+		Ok(())
+	}
+
+	/// Keeps the scrobbler from writing into the database until the returned guard is dropped.
+	pub async fn pause(&self) -> Option<OwnedMutexGuard<()>> {
+		let pause = self.0.lock().await.as_ref().map(|x| x.pause());
+
+		match pause {
+			Some(x) => Some(x.await),
+			None => None,
+		}
+	}
+
+	/// Stops the scrobbler, so it lets go of the database until it's started again with [ScrobbleState::resume].
+	pub async fn stop(&self) -> Option<StoppedScrobbler> {
+		let scrobbler = self.0.lock().await.take();
+
+		match scrobbler {
+			Some(x) => Some(x.stop().await),
+			None => None,
+		}
+	}
+
+	/// Starts a scrobbler stopped with [ScrobbleState::stop] again, with another handle to the database.
+	pub async fn resume(&self, scrobbler: StoppedScrobbler, database: BonsaiDatabase) -> Result<()> {
+		let scrobbler = scrobbler.start(database)?;
+		self.0.lock().await.replace(scrobbler);
+
+		Ok(())
+	}
+
+	/// Forwards the playback to the scrobbler, if it was initialized.
+	pub async fn report(&self, playback: &Playback, duration: Option<u64>) -> Result<()> {
+		match self.0.lock().await.as_mut() {
+			Some(scrobbler) => scrobbler.report(playback, duration).await,
+			None => Ok(()),
+		}
+	}
+}
+
 impl DatabaseState {
 	pub async fn initialize(&self, database_dir: &Path) -> Result<()> {
 		let db = Database::new(database_dir).await?;
@@ -75,3 +401,49 @@ impl DatabaseState {
 		self.0.lock().await
 	}
 }
+
+impl PluginState {
+	/// Loads the plugins in the given directory, compiling their modules in a blocking thread.
+	pub async fn initialize(&self, plugins_dir: PathBuf, database: BonsaiDatabase) -> Result<()> {
+		let library = LibraryAccess {
+			database,
+			handle: Handle::current(),
+		};
+
+		let host = tokio::task::spawn_blocking(move || {
+			let mut host = PluginHost::new()?;
+			host.load_dir(&plugins_dir, library)?;
+
+			Ok::<_, Error>(host)
+		})
+		.await??;
+
+		self.get().await.replace(host);
+
+		Ok(())
+	}
+
+	/// Unloads the plugins, so they let go of the database until they're loaded again with [PluginState::initialize].
+	pub async fn unload(&self) {
+		self.get().await.take();
+	}
+
+	/// Sends an event to the plugins from a blocking thread, as plugins may take their time handling it.
+	pub async fn dispatch(&self, event: PluginEvent) -> Result<()> {
+		let state = self.0.clone();
+
+		tokio::task::spawn_blocking(move || {
+			if let Some(host) = state.blocking_lock().as_mut() {
+				host.dispatch(&event);
+			}
+		})
+		.await?;
+
+		Ok(())
+	}
+
+	#[inline(always)]
+	pub async fn get(&self) -> AsyncMutexGuard<'_, Option<PluginHost>> {
+		self.0.lock().await
+	}
+}
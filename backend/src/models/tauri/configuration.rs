@@ -0,0 +1,35 @@
+use std::path::PathBuf;
+
+use crate::{
+	errors::Error,
+	models::{
+		configuration::Configuration,
+		tauri::{EventPayload, SerializablePathedError, WindowEventManager, WindowEventType},
+	},
+};
+
+pub type SettingsEventManager = WindowEventManager<SettingsEventType, Configuration, SerializablePathedError>;
+
+pub type SettingsEventPayload = EventPayload<Configuration, SerializablePathedError>;
+impl SettingsEventPayload {
+	pub fn changed(data: Configuration) -> Self {
+		Self::Ok(data)
+	}
+
+	pub fn error(error: Error, path: PathBuf) -> Self {
+		Self::Error(SerializablePathedError { error, path })
+	}
+}
+
+#[derive(Debug)]
+pub enum SettingsEventType {
+	Changed,
+}
+
+impl WindowEventType for SettingsEventType {
+	fn get_name(&self) -> &'static str {
+		match self {
+			SettingsEventType::Changed => "settings_changed",
+		}
+	}
+}
@@ -0,0 +1,28 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::database::models::{label::Label, person::Person, release::Release};
+
+use super::cover::DisplayCover;
+
+#[derive(Debug, Serialize)]
+pub struct LabelEntry {
+	pub id: u64,
+	#[serde(flatten)]
+	pub label: Label,
+
+	pub release_count: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DisplayLabel {
+	pub label: Label,
+
+	/// Releases published under this label, ordered by their catalog number and release date.
+	pub release_ids: Vec<u64>,
+
+	pub releases: HashMap<u64, Release>,
+	pub artists: HashMap<u64, Person>,
+	pub covers: HashMap<u64, DisplayCover>,
+}
@@ -3,10 +3,19 @@ use std::path::PathBuf;
 use serde::Serialize;
 
 use crate::{
+	database::models::library::Library,
 	errors::Error,
 	models::tauri::{EventPayload, SerializablePathedError, WindowEventManager, WindowEventType},
 };
 
+#[derive(Debug, Serialize)]
+pub struct LibraryEntry {
+	pub id: u64,
+	#[serde(flatten)]
+	pub library: Library,
+	pub active: bool,
+}
+
 pub type LibraryEventManager = WindowEventManager<LibraryEventType, LibraryEvent, SerializablePathedError>;
 
 pub type LibraryEventPayload = EventPayload<LibraryEvent, SerializablePathedError>;
@@ -1,13 +1,28 @@
 use std::{marker::PhantomData, path::PathBuf};
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use crate::errors::{Error, Result};
+use crate::{
+	database::views::SortKey,
+	errors::{Error, Result},
+};
 
+pub mod configuration;
 pub mod cover;
+pub mod label;
 pub mod library;
+pub mod person;
+#[cfg(target_os = "linux")]
+pub mod playback;
+pub mod playlist;
+pub mod plugin;
+pub mod portable;
 pub mod release;
+pub mod scrobble;
+pub mod tag;
+pub mod theme;
 pub mod track;
+pub mod work;
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(tag = "type", content = "data", rename_all = "snake_case")]
@@ -34,6 +49,50 @@ impl<T: Serialize> Entity<T> {
 	}
 }
 
+/// Points to the last entry of a page, see [crate::database::views::SortKey].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Cursor {
+	pub value: String,
+	pub id: u64,
+}
+
+impl From<SortKey> for Cursor {
+	fn from((value, id): SortKey) -> Self {
+		Self { value, id }
+	}
+}
+
+impl From<Cursor> for SortKey {
+	fn from(cursor: Cursor) -> Self {
+		(cursor.value, cursor.id)
+	}
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortDirection {
+	#[default]
+	Ascending,
+	Descending,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PageRequest {
+	/// The cursor of the previous page, or none to start from the beginning.
+	pub cursor: Option<Cursor>,
+	#[serde(default)]
+	pub direction: SortDirection,
+	/// The maximum number of items in the page, where zero yields an empty page.
+	pub limit: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Page<T: Serialize> {
+	pub items: Vec<Entity<T>>,
+	/// The cursor to request the next page with, none if this was the last page.
+	pub next_cursor: Option<Cursor>,
+}
+
 pub struct WindowEventManager<T, D, E>
 where
 	T: WindowEventType,
@@ -0,0 +1,36 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::database::models::{
+	person::{Person, PersonType},
+	release::Release,
+	track::Track,
+};
+
+use super::cover::DisplayCover;
+
+#[derive(Debug, Serialize)]
+pub struct DisplayArtist {
+	pub person: Person,
+
+	/// Releases credited to this person, ordered by their release date.
+	pub release_ids: Vec<u64>,
+	/// Tracks this person is credited as an artist in.
+	pub track_ids: Vec<u64>,
+	/// Tracks this person is credited in by every other role, like composer or performer.
+	pub credited_track_ids: HashMap<PersonType, Vec<u64>>,
+
+	pub releases: HashMap<u64, Release>,
+	pub tracks: HashMap<u64, Track>,
+	pub artists: HashMap<u64, Person>,
+	pub covers: HashMap<u64, DisplayCover>,
+}
+
+/// Details of a person split from another one.
+#[derive(Debug, Deserialize)]
+pub struct PersonSplit {
+	pub name: String,
+	pub name_sort: Option<String>,
+	pub mbz_id: Option<String>,
+}
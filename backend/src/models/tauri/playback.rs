@@ -0,0 +1,29 @@
+use crate::{
+	errors::Error,
+	models::{
+		playback::PlayerControl,
+		tauri::{EventPayload, WindowEventManager, WindowEventType},
+	},
+};
+
+pub type PlayerEventManager = WindowEventManager<PlayerEventType, PlayerControl, Error>;
+
+pub type PlayerEventPayload = EventPayload<PlayerControl, Error>;
+impl PlayerEventPayload {
+	pub fn control(data: PlayerControl) -> Self {
+		Self::Ok(data)
+	}
+}
+
+#[derive(Debug)]
+pub enum PlayerEventType {
+	Control,
+}
+
+impl WindowEventType for PlayerEventType {
+	fn get_name(&self) -> &'static str {
+		match self {
+			PlayerEventType::Control => "player_control",
+		}
+	}
+}
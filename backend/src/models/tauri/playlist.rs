@@ -0,0 +1,27 @@
+use serde::Serialize;
+
+use crate::database::models::playlist::Playlist;
+
+#[derive(Debug, Serialize)]
+pub struct PlaylistEntry {
+	pub id: u64,
+	#[serde(flatten)]
+	pub playlist: Playlist,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PlaylistImport {
+	pub id: u64,
+	pub playlist: Playlist,
+	/// Entries of the file that couldn't be matched against the library, left out of the playlist.
+	pub unresolved: Vec<UnresolvedItem>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UnresolvedItem {
+	/// Position of the entry in the file, starting from 1.
+	pub position: usize,
+	pub location: String,
+	pub title: Option<String>,
+	pub artist: Option<String>,
+}
@@ -0,0 +1,24 @@
+use serde::Serialize;
+
+use crate::{
+	errors::Error,
+	plugins::{manifest::PluginManifest, PluginStatus},
+};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginEntry {
+	pub id: String,
+	/// None if the manifest itself couldn't be read.
+	pub manifest: Option<PluginManifest>,
+	pub status: PluginStatus,
+}
+
+impl PluginEntry {
+	pub fn failed(id: String, manifest: Option<PluginManifest>, error: Error) -> Self {
+		Self {
+			id,
+			manifest,
+			status: PluginStatus::Failed(error),
+		}
+	}
+}
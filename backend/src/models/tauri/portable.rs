@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+/// Rewrites the paths of an export that start with `from`, for music that lives under another directory.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PathMapping {
+	pub from: String,
+	pub to: String,
+}
+
+/// Counts of the records matched against the library while importing an export.
+#[derive(Debug, Default, Serialize)]
+pub struct PortableImport {
+	pub tracks: usize,
+	pub releases: usize,
+	pub people: usize,
+	pub labels: usize,
+	pub tags: usize,
+	/// Ratings set on the matched tracks that had none.
+	pub ratings: usize,
+	/// Plays added to the history, skipping the ones already in it.
+	pub plays: usize,
+	/// Playlists created by the import, skipping the ones that already existed.
+	pub playlist_ids: Vec<u64>,
+	/// Paths of the tracks that couldn't be matched, as written in the export.
+	pub unmatched_paths: Vec<String>,
+}
@@ -1,10 +1,14 @@
 use std::collections::HashMap;
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use crate::database::models::{person::Person, release::Release};
+use crate::database::models::{
+	person::Person,
+	release::{Release, ReleaseType, ReleaseTypeSecondary},
+	CountryCode,
+};
 
-use super::{cover::DisplayCover, Entity};
+use super::{cover::DisplayCover, Entity, Page};
 
 pub type ReleaseEntity = Entity<Release>;
 
@@ -14,3 +18,55 @@ pub struct DisplayReleases {
 	pub artists: HashMap<u64, Person>,
 	pub covers: HashMap<u64, DisplayCover>,
 }
+
+#[derive(Debug, Serialize)]
+pub struct DisplayReleasePage {
+	#[serde(flatten)]
+	pub page: Page<Release>,
+	pub artists: HashMap<u64, Person>,
+	pub covers: HashMap<u64, DisplayCover>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReleaseSort {
+	Name,
+	Artist,
+	Date,
+	Added,
+	/// Sums the plays of the tracks of every release.
+	PlayCount,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ReleaseFilter {
+	/// Defaults to the active library.
+	pub library_id: Option<u64>,
+	#[serde(rename = "type")]
+	pub type_: Option<ReleaseType>,
+	pub type_secondary: Option<ReleaseTypeSecondary>,
+	pub genre_id: Option<u64>,
+	pub label_id: Option<u64>,
+	/// Inclusive lower bound of the release year.
+	pub year_from: Option<i32>,
+	/// Inclusive upper bound of the release year.
+	pub year_to: Option<i32>,
+	pub country: Option<CountryCode>,
+}
+
+impl ReleaseFilter {
+	/// Checks the fields that are not backed by a view.
+	pub fn matches(&self, release: &Release) -> bool {
+		let type_matches = self.type_.as_ref().map_or(true, |x| *x == release.type_);
+		let country_matches = self
+			.country
+			.as_ref()
+			.map_or(true, |x| release.country.as_ref() == Some(x));
+		let secondary_matches = self
+			.type_secondary
+			.as_ref()
+			.map_or(true, |x| release.type_secondary.as_ref().is_some_and(|y| y.contains(x)));
+
+		type_matches && country_matches && secondary_matches
+	}
+}
@@ -0,0 +1,36 @@
+use serde::Serialize;
+
+use crate::{
+	database::models::scrobble::ScrobbleService,
+	errors::Error,
+	models::tauri::{EventPayload, WindowEventManager, WindowEventType},
+};
+
+pub type ScrobbleEventManager = WindowEventManager<ScrobbleEventType, (), ScrobbleRejection>;
+
+pub type ScrobbleEventPayload = EventPayload<(), ScrobbleRejection>;
+impl ScrobbleEventPayload {
+	pub fn rejected(service: ScrobbleService, error: Error) -> Self {
+		Self::Error(ScrobbleRejection { service, error })
+	}
+}
+
+/// A listen refused by a service, like when its credentials are no longer valid.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScrobbleRejection {
+	pub service: ScrobbleService,
+	pub error: Error,
+}
+
+#[derive(Debug)]
+pub enum ScrobbleEventType {
+	Rejected,
+}
+
+impl WindowEventType for ScrobbleEventType {
+	fn get_name(&self) -> &'static str {
+		match self {
+			ScrobbleEventType::Rejected => "scrobble_rejected",
+		}
+	}
+}
@@ -0,0 +1,15 @@
+use serde::Serialize;
+
+use crate::database::models::tag::Tag;
+
+#[derive(Debug, Serialize)]
+pub struct GenreEntry {
+	pub id: u64,
+	#[serde(flatten)]
+	pub tag: Tag,
+
+	/// Count of releases directly tagged with this genre, excluding its sub-genres.
+	pub release_count: u64,
+	/// Count of tracks directly tagged with this genre, excluding its sub-genres.
+	pub track_count: u64,
+}
@@ -0,0 +1,43 @@
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::{
+	errors::Error,
+	models::{
+		tauri::{EventPayload, SerializablePathedError, WindowEventManager, WindowEventType},
+		theme::Theme,
+	},
+};
+
+pub type ThemeEventManager = WindowEventManager<ThemeEventType, ThemeEvent, SerializablePathedError>;
+
+pub type ThemeEventPayload = EventPayload<ThemeEvent, SerializablePathedError>;
+impl ThemeEventPayload {
+	pub fn changed(id: String, theme: Theme) -> Self {
+		Self::Ok(ThemeEvent { id, theme })
+	}
+
+	pub fn error(error: Error, path: PathBuf) -> Self {
+		Self::Error(SerializablePathedError { error, path })
+	}
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ThemeEvent {
+	pub id: String,
+	pub theme: Theme,
+}
+
+#[derive(Debug)]
+pub enum ThemeEventType {
+	Changed,
+}
+
+impl WindowEventType for ThemeEventType {
+	fn get_name(&self) -> &'static str {
+		match self {
+			ThemeEventType::Changed => "theme_changed",
+		}
+	}
+}
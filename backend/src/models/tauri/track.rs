@@ -1,11 +1,39 @@
 use std::collections::HashMap;
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::database::models::{person::Person, track::Track};
 
+use super::Page;
+
 #[derive(Debug, Serialize)]
 pub struct DisplayTrackList {
 	pub tracks: Vec<Track>,
 	pub artists: HashMap<u64, Person>,
 }
+
+#[derive(Debug, Serialize)]
+pub struct DisplayTrackPage {
+	#[serde(flatten)]
+	pub page: Page<Track>,
+	pub artists: HashMap<u64, Person>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrackSort {
+	Title,
+	Artist,
+	Date,
+	Added,
+	PlayCount,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct TrackFilter {
+	/// Defaults to the active library.
+	pub library_id: Option<u64>,
+	pub release_id: Option<u64>,
+	pub artist_id: Option<u64>,
+	pub genre_id: Option<u64>,
+}
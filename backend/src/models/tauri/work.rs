@@ -0,0 +1,37 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::database::models::{person::Person, release::Release, track::Track, work::Work};
+
+use super::cover::DisplayCover;
+
+#[derive(Debug, Serialize)]
+pub struct WorkEntry {
+	pub id: u64,
+	#[serde(flatten)]
+	pub work: Work,
+
+	/// Number of releases recording this work.
+	pub recording_count: u64,
+}
+
+/// The tracks of a release recording a work.
+#[derive(Debug, Serialize)]
+pub struct WorkRecording {
+	pub release_id: u64,
+	/// Ordered by their movement, disc and track number.
+	pub track_ids: Vec<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DisplayWork {
+	pub work: Work,
+	pub recordings: Vec<WorkRecording>,
+
+	pub releases: HashMap<u64, Release>,
+	pub tracks: HashMap<u64, Track>,
+	/// Composers of the work along with the artists of the recordings.
+	pub artists: HashMap<u64, Person>,
+	pub covers: HashMap<u64, DisplayCover>,
+}
@@ -1,6 +1,14 @@
-use blake3::Hash;
+use std::path::Path;
 
-use crate::database::models::cover::{Cover, CoverMediaType, CoverType};
+use {blake3::Hash, chrono::Utc};
+
+use crate::{
+	database::models::cover::{Cover, CoverMediaType, CoverType},
+	errors::{
+		pre::{unsupported_image_size, unsupported_image_type},
+		Result,
+	},
+};
 
 pub struct TempCover {
 	pub type_: CoverType,
@@ -11,6 +19,30 @@ pub struct TempCover {
 }
 
 impl TempCover {
+	/// Reads an image file from the disk into a [TempCover].
+	///
+	/// Only the formats supported by [CoverMediaType] are accepted, in sizes that fit [Cover::resolution].
+	pub fn from_path(type_: CoverType, path: &Path) -> Result<Self> {
+		let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default();
+		let media_type = CoverMediaType::from_extension(extension).ok_or_else(|| unsupported_image_type(extension))?;
+
+		let (width, height) = image::image_dimensions(path)?;
+		let resolution = match (u16::try_from(width), u16::try_from(height)) {
+			(Ok(width), Ok(height)) => (width, height),
+			_ => return Err(unsupported_image_size(width, height)),
+		};
+
+		let data = std::fs::read(path)?.into_boxed_slice();
+
+		Ok(Self {
+			type_,
+			media_type,
+			resolution,
+			comment: None,
+			data,
+		})
+	}
+
 	/// Implies whether this cover needs a thumb.
 	///
 	/// True for all images where [TempCover::resolution] is >512
@@ -19,6 +51,8 @@ impl TempCover {
 	}
 
 	pub fn into_cover(self, hash: Hash, has_thumb: bool) -> Cover {
+		let now = Utc::now();
+
 		Cover {
 			type_: self.type_,
 			media_type: self.media_type,
@@ -26,6 +60,8 @@ impl TempCover {
 			comment: self.comment,
 			hash,
 			has_thumb,
+			date_added: now,
+			date_modified: now,
 		}
 	}
 }
@@ -0,0 +1,24 @@
+use chrono::Utc;
+
+use crate::database::models::lyrics::{Lyrics, LyricsLine};
+
+#[derive(Debug, Default)]
+pub struct TempLyrics {
+	pub language: Option<String>,
+	pub lines: Vec<LyricsLine>,
+}
+
+impl TempLyrics {
+	pub fn into_lyrics(self, track_id: u64) -> Lyrics {
+		let now = Utc::now();
+
+		Lyrics {
+			track_id,
+			language: self.language,
+			lines: self.lines,
+			edited: false,
+			date_added: now,
+			date_modified: now,
+		}
+	}
+}
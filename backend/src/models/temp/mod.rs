@@ -1,10 +1,14 @@
-use crate::database::models::{label::Label, person::Person, release::ReleaseType, tag::Tag, InlinedArtist};
+use crate::database::models::{
+	label::Label, person::Person, release::ReleaseType, tag::Tag, track::Credit, InlinedArtist,
+};
 
-use self::{cover::TempCover, release::TempRelease, track::TempTrack};
+use self::{cover::TempCover, lyrics::TempLyrics, release::TempRelease, track::TempTrack, work::TempWork};
 
 pub mod cover;
+pub mod lyrics;
 pub mod release;
 pub mod track;
+pub mod work;
 
 /// Type representing a probable date in the (year, month, day) format.
 pub type OptionedDate = Option<(Option<i32>, Option<u32>, Option<u32>)>;
@@ -36,6 +40,23 @@ impl TempInlinedArtist {
 	}
 }
 
+/// A person credited in a track, where the type of the person is the credited role.
+#[derive(Debug)]
+pub struct TempCredit {
+	pub person: Person,
+	pub detail: Option<String>,
+}
+
+impl TempCredit {
+	pub fn new(person: Person, detail: Option<String>) -> Self {
+		Self { person, detail }
+	}
+
+	pub fn into_credit(self, person_id: u64) -> Credit {
+		Credit::new(person_id, self.person.type_, self.detail)
+	}
+}
+
 #[derive(Debug, Default)]
 pub struct TempTrackMeta {
 	pub track: Option<TempTrack>,
@@ -43,12 +64,13 @@ pub struct TempTrackMeta {
 
 	pub artists: Option<Vec<TempInlinedArtist>>,
 	pub release_artists: Option<Vec<TempInlinedArtist>>,
-	pub composers: Option<Vec<Person>>,
-	pub producers: Option<Vec<Person>>,
+	pub credits: Option<Vec<TempCredit>>,
 
 	pub labels: Option<Vec<Label>>,
 	pub genres: Option<Vec<Tag>>,
 	pub tags: Option<Vec<Tag>>,
+	pub lyrics: Option<TempLyrics>,
+	pub work: Option<TempWork>,
 
 	pub path: String,
 }
@@ -64,9 +86,15 @@ impl TempTrackMeta {
 			artist_sort: None,
 			mbz_id: None,
 			path: String::with_capacity(0),
+			segment: None,
+			movement: None,
 		})
 	}
 
+	pub fn get_or_default_work(&mut self) -> &mut TempWork {
+		self.work.get_or_insert_with(TempWork::default)
+	}
+
 	pub fn get_or_default_release(&mut self) -> &mut TempRelease {
 		self.release.get_or_insert_with(|| TempRelease {
 			name: String::with_capacity(0),
@@ -90,4 +118,7 @@ impl TempTrackMeta {
 pub struct TempTrackResource {
 	pub track_covers: Option<Vec<TempCover>>,
 	pub release_covers: Option<Vec<TempCover>>,
+
+	/// Artist image that belongs to the first release artist, or the first track artist if the former is missing.
+	pub artist_cover: Option<TempCover>,
 }
@@ -1,4 +1,4 @@
-use chrono::NaiveDate;
+use chrono::{NaiveDate, Utc};
 
 use crate::database::models::{
 	release::{Release, ReleaseType, ReleaseTypeSecondary},
@@ -31,10 +31,14 @@ pub struct TempReleaseIntoArg {
 	pub genre_ids: Option<Vec<u64>>,
 	pub tag_ids: Option<Vec<u64>>,
 	pub cover_ids: Option<Vec<u64>>,
+	pub library_id: u64,
+	pub directory: Option<String>,
 }
 
 impl TempRelease {
 	pub fn into_release(self, arg: TempReleaseIntoArg) -> Release {
+		let now = Utc::now();
+
 		Release {
 			name: self.name,
 			name_sort: self.name_sort,
@@ -43,6 +47,7 @@ impl TempRelease {
 			country: self.country,
 			script: self.script,
 			total_tracks: self.total_tracks,
+			total_discs: self.total_discs,
 			catalog_number: self.catalog_number,
 			type_: self.type_,
 			type_secondary: self.type_secondary,
@@ -54,6 +59,11 @@ impl TempRelease {
 			genre_ids: arg.genre_ids,
 			tag_ids: arg.tag_ids,
 			cover_ids: arg.cover_ids,
+			library_id: arg.library_id,
+			directory: arg.directory,
+
+			date_added: now,
+			date_modified: now,
 		}
 	}
 }
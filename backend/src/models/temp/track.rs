@@ -1,8 +1,11 @@
-use chrono::NaiveDate;
+use chrono::{NaiveDate, Utc};
 
 use crate::{
 	constants,
-	database::models::{track::Track, InlinedArtist},
+	database::models::{
+		track::{Credit, Movement, Track, TrackSegment},
+		InlinedArtist,
+	},
 };
 
 #[derive(Debug)]
@@ -15,20 +18,25 @@ pub struct TempTrack {
 	pub artist_sort: Option<String>,
 	pub mbz_id: Option<String>,
 	pub path: String,
+	pub segment: Option<TrackSegment>,
+	pub movement: Option<Movement>,
 }
 
 pub struct TempTrackIntoArg {
 	pub artists: Option<Vec<InlinedArtist>>,
 	pub release_id: Option<u64>,
-	pub composer_ids: Option<Vec<u64>>,
-	pub producer_ids: Option<Vec<u64>>,
+	pub credits: Vec<Credit>,
 	pub genre_ids: Option<Vec<u64>>,
 	pub tag_ids: Option<Vec<u64>>,
 	pub cover_ids: Option<Vec<u64>>,
+	pub library_id: u64,
+	pub work_id: Option<u64>,
 }
 
 impl TempTrack {
 	pub fn into_track(self, arg: TempTrackIntoArg) -> Track {
+		let now = Utc::now();
+
 		Track {
 			title: self.title,
 			title_sort: self.title_sort,
@@ -38,14 +46,24 @@ impl TempTrack {
 			artist_sort: self.artist_sort,
 			mbz_id: self.mbz_id,
 			path: self.path,
+			segment: self.segment,
 
 			artists: arg.artists.unwrap_or_else(|| vec![InlinedArtist::unknown()]),
 			release_id: arg.release_id.unwrap_or(constants::UNKNOWN_RELEASE_ID),
-			composer_ids: arg.composer_ids,
-			producer_ids: arg.producer_ids,
+			credits: arg.credits,
 			genre_ids: arg.genre_ids,
 			tag_ids: arg.tag_ids,
 			cover_ids: arg.cover_ids,
+			library_id: arg.library_id,
+			work_id: arg.work_id,
+			movement: self.movement,
+			rating: None,
+
+			date_added: now,
+			date_modified: now,
+
+			composer_ids: None,
+			producer_ids: None,
 		}
 	}
 }
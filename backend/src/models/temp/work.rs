@@ -0,0 +1,23 @@
+use crate::{database::models::work::Work, utils::matchers};
+
+#[derive(Debug, Default)]
+pub struct TempWork {
+	pub name: String,
+	pub mbz_id: Option<String>,
+}
+
+impl TempWork {
+	/// Converts into a [Work], reading the catalogue number and the key from the name when present.
+	pub fn into_work(self, composer_ids: Vec<u64>) -> Work {
+		let catalogue_number = matchers::reg::find_catalogue_number(&self.name);
+		let key = matchers::reg::find_musical_key(&self.name);
+
+		Work {
+			composer_ids,
+			catalogue_number,
+			key,
+			mbz_id: self.mbz_id,
+			..Work::new(self.name)
+		}
+	}
+}
@@ -0,0 +1,220 @@
+use std::{
+	borrow::Cow,
+	collections::{BTreeMap, HashSet},
+	path::{Component, Path, PathBuf},
+};
+
+use {
+	once_cell::sync::Lazy,
+	serde::{Deserialize, Serialize},
+	serde_json::Value,
+};
+
+use crate::errors::{pre::invalid_theme_id, Error, Result};
+
+/// Color keys defined in `resources/schemas/theme.json`.
+static COLOR_KEYS: Lazy<HashSet<String>> = Lazy::new(|| {
+	let schema: Value = serde_json::from_str(include_str!("../../../resources/schemas/theme.json")).unwrap();
+
+	schema["properties"]["colors"]["properties"]
+		.as_object()
+		.unwrap()
+		.keys()
+		.cloned()
+		.collect()
+});
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Theme {
+	pub name: String,
+	pub mode: ThemeMode,
+	pub colors: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemeMode {
+	Light,
+	Dark,
+}
+
+/// A theme file found in the themes directory.
+///
+/// Invalid themes are still listed, with [ThemeEntry::error] describing why they can't be used.
+#[derive(Debug, Clone, Serialize)]
+pub struct ThemeEntry {
+	/// File stem of the theme, used to refer to it in the settings.
+	pub id: String,
+	pub path: PathBuf,
+	pub name: Option<String>,
+	pub mode: Option<ThemeMode>,
+	pub error: Option<Error>,
+}
+
+impl Theme {
+	/// Reads and validates the theme with the given id from the themes directory.
+	///
+	/// Ids that would point outside of the themes directory, like ones with separators or `..`, are refused.
+	pub fn load(themes_dir: &Path, id: &str) -> Result<Self> {
+		if !is_valid_id(id) {
+			return Err(invalid_theme_id(id));
+		}
+
+		let path = themes_dir.join(format!("{id}.json"));
+		let contents = std::fs::read_to_string(&path)
+			.map_err(|e| Error::from(e).append_message(&format!("Failed to read the theme file '{id}.json'")))?;
+
+		Self::parse(&contents)
+	}
+
+	/// Parses a theme, validating it against the theme schema.
+	///
+	/// Every violation is reported in the error message, each prefixed with the path of the offending value.
+	pub fn parse(contents: &str) -> Result<Self> {
+		let value = serde_json::from_str::<Value>(contents)?;
+		let violations = validate(&value);
+
+		if !violations.is_empty() {
+			return Err(Error::new("Invalid theme", Cow::Owned(violations.join("\n"))));
+		}
+
+		Ok(serde_json::from_value(value)?)
+	}
+}
+
+impl ThemeEntry {
+	pub fn from_path(path: PathBuf) -> Option<Self> {
+		let id = path.file_stem()?.to_str()?.to_string();
+		let result = std::fs::read_to_string(&path)
+			.map_err(Error::from)
+			.and_then(|x| Theme::parse(&x));
+
+		let entry = match result {
+			Ok(theme) => Self {
+				id,
+				path,
+				name: Some(theme.name),
+				mode: Some(theme.mode),
+				error: None,
+			},
+			Err(e) => Self {
+				id,
+				path,
+				name: None,
+				mode: None,
+				error: Some(e),
+			},
+		};
+
+		Some(entry)
+	}
+}
+
+/// Lists the theme files in the themes directory, ordered by their id.
+pub fn discover(themes_dir: &Path) -> Result<Vec<ThemeEntry>> {
+	let mut entries = Vec::new();
+
+	for child in std::fs::read_dir(themes_dir)? {
+		let path = child?.path();
+
+		if path.extension().is_some_and(|x| x == "json") {
+			entries.extend(ThemeEntry::from_path(path));
+		}
+	}
+
+	entries.sort_by(|a, b| a.id.cmp(&b.id));
+
+	Ok(entries)
+}
+
+fn validate(value: &Value) -> Vec<String> {
+	let mut violations = Vec::new();
+
+	let Some(root) = value.as_object() else {
+		return vec!["$: expected an object".to_string()];
+	};
+
+	match root.get("name") {
+		Some(Value::String(_)) => {}
+		Some(_) => violations.push("name: expected a string".to_string()),
+		None => violations.push("name: missing required property".to_string()),
+	}
+
+	match root.get("mode").map(|x| x.as_str()) {
+		Some(Some("light" | "dark")) => {}
+		Some(_) => violations.push("mode: expected either \"light\" or \"dark\"".to_string()),
+		None => violations.push("mode: missing required property".to_string()),
+	}
+
+	match root.get("colors") {
+		Some(Value::Object(colors)) => {
+			for (key, value) in colors {
+				if !COLOR_KEYS.contains(key) {
+					violations.push(format!("colors.{key}: unknown color key"));
+				} else if !value.as_str().is_some_and(is_hex_color) {
+					violations.push(format!("colors.{key}: expected a hex color, got {value}"));
+				}
+			}
+		}
+		Some(_) => violations.push("colors: expected an object".to_string()),
+		None => violations.push("colors: missing required property".to_string()),
+	}
+
+	violations
+}
+
+/// Checks that the id is a plain file name, so the theme file it names is directly in the themes directory.
+fn is_valid_id(id: &str) -> bool {
+	let mut components = Path::new(id).components();
+
+	!id.contains(['/', '\\'])
+		&& matches!(components.next(), Some(Component::Normal(x)) if x == id)
+		&& components.next().is_none()
+}
+
+/// Matches `#rgb`, `#rgba`, `#rrggbb` and `#rrggbbaa`.
+fn is_hex_color(value: &str) -> bool {
+	value
+		.strip_prefix('#')
+		.is_some_and(|x| matches!(x.len(), 3 | 4 | 6 | 8) && x.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+#[cfg(test)]
+mod test {
+	use super::{is_hex_color, is_valid_id, Theme, ThemeMode};
+
+	#[test]
+	fn test_parse() {
+		let theme = Theme::parse(include_str!("../../../app/src/assets/themes/dark.json")).unwrap();
+		assert_eq!(theme.mode, ThemeMode::Dark);
+
+		let error =
+			Theme::parse(r##"{ "name": "Broken", "mode": "dim", "colors": { "text.1": "#ggg", "text.9": "#fff" } }"##)
+				.unwrap_err();
+		let message = error.message.unwrap();
+
+		assert!(message.contains("mode: expected"));
+		assert!(message.contains("colors.text.1: expected a hex color"));
+		assert!(message.contains("colors.text.9: unknown color key"));
+	}
+
+	#[test]
+	fn test_is_valid_id() {
+		assert!(is_valid_id("dark"));
+		assert!(is_valid_id("solarized.light"));
+		assert!(!is_valid_id(""));
+		assert!(!is_valid_id(".."));
+		assert!(!is_valid_id("../settings"));
+		assert!(!is_valid_id("themes/dark"));
+		assert!(!is_valid_id("themes\\dark"));
+		assert!(!is_valid_id("/etc/passwd"));
+	}
+
+	#[test]
+	fn test_is_hex_color() {
+		assert!(is_hex_color("#fff"));
+		assert!(is_hex_color("#ffffff15"));
+		assert!(!is_hex_color("ffffff"));
+		assert!(!is_hex_color("#fffff"));
+	}
+}
@@ -0,0 +1,390 @@
+use std::{collections::HashMap, sync::Arc};
+
+use {
+	tracing::debug,
+	zbus::{
+		dbus_interface,
+		zvariant::{ObjectPath, OwnedValue, Value},
+		Connection, ConnectionBuilder, SignalContext,
+	},
+};
+
+use crate::{
+	errors::Result,
+	models::playback::{Playback, PlaybackStatus, PlayerControl},
+};
+
+pub const BUS_NAME: &str = "org.mpris.MediaPlayer2.melody";
+pub const OBJECT_PATH: &str = "/org/mpris/MediaPlayer2";
+
+/// Prefix of the `mpris:trackid` object paths, followed by the id of the track.
+const TRACK_PATH_PREFIX: &str = "/moe/curstantine/melody/track/";
+const NO_TRACK_PATH: &str = "/org/mpris/MediaPlayer2/TrackList/NoTrack";
+
+type ControlHandler = Arc<dyn Fn(PlayerControl) + Send + Sync>;
+
+/// Exposes the player on the session bus through the MPRIS specification.
+///
+/// The player itself lives in the frontend, so calls made to this interface are forwarded as [PlayerControl]s,
+/// and the state is mirrored back through [MprisServer::update].
+pub struct MprisServer {
+	connection: Connection,
+}
+
+struct RootInterface {
+	on_control: ControlHandler,
+}
+
+struct PlayerInterface {
+	playback: Playback,
+	on_control: ControlHandler,
+}
+
+impl MprisServer {
+	pub async fn new(on_control: impl Fn(PlayerControl) + Send + Sync + 'static) -> Result<Self> {
+		Self::with_name(BUS_NAME, on_control).await
+	}
+
+	/// Registers the interface under the given bus name, like `org.mpris.MediaPlayer2.melody.instance2`.
+	async fn with_name(bus_name: &str, on_control: impl Fn(PlayerControl) + Send + Sync + 'static) -> Result<Self> {
+		let on_control: ControlHandler = Arc::new(on_control);
+
+		let root = RootInterface {
+			on_control: on_control.clone(),
+		};
+		let player = PlayerInterface {
+			playback: Playback::default(),
+			on_control,
+		};
+
+		let connection = ConnectionBuilder::session()?
+			.name(bus_name)?
+			.serve_at(OBJECT_PATH, root)?
+			.serve_at(OBJECT_PATH, player)?
+			.build()
+			.await?;
+
+		debug!("Registered '{bus_name}' on the session bus");
+
+		Ok(Self { connection })
+	}
+
+	/// Replaces the mirrored state, notifying the clients about the properties that changed.
+	///
+	/// The position is not announced as it advances, except when it jumps as reported by `seeked`.
+	pub async fn update(&self, playback: Playback, seeked: bool) -> Result<()> {
+		let iface_ref = self
+			.connection
+			.object_server()
+			.interface::<_, PlayerInterface>(OBJECT_PATH)
+			.await?;
+
+		let mut iface = iface_ref.get_mut().await;
+		let previous = std::mem::replace(&mut iface.playback, playback);
+		let current = &iface.playback;
+		let ctxt = iface_ref.signal_context();
+
+		let status_changed = previous.status != current.status;
+		let track_changed = previous.track_id() != current.track_id();
+		let volume_changed = previous.volume != current.volume;
+		let controls_changed =
+			previous.can_go_next != current.can_go_next || previous.can_go_previous != current.can_go_previous;
+
+		if status_changed {
+			iface.playback_status_changed(ctxt).await?;
+		}
+
+		if track_changed {
+			iface.metadata_changed(ctxt).await?;
+		}
+
+		if volume_changed {
+			iface.volume_changed(ctxt).await?;
+		}
+
+		if controls_changed {
+			iface.can_go_next_changed(ctxt).await?;
+			iface.can_go_previous_changed(ctxt).await?;
+		}
+
+		if seeked {
+			PlayerInterface::seeked(ctxt, to_micros(iface.playback.position)).await?;
+		}
+
+		Ok(())
+	}
+}
+
+#[dbus_interface(name = "org.mpris.MediaPlayer2")]
+impl RootInterface {
+	fn raise(&self) {
+		(self.on_control)(PlayerControl::Raise);
+	}
+
+	fn quit(&self) {}
+
+	#[dbus_interface(property)]
+	fn can_quit(&self) -> bool {
+		false
+	}
+
+	#[dbus_interface(property)]
+	fn can_raise(&self) -> bool {
+		true
+	}
+
+	#[dbus_interface(property)]
+	fn has_track_list(&self) -> bool {
+		false
+	}
+
+	#[dbus_interface(property)]
+	fn identity(&self) -> &str {
+		"Melody"
+	}
+
+	#[dbus_interface(property)]
+	fn desktop_entry(&self) -> &str {
+		"melody"
+	}
+
+	#[dbus_interface(property)]
+	fn supported_uri_schemes(&self) -> Vec<String> {
+		Vec::new()
+	}
+
+	#[dbus_interface(property)]
+	fn supported_mime_types(&self) -> Vec<String> {
+		Vec::new()
+	}
+}
+
+#[dbus_interface(name = "org.mpris.MediaPlayer2.Player")]
+impl PlayerInterface {
+	fn next(&self) {
+		(self.on_control)(PlayerControl::Next);
+	}
+
+	fn previous(&self) {
+		(self.on_control)(PlayerControl::Previous);
+	}
+
+	fn pause(&self) {
+		(self.on_control)(PlayerControl::Pause);
+	}
+
+	fn play_pause(&self) {
+		(self.on_control)(PlayerControl::PlayPause);
+	}
+
+	fn stop(&self) {
+		(self.on_control)(PlayerControl::Stop);
+	}
+
+	fn play(&self) {
+		(self.on_control)(PlayerControl::Play);
+	}
+
+	/// Offset is given in microseconds.
+	fn seek(&self, offset: i64) {
+		(self.on_control)(PlayerControl::Seek { offset: offset / 1000 });
+	}
+
+	/// Ignored if the track is no longer the current one, as required by the specification.
+	fn set_position(&self, track_id: ObjectPath<'_>, position: i64) {
+		let Some(id) = self.playback.track_id() else {
+			return;
+		};
+
+		if track_id.as_str() == track_path(id).as_str() && position >= 0 {
+			(self.on_control)(PlayerControl::SetPosition {
+				track_id: id,
+				position: position as u64 / 1000,
+			});
+		}
+	}
+
+	fn open_uri(&self, _uri: &str) {}
+
+	#[dbus_interface(signal)]
+	async fn seeked(ctxt: &SignalContext<'_>, position: i64) -> zbus::Result<()>;
+
+	#[dbus_interface(property)]
+	fn playback_status(&self) -> &str {
+		match self.playback.status {
+			PlaybackStatus::Playing => "Playing",
+			PlaybackStatus::Paused => "Paused",
+			PlaybackStatus::Stopped => "Stopped",
+		}
+	}
+
+	#[dbus_interface(property)]
+	fn rate(&self) -> f64 {
+		1.0
+	}
+
+	#[dbus_interface(property)]
+	fn minimum_rate(&self) -> f64 {
+		1.0
+	}
+
+	#[dbus_interface(property)]
+	fn maximum_rate(&self) -> f64 {
+		1.0
+	}
+
+	#[dbus_interface(property)]
+	fn metadata(&self) -> HashMap<String, OwnedValue> {
+		let mut metadata = HashMap::new();
+		let mut insert = |key: &str, value: Value<'_>| {
+			metadata.insert(key.to_string(), OwnedValue::from(value));
+		};
+
+		let Some(track) = &self.playback.track else {
+			insert(
+				"mpris:trackid",
+				Value::from(ObjectPath::from_static_str_unchecked(NO_TRACK_PATH)),
+			);
+			return metadata;
+		};
+
+		insert("mpris:trackid", Value::from(track_path(track.id)));
+		insert("xesam:title", Value::from(track.title.clone()));
+		insert("xesam:artist", Value::from(track.artists.clone()));
+		insert("xesam:albumArtist", Value::from(track.album_artists.clone()));
+		insert("xesam:url", Value::from(format!("file://{}", track.path)));
+
+		if let Some(album) = &track.album {
+			insert("xesam:album", Value::from(album.clone()));
+		}
+
+		if let Some(x) = track.track_number {
+			insert("xesam:trackNumber", Value::from(x as i32));
+		}
+
+		if let Some(x) = track.disc_number {
+			insert("xesam:discNumber", Value::from(x as i32));
+		}
+
+		if let Some(x) = track.length {
+			insert("mpris:length", Value::from(to_micros(x)));
+		}
+
+		if let Some(x) = &track.cover_path {
+			insert("mpris:artUrl", Value::from(format!("file://{}", x.display())));
+		}
+
+		metadata
+	}
+
+	#[dbus_interface(property)]
+	fn volume(&self) -> f64 {
+		self.playback.volume
+	}
+
+	#[dbus_interface(property)]
+	fn set_volume(&mut self, volume: f64) {
+		(self.on_control)(PlayerControl::SetVolume {
+			volume: volume.clamp(0.0, 1.0),
+		});
+	}
+
+	/// Read on demand by the clients, as the specification doesn't allow announcing its changes.
+	#[dbus_interface(property(emits_changed_signal = "false"))]
+	fn position(&self) -> i64 {
+		to_micros(self.playback.position)
+	}
+
+	#[dbus_interface(property)]
+	fn can_go_next(&self) -> bool {
+		self.playback.can_go_next
+	}
+
+	#[dbus_interface(property)]
+	fn can_go_previous(&self) -> bool {
+		self.playback.can_go_previous
+	}
+
+	#[dbus_interface(property)]
+	fn can_play(&self) -> bool {
+		self.playback.track.is_some()
+	}
+
+	#[dbus_interface(property)]
+	fn can_pause(&self) -> bool {
+		self.playback.track.is_some()
+	}
+
+	#[dbus_interface(property)]
+	fn can_seek(&self) -> bool {
+		self.playback.track.is_some()
+	}
+
+	#[dbus_interface(property)]
+	fn can_control(&self) -> bool {
+		true
+	}
+}
+
+fn track_path(id: u64) -> ObjectPath<'static> {
+	ObjectPath::try_from(format!("{TRACK_PATH_PREFIX}{id}")).unwrap()
+}
+
+#[inline]
+fn to_micros(millis: u64) -> i64 {
+	(millis * 1000) as i64
+}
+
+#[cfg(test)]
+mod test {
+	use std::sync::mpsc;
+
+	use zbus::{dbus_proxy, ConnectionBuilder};
+
+	use crate::{
+		errors::Result,
+		models::playback::{Playback, PlaybackStatus, PlayerControl},
+		mpris::{MprisServer, BUS_NAME, OBJECT_PATH},
+	};
+
+	#[dbus_proxy(interface = "org.mpris.MediaPlayer2.Player", assume_defaults = false)]
+	trait Player {
+		fn play_pause(&self) -> zbus::Result<()>;
+
+		#[dbus_proxy(property)]
+		fn playback_status(&self) -> zbus::Result<String>;
+	}
+
+	/// Runs against the session bus, so it's only run on request, like `dbus-run-session cargo test -- --ignored`.
+	///
+	/// Registers under its own name, so it doesn't clash with a running instance of the application.
+	#[tokio::test]
+	#[ignore = "requires a D-Bus session bus"]
+	async fn test_mpris() -> Result<()> {
+		let bus_name = format!("{BUS_NAME}.test{}", ulid::Ulid::new());
+
+		let (tx, rx) = mpsc::channel::<PlayerControl>();
+		let tx = std::sync::Mutex::new(tx);
+		let server = MprisServer::with_name(&bus_name, move |x| tx.lock().unwrap().send(x).unwrap()).await?;
+
+		let playback = Playback {
+			status: PlaybackStatus::Playing,
+			..Default::default()
+		};
+		server.update(playback, false).await?;
+
+		let client = ConnectionBuilder::session()?.build().await?;
+		let proxy = PlayerProxy::builder(&client)
+			.destination(bus_name.as_str())?
+			.path(OBJECT_PATH)?
+			.build()
+			.await?;
+
+		assert_eq!(proxy.playback_status().await?, "Playing");
+
+		proxy.play_pause().await?;
+		assert!(matches!(rx.recv().unwrap(), PlayerControl::PlayPause));
+
+		Ok(())
+	}
+}
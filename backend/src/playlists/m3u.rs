@@ -0,0 +1,118 @@
+use crate::playlists::{PlaylistFile, PlaylistItem};
+
+/// Parses an M3U playlist, reading the duration, artist and title from the `#EXTINF` line preceding an entry.
+///
+/// The name of the playlist is read from the `#PLAYLIST` directive, and other comments are skipped.
+pub fn parse(contents: &str) -> PlaylistFile {
+	let mut file = PlaylistFile::default();
+	let mut info = None::<PlaylistItem>;
+
+	for line in contents.lines().map(str::trim).filter(|x| !x.is_empty()) {
+		if let Some(value) = line.strip_prefix("#EXTINF:") {
+			info = Some(parse_extinf(value));
+		} else if let Some(value) = line.strip_prefix("#PLAYLIST:") {
+			file.title = Some(value.trim().to_string());
+		} else if !line.starts_with('#') {
+			file.items.push(PlaylistItem {
+				location: line.to_string(),
+				..info.take().unwrap_or_default()
+			});
+		}
+	}
+
+	file
+}
+
+pub fn write(file: &PlaylistFile) -> String {
+	let mut out = String::from("#EXTM3U\n");
+
+	if let Some(title) = &file.title {
+		out.push_str(&format!("#PLAYLIST:{title}\n"));
+	}
+
+	for item in &file.items {
+		let seconds = item.duration.map_or(-1, |x| (x / 1000) as i64);
+		let display = match (&item.artist, &item.title) {
+			(Some(artist), Some(title)) => format!("{artist} - {title}"),
+			(None, Some(x)) | (Some(x), None) => x.clone(),
+			(None, None) => String::new(),
+		};
+
+		out.push_str(&format!("#EXTINF:{seconds},{display}\n{}\n", item.location));
+	}
+
+	out
+}
+
+/// Reads `duration [attributes],Artist - Title`, where the duration is in seconds and `-1` when unknown.
+fn parse_extinf(value: &str) -> PlaylistItem {
+	let (head, display) = value.split_once(',').unwrap_or((value, ""));
+
+	let duration = head
+		.split_whitespace()
+		.next()
+		.and_then(|x| x.parse::<f64>().ok())
+		.filter(|x| *x > 0.0)
+		.map(|x| (x * 1000.0) as u64);
+
+	let display = display.trim();
+	let (artist, title) = match display.split_once(" - ") {
+		Some((artist, title)) => (Some(artist.trim()), title.trim()),
+		None => (None, display),
+	};
+
+	PlaylistItem {
+		location: String::new(),
+		title: Some(title.to_string()).filter(|x| !x.is_empty()),
+		artist: artist.map(str::to_string),
+		duration,
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use crate::playlists::{
+		m3u::{parse, write},
+		PlaylistFile, PlaylistItem,
+	};
+
+	#[test]
+	fn test_parse() {
+		let contents =
+			"#EXTM3U\n#PLAYLIST:Mix\n#EXTINF:215,Artist - Title - Live\nArtist\\01.flac\n\n# Comment\n/music/02.mp3\n";
+		let file = parse(contents);
+
+		assert_eq!(file.title.as_deref(), Some("Mix"));
+		assert_eq!(
+			file.items,
+			vec![
+				PlaylistItem {
+					location: "Artist\\01.flac".to_string(),
+					title: Some("Title - Live".to_string()),
+					artist: Some("Artist".to_string()),
+					duration: Some(215_000),
+				},
+				PlaylistItem {
+					location: "/music/02.mp3".to_string(),
+					..Default::default()
+				},
+			]
+		);
+	}
+
+	#[test]
+	fn test_write() {
+		let file = PlaylistFile {
+			title: None,
+			items: vec![PlaylistItem {
+				location: "A/01.flac".to_string(),
+				title: Some("Title".to_string()),
+				artist: Some("Artist".to_string()),
+				duration: None,
+			}],
+		};
+
+		assert_eq!(write(&file), "#EXTM3U\n#EXTINF:-1,Artist - Title\nA/01.flac\n");
+		assert_eq!(parse(&write(&file)).items, file.items);
+	}
+}
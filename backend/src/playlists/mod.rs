@@ -0,0 +1,383 @@
+use std::{
+	collections::{HashMap, HashSet},
+	path::{Component, Path, PathBuf},
+};
+
+use bonsaidb::{
+	core::{document::DocumentId, schema::SerializedCollection},
+	local::AsyncDatabase,
+};
+
+use crate::{
+	database::{
+		methods,
+		models::{person::Person, playlist::Playlist, track::Track, InlinedArtist},
+	},
+	errors::{pre::unsupported_playlist_format, Result},
+	models::tauri::playlist::{PlaylistImport, UnresolvedItem},
+	playlists::resolve::TrackMatcher,
+};
+
+mod m3u;
+mod pls;
+mod resolve;
+mod xspf;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaylistFormat {
+	/// Extended M3U, written as UTF-8 with `#EXTINF` lines.
+	M3u8,
+	Pls,
+	Xspf,
+}
+
+/// An entry of a playlist file, before it's matched against the library.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlaylistItem {
+	/// Path or URL of the file, as written in the playlist.
+	pub location: String,
+	pub title: Option<String>,
+	pub artist: Option<String>,
+	/// Length in milliseconds.
+	pub duration: Option<u64>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct PlaylistFile {
+	pub title: Option<String>,
+	pub items: Vec<PlaylistItem>,
+}
+
+impl PlaylistFormat {
+	/// Resolves the format from the extension of a file, where `.m3u` files are read as M3U8.
+	pub fn from_path(path: &Path) -> Result<Self> {
+		let extension = path
+			.extension()
+			.map(|x| x.to_string_lossy().to_lowercase())
+			.unwrap_or_default();
+
+		match extension.as_str() {
+			"m3u" | "m3u8" => Ok(Self::M3u8),
+			"pls" => Ok(Self::Pls),
+			"xspf" => Ok(Self::Xspf),
+			x => Err(unsupported_playlist_format(x)),
+		}
+	}
+}
+
+impl PlaylistFile {
+	pub fn parse(format: PlaylistFormat, contents: &str) -> Result<Self> {
+		let contents = contents.trim_start_matches('\u{feff}');
+
+		match format {
+			PlaylistFormat::M3u8 => Ok(m3u::parse(contents)),
+			PlaylistFormat::Pls => Ok(pls::parse(contents)),
+			PlaylistFormat::Xspf => xspf::parse(contents),
+		}
+	}
+
+	pub fn write(&self, format: PlaylistFormat) -> String {
+		match format {
+			PlaylistFormat::M3u8 => m3u::write(self),
+			PlaylistFormat::Pls => pls::write(self),
+			PlaylistFormat::Xspf => xspf::write(self),
+		}
+	}
+}
+
+/// Imports a playlist file, matching its entries against the tracks of the library.
+///
+/// Entries that couldn't be matched are left out of the playlist and reported back.
+pub async fn import(database: &AsyncDatabase, path: &Path) -> Result<PlaylistImport> {
+	let format = PlaylistFormat::from_path(path)?;
+	let contents = decode_text(&tokio::fs::read(path).await?);
+	let file = PlaylistFile::parse(format, &contents)?;
+
+	let people = Person::all_async(database)
+		.await?
+		.into_iter()
+		.map(|x| (x.header.id, x.contents.name))
+		.collect::<HashMap<u64, String>>();
+
+	let tracks = Track::all_async(database)
+		.await?
+		.into_iter()
+		.map(|x| (x.header.id, x.contents))
+		.collect::<Vec<(u64, Track)>>();
+
+	let matcher = TrackMatcher::new(&tracks, &people);
+	let base_dir = path.parent().unwrap_or(Path::new(""));
+
+	let mut track_ids = Vec::with_capacity(file.items.len());
+	let mut unresolved = Vec::new();
+
+	for (i, item) in file.items.into_iter().enumerate() {
+		match matcher.resolve(&item, base_dir) {
+			Some(id) => track_ids.push(id),
+			None => unresolved.push(UnresolvedItem {
+				position: i + 1,
+				location: item.location,
+				title: item.title,
+				artist: item.artist,
+			}),
+		}
+	}
+
+	let name = file
+		.title
+		.filter(|x| !x.trim().is_empty())
+		.or_else(|| path.file_stem().map(|x| x.to_string_lossy().to_string()))
+		.unwrap_or_default();
+
+	let playlist = Playlist::new(name, track_ids);
+	let id = methods::playlist::insert(database, playlist.clone()).await?;
+
+	Ok(PlaylistImport {
+		id,
+		playlist,
+		unresolved,
+	})
+}
+
+/// Writes the given tracks into a playlist file, in the format of its extension.
+///
+/// Tracks placed under the directory of the playlist are written with relative paths, the rest with absolute ones.
+pub async fn export(
+	database: &AsyncDatabase,
+	path: &Path,
+	title: Option<String>,
+	tracks: &[(u64, Track)],
+) -> Result<()> {
+	let format = PlaylistFormat::from_path(path)?;
+	let base_dir = path.parent().unwrap_or(Path::new(""));
+
+	let person_ids = tracks
+		.iter()
+		.flat_map(|(_, x)| x.artists.iter().map(|e| DocumentId::from_u64(e.id)))
+		.collect::<HashSet<DocumentId>>();
+
+	let people = Person::get_multiple_async(&person_ids, database)
+		.await?
+		.into_iter()
+		.map(|x| (x.header.id, x.contents.name))
+		.collect::<HashMap<u64, String>>();
+
+	let items = tracks
+		.iter()
+		.map(|(_, track)| PlaylistItem {
+			location: path_to_location(Path::new(&track.path), base_dir),
+			title: Some(track.title.clone()),
+			artist: Some(get_artist_name(&track.artists, &people)).filter(|x| !x.is_empty()),
+			duration: track.segment.and_then(|x| Some(x.end? - x.start)),
+		})
+		.collect();
+
+	let file = PlaylistFile { title, items };
+	tokio::fs::write(path, file.write(format)).await?;
+
+	Ok(())
+}
+
+/// Joins the credited names of the artists, like "A feat. B".
+fn get_artist_name(artists: &[InlinedArtist], people: &HashMap<u64, String>) -> String {
+	let mut name = String::new();
+
+	for (i, artist) in artists.iter().enumerate() {
+		match &artist.credited_as {
+			Some(x) => name.push_str(x),
+			None => name.push_str(people.get(&artist.id).map_or("", String::as_str)),
+		}
+
+		if i + 1 < artists.len() {
+			name.push_str(artist.join.as_deref().unwrap_or(", "));
+		}
+	}
+
+	name
+}
+
+/// Reads a playlist as UTF-8, falling back to Latin-1 for the M3U files written in a legacy code page.
+fn decode_text(bytes: &[u8]) -> String {
+	match std::str::from_utf8(bytes) {
+		Ok(x) => x.to_string(),
+		Err(_) => bytes.iter().map(|x| *x as char).collect(),
+	}
+}
+
+/// Turns a location into a path, resolving relative paths against the directory of the playlist.
+///
+/// `file://` URLs are decoded, while URLs of other schemes have no path and return [None].
+/// Backslashes are read as separators, as playlists written on Windows use them.
+fn location_to_path(location: &str, base_dir: &Path) -> Option<PathBuf> {
+	let location = location.trim();
+
+	let path = match location.get(..7) {
+		Some(x) if x.eq_ignore_ascii_case("file://") => {
+			let rest = &location[7..];
+			let decoded = percent_decode(rest.strip_prefix("localhost").unwrap_or(rest));
+
+			// Drive letters are preceded by a slash, like `file:///C:/Music`.
+			match decoded.as_bytes() {
+				[b'/', x, b':', ..] if x.is_ascii_alphabetic() => decoded[1..].to_string(),
+				_ => decoded,
+			}
+		}
+		_ if has_scheme(location) => return None,
+		_ => location.to_string(),
+	};
+
+	if path.is_empty() {
+		return None;
+	}
+
+	let path = PathBuf::from(path.replace('\\', "/"));
+	let path = match is_absolute(&path) {
+		true => path,
+		false => base_dir.join(path),
+	};
+
+	Some(normalize_path(&path))
+}
+
+/// Writes a path relative to the directory of the playlist when it's placed under it, or as an absolute path.
+fn path_to_location(path: &Path, base_dir: &Path) -> String {
+	match path.strip_prefix(base_dir) {
+		Ok(x) if !base_dir.as_os_str().is_empty() => x
+			.components()
+			.map(|x| x.as_os_str().to_string_lossy())
+			.collect::<Vec<_>>()
+			.join("/"),
+		_ => path.to_string_lossy().to_string(),
+	}
+}
+
+/// Tells apart URLs from Windows paths, which both have a colon after their first letters.
+fn has_scheme(location: &str) -> bool {
+	match location.split_once("://") {
+		Some((scheme, _)) => {
+			scheme.len() > 1
+				&& scheme
+					.chars()
+					.all(|x| x.is_ascii_alphanumeric() || matches!(x, '+' | '-' | '.'))
+		}
+		None => false,
+	}
+}
+
+fn is_absolute(path: &Path) -> bool {
+	let value = path.to_string_lossy();
+	let bytes = value.as_bytes();
+
+	path.is_absolute() || bytes.first() == Some(&b'/') || matches!(bytes, [x, b':', ..] if x.is_ascii_alphabetic())
+}
+
+/// Resolves the `.` and `..` components without touching the file system.
+fn normalize_path(path: &Path) -> PathBuf {
+	let mut normalized = PathBuf::new();
+
+	for component in path.components() {
+		match component {
+			Component::CurDir => {}
+			Component::ParentDir => {
+				normalized.pop();
+			}
+			x => normalized.push(x.as_os_str()),
+		}
+	}
+
+	normalized
+}
+
+fn percent_decode(value: &str) -> String {
+	let bytes = value.as_bytes();
+	let mut decoded = Vec::with_capacity(bytes.len());
+	let mut i = 0;
+
+	while i < bytes.len() {
+		let hex = bytes.get(i + 1..i + 3).and_then(|x| std::str::from_utf8(x).ok());
+
+		match (bytes[i], hex.and_then(|x| u8::from_str_radix(x, 16).ok())) {
+			(b'%', Some(x)) => {
+				decoded.push(x);
+				i += 3;
+			}
+			(x, _) => {
+				decoded.push(x);
+				i += 1;
+			}
+		}
+	}
+
+	String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// Encodes everything but the unreserved characters and the separators of a path.
+fn percent_encode(value: &str) -> String {
+	let mut encoded = String::with_capacity(value.len());
+
+	for x in value.bytes() {
+		match x {
+			b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+				encoded.push(x as char)
+			}
+			x => encoded.push_str(&format!("%{x:02X}")),
+		}
+	}
+
+	encoded
+}
+
+#[cfg(test)]
+mod test {
+	use std::path::{Path, PathBuf};
+
+	use super::{location_to_path, path_to_location, percent_decode, percent_encode};
+
+	#[test]
+	fn test_location_to_path() {
+		let base_dir = Path::new("/music/playlists");
+
+		let cases = [
+			("../Artist/01 Track.flac", Some("/music/Artist/01 Track.flac")),
+			("/music/./Artist/02.flac", Some("/music/Artist/02.flac")),
+			("Artist\\03.flac", Some("/music/playlists/Artist/03.flac")),
+			(
+				"file:///music/Artist/04%20Track.flac",
+				Some("/music/Artist/04 Track.flac"),
+			),
+			("file://localhost/music/05.flac", Some("/music/05.flac")),
+			("file:///C:/Music/06.flac", Some("C:/Music/06.flac")),
+			("D:\\Music\\07.flac", Some("D:/Music/07.flac")),
+			("http://radio.example/stream", None),
+			("", None),
+		];
+
+		for (location, expected) in cases {
+			assert_eq!(
+				location_to_path(location, base_dir),
+				expected.map(PathBuf::from),
+				"{location}"
+			);
+		}
+	}
+
+	#[test]
+	fn test_path_to_location() {
+		let base_dir = Path::new("/music");
+
+		assert_eq!(path_to_location(Path::new("/music/A/01.flac"), base_dir), "A/01.flac");
+		assert_eq!(
+			path_to_location(Path::new("/other/01.flac"), base_dir),
+			"/other/01.flac"
+		);
+	}
+
+	#[test]
+	fn test_percent_encoding() {
+		let value = "/music/Sigur Rós/Ágætis byrjun #1.flac";
+		let encoded = percent_encode(value);
+
+		assert_eq!(encoded, "/music/Sigur%20R%C3%B3s/%C3%81g%C3%A6tis%20byrjun%20%231.flac");
+		assert_eq!(percent_decode(&encoded), value);
+	}
+}
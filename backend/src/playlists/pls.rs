@@ -0,0 +1,115 @@
+use std::collections::BTreeMap;
+
+use crate::playlists::{PlaylistFile, PlaylistItem};
+
+/// Parses a PLS playlist, where the `FileN`, `TitleN` and `LengthN` keys describe the entry `N`.
+///
+/// Entries are ordered by their number, and the ones without a `FileN` key are skipped.
+pub fn parse(contents: &str) -> PlaylistFile {
+	let mut items = BTreeMap::<u32, PlaylistItem>::new();
+
+	for line in contents.lines().map(str::trim) {
+		let Some((key, value)) = line.split_once('=') else {
+			continue;
+		};
+
+		let key = key.trim().to_lowercase();
+		let split = key.find(|x: char| x.is_ascii_digit()).unwrap_or(key.len());
+		let (name, number) = key.split_at(split);
+
+		let Ok(number) = number.parse::<u32>() else {
+			continue;
+		};
+
+		let item = items.entry(number).or_default();
+		let value = value.trim();
+
+		match name {
+			"file" => item.location = value.to_string(),
+			"title" => item.title = Some(value.to_string()).filter(|x| !x.is_empty()),
+			"length" => {
+				item.duration = value.parse::<i64>().ok().filter(|x| *x > 0).map(|x| x as u64 * 1000);
+			}
+			_ => {}
+		}
+	}
+
+	PlaylistFile {
+		title: None,
+		items: items.into_values().filter(|x| !x.location.is_empty()).collect(),
+	}
+}
+
+/// Writes a PLS playlist, where the title of an entry holds both its artist and title.
+pub fn write(file: &PlaylistFile) -> String {
+	let mut out = String::from("[playlist]\n");
+
+	for (i, item) in file.items.iter().enumerate() {
+		let number = i + 1;
+		out.push_str(&format!("File{number}={}\n", item.location));
+
+		let display = match (&item.artist, &item.title) {
+			(Some(artist), Some(title)) => Some(format!("{artist} - {title}")),
+			(None, Some(x)) => Some(x.clone()),
+			_ => None,
+		};
+
+		if let Some(x) = display {
+			out.push_str(&format!("Title{number}={x}\n"));
+		}
+
+		let seconds = item.duration.map_or(-1, |x| (x / 1000) as i64);
+		out.push_str(&format!("Length{number}={seconds}\n"));
+	}
+
+	out.push_str(&format!("NumberOfEntries={}\nVersion=2\n", file.items.len()));
+	out
+}
+
+#[cfg(test)]
+mod test {
+	use crate::playlists::{
+		pls::{parse, write},
+		PlaylistFile, PlaylistItem,
+	};
+
+	#[test]
+	fn test_parse() {
+		let contents = "[playlist]\nFile2=02.flac\nFile1=01.flac\nTitle1=First\nLength1=60\nLength2=-1\nTitle3=Orphan\nNumberOfEntries=2\n";
+		let file = parse(contents);
+
+		assert_eq!(
+			file.items,
+			vec![
+				PlaylistItem {
+					location: "01.flac".to_string(),
+					title: Some("First".to_string()),
+					artist: None,
+					duration: Some(60_000),
+				},
+				PlaylistItem {
+					location: "02.flac".to_string(),
+					..Default::default()
+				},
+			]
+		);
+	}
+
+	#[test]
+	fn test_write() {
+		let file = PlaylistFile {
+			title: None,
+			items: vec![PlaylistItem {
+				location: "01.flac".to_string(),
+				title: Some("Title".to_string()),
+				artist: Some("Artist".to_string()),
+				duration: Some(61_500),
+			}],
+		};
+
+		assert_eq!(
+			write(&file),
+			"[playlist]\nFile1=01.flac\nTitle1=Artist - Title\nLength1=61\nNumberOfEntries=1\nVersion=2\n"
+		);
+	}
+}
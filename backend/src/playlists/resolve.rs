@@ -0,0 +1,267 @@
+use std::{
+	collections::HashMap,
+	path::{Path, PathBuf},
+};
+
+use crate::{
+	database::models::track::Track,
+	playlists::{get_artist_name, location_to_path, normalize_path, PlaylistItem},
+};
+
+/// Matches the entries of a playlist file against the tracks of the library.
+///
+/// An entry is looked up by its path first. Files that moved are then matched by their file name,
+/// preferring the tracks sharing the most parent directories, and finally by their title and artist.
+/// A file name alone isn't enough, so it must come with a shared parent directory or a matching title.
+/// Entries matching more than one track by their title and artist are left unresolved.
+pub struct TrackMatcher {
+	tracks: Vec<IndexedTrack>,
+	by_path: HashMap<String, Vec<usize>>,
+	by_file_name: HashMap<String, Vec<usize>>,
+	by_title: HashMap<String, Vec<usize>>,
+}
+
+struct IndexedTrack {
+	id: u64,
+	/// Lowercase components of the path, compared case-insensitively as the playlist may come from another system.
+	components: Vec<String>,
+	title: String,
+	artists: Vec<String>,
+	/// Start of the segment, to pick the first track of a file split by a CUE sheet.
+	start: u64,
+}
+
+impl TrackMatcher {
+	pub fn new(tracks: &[(u64, Track)], people: &HashMap<u64, String>) -> Self {
+		let mut matcher = Self {
+			tracks: Vec::with_capacity(tracks.len()),
+			by_path: HashMap::new(),
+			by_file_name: HashMap::new(),
+			by_title: HashMap::new(),
+		};
+
+		for (i, (id, track)) in tracks.iter().enumerate() {
+			let mut artists = track
+				.artists
+				.iter()
+				.filter_map(|x| x.credited_as.as_ref().or_else(|| people.get(&x.id)))
+				.map(|x| normalize_text(x))
+				.collect::<Vec<String>>();
+			artists.push(normalize_text(&get_artist_name(&track.artists, people)));
+
+			let components = get_components(&normalize_path(&PathBuf::from(track.path.replace('\\', "/"))));
+			let indexed = IndexedTrack {
+				id: *id,
+				title: normalize_text(&track.title),
+				artists,
+				start: track.segment.map_or(0, |x| x.start),
+				components,
+			};
+
+			matcher.by_path.entry(indexed.components.join("/")).or_default().push(i);
+			if let Some(x) = indexed.components.last() {
+				matcher.by_file_name.entry(x.clone()).or_default().push(i);
+			}
+			matcher.by_title.entry(indexed.title.clone()).or_default().push(i);
+
+			matcher.tracks.push(indexed);
+		}
+
+		matcher
+	}
+
+	pub fn resolve(&self, item: &PlaylistItem, base_dir: &Path) -> Option<u64> {
+		let (artist, title) = split_display(item);
+
+		if let Some(path) = location_to_path(&item.location, base_dir) {
+			let components = get_components(&path);
+
+			if let Some(candidates) = self.by_path.get(&components.join("/")) {
+				if let Some(id) = self.pick(candidates, title.as_deref()) {
+					return Some(id);
+				}
+			}
+
+			let candidates = components.last().and_then(|x| self.by_file_name.get(x));
+			if let Some(candidates) = candidates {
+				let score = |i: &usize| {
+					let other = &self.tracks[*i].components;
+					components
+						.iter()
+						.rev()
+						.zip(other.iter().rev())
+						.take_while(|(a, b)| a == b)
+						.count()
+				};
+
+				let best = candidates.iter().map(score).max().unwrap_or(0);
+				let candidates = candidates
+					.iter()
+					.copied()
+					.filter(|x| score(x) == best)
+					// Common file names like `01.flac` only count along with a parent directory or the title.
+					.filter(|x| best > 1 || title.as_ref().is_some_and(|y| self.tracks[*x].title == *y))
+					.collect::<Vec<_>>();
+
+				if let Some(id) = self.pick(&candidates, title.as_deref()) {
+					return Some(id);
+				}
+			}
+		}
+
+		let title = title?;
+		let mut candidates = self.by_title.get(&title)?.clone();
+
+		if let Some(artist) = artist {
+			candidates.retain(|i| self.tracks[*i].artists.iter().any(|x| !x.is_empty() && *x == artist));
+		}
+
+		match candidates[..] {
+			[i] => Some(self.tracks[i].id),
+			_ => None,
+		}
+	}
+
+	/// Picks the only candidate, narrowing them down by title when there's more than one.
+	///
+	/// Candidates that all share a single file are the tracks of a CUE sheet, where the first one is picked.
+	fn pick(&self, candidates: &[usize], title: Option<&str>) -> Option<u64> {
+		let mut candidates = candidates.to_vec();
+
+		if let Some(title) = title.filter(|_| candidates.len() > 1) {
+			let matches = candidates
+				.iter()
+				.copied()
+				.filter(|i| self.tracks[*i].title == title)
+				.collect::<Vec<_>>();
+
+			if !matches.is_empty() {
+				candidates = matches;
+			}
+		}
+
+		let first = &self.tracks[*candidates.first()?];
+		if candidates
+			.iter()
+			.all(|i| self.tracks[*i].components == first.components)
+		{
+			return candidates
+				.iter()
+				.map(|i| &self.tracks[*i])
+				.min_by_key(|x| x.start)
+				.map(|x| x.id);
+		}
+
+		None
+	}
+}
+
+/// Gets the normalized artist and title of an entry, splitting titles like "Artist - Title" when there's no artist.
+fn split_display(item: &PlaylistItem) -> (Option<String>, Option<String>) {
+	let title = item.title.as_deref().map(str::trim).filter(|x| !x.is_empty());
+
+	match (item.artist.as_deref(), title) {
+		(None, Some(x)) if x.contains(" - ") => {
+			let (artist, title) = x.split_once(" - ").unwrap();
+			(Some(normalize_text(artist)), Some(normalize_text(title)))
+		}
+		(artist, title) => (artist.map(normalize_text), title.map(normalize_text)),
+	}
+}
+
+fn get_components(path: &Path) -> Vec<String> {
+	path.components()
+		.map(|x| x.as_os_str().to_string_lossy().to_lowercase())
+		.filter(|x| x != "/")
+		.collect()
+}
+
+/// Lowercases the text and drops everything but letters and digits, so punctuation and spacing don't matter.
+fn normalize_text(value: &str) -> String {
+	value
+		.chars()
+		.filter(|x| x.is_alphanumeric())
+		.flat_map(char::to_lowercase)
+		.collect()
+}
+
+#[cfg(test)]
+mod test {
+	use std::{collections::HashMap, path::Path};
+
+	use crate::{
+		database::models::{
+			track::{Track, TrackSegment},
+			InlinedArtist,
+		},
+		playlists::{resolve::TrackMatcher, PlaylistItem},
+	};
+
+	fn track(path: &str, title: &str, start: Option<u64>) -> Track {
+		Track {
+			title: title.to_string(),
+			artists: vec![InlinedArtist {
+				id: 1,
+				credited_as: None,
+				join: None,
+			}],
+			path: path.to_string(),
+			segment: start.map(|start| TrackSegment { start, end: None }),
+			..Default::default()
+		}
+	}
+
+	fn item(location: &str, title: Option<&str>, artist: Option<&str>) -> PlaylistItem {
+		PlaylistItem {
+			location: location.to_string(),
+			title: title.map(str::to_string),
+			artist: artist.map(str::to_string),
+			duration: None,
+		}
+	}
+
+	#[test]
+	fn test_resolve() {
+		let tracks = vec![
+			(1, track("/music/Artist/Album/01.flac", "Intro", None)),
+			(2, track("/music/Artist/Other/01.flac", "Opening", None)),
+			(3, track("/music/Artist/Image.flac", "First", Some(0))),
+			(4, track("/music/Artist/Image.flac", "Second", Some(60_000))),
+			(5, track("/music/Artist/Album/02 Song.flac", "Don't Stop", None)),
+			(6, track("/music/Artist/Album/03.flac", "Reprise", None)),
+			(7, track("/music/Artist/Other/03.flac", "Reprise", None)),
+		];
+		let people = HashMap::from([(1, "The Artist".to_string())]);
+		let matcher = TrackMatcher::new(&tracks, &people);
+		let base_dir = Path::new("/music/playlists");
+
+		let cases = [
+			// Relative path.
+			(item("../Artist/Album/01.flac", None, None), Some(1)),
+			// Moved library, matched by the parent directories.
+			(item("D:\\Music\\Artist\\Other\\01.flac", None, None), Some(2)),
+			// Same file name in two directories, told apart by the title.
+			(item("/old/01.flac", Some("Opening"), None), Some(2)),
+			(item("/old/01.flac", None, None), None),
+			// A file name alone isn't enough.
+			(item("/old/02 Song.flac", None, None), None),
+			(item("/old/Album/02 Song.flac", None, None), Some(5)),
+			// Tracks of a CUE sheet.
+			(item("/music/Artist/Image.flac", Some("Second"), None), Some(4)),
+			(item("/music/Artist/Image.flac", None, None), Some(3)),
+			// Missing file, matched by the title and artist.
+			(item("/old/song.mp3", Some("dont stop"), Some("the artist")), Some(5)),
+			(item("http://stream", Some("The Artist - Don't Stop"), None), Some(5)),
+			(item("/old/song.mp3", Some("Don't Stop"), Some("Someone Else")), None),
+			// Artists are compared as a whole.
+			(item("/old/song.mp3", Some("Don't Stop"), Some("Artist")), None),
+			// Titles shared by more than one track.
+			(item("/old/song.mp3", Some("Intro"), None), Some(1)),
+			(item("/old/song.mp3", Some("Reprise"), None), None),
+		];
+
+		for (item, expected) in cases {
+			assert_eq!(matcher.resolve(&item, base_dir), expected, "{item:?}");
+		}
+	}
+}
@@ -0,0 +1,193 @@
+use std::path::Path;
+
+use quick_xml::{escape::escape, events::Event, Reader};
+
+use crate::{
+	errors::{pre::invalid_playlist, Result},
+	playlists::{has_scheme, is_absolute, percent_decode, percent_encode, PlaylistFile, PlaylistItem},
+};
+
+/// Parses an XSPF playlist, reading the first `location` of every `track`.
+///
+/// Relative locations are percent-decoded here, while `file://` URLs are decoded when they're resolved.
+pub fn parse(contents: &str) -> Result<PlaylistFile> {
+	let mut reader = Reader::from_str(contents);
+	reader.trim_text(true);
+
+	let mut file = PlaylistFile::default();
+	let mut stack = Vec::<String>::new();
+
+	loop {
+		let event = reader
+			.read_event()
+			.map_err(|e| invalid_playlist(reader.buffer_position(), &e.to_string()))?;
+
+		let text = match event {
+			Event::Start(e) => {
+				let name = String::from_utf8_lossy(e.local_name().as_ref()).to_string();
+				if name == "track" && stack.last().is_some_and(|x| x == "trackList") {
+					file.items.push(PlaylistItem::default());
+				}
+
+				stack.push(name);
+				continue;
+			}
+			Event::End(_) => {
+				stack.pop();
+				continue;
+			}
+			Event::Text(e) => e
+				.unescape()
+				.map_err(|e| invalid_playlist(reader.buffer_position(), &e.to_string()))?
+				.to_string(),
+			Event::CData(e) => String::from_utf8_lossy(&e.into_inner()).to_string(),
+			Event::Eof => break,
+			_ => continue,
+		};
+
+		match stack.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+			[.., "playlist", "title"] => file.title = Some(text),
+			[.., "track", field] => {
+				let Some(item) = file.items.last_mut() else {
+					continue;
+				};
+
+				match field {
+					"location" if item.location.is_empty() => {
+						item.location = match has_scheme(&text) {
+							true => text,
+							false => percent_decode(&text),
+						};
+					}
+					"title" => item.title = Some(text),
+					"creator" => item.artist = Some(text),
+					"duration" => item.duration = text.parse::<u64>().ok(),
+					_ => {}
+				}
+			}
+			_ => {}
+		}
+	}
+
+	Ok(file)
+}
+
+/// Writes an XSPF playlist, turning the absolute paths into `file://` URLs.
+pub fn write(file: &PlaylistFile) -> String {
+	let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+	out.push_str("<playlist version=\"1\" xmlns=\"http://xspf.org/ns/0/\">\n");
+
+	if let Some(title) = &file.title {
+		out.push_str(&format!("\t<title>{}</title>\n", escape(title)));
+	}
+
+	out.push_str("\t<trackList>\n");
+
+	for item in &file.items {
+		out.push_str("\t\t<track>\n");
+		out.push_str(&format!(
+			"\t\t\t<location>{}</location>\n",
+			escape(&to_uri(&item.location))
+		));
+
+		if let Some(x) = &item.title {
+			out.push_str(&format!("\t\t\t<title>{}</title>\n", escape(x)));
+		}
+
+		if let Some(x) = &item.artist {
+			out.push_str(&format!("\t\t\t<creator>{}</creator>\n", escape(x)));
+		}
+
+		if let Some(x) = item.duration {
+			out.push_str(&format!("\t\t\t<duration>{x}</duration>\n"));
+		}
+
+		out.push_str("\t\t</track>\n");
+	}
+
+	out.push_str("\t</trackList>\n</playlist>\n");
+	out
+}
+
+fn to_uri(location: &str) -> String {
+	let location = location.replace('\\', "/");
+
+	match is_absolute(Path::new(&location)) {
+		true if location.starts_with('/') => format!("file://{}", percent_encode(&location)),
+		true => format!("file:///{}", percent_encode(&location)),
+		false => percent_encode(&location),
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use crate::playlists::{
+		xspf::{parse, write},
+		PlaylistFile, PlaylistItem,
+	};
+
+	#[test]
+	fn test_parse() {
+		let contents = r#"<?xml version="1.0" encoding="UTF-8"?>
+			<playlist version="1" xmlns="http://xspf.org/ns/0/">
+				<title>Rock &amp; Roll</title>
+				<trackList>
+					<track>
+						<location>Artist/01%20Track.flac</location>
+						<location>ignored.flac</location>
+						<title>Track</title>
+						<creator>Artist</creator>
+						<duration>215000</duration>
+					</track>
+					<track><location>file:///music/02%20Track.flac</location></track>
+				</trackList>
+			</playlist>"#;
+
+		let file = parse(contents).unwrap();
+		assert_eq!(file.title.as_deref(), Some("Rock & Roll"));
+		assert_eq!(
+			file.items,
+			vec![
+				PlaylistItem {
+					location: "Artist/01 Track.flac".to_string(),
+					title: Some("Track".to_string()),
+					artist: Some("Artist".to_string()),
+					duration: Some(215_000),
+				},
+				PlaylistItem {
+					location: "file:///music/02%20Track.flac".to_string(),
+					..Default::default()
+				},
+			]
+		);
+
+		assert!(parse("<playlist><trackList></playlist>").is_err());
+	}
+
+	#[test]
+	fn test_write() {
+		let file = PlaylistFile {
+			title: Some("A & B".to_string()),
+			items: vec![
+				PlaylistItem {
+					location: "/music/01 Track.flac".to_string(),
+					title: Some("<Intro>".to_string()),
+					..Default::default()
+				},
+				PlaylistItem {
+					location: "C:\\Music\\02.flac".to_string(),
+					..Default::default()
+				},
+			],
+		};
+
+		let xml = write(&file);
+		assert!(xml.contains("<title>A &amp; B</title>"));
+		assert!(xml.contains("<location>file:///music/01%20Track.flac</location>"));
+		assert!(xml.contains("<title>&lt;Intro&gt;</title>"));
+		assert!(xml.contains("<location>file:///C:/Music/02.flac</location>"));
+
+		let parsed = parse(&xml).unwrap();
+		assert_eq!(parsed.items[0].location, "file:///music/01%20Track.flac");
+	}
+}
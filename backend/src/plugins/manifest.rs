@@ -0,0 +1,104 @@
+use std::{collections::HashSet, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{pre::invalid_plugin_manifest, Error, Result};
+
+/// Describes a plugin, read from the `plugin.toml` placed next to its `plugin.wasm`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginManifest {
+	/// Unique identifier of the plugin, restricted to lowercase alphanumerics, `-` and `_`.
+	pub id: String,
+	pub name: String,
+	pub version: String,
+
+	/// APIs the plugin is allowed to use. Host functions outside of these are not linked.
+	#[serde(default)]
+	pub capabilities: HashSet<Capability>,
+	/// Commands the plugin handles, requires [Capability::Commands].
+	#[serde(default)]
+	pub commands: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+	/// Imports `melody.library_query` to read releases and tracks.
+	LibraryRead,
+	/// Receives library events through `melody_on_event`.
+	LibraryEvents,
+	/// Receives playback events through `melody_on_event`.
+	PlaybackEvents,
+	/// Contributes track metadata through `melody_provide_metadata`.
+	MetadataProvider,
+	/// Handles the commands listed in [PluginManifest::commands] through `melody_command`.
+	Commands,
+}
+
+impl PluginManifest {
+	pub fn load(path: &Path) -> Result<Self> {
+		let contents = std::fs::read_to_string(path)
+			.map_err(|e| Error::from(e).append_message("Failed to read the plugin manifest"))?;
+
+		Self::parse(&contents)
+	}
+
+	pub fn parse(contents: &str) -> Result<Self> {
+		let manifest = toml::from_str::<Self>(contents)?;
+		manifest.validate()?;
+
+		Ok(manifest)
+	}
+
+	pub fn validate(&self) -> Result<()> {
+		let is_valid_id = !self.id.is_empty()
+			&& self
+				.id
+				.chars()
+				.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+
+		if !is_valid_id {
+			return Err(invalid_plugin_manifest(
+				"id must only contain lowercase alphanumerics, '-' and '_'",
+			));
+		}
+
+		if !self.commands.is_empty() && !self.has(Capability::Commands) {
+			return Err(invalid_plugin_manifest(
+				"commands can't be declared without the 'commands' capability",
+			));
+		}
+
+		Ok(())
+	}
+
+	#[inline]
+	pub fn has(&self, capability: Capability) -> bool {
+		self.capabilities.contains(&capability)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::{Capability, PluginManifest};
+
+	#[test]
+	fn test_parse() {
+		let manifest = PluginManifest::parse(
+			r#"
+			id = "lrc-fetcher"
+			name = "LRC Fetcher"
+			version = "0.1.0"
+			capabilities = ["library_read", "commands"]
+			commands = ["fetch"]
+			"#,
+		)
+		.unwrap();
+
+		assert!(manifest.has(Capability::LibraryRead));
+		assert!(!manifest.has(Capability::LibraryEvents));
+
+		assert!(PluginManifest::parse("id = \"Bad Id\"\nname = \"x\"\nversion = \"0.1.0\"").is_err());
+		assert!(PluginManifest::parse("id = \"x\"\nname = \"x\"\nversion = \"0.1.0\"\ncommands = [\"y\"]").is_err());
+	}
+}
@@ -0,0 +1,275 @@
+use std::path::Path;
+
+use {
+	serde::Serialize,
+	serde_json::{json, Value},
+	tracing::{error, info},
+	wasmtime::{Config, Engine},
+};
+
+use crate::{
+	errors::{
+		pre::{duplicate_plugin, plugin_command_not_found, plugin_not_found},
+		Error, Result,
+	},
+	models::{playback::PlaybackStatus, tauri::plugin::PluginEntry},
+	plugins::{
+		manifest::{Capability, PluginManifest},
+		runtime::{LibraryAccess, Plugin},
+	},
+};
+
+pub mod manifest;
+pub mod runtime;
+
+const MANIFEST_FILE_NAME: &str = "plugin.toml";
+const MODULE_FILE_NAME: &str = "plugin.wasm";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum PluginStatus {
+	Active,
+	/// The plugin either failed to load or trapped, and no longer receives calls.
+	Failed(Error),
+}
+
+/// Events broadcasted through `melody_on_event` to the plugins that have the capability for them.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum PluginEvent {
+	LibraryScanned {
+		locations: Vec<String>,
+		indexed: u64,
+	},
+	PlaybackChanged {
+		track_id: Option<u64>,
+		status: PlaybackStatus,
+	},
+}
+
+impl PluginEvent {
+	fn capability(&self) -> Capability {
+		match self {
+			PluginEvent::LibraryScanned { .. } => Capability::LibraryEvents,
+			PluginEvent::PlaybackChanged { .. } => Capability::PlaybackEvents,
+		}
+	}
+}
+
+/// Loads and runs the WASM plugins found in `data_directory/plugins`.
+///
+/// Every plugin lives in its own folder containing a `plugin.toml` manifest and a `plugin.wasm` module.
+pub struct PluginHost {
+	engine: Engine,
+	plugins: Vec<Plugin>,
+	/// Plugins that couldn't be loaded, kept to report the reason.
+	unloaded: Vec<PluginEntry>,
+}
+
+impl PluginHost {
+	pub fn new() -> Result<Self> {
+		let mut config = Config::new();
+		config.consume_fuel(true);
+
+		Ok(Self {
+			engine: Engine::new(&config)?,
+			plugins: Vec::new(),
+			unloaded: Vec::new(),
+		})
+	}
+
+	pub fn load_dir(&mut self, plugins_dir: &Path, library: LibraryAccess) -> Result<()> {
+		for child in std::fs::read_dir(plugins_dir)? {
+			let path = child?.path();
+			if !path.is_dir() {
+				continue;
+			}
+
+			let dir_name = path.file_name().unwrap().to_string_lossy().to_string();
+			let manifest = match PluginManifest::load(&path.join(MANIFEST_FILE_NAME)) {
+				Ok(x) => x,
+				Err(e) => {
+					error!("Failed to read the manifest of the plugin in '{path:?}': {e:#?}");
+					self.unloaded.push(PluginEntry::failed(dir_name, None, e));
+					continue;
+				}
+			};
+
+			match self.load(&path, manifest.clone(), library.clone()) {
+				Ok(plugin) => {
+					info!("Loaded plugin '{}' v{}", manifest.id, manifest.version);
+					self.plugins.push(plugin);
+				}
+				Err(e) => {
+					error!("Failed to load the plugin '{}': {e:#?}", manifest.id);
+					self.unloaded
+						.push(PluginEntry::failed(manifest.id.clone(), Some(manifest), e));
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	fn load(&self, path: &Path, manifest: PluginManifest, library: LibraryAccess) -> Result<Plugin> {
+		if self.plugins.iter().any(|x| x.manifest.id == manifest.id) {
+			return Err(duplicate_plugin(&manifest.id));
+		}
+
+		let wasm = std::fs::read(path.join(MODULE_FILE_NAME))
+			.map_err(|e| Error::from(e).append_message("Failed to read the plugin module"))?;
+
+		Plugin::new(&self.engine, manifest, &wasm, library)
+	}
+
+	pub fn entries(&self) -> Vec<PluginEntry> {
+		let loaded = self.plugins.iter().map(|x| PluginEntry {
+			id: x.manifest.id.clone(),
+			manifest: Some(x.manifest.clone()),
+			status: x.status.clone(),
+		});
+
+		loaded.chain(self.unloaded.iter().cloned()).collect()
+	}
+
+	/// Sends an event to the active plugins that have the capability for it.
+	///
+	/// Failures are contained to the plugin that caused them, so this never fails.
+	pub fn dispatch(&mut self, event: &PluginEvent) {
+		let capability = event.capability();
+		let payload = match serde_json::to_vec(event) {
+			Ok(x) => x,
+			Err(e) => {
+				error!("Failed to serialize the plugin event {event:?}: {e:#?}");
+				return;
+			}
+		};
+
+		let subscribers = self
+			.plugins
+			.iter_mut()
+			.filter(|x| x.manifest.has(capability) && matches!(x.status, PluginStatus::Active));
+
+		for plugin in subscribers {
+			// Errors are already logged, and the plugin is marked as failed.
+			let _ = plugin.call("melody_on_event", &payload);
+		}
+	}
+
+	/// Runs a command declared in the manifest of a plugin, returning its JSON output.
+	pub fn invoke_command(&mut self, plugin_id: &str, command: &str, payload: Value) -> Result<Value> {
+		let plugin = self
+			.plugins
+			.iter_mut()
+			.find(|x| x.manifest.id == plugin_id)
+			.ok_or_else(|| plugin_not_found(plugin_id))?;
+
+		if !plugin.manifest.commands.iter().any(|x| x == command) {
+			return Err(plugin_command_not_found(plugin_id, command));
+		}
+
+		let input = serde_json::to_vec(&json!({ "command": command, "payload": payload }))?;
+		let output = plugin.call("melody_command", &input)?;
+
+		match output {
+			Some(x) => Ok(serde_json::from_slice(&x)?),
+			None => Ok(Value::Null),
+		}
+	}
+
+	/// Ids of the active plugins that have the [Capability::MetadataProvider] capability.
+	pub fn metadata_provider_ids(&self) -> Vec<String> {
+		self.plugins
+			.iter()
+			.filter(|x| x.manifest.has(Capability::MetadataProvider) && matches!(x.status, PluginStatus::Active))
+			.map(|x| x.manifest.id.clone())
+			.collect()
+	}
+
+	/// Asks a plugin for the tags of a track through `melody_provide_metadata`.
+	pub fn provide_metadata(&mut self, plugin_id: &str, input: &Value) -> Result<Option<Value>> {
+		let plugin = self
+			.plugins
+			.iter_mut()
+			.find(|x| x.manifest.id == plugin_id && x.manifest.has(Capability::MetadataProvider))
+			.ok_or_else(|| plugin_not_found(plugin_id))?;
+
+		let output = plugin.call("melody_provide_metadata", &serde_json::to_vec(input)?)?;
+
+		match output {
+			Some(x) => Ok(Some(serde_json::from_slice(&x)?)),
+			None => Ok(None),
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use {serde_json::json, tokio::runtime::Handle};
+
+	use crate::{
+		database::Database,
+		errors::Result,
+		plugins::{
+			manifest::PluginManifest,
+			runtime::{LibraryAccess, Plugin},
+			PluginEvent, PluginHost, PluginStatus,
+		},
+	};
+
+	/// Echoes the command input back, and traps on every event.
+	const ECHO_PLUGIN: &str = r#"
+		(module
+			(memory (export "memory") 1)
+			(global $next (mut i32) (i32.const 1024))
+			(func (export "melody_alloc") (param $len i32) (result i32)
+				(local $ptr i32)
+				(local.set $ptr (global.get $next))
+				(global.set $next (i32.add (global.get $next) (local.get $len)))
+				(local.get $ptr))
+			(func (export "melody_command") (param $ptr i32) (param $len i32) (result i64)
+				(i64.or
+					(i64.shl (i64.extend_i32_u (local.get $ptr)) (i64.const 32))
+					(i64.extend_i32_u (local.get $len))))
+			(func (export "melody_on_event") (param i32 i32) (result i64)
+				unreachable))
+	"#;
+
+	#[tokio::test]
+	async fn test_plugin_isolation() -> Result<()> {
+		let db = Database::testing().await?;
+		let library = LibraryAccess {
+			database: db.0,
+			handle: Handle::current(),
+		};
+
+		let manifest = PluginManifest::parse(
+			r#"
+			id = "echo"
+			name = "Echo"
+			version = "0.1.0"
+			capabilities = ["commands", "library_events"]
+			commands = ["echo"]
+			"#,
+		)?;
+
+		let mut host = PluginHost::new()?;
+		let plugin = Plugin::new(&host.engine, manifest, ECHO_PLUGIN.as_bytes(), library)?;
+		host.plugins.push(plugin);
+
+		let output = host.invoke_command("echo", "echo", json!({ "value": 1 }))?;
+		assert_eq!(output, json!({ "command": "echo", "payload": { "value": 1 } }));
+		assert!(host.invoke_command("echo", "unknown", json!(null)).is_err());
+
+		let event = PluginEvent::LibraryScanned {
+			locations: vec![],
+			indexed: 0,
+		};
+		host.dispatch(&event);
+
+		assert!(matches!(host.plugins[0].status, PluginStatus::Failed(_)));
+		assert!(host.invoke_command("echo", "echo", json!(null)).is_err());
+
+		Ok(())
+	}
+}
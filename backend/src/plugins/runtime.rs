@@ -0,0 +1,302 @@
+use {
+	bonsaidb::local::AsyncDatabase,
+	serde::{Deserialize, Serialize},
+	tokio::runtime::Handle,
+	tracing::{debug, error, info, warn},
+	wasmtime::{Caller, Engine, Instance, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder, TypedFunc},
+};
+
+use crate::{
+	database::methods,
+	errors::{
+		pre::{invalid_plugin_buffer, invalid_plugin_module, plugin_failed},
+		Error, Result,
+	},
+	models::tauri::{
+		release::{ReleaseFilter, ReleaseSort},
+		track::{TrackFilter, TrackSort},
+		PageRequest,
+	},
+	plugins::{
+		manifest::{Capability, PluginManifest},
+		PluginStatus,
+	},
+};
+
+/// Fuel given to a plugin for every call into it, roughly translating to the count of executed instructions.
+const FUEL_PER_CALL: u64 = 1_000_000_000;
+const MAX_MEMORY_SIZE: usize = 64 * 1024 * 1024;
+
+/// Host side state of a plugin instance.
+pub struct PluginContext {
+	id: String,
+	limits: StoreLimits,
+	library: LibraryAccess,
+}
+
+/// Handle to the library, only reachable through the host functions linked for [Capability::LibraryRead].
+#[derive(Clone)]
+pub struct LibraryAccess {
+	pub database: AsyncDatabase,
+	pub handle: Handle,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum LibraryQuery {
+	Releases {
+		page: PageRequest,
+		sort: ReleaseSort,
+		#[serde(default)]
+		filter: ReleaseFilter,
+	},
+	Tracks {
+		page: PageRequest,
+		sort: TrackSort,
+		#[serde(default)]
+		filter: TrackFilter,
+	},
+}
+
+/// Response of a host function, serialized into the memory of the plugin.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+enum HostResponse {
+	Ok(serde_json::Value),
+	Error(Error),
+}
+
+impl LibraryAccess {
+	/// Runs a query on the runtime of the application, blocking until it's done.
+	///
+	/// Plugins are only called from blocking threads, see [PluginState](crate::models::state::PluginState),
+	/// as blocking on a thread of the runtime would panic.
+	fn query(&self, request: &[u8]) -> Result<serde_json::Value> {
+		let query = serde_json::from_slice::<LibraryQuery>(request)?;
+		let database = &self.database;
+
+		self.handle.block_on(async move {
+			let value = match query {
+				LibraryQuery::Releases { page, sort, filter } => {
+					serde_json::to_value(methods::release::query(database, page, sort, filter).await?)?
+				}
+				LibraryQuery::Tracks { page, sort, filter } => {
+					serde_json::to_value(methods::track::query(database, page, sort, filter).await?)?
+				}
+			};
+
+			Ok(value)
+		})
+	}
+}
+
+/// A loaded plugin instance with its own store, isolated from the other plugins.
+///
+/// Data is exchanged as JSON through the linear memory of the plugin:
+/// - The host allocates the input with `melody_alloc(len) -> ptr` and passes it as `(ptr, len)`.
+/// - Exports return their output packed as `ptr << 32 | len`, or `0` if there's none.
+pub struct Plugin {
+	pub manifest: PluginManifest,
+	pub status: PluginStatus,
+
+	store: Store<PluginContext>,
+	instance: Instance,
+	memory: Memory,
+	alloc: TypedFunc<u32, u32>,
+}
+
+impl Plugin {
+	pub fn new(engine: &Engine, manifest: PluginManifest, wasm: &[u8], library: LibraryAccess) -> Result<Self> {
+		let module = Module::new(engine, wasm)?;
+		let linker = create_linker(engine, &manifest)?;
+
+		let context = PluginContext {
+			id: manifest.id.clone(),
+			limits: StoreLimitsBuilder::new()
+				.memory_size(MAX_MEMORY_SIZE)
+				.instances(1)
+				.build(),
+			library,
+		};
+
+		let mut store = Store::new(engine, context);
+		store.limiter(|x| &mut x.limits);
+		store.set_fuel(FUEL_PER_CALL)?;
+
+		// Imports outside of the granted capabilities are not defined, so instantiating such a module fails here.
+		let instance = linker.instantiate(&mut store, &module).map_err(|e| {
+			Error::from(e).append_message("The plugin might be importing APIs it has no capability for")
+		})?;
+
+		let memory = instance
+			.get_memory(&mut store, "memory")
+			.ok_or_else(|| invalid_plugin_module("missing the 'memory' export"))?;
+		let alloc = instance
+			.get_typed_func::<u32, u32>(&mut store, "melody_alloc")
+			.map_err(|_| invalid_plugin_module("missing the 'melody_alloc' export"))?;
+
+		let mut plugin = Self {
+			manifest,
+			status: PluginStatus::Active,
+			store,
+			instance,
+			memory,
+			alloc,
+		};
+
+		if plugin.has_export("melody_init") {
+			plugin.call("melody_init", b"")?;
+		}
+
+		Ok(plugin)
+	}
+
+	pub fn has_export(&mut self, name: &str) -> bool {
+		self.instance.get_func(&mut self.store, name).is_some()
+	}
+
+	/// Calls an export of the plugin, passing the input through its memory.
+	///
+	/// Any trap, including running out of fuel, marks the plugin as failed and later calls are rejected.
+	pub fn call(&mut self, export: &str, input: &[u8]) -> Result<Option<Vec<u8>>> {
+		if let PluginStatus::Failed(_) = self.status {
+			return Err(plugin_failed(&self.manifest.id));
+		}
+
+		let result = self.call_unchecked(export, input);
+
+		if let Err(e) = &result {
+			error!("Plugin '{}' failed while calling '{export}': {e:#?}", self.manifest.id);
+			self.status = PluginStatus::Failed(e.clone());
+		}
+
+		result
+	}
+
+	fn call_unchecked(&mut self, export: &str, input: &[u8]) -> Result<Option<Vec<u8>>> {
+		self.store.set_fuel(FUEL_PER_CALL)?;
+
+		let func = self
+			.instance
+			.get_typed_func::<(u32, u32), u64>(&mut self.store, export)?;
+
+		let len = input.len() as u32;
+		let ptr = self.alloc.call(&mut self.store, len)?;
+		self.memory.write(&mut self.store, ptr as usize, input)?;
+
+		let packed = func.call(&mut self.store, (ptr, len))?;
+		if packed == 0 {
+			return Ok(None);
+		}
+
+		let (ptr, len) = unpack(packed);
+		let output = copy_out(self.memory.data(&self.store), ptr, len)
+			.ok_or_else(|| invalid_plugin_buffer(&self.manifest.id))?;
+
+		Ok(Some(output))
+	}
+}
+
+/// Defines the host functions under the `melody` import module.
+fn create_linker(engine: &Engine, manifest: &PluginManifest) -> Result<Linker<PluginContext>> {
+	let mut linker = Linker::new(engine);
+
+	linker.func_wrap(
+		"melody",
+		"log",
+		|mut caller: Caller<'_, PluginContext>, level: u32, ptr: u32, len: u32| -> wasmtime::Result<()> {
+			let bytes = read_bytes(&mut caller, ptr, len)?;
+			let message = String::from_utf8_lossy(&bytes);
+			let id = &caller.data().id;
+
+			match level {
+				0 => debug!("[plugin:{id}] {message}"),
+				1 => info!("[plugin:{id}] {message}"),
+				2 => warn!("[plugin:{id}] {message}"),
+				_ => error!("[plugin:{id}] {message}"),
+			}
+
+			Ok(())
+		},
+	)?;
+
+	if manifest.has(Capability::LibraryRead) {
+		linker.func_wrap(
+			"melody",
+			"library_query",
+			|mut caller: Caller<'_, PluginContext>, ptr: u32, len: u32| -> wasmtime::Result<u64> {
+				let request = read_bytes(&mut caller, ptr, len)?;
+				let response = match caller.data().library.query(&request) {
+					Ok(value) => HostResponse::Ok(value),
+					Err(e) => HostResponse::Error(e),
+				};
+
+				write_bytes(&mut caller, &serde_json::to_vec(&response)?)
+			},
+		)?;
+	}
+
+	Ok(linker)
+}
+
+fn get_memory(caller: &mut Caller<'_, PluginContext>) -> wasmtime::Result<Memory> {
+	caller
+		.get_export("memory")
+		.and_then(|x| x.into_memory())
+		.ok_or_else(|| wasmtime::Error::msg("Plugin is missing the 'memory' export"))
+}
+
+fn read_bytes(caller: &mut Caller<'_, PluginContext>, ptr: u32, len: u32) -> wasmtime::Result<Vec<u8>> {
+	let memory = get_memory(caller)?;
+	copy_out(memory.data(&*caller), ptr as usize, len as usize)
+		.ok_or_else(|| wasmtime::Error::msg("Plugin passed a buffer outside of its memory"))
+}
+
+/// Copies a buffer out of the memory of a plugin, or none if it doesn't fit in the memory.
+///
+/// The bounds are checked before anything is allocated, as the length comes from the plugin.
+fn copy_out(data: &[u8], ptr: usize, len: usize) -> Option<Vec<u8>> {
+	let end = ptr.checked_add(len)?;
+	data.get(ptr..end).map(<[u8]>::to_vec)
+}
+
+/// Copies the bytes into a buffer allocated by the plugin, returning the packed pointer.
+fn write_bytes(caller: &mut Caller<'_, PluginContext>, bytes: &[u8]) -> wasmtime::Result<u64> {
+	let memory = get_memory(caller)?;
+	let alloc = caller
+		.get_export("melody_alloc")
+		.and_then(|x| x.into_func())
+		.ok_or_else(|| wasmtime::Error::msg("Plugin is missing the 'melody_alloc' export"))?
+		.typed::<u32, u32>(&*caller)?;
+
+	let len = bytes.len() as u32;
+	let ptr = alloc.call(&mut *caller, len)?;
+	memory.write(&mut *caller, ptr as usize, bytes)?;
+
+	Ok(pack(ptr, len))
+}
+
+#[inline]
+fn pack(ptr: u32, len: u32) -> u64 {
+	((ptr as u64) << 32) | len as u64
+}
+
+#[inline]
+fn unpack(packed: u64) -> (usize, usize) {
+	((packed >> 32) as usize, (packed & 0xFFFF_FFFF) as usize)
+}
+
+#[cfg(test)]
+mod test {
+	use super::copy_out;
+
+	#[test]
+	fn test_copy_out() {
+		let data = [1, 2, 3, 4];
+
+		assert_eq!(copy_out(&data, 1, 2), Some(vec![2, 3]));
+		assert_eq!(copy_out(&data, 4, 0), Some(vec![]));
+		assert_eq!(copy_out(&data, 3, 2), None);
+		assert_eq!(copy_out(&data, usize::MAX, 2), None);
+	}
+}
@@ -0,0 +1,665 @@
+use std::collections::{hash_map::Entry, HashMap, HashSet};
+
+use {
+	bonsaidb::{
+		core::{
+			document::{CollectionDocument, DocumentId},
+			schema::{SerializedCollection, SerializedView},
+		},
+		local::AsyncDatabase,
+	},
+	chrono::{DateTime, Utc},
+	tracing::warn,
+};
+
+use crate::{
+	database::{
+		methods,
+		models::{
+			label::Label,
+			person::Person,
+			play::Play,
+			playlist::Playlist,
+			release::Release,
+			tag::Tag,
+			track::{Track, MAX_RATING},
+		},
+		views::label::LabelByName,
+	},
+	errors::{pre::invalid_library_export, Result},
+	models::tauri::portable::{PathMapping, PortableImport},
+	portable::records::{
+		HeaderRecord, LabelRecord, PersonRecord, PlayRecord, PlaylistRecord, RatingRecord, Record, ReleaseRecord,
+		TagRecord, TrackRecord,
+	},
+};
+
+pub mod records;
+
+/// Version of the export format, bumped whenever a field is removed or changes its meaning.
+///
+/// Fields and record kinds can be added without a bump, as they're skipped by older versions.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Writes the metadata of a library as JSON Lines, one [Record] per line.
+///
+/// People, labels and tags are shared between libraries, so they're written in full.
+/// Playlists, ratings and plays only keep the tracks of the exported library.
+pub async fn export(database: &AsyncDatabase, library_id: u64) -> Result<String> {
+	let mut records = vec![Record::Header(HeaderRecord {
+		version: FORMAT_VERSION,
+		exported_at: Utc::now(),
+	})];
+
+	for doc in Person::all_async(database).await? {
+		records.push(Record::Person(PersonRecord {
+			id: doc.header.id,
+			name: doc.contents.name,
+			name_sort: doc.contents.name_sort,
+			mbz_id: doc.contents.mbz_id,
+			type_: doc.contents.type_,
+		}));
+	}
+
+	for doc in Label::all_async(database).await? {
+		records.push(Record::Label(LabelRecord {
+			id: doc.header.id,
+			name: doc.contents.name,
+			name_sort: doc.contents.name_sort,
+			country: doc.contents.country,
+			mbz_id: doc.contents.mbz_id,
+		}));
+	}
+
+	for doc in Tag::all_async(database).await? {
+		records.push(Record::Tag(TagRecord {
+			id: doc.header.id,
+			name: doc.contents.name,
+			type_: doc.contents.type_,
+			parent_id: doc.contents.parent_id,
+			aliases: doc.contents.aliases.unwrap_or_default(),
+		}));
+	}
+
+	let release_ids = methods::library::get_release_ids(database, library_id).await?;
+	for (id, release) in get_sorted::<Release>(database, &release_ids).await? {
+		records.push(Record::Release(ReleaseRecord {
+			id,
+			name: release.name,
+			mbz_id: release.mbz_id,
+			catalog_number: release.catalog_number,
+			date: release.date,
+			year: release.year,
+			artist_ids: release.artists.iter().map(|x| x.id).collect(),
+			label_ids: release.label_ids.unwrap_or_default(),
+			genre_ids: release.genre_ids.unwrap_or_default(),
+			tag_ids: release.tag_ids.unwrap_or_default(),
+			date_added: release.date_added,
+		}));
+	}
+
+	let track_ids = methods::library::get_track_ids(database, library_id).await?;
+	let mut ratings = Vec::new();
+	for (id, track) in get_sorted::<Track>(database, &track_ids).await? {
+		if let Some(value) = track.rating {
+			ratings.push(Record::Rating(RatingRecord { track_id: id, value }));
+		}
+
+		records.push(Record::Track(TrackRecord {
+			id,
+			path: track.path,
+			segment_start: track.segment.map(|x| x.start),
+			mbz_id: track.mbz_id,
+			title: track.title,
+			release_id: track.release_id,
+			artist_ids: track.artists.iter().map(|x| x.id).collect(),
+			credits: track.credits,
+			genre_ids: track.genre_ids.unwrap_or_default(),
+			tag_ids: track.tag_ids.unwrap_or_default(),
+			date_added: track.date_added,
+		}));
+	}
+
+	for (_, playlist) in methods::playlist::get_all(database).await? {
+		records.push(Record::Playlist(PlaylistRecord {
+			name: playlist.name,
+			description: playlist.description,
+			track_ids: playlist
+				.track_ids
+				.into_iter()
+				.filter(|x| track_ids.contains(x))
+				.collect(),
+			date_added: playlist.date_added,
+		}));
+	}
+
+	records.extend(ratings);
+
+	let mut plays = Play::all_async(database)
+		.await?
+		.into_iter()
+		.map(|x| x.contents)
+		.filter(|x| track_ids.contains(&x.track_id))
+		.collect::<Vec<Play>>();
+	plays.sort_by_key(|x| (x.played_at, x.track_id));
+
+	for play in plays {
+		records.push(Record::Play(PlayRecord {
+			track_id: play.track_id,
+			played_at: play.played_at,
+		}));
+	}
+
+	let mut contents = String::new();
+	for record in &records {
+		contents.push_str(&serde_json::to_string(record)?);
+		contents.push('\n');
+	}
+
+	Ok(contents)
+}
+
+/// Merges an export into a library, matching its records against what's already in the database.
+///
+/// Tracks are matched by their path, rewritten through the first matching mapping, and then by their MusicBrainz
+/// recording id. Releases are matched by their MusicBrainz id, or through their matched tracks.
+/// Nothing is scanned, so records without a match are skipped instead of being inserted, except for tags and playlists.
+/// Matched entries only gain the tags, labels and details they're missing, along with the earlier date they were added.
+/// Ratings are only set on the matched tracks without one, and plays are added unless the track has one at that time.
+pub async fn import(
+	database: &AsyncDatabase,
+	library_id: u64,
+	contents: &str,
+	mappings: &[PathMapping],
+) -> Result<PortableImport> {
+	let mut people = Vec::new();
+	let mut labels = Vec::new();
+	let mut tags = Vec::new();
+	let mut releases = Vec::new();
+	let mut tracks = Vec::new();
+	let mut playlists = Vec::new();
+	let mut ratings = Vec::new();
+	let mut plays = Vec::new();
+
+	for record in parse(contents)? {
+		match record {
+			Record::Person(x) => people.push(x),
+			Record::Label(x) => labels.push(x),
+			Record::Tag(x) => tags.push(x),
+			Record::Release(x) => releases.push(x),
+			Record::Track(x) => tracks.push(x),
+			Record::Playlist(x) => playlists.push(x),
+			Record::Rating(x) => ratings.push(x),
+			Record::Play(x) => plays.push(x),
+			Record::Header(_) | Record::Unknown => {}
+		}
+	}
+
+	let mut summary = PortableImport::default();
+
+	for record in people {
+		let person = Person {
+			name_sort: record.name_sort,
+			mbz_id: record.mbz_id,
+			..Person::new(record.name, record.type_)
+		};
+
+		if let Some(mut doc) = methods::person::find(database, &person).await? {
+			methods::person::fill_in(database, &mut doc, person).await?;
+			summary.people += 1;
+		}
+	}
+
+	let mut label_ids = HashMap::<u64, u64>::new();
+	for record in labels {
+		let matches = LabelByName::entries_async(database)
+			.with_key(&record.name)
+			.query_with_collection_docs()
+			.await?;
+
+		if let Some(mut doc) = matches.documents.into_values().next() {
+			let x = &mut doc.contents;
+			if (x.name_sort.is_none() && record.name_sort.is_some())
+				|| (x.country.is_none() && record.country.is_some())
+				|| (x.mbz_id.is_none() && record.mbz_id.is_some())
+			{
+				x.name_sort = x.name_sort.take().or(record.name_sort);
+				x.country = x.country.take().or(record.country);
+				x.mbz_id = x.mbz_id.take().or(record.mbz_id);
+				x.date_modified = Utc::now();
+				doc.update_async(database).await?;
+			}
+
+			label_ids.insert(record.id, doc.header.id);
+			summary.labels += 1;
+		}
+	}
+
+	let mut tag_ids = HashMap::<u64, u64>::new();
+	for record in &tags {
+		let id = methods::tag::get_or_insert(database, Tag::new(record.name.clone(), record.type_.clone())).await?;
+		for alias in &record.aliases {
+			if let Err(e) = methods::tag::add_alias(database, id, alias.clone()).await {
+				warn!("Skipped the imported alias '{alias}' of tag '{}': {e:#?}", record.name);
+			}
+		}
+
+		tag_ids.insert(record.id, id);
+		summary.tags += 1;
+	}
+
+	// Parents are set once every tag exists, since a parent may come after its children.
+	for record in &tags {
+		let (Some(id), Some(parent_id)) = (tag_ids.get(&record.id), record.parent_id.and_then(|x| tag_ids.get(&x)))
+		else {
+			continue;
+		};
+
+		let has_parent = Tag::get_async(id, database)
+			.await?
+			.map_or(true, |x| x.contents.parent_id.is_some());
+
+		if !has_parent {
+			if let Err(e) = methods::tag::set_parent(database, *id, Some(*parent_id)).await {
+				warn!("Skipped the imported parent of tag '{}': {e:#?}", record.name);
+			}
+		}
+	}
+
+	let resolve_tags = |ids: &[u64]| ids.iter().filter_map(|x| tag_ids.get(x).copied()).collect::<Vec<u64>>();
+
+	let library_track_ids = methods::library::get_track_ids(database, library_id).await?;
+	let mut library_tracks = get_documents::<Track>(database, &library_track_ids).await?;
+
+	let mut by_path = HashMap::<(String, Option<u64>), u64>::new();
+	let mut by_mbz_id = HashMap::<String, Vec<u64>>::new();
+	for (id, doc) in &library_tracks {
+		by_path.insert((doc.contents.path.clone(), doc.contents.segment.map(|x| x.start)), *id);
+		if let Some(x) = &doc.contents.mbz_id {
+			by_mbz_id.entry(x.to_lowercase()).or_default().push(*id);
+		}
+	}
+
+	let mut track_ids = HashMap::<u64, u64>::new();
+	let mut matched_release_ids = HashMap::<u64, u64>::new();
+
+	for record in tracks {
+		let path = map_path(&record.path, mappings);
+		let id = by_path.get(&(path, record.segment_start)).copied().or_else(|| {
+			// A recording can be on several releases, in which case the path has to tell them apart.
+			match by_mbz_id.get(&record.mbz_id.as_ref()?.to_lowercase())?.as_slice() {
+				[x] => Some(*x),
+				_ => None,
+			}
+		});
+
+		let Some(doc) = id.and_then(|x| library_tracks.get_mut(&x)) else {
+			summary.unmatched_paths.push(record.path);
+			continue;
+		};
+
+		track_ids.insert(record.id, doc.header.id);
+		matched_release_ids
+			.entry(record.release_id)
+			.or_insert(doc.contents.release_id);
+
+		let x = &mut doc.contents;
+		let mut changed = merge_ids(&mut x.genre_ids, &resolve_tags(&record.genre_ids));
+		changed |= merge_ids(&mut x.tag_ids, &resolve_tags(&record.tag_ids));
+		changed |= merge_date_added(&mut x.date_added, record.date_added);
+
+		if changed {
+			x.date_modified = Utc::now();
+			doc.update_async(database).await?;
+		}
+
+		summary.tracks += 1;
+	}
+
+	let library_release_ids = methods::library::get_release_ids(database, library_id).await?;
+	let mut library_releases = get_documents::<Release>(database, &library_release_ids).await?;
+
+	let release_by_mbz_id = library_releases
+		.iter()
+		.filter_map(|(id, doc)| Some((doc.contents.mbz_id.as_ref()?.to_lowercase(), *id)))
+		.collect::<HashMap<String, u64>>();
+
+	for record in releases {
+		let id = record
+			.mbz_id
+			.as_ref()
+			.and_then(|x| release_by_mbz_id.get(&x.to_lowercase()))
+			.or_else(|| matched_release_ids.get(&record.id));
+
+		let Some(doc) = id.and_then(|x| library_releases.get_mut(x)) else {
+			continue;
+		};
+
+		let x = &mut doc.contents;
+		let labels = record
+			.label_ids
+			.iter()
+			.filter_map(|x| label_ids.get(x).copied())
+			.collect::<Vec<u64>>();
+
+		let mut changed = merge_ids(&mut x.genre_ids, &resolve_tags(&record.genre_ids));
+		changed |= merge_ids(&mut x.tag_ids, &resolve_tags(&record.tag_ids));
+		changed |= merge_ids(&mut x.label_ids, &labels);
+		changed |= merge_date_added(&mut x.date_added, record.date_added);
+
+		if (x.mbz_id.is_none() && record.mbz_id.is_some())
+			|| (x.catalog_number.is_none() && record.catalog_number.is_some())
+		{
+			x.mbz_id = x.mbz_id.take().or(record.mbz_id);
+			x.catalog_number = x.catalog_number.take().or(record.catalog_number);
+			changed = true;
+		}
+
+		if changed {
+			x.date_modified = Utc::now();
+			doc.update_async(database).await?;
+		}
+
+		summary.releases += 1;
+	}
+
+	// Importing the same export twice shouldn't duplicate the playlists, so the ones sharing a name are skipped.
+	let playlist_names = methods::playlist::get_all(database)
+		.await?
+		.into_iter()
+		.map(|(_, x)| x.name.to_lowercase())
+		.collect::<HashSet<String>>();
+
+	for record in playlists {
+		if playlist_names.contains(&record.name.to_lowercase()) {
+			continue;
+		}
+
+		let ids = record
+			.track_ids
+			.iter()
+			.filter_map(|x| track_ids.get(x).copied())
+			.collect();
+		let playlist = Playlist {
+			description: record.description,
+			date_added: record.date_added,
+			..Playlist::new(record.name, ids)
+		};
+
+		summary
+			.playlist_ids
+			.push(methods::playlist::insert(database, playlist).await?);
+	}
+
+	for record in ratings {
+		let Some(doc) = track_ids.get(&record.track_id).and_then(|x| library_tracks.get_mut(x)) else {
+			continue;
+		};
+
+		if doc.contents.rating.is_some() || !(1..=MAX_RATING).contains(&record.value) {
+			continue;
+		}
+
+		doc.contents.rating = Some(record.value);
+		doc.contents.date_modified = Utc::now();
+		doc.update_async(database).await?;
+		summary.ratings += 1;
+	}
+
+	let mut played_at = HashMap::<u64, HashSet<DateTime<Utc>>>::new();
+	for record in plays {
+		let Some(id) = track_ids.get(&record.track_id).copied() else {
+			continue;
+		};
+
+		let existing = match played_at.entry(id) {
+			Entry::Occupied(x) => x.into_mut(),
+			Entry::Vacant(x) => {
+				let plays = methods::play::get_by_track_id(database, id).await?;
+				x.insert(plays.into_iter().map(|x| x.played_at).collect())
+			}
+		};
+
+		// Importing the same export twice shouldn't count its plays twice.
+		if existing.insert(record.played_at) {
+			methods::play::insert(database, Play::new(id, record.played_at)).await?;
+			summary.plays += 1;
+		}
+	}
+
+	Ok(summary)
+}
+
+/// Reads the records of an export, which must start with a header of a version this build understands.
+fn parse(contents: &str) -> Result<Vec<Record>> {
+	let mut records = Vec::new();
+
+	for (i, line) in contents.lines().enumerate() {
+		if line.trim().is_empty() {
+			continue;
+		}
+
+		let record = serde_json::from_str::<Record>(line).map_err(|e| invalid_library_export(i + 1, &e.to_string()))?;
+		match (&record, records.is_empty()) {
+			(Record::Header(x), true) if x.version > FORMAT_VERSION => {
+				let m = format!("Version {} is newer than this app.", x.version);
+				return Err(invalid_library_export(i + 1, &m));
+			}
+			(Record::Header(_), true) => {}
+			(_, true) => return Err(invalid_library_export(i + 1, "Expected a header.")),
+			(Record::Header(_), false) => return Err(invalid_library_export(i + 1, "Unexpected header.")),
+			_ => {}
+		}
+
+		records.push(record);
+	}
+
+	if records.is_empty() {
+		return Err(invalid_library_export(1, "Expected a header."));
+	}
+
+	Ok(records)
+}
+
+fn map_path(path: &str, mappings: &[PathMapping]) -> String {
+	mappings
+		.iter()
+		.find_map(|x| Some(format!("{}{}", x.to, path.strip_prefix(&x.from)?)))
+		.unwrap_or_else(|| path.to_string())
+}
+
+/// Adds the ids missing from a list, returning whether any was added.
+fn merge_ids(ids: &mut Option<Vec<u64>>, other: &[u64]) -> bool {
+	let mut changed = false;
+
+	for id in other {
+		let ids = ids.get_or_insert_with(Vec::new);
+		if !ids.contains(id) {
+			ids.push(*id);
+			changed = true;
+		}
+	}
+
+	changed
+}
+
+fn merge_date_added(date_added: &mut DateTime<Utc>, other: DateTime<Utc>) -> bool {
+	if other < *date_added {
+		*date_added = other;
+		return true;
+	}
+
+	false
+}
+
+async fn get_documents<C>(database: &AsyncDatabase, ids: &HashSet<u64>) -> Result<HashMap<u64, CollectionDocument<C>>>
+where
+	C: SerializedCollection<PrimaryKey = u64, Contents = C>,
+{
+	let ids = ids
+		.iter()
+		.map(|x| DocumentId::from_u64(*x))
+		.collect::<Vec<DocumentId>>();
+	let documents = C::get_multiple_async(&ids, database)
+		.await?
+		.into_iter()
+		.map(|x| (x.header.id, x))
+		.collect();
+
+	Ok(documents)
+}
+
+/// Gets the documents of the given ids ordered by their id, so exports of the same library are written the same way.
+async fn get_sorted<C>(database: &AsyncDatabase, ids: &HashSet<u64>) -> Result<Vec<(u64, C)>>
+where
+	C: SerializedCollection<PrimaryKey = u64, Contents = C>,
+{
+	let mut documents = get_documents::<C>(database, ids)
+		.await?
+		.into_iter()
+		.map(|(id, x)| (id, x.contents))
+		.collect::<Vec<(u64, C)>>();
+	documents.sort_by_key(|(id, _)| *id);
+
+	Ok(documents)
+}
+
+#[cfg(test)]
+mod test {
+	use {
+		bonsaidb::core::schema::SerializedCollection,
+		chrono::{Duration, Utc},
+	};
+
+	use crate::{
+		constants::DEFAULT_LIBRARY_ID,
+		database::{
+			methods,
+			models::{
+				play::Play,
+				playlist::Playlist,
+				tag::{Tag, TagType},
+				track::Track,
+			},
+			Database,
+		},
+		errors::Result,
+		models::tauri::portable::PathMapping,
+		portable::{export, import, parse, records::Record},
+	};
+
+	#[tokio::test]
+	async fn test_export_and_import() -> Result<()> {
+		let source = Database::testing().await?.0;
+		methods::library::ensure_default(&source).await?;
+
+		let tag_id = methods::tag::get_or_insert(&source, Tag::new("Favourite".to_string(), TagType::Other)).await?;
+		let moved = Track {
+			path: "/mnt/music/Artist/Release/01.flac".to_string(),
+			tag_ids: Some(vec![tag_id]),
+			..Default::default()
+		};
+		let tagged = Track {
+			path: "/mnt/music/Artist/Release/02.flac".to_string(),
+			mbz_id: Some("9b3b3c1e-8b1a-4d38-9a7e-0c1e5f0a5c8e".to_string()),
+			tag_ids: Some(vec![tag_id]),
+			..Default::default()
+		};
+		let missing = Track {
+			path: "/mnt/music/Artist/Release/03.flac".to_string(),
+			..Default::default()
+		};
+
+		let moved_id = methods::track::insert_or_update(&source, moved).await?;
+		let tagged_id = methods::track::insert_or_update(&source, tagged).await?;
+		let missing_id = methods::track::insert_or_update(&source, missing).await?;
+
+		let playlist = Playlist::new("Mix".to_string(), vec![missing_id, tagged_id, moved_id]);
+		methods::playlist::insert(&source, playlist).await?;
+
+		let played_at = Utc::now() - Duration::days(1);
+		methods::track::set_rating(&source, moved_id, Some(5)).await?;
+		methods::track::set_rating(&source, tagged_id, Some(2)).await?;
+		methods::play::insert(&source, Play::new(moved_id, played_at)).await?;
+		methods::play::insert(&source, Play::new(tagged_id, played_at)).await?;
+		methods::play::insert(&source, Play::new(missing_id, played_at)).await?;
+
+		let contents = export(&source, DEFAULT_LIBRARY_ID).await?;
+
+		let target = Database::testing().await?.0;
+		methods::library::ensure_default(&target).await?;
+
+		let moved = Track {
+			path: "/home/user/Music/Artist/Release/01.flac".to_string(),
+			..Default::default()
+		};
+		// Renamed since the export, so it can only be matched by its recording id.
+		let tagged = Track {
+			path: "/home/user/Music/Artist/Release/02 Title.flac".to_string(),
+			mbz_id: Some("9B3B3C1E-8B1A-4D38-9A7E-0C1E5F0A5C8E".to_string()),
+			..Default::default()
+		};
+
+		let moved_id = methods::track::insert_or_update(&target, moved).await?;
+		let tagged_id = methods::track::insert_or_update(&target, tagged).await?;
+		// Ratings given in the importing library take precedence.
+		methods::track::set_rating(&target, tagged_id, Some(4)).await?;
+		methods::play::insert(&target, Play::new(tagged_id, Utc::now())).await?;
+
+		let mappings = vec![PathMapping {
+			from: "/mnt/music/".to_string(),
+			to: "/home/user/Music/".to_string(),
+		}];
+		let summary = import(&target, DEFAULT_LIBRARY_ID, &contents, &mappings).await?;
+
+		assert_eq!(summary.tracks, 2);
+		assert_eq!(summary.tags, 1);
+		assert_eq!(summary.unmatched_paths, vec!["/mnt/music/Artist/Release/03.flac"]);
+		assert_eq!(summary.playlist_ids.len(), 1);
+		assert_eq!(summary.ratings, 1);
+		assert_eq!(summary.plays, 2);
+
+		let imported_tag_id =
+			methods::tag::get_or_insert(&target, Tag::new("favourite".to_string(), TagType::Other)).await?;
+		for id in [moved_id, tagged_id] {
+			let track = Track::get_async(&id, &target).await?.unwrap().contents;
+			assert_eq!(track.tag_ids, Some(vec![imported_tag_id]));
+		}
+
+		let playlist = Playlist::get_async(&summary.playlist_ids[0], &target).await?.unwrap();
+		assert_eq!(playlist.contents.track_ids, vec![tagged_id, moved_id]);
+
+		let moved = Track::get_async(&moved_id, &target).await?.unwrap().contents;
+		let tagged = Track::get_async(&tagged_id, &target).await?.unwrap().contents;
+		assert_eq!((moved.rating, tagged.rating), (Some(5), Some(4)));
+
+		let plays = methods::play::get_by_track_id(&target, moved_id).await?;
+		assert_eq!(plays, vec![Play::new(moved_id, played_at)]);
+		assert_eq!(methods::play::get_by_track_id(&target, tagged_id).await?.len(), 2);
+
+		// Importing again only finds what was already merged.
+		let summary = import(&target, DEFAULT_LIBRARY_ID, &contents, &mappings).await?;
+		assert!(summary.playlist_ids.is_empty());
+		assert_eq!((summary.ratings, summary.plays), (0, 0));
+		assert_eq!(Tag::all_async(&target).await?.len(), 1);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_parse() {
+		let header = r#"{"kind":"header","version":1,"exported_at":"2024-01-01T00:00:00Z"}"#;
+		let rating = r#"{"kind":"rating","track_id":1,"value":5}"#;
+		let unknown = r#"{"kind":"bookmark","track_id":1,"position":1000}"#;
+
+		let records = parse(&format!("{header}\n\n{rating}\n{unknown}\n")).unwrap();
+		assert_eq!(records.len(), 3);
+		assert!(matches!(&records[1], Record::Rating(x) if x.track_id == 1 && x.value == 5));
+		assert_eq!(records[2], Record::Unknown);
+
+		assert!(parse("").is_err());
+		assert!(parse(rating).is_err());
+		assert!(parse(&format!("{header}\n{header}")).is_err());
+		assert!(parse(&header.replace("\"version\":1", "\"version\":2")).is_err());
+	}
+}
@@ -0,0 +1,127 @@
+use {
+	chrono::{DateTime, NaiveDate, Utc},
+	serde::{Deserialize, Serialize},
+};
+
+use crate::database::models::{person::PersonType, tag::TagType, track::Credit, CountryCode};
+
+/// A line of an export, tagged by its `kind`.
+///
+/// Records refer to each other by the `id` they had in the exporting database, which means nothing to the importing one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Record {
+	Header(HeaderRecord),
+	Person(PersonRecord),
+	Label(LabelRecord),
+	Tag(TagRecord),
+	Release(ReleaseRecord),
+	Track(TrackRecord),
+	Playlist(PlaylistRecord),
+	Rating(RatingRecord),
+	Play(PlayRecord),
+	/// Kinds added by a later version of the format, which are skipped.
+	#[serde(other)]
+	Unknown,
+}
+
+/// The first line of an export.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HeaderRecord {
+	pub version: u32,
+	pub exported_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PersonRecord {
+	pub id: u64,
+	pub name: String,
+	pub name_sort: Option<String>,
+	pub mbz_id: Option<String>,
+	#[serde(rename = "type")]
+	pub type_: PersonType,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LabelRecord {
+	pub id: u64,
+	pub name: String,
+	pub name_sort: Option<String>,
+	pub country: Option<CountryCode>,
+	pub mbz_id: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TagRecord {
+	pub id: u64,
+	pub name: String,
+	#[serde(rename = "type")]
+	pub type_: TagType,
+	pub parent_id: Option<u64>,
+	#[serde(default)]
+	pub aliases: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReleaseRecord {
+	pub id: u64,
+	pub name: String,
+	pub mbz_id: Option<String>,
+	pub catalog_number: Option<String>,
+	pub date: Option<NaiveDate>,
+	pub year: Option<i32>,
+	#[serde(default)]
+	pub artist_ids: Vec<u64>,
+	#[serde(default)]
+	pub label_ids: Vec<u64>,
+	#[serde(default)]
+	pub genre_ids: Vec<u64>,
+	#[serde(default)]
+	pub tag_ids: Vec<u64>,
+	pub date_added: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrackRecord {
+	pub id: u64,
+	pub path: String,
+	/// Start of the segment in milliseconds, for the tracks split from a single file.
+	pub segment_start: Option<u64>,
+	pub mbz_id: Option<String>,
+	pub title: String,
+	pub release_id: u64,
+	#[serde(default)]
+	pub artist_ids: Vec<u64>,
+	#[serde(default)]
+	pub credits: Vec<Credit>,
+	#[serde(default)]
+	pub genre_ids: Vec<u64>,
+	#[serde(default)]
+	pub tag_ids: Vec<u64>,
+	pub date_added: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlaylistRecord {
+	pub name: String,
+	pub description: Option<String>,
+	/// Ids of track records, in the order of the playlist.
+	pub track_ids: Vec<u64>,
+	pub date_added: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RatingRecord {
+	/// Id of a track record.
+	pub track_id: u64,
+	/// From 1 to [MAX_RATING](crate::database::models::track::MAX_RATING).
+	pub value: u8,
+}
+
+/// A listen of a track, as recorded in the play history.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayRecord {
+	/// Id of a track record.
+	pub track_id: u64,
+	pub played_at: DateTime<Utc>,
+}
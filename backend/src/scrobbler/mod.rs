@@ -0,0 +1,486 @@
+use std::{
+	future::Future,
+	sync::{Arc, Mutex as BlockingMutex},
+	time::{Duration, Instant},
+};
+
+use {
+	bonsaidb::local::AsyncDatabase,
+	chrono::{DateTime, Utc},
+	reqwest::Client,
+	tokio::{
+		sync::{Mutex as AsyncMutex, Notify, OwnedMutexGuard},
+		task::JoinHandle,
+	},
+	tracing::{error, warn},
+};
+
+use crate::{
+	database::{
+		methods,
+		models::{
+			play::Play,
+			scrobble::{Scrobble, ScrobbleService, ScrobbleTrack},
+		},
+	},
+	errors::{Error, Result},
+	models::{
+		configuration::{Configuration, ScrobblingConfiguration},
+		playback::{Playback, PlaybackTrack},
+	},
+	scrobbler::{
+		services::{active_services, submit, Failure, Submission},
+		tracker::{ScrobbleAction, ScrobbleTracker},
+	},
+};
+
+pub mod services;
+mod tracker;
+
+/// Interval between the attempts to submit the queued listens, unless a new listen wakes the worker.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(5 * 60);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_BACKOFF_SECONDS: i64 = 6 * 60 * 60;
+const BATCH_SIZE: u32 = 100;
+
+/// Called with the listens a service refused, so the user can be told about them.
+pub type RejectionHandler = Arc<dyn Fn(ScrobbleService, &Error) + Send + Sync>;
+
+/// Records the plays of the reported playback and submits them to ListenBrainz and Last.fm.
+///
+/// Listens are stored in the `scrobbles` collection first, and a background worker submits them,
+/// so listens made while offline are kept until the services can be reached again.
+pub struct Scrobbler {
+	tracker: ScrobbleTracker,
+	/// Track of the last report, kept for the listen completed by the report that moves on to another track.
+	track: Option<ScrobbleTrack>,
+	database: AsyncDatabase,
+	client: Client,
+	configuration: Arc<BlockingMutex<Option<Configuration>>>,
+	wake: Arc<Notify>,
+	/// Held while the listens are written into the database, see [Scrobbler::pause].
+	gate: Arc<AsyncMutex<()>>,
+	on_rejected: RejectionHandler,
+	worker: JoinHandle<()>,
+}
+
+/// A [Scrobbler] stopped with [Scrobbler::stop], keeping the playback it followed so no listen is lost.
+pub struct StoppedScrobbler {
+	tracker: ScrobbleTracker,
+	track: Option<ScrobbleTrack>,
+	configuration: Arc<BlockingMutex<Option<Configuration>>>,
+	on_rejected: RejectionHandler,
+}
+
+impl Scrobbler {
+	/// Starts the worker, reading the settings from the shared configuration so changes apply without a restart.
+	///
+	/// Listens refused by a service, like when the credentials are no longer valid, are passed to `on_rejected`.
+	pub fn new(
+		database: AsyncDatabase,
+		configuration: Arc<BlockingMutex<Option<Configuration>>>,
+		on_rejected: impl Fn(ScrobbleService, &Error) + Send + Sync + 'static,
+	) -> Result<Self> {
+		Self::start(database, configuration, Arc::new(on_rejected))
+	}
+
+	fn start(
+		database: AsyncDatabase,
+		configuration: Arc<BlockingMutex<Option<Configuration>>>,
+		on_rejected: RejectionHandler,
+	) -> Result<Self> {
+		let client = Client::builder().timeout(REQUEST_TIMEOUT).build()?;
+		let wake = Arc::new(Notify::new());
+		let gate = Arc::new(AsyncMutex::new(()));
+
+		let worker = tokio::spawn(run_worker(
+			database.clone(),
+			client.clone(),
+			configuration.clone(),
+			wake.clone(),
+			gate.clone(),
+			on_rejected.clone(),
+		));
+
+		Ok(Self {
+			tracker: ScrobbleTracker::default(),
+			track: None,
+			database,
+			client,
+			configuration,
+			wake,
+			gate,
+			on_rejected,
+			worker,
+		})
+	}
+
+	/// Waits for the running flush to finish, then stops the worker so the scrobbler lets go of the database.
+	pub async fn stop(mut self) -> StoppedScrobbler {
+		let _gate = self.gate.clone().lock_owned().await;
+		self.worker.abort();
+		let _ = (&mut self.worker).await;
+
+		StoppedScrobbler {
+			tracker: std::mem::take(&mut self.tracker),
+			track: self.track.take(),
+			configuration: self.configuration.clone(),
+			on_rejected: self.on_rejected.clone(),
+		}
+	}
+
+	/// Waits for the running flush to finish, then keeps the scrobbler from writing into the database until the
+	/// returned guard is dropped.
+	pub fn pause(&self) -> impl Future<Output = OwnedMutexGuard<()>> {
+		self.gate.clone().lock_owned()
+	}
+
+	/// Follows the playback, announcing new tracks, and recording and queueing the ones played for long enough.
+	///
+	/// The length of the track is taken from `duration`, as reported by the player.
+	pub async fn report(&mut self, playback: &Playback, duration: Option<u64>) -> Result<()> {
+		let duration = duration.or(playback.track.as_ref().and_then(|x| x.length));
+		let actions = self.tracker.update(
+			playback.track_id(),
+			playback.status,
+			playback.position,
+			duration,
+			Instant::now(),
+		);
+
+		let current = playback.track.as_ref().map(|x| to_scrobble_track(x, duration));
+		let previous = std::mem::replace(&mut self.track, current.clone());
+
+		if actions.is_empty() {
+			return Ok(());
+		}
+
+		let configuration = get_scrobbling_configuration(&self.configuration);
+
+		for action in actions {
+			let (ScrobbleAction::NowPlaying { track_id } | ScrobbleAction::Listen { track_id, .. }) = action;
+			let Some(track) = [&current, &previous]
+				.into_iter()
+				.flatten()
+				.find(|x| x.track_id == track_id)
+			else {
+				warn!("Couldn't find the metadata of track {track_id}, skipping {action:?}");
+				continue;
+			};
+
+			match action {
+				ScrobbleAction::NowPlaying { .. } => {
+					for service in active_services(&configuration) {
+						let client = self.client.clone();
+						let configuration = configuration.clone();
+						let track = track.clone();
+
+						// Now playing notices are not worth retrying, as they are outdated by the time a retry happens.
+						tokio::spawn(async move {
+							let submission = Submission::NowPlaying(&track);
+							if let Err(e) = submit(&client, &configuration, service, submission).await {
+								warn!("Failed to announce the playing track to {service:?}: {e:#?}");
+							}
+						});
+					}
+				}
+				ScrobbleAction::Listen { listened_at, .. } => {
+					let _gate = self.gate.lock().await;
+					// The play history is kept even when no service is set up.
+					methods::play::insert(&self.database, Play::new(track.track_id, listened_at)).await?;
+
+					for service in active_services(&configuration) {
+						let scrobble = Scrobble::new(service, track.clone(), listened_at);
+						methods::scrobble::enqueue(&self.database, scrobble).await?;
+					}
+
+					self.wake.notify_one();
+				}
+			}
+		}
+
+		Ok(())
+	}
+}
+
+impl StoppedScrobbler {
+	/// Starts the scrobbler again with another handle to the database.
+	pub fn start(self, database: AsyncDatabase) -> Result<Scrobbler> {
+		let mut scrobbler = Scrobbler::start(database, self.configuration, self.on_rejected)?;
+		scrobbler.tracker = self.tracker;
+		scrobbler.track = self.track;
+
+		Ok(scrobbler)
+	}
+}
+
+impl Drop for Scrobbler {
+	fn drop(&mut self) {
+		self.worker.abort();
+	}
+}
+
+async fn run_worker(
+	database: AsyncDatabase,
+	client: Client,
+	configuration: Arc<BlockingMutex<Option<Configuration>>>,
+	wake: Arc<Notify>,
+	gate: Arc<AsyncMutex<()>>,
+	on_rejected: RejectionHandler,
+) {
+	loop {
+		let scrobbling = get_scrobbling_configuration(&configuration);
+		let flushed = {
+			let _gate = gate.lock().await;
+			flush(&database, &client, &scrobbling, Utc::now(), on_rejected.as_ref()).await
+		};
+
+		if let Err(e) = flushed {
+			error!("Failed to submit the queued listens: {e:#?}");
+		}
+
+		tokio::select! {
+			_ = wake.notified() => {},
+			_ = tokio::time::sleep(FLUSH_INTERVAL) => {},
+		}
+	}
+}
+
+/// Submits the listens due at the given time, removing the ones that were accepted or rejected.
+///
+/// Listens are read separately for every enabled service, so listens of disabled services are kept in the queue
+/// without holding back the others. Listens that failed for a transient reason are retried with an exponential
+/// backoff, and the rest of the listens of the same service are skipped until the next flush.
+/// Rejected listens are passed to `on_rejected`.
+pub async fn flush(
+	database: &AsyncDatabase,
+	client: &Client,
+	configuration: &ScrobblingConfiguration,
+	now: DateTime<Utc>,
+	on_rejected: &(dyn Fn(ScrobbleService, &Error) + Send + Sync),
+) -> Result<()> {
+	for service in active_services(configuration) {
+		for (id, scrobble) in methods::scrobble::get_due(database, service, now, BATCH_SIZE).await? {
+			let submission = Submission::Listen(&scrobble.track, scrobble.listened_at);
+			match submit(client, configuration, service, submission).await {
+				Ok(()) => methods::scrobble::delete(database, id).await?,
+				Err(Failure::Rejected(e)) => {
+					error!(
+						"{service:?} rejected the listen of {:?}, dropping it: {e:#?}",
+						scrobble.track
+					);
+					methods::scrobble::delete(database, id).await?;
+					on_rejected(service, &e);
+				}
+				Err(Failure::Transient(e)) => {
+					warn!("Failed to submit a listen to {service:?}, retrying later: {e:#?}");
+					methods::scrobble::postpone(database, id, get_backoff(scrobble.attempts + 1)).await?;
+					break;
+				}
+			}
+		}
+	}
+
+	Ok(())
+}
+
+/// Doubles the delay with every attempt, starting from 30 seconds up to 6 hours.
+fn get_backoff(attempts: u32) -> chrono::Duration {
+	let seconds = 30_i64.saturating_mul(1 << attempts.saturating_sub(1).min(16));
+	chrono::Duration::seconds(seconds.min(MAX_BACKOFF_SECONDS))
+}
+
+fn get_scrobbling_configuration(configuration: &BlockingMutex<Option<Configuration>>) -> ScrobblingConfiguration {
+	let guard = configuration.lock().unwrap();
+	guard.as_ref().map(|x| x.scrobbling.clone()).unwrap_or_default()
+}
+
+fn to_scrobble_track(track: &PlaybackTrack, duration: Option<u64>) -> ScrobbleTrack {
+	ScrobbleTrack {
+		track_id: track.id,
+		title: track.title.clone(),
+		artists: track.artists.clone(),
+		release: track.album.clone(),
+		release_artists: track.album_artists.clone(),
+		track_number: track.track_number,
+		duration,
+		recording_mbz_id: track.mbz_id.clone(),
+		release_mbz_id: track.release_mbz_id.clone(),
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::{
+		net::TcpListener,
+		sync::{Arc, Mutex},
+	};
+
+	use {
+		axum::{extract::State, http::StatusCode, routing::post, Json, Router},
+		chrono::{Duration, Utc},
+		reqwest::Client,
+		serde_json::Value,
+	};
+
+	use crate::{
+		database::{
+			methods,
+			models::scrobble::{Scrobble, ScrobbleService, ScrobbleTrack},
+			Database,
+		},
+		errors::Result,
+		models::configuration::{ListenBrainzConfiguration, ScrobblingConfiguration},
+		scrobbler::flush,
+	};
+
+	type Received = Arc<Mutex<Vec<Value>>>;
+
+	/// Serves a ListenBrainz compatible endpoint on a random port, recording the submitted bodies.
+	///
+	/// Every submission is answered with the given status.
+	fn mock_listenbrainz(status: StatusCode) -> (String, Received) {
+		let received = Received::default();
+
+		async fn submit(
+			State((received, status)): State<(Received, StatusCode)>,
+			Json(body): Json<Value>,
+		) -> (StatusCode, Json<Value>) {
+			received.lock().unwrap().push(body);
+			(status, Json(serde_json::json!({ "status": "ok" })))
+		}
+
+		let router = Router::new()
+			.route("/1/submit-listens", post(submit))
+			.with_state((received.clone(), status));
+
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		listener.set_nonblocking(true).unwrap();
+		let address = listener.local_addr().unwrap();
+
+		let server = axum::Server::from_tcp(listener)
+			.unwrap()
+			.serve(router.into_make_service());
+		tokio::spawn(server);
+
+		(format!("http://{address}"), received)
+	}
+
+	/// Reserves a port that nothing listens on.
+	fn unreachable_endpoint() -> String {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		format!("http://{}", listener.local_addr().unwrap())
+	}
+
+	#[tokio::test]
+	async fn test_flush() -> Result<()> {
+		let db = Database::testing().await?;
+		let dbx = db.0;
+		let client = Client::new();
+
+		let track = ScrobbleTrack {
+			track_id: 1,
+			title: "Test Track".to_string(),
+			artists: vec!["Test Artist".to_string()],
+			release: Some("Test Release".to_string()),
+			release_artists: vec!["Test Artist".to_string()],
+			track_number: Some(1),
+			duration: Some(180_000),
+			recording_mbz_id: Some("5e3a8c34-6f7a-4b3e-9a0d-2f3e1c6d7b8a".to_string()),
+			release_mbz_id: None,
+		};
+		// Listens of a disabled service stay in the queue.
+		let disabled = Scrobble::new(ScrobbleService::LastFm, track.clone(), Utc::now());
+		methods::scrobble::enqueue(&dbx, disabled).await?;
+
+		let scrobble = Scrobble::new(ScrobbleService::ListenBrainz, track, Utc::now());
+		methods::scrobble::enqueue(&dbx, scrobble).await?;
+
+		let mut configuration = ScrobblingConfiguration {
+			listenbrainz: ListenBrainzConfiguration {
+				enabled: true,
+				token: "token".to_string(),
+				endpoint: unreachable_endpoint(),
+			},
+			..Default::default()
+		};
+
+		flush(&dbx, &client, &configuration, Utc::now(), &|_, _| {}).await?;
+
+		let later = Utc::now() + Duration::days(1);
+		let queued = methods::scrobble::get_due(&dbx, ScrobbleService::ListenBrainz, later, 10).await?;
+		assert_eq!(queued.len(), 1);
+		assert_eq!(queued[0].1.attempts, 1);
+
+		let (endpoint, received) = mock_listenbrainz(StatusCode::OK);
+		configuration.listenbrainz.endpoint = endpoint;
+
+		// Postponed listens are not due yet.
+		flush(&dbx, &client, &configuration, Utc::now(), &|_, _| {}).await?;
+		assert!(received.lock().unwrap().is_empty());
+
+		flush(&dbx, &client, &configuration, later, &|_, _| {}).await?;
+		let queued = methods::scrobble::get_due(&dbx, ScrobbleService::ListenBrainz, later, 10).await?;
+		assert!(queued.is_empty());
+
+		let queued = methods::scrobble::get_due(&dbx, ScrobbleService::LastFm, later, 10).await?;
+		assert_eq!(queued.len(), 1);
+
+		let received = received.lock().unwrap();
+		let listen = &received[0]["payload"][0];
+		assert_eq!(received[0]["listen_type"], "single");
+		assert_eq!(listen["track_metadata"]["track_name"], "Test Track");
+		assert_eq!(
+			listen["track_metadata"]["additional_info"]["recording_mbid"],
+			"5e3a8c34-6f7a-4b3e-9a0d-2f3e1c6d7b8a"
+		);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_flush_rejected() -> Result<()> {
+		let db = Database::testing().await?;
+		let dbx = db.0;
+		let client = Client::new();
+
+		let track = ScrobbleTrack {
+			track_id: 1,
+			title: "Test Track".to_string(),
+			artists: vec!["Test Artist".to_string()],
+			release: None,
+			release_artists: vec![],
+			track_number: None,
+			duration: Some(180_000),
+			recording_mbz_id: None,
+			release_mbz_id: None,
+		};
+		let scrobble = Scrobble::new(ScrobbleService::ListenBrainz, track, Utc::now());
+		methods::scrobble::enqueue(&dbx, scrobble).await?;
+
+		// A revoked token is reported instead of being retried forever.
+		let (endpoint, _) = mock_listenbrainz(StatusCode::UNAUTHORIZED);
+		let configuration = ScrobblingConfiguration {
+			listenbrainz: ListenBrainzConfiguration {
+				enabled: true,
+				token: "revoked".to_string(),
+				endpoint,
+			},
+			..Default::default()
+		};
+
+		let rejected = Mutex::new(Vec::new());
+		flush(&dbx, &client, &configuration, Utc::now(), &|service, _| {
+			rejected.lock().unwrap().push(service)
+		})
+		.await?;
+
+		assert_eq!(*rejected.lock().unwrap(), vec![ScrobbleService::ListenBrainz]);
+
+		let later = Utc::now() + Duration::days(1);
+		let queued = methods::scrobble::get_due(&dbx, ScrobbleService::ListenBrainz, later, 10).await?;
+		assert!(queued.is_empty());
+
+		Ok(())
+	}
+}
@@ -0,0 +1,269 @@
+use std::collections::BTreeMap;
+
+use {
+	chrono::{DateTime, Utc},
+	reqwest::{Client, StatusCode},
+	serde_json::{json, Map, Value},
+};
+
+use crate::{
+	database::models::scrobble::{ScrobbleService, ScrobbleTrack},
+	errors::{pre::scrobble_rejected, Error, Result},
+	models::configuration::{LastFmConfiguration, ListenBrainzConfiguration, ScrobblingConfiguration},
+};
+
+const CLIENT_NAME: &str = "Melody";
+
+pub enum Submission<'a> {
+	NowPlaying(&'a ScrobbleTrack),
+	Listen(&'a ScrobbleTrack, DateTime<Utc>),
+}
+
+/// Reason of a failed submission.
+#[derive(Debug)]
+pub enum Failure {
+	/// The service couldn't be reached or is rate limiting. Retried later.
+	Transient(Error),
+	/// The service refused the submission or the credentials, which would fail again if retried.
+	Rejected(Error),
+}
+
+/// Resolves the services that are enabled and have their credentials set.
+pub fn active_services(configuration: &ScrobblingConfiguration) -> Vec<ScrobbleService> {
+	let mut services = Vec::with_capacity(2);
+
+	let listenbrainz = &configuration.listenbrainz;
+	if listenbrainz.enabled && !listenbrainz.token.is_empty() {
+		services.push(ScrobbleService::ListenBrainz);
+	}
+
+	let lastfm = &configuration.lastfm;
+	if lastfm.enabled && lastfm.session_key.is_some() {
+		services.push(ScrobbleService::LastFm);
+	}
+
+	services
+}
+
+pub async fn submit(
+	client: &Client,
+	configuration: &ScrobblingConfiguration,
+	service: ScrobbleService,
+	submission: Submission<'_>,
+) -> std::result::Result<(), Failure> {
+	match service {
+		ScrobbleService::ListenBrainz => submit_listenbrainz(client, &configuration.listenbrainz, submission).await,
+		ScrobbleService::LastFm => submit_lastfm(client, &configuration.lastfm, submission).await,
+	}
+}
+
+async fn submit_listenbrainz(
+	client: &Client,
+	configuration: &ListenBrainzConfiguration,
+	submission: Submission<'_>,
+) -> std::result::Result<(), Failure> {
+	let (listen_type, track, listened_at) = match submission {
+		Submission::NowPlaying(track) => ("playing_now", track, None),
+		Submission::Listen(track, at) => ("single", track, Some(at.timestamp())),
+	};
+
+	let mut additional_info = Map::new();
+	additional_info.insert("submission_client".into(), json!(CLIENT_NAME));
+	additional_info.insert("submission_client_version".into(), json!(env!("CARGO_PKG_VERSION")));
+	additional_info.insert("artist_names".into(), json!(track.artists));
+	insert_some(&mut additional_info, "recording_mbid", &track.recording_mbz_id);
+	insert_some(&mut additional_info, "release_mbid", &track.release_mbz_id);
+	insert_some(&mut additional_info, "duration_ms", &track.duration);
+	insert_some(&mut additional_info, "tracknumber", &track.track_number);
+
+	let mut track_metadata = Map::new();
+	track_metadata.insert("artist_name".into(), json!(track.artists.join(", ")));
+	track_metadata.insert("track_name".into(), json!(track.title));
+	track_metadata.insert("additional_info".into(), Value::Object(additional_info));
+	insert_some(&mut track_metadata, "release_name", &track.release);
+
+	let mut listen = Map::new();
+	listen.insert("track_metadata".into(), Value::Object(track_metadata));
+	insert_some(&mut listen, "listened_at", &listened_at);
+
+	let url = format!("{}/1/submit-listens", configuration.endpoint.trim_end_matches('/'));
+	let response = client
+		.post(url)
+		.header("Authorization", format!("Token {}", configuration.token))
+		.json(&json!({ "listen_type": listen_type, "payload": [listen] }))
+		.send()
+		.await
+		.map_err(|e| Failure::Transient(e.into()))?;
+
+	let status = response.status();
+	if status.is_success() {
+		return Ok(());
+	}
+
+	let body = response.text().await.unwrap_or_default();
+	let error = scrobble_rejected("ListenBrainz", status.as_u16(), &body);
+
+	match status {
+		StatusCode::BAD_REQUEST | StatusCode::UNAUTHORIZED => Err(Failure::Rejected(error)),
+		_ => Err(Failure::Transient(error)),
+	}
+}
+
+async fn submit_lastfm(
+	client: &Client,
+	configuration: &LastFmConfiguration,
+	submission: Submission<'_>,
+) -> std::result::Result<(), Failure> {
+	let (method, track, timestamp) = match submission {
+		Submission::NowPlaying(track) => ("track.updateNowPlaying", track, None),
+		Submission::Listen(track, at) => ("track.scrobble", track, Some(at.timestamp())),
+	};
+
+	let mut params = BTreeMap::<&str, String>::new();
+	params.insert("method", method.to_string());
+	params.insert("artist", track.artists.join(", "));
+	params.insert("track", track.title.clone());
+	params.insert("sk", configuration.session_key.clone().unwrap_or_default());
+
+	if let Some(x) = timestamp {
+		params.insert("timestamp", x.to_string());
+	}
+
+	if let Some(x) = &track.release {
+		params.insert("album", x.clone());
+	}
+
+	if !track.release_artists.is_empty() {
+		params.insert("albumArtist", track.release_artists.join(", "));
+	}
+
+	if let Some(x) = track.track_number {
+		params.insert("trackNumber", x.to_string());
+	}
+
+	if let Some(x) = track.duration {
+		params.insert("duration", (x / 1000).to_string());
+	}
+
+	if let Some(x) = &track.recording_mbz_id {
+		params.insert("mbid", x.clone());
+	}
+
+	let response = call_lastfm(client, configuration, params).await?;
+	let accepted = response["scrobbles"]["@attr"]["ignored"].as_u64().unwrap_or(0) == 0;
+
+	if !accepted {
+		let message = response["scrobbles"]["scrobble"]["ignoredMessage"]["#text"]
+			.as_str()
+			.unwrap_or("Scrobble was ignored");
+		return Err(Failure::Rejected(scrobble_rejected("Last.fm", 200, message)));
+	}
+
+	Ok(())
+}
+
+/// Exchanges the credentials of a user for a session key through `auth.getMobileSession`.
+pub async fn authenticate_lastfm(
+	client: &Client,
+	configuration: &LastFmConfiguration,
+	username: &str,
+	password: &str,
+) -> Result<String> {
+	let mut params = BTreeMap::<&str, String>::new();
+	params.insert("method", "auth.getMobileSession".to_string());
+	params.insert("username", username.to_string());
+	params.insert("password", password.to_string());
+
+	let response = call_lastfm(client, configuration, params).await?;
+
+	match response["session"]["key"].as_str() {
+		Some(x) => Ok(x.to_string()),
+		None => Err(scrobble_rejected("Last.fm", 200, "Response is missing the session key")),
+	}
+}
+
+/// Calls a signed write method of the Last.fm API.
+async fn call_lastfm(
+	client: &Client,
+	configuration: &LastFmConfiguration,
+	mut params: BTreeMap<&str, String>,
+) -> std::result::Result<Value, Failure> {
+	params.insert("api_key", configuration.api_key.clone());
+	params.insert("api_sig", sign_lastfm(&params, &configuration.api_secret));
+	params.insert("format", "json".to_string());
+
+	let response = client
+		.post(&configuration.endpoint)
+		.form(&params)
+		.send()
+		.await
+		.map_err(|e| Failure::Transient(e.into()))?;
+
+	let status = response.status();
+	let body = response
+		.json::<Value>()
+		.await
+		.map_err(|e| Failure::Transient(e.into()))?;
+
+	let Some(code) = body["error"].as_u64() else {
+		if status.is_success() {
+			return Ok(body);
+		}
+
+		return Err(Failure::Transient(scrobble_rejected("Last.fm", status.as_u16(), "")));
+	};
+
+	let message = body["message"].as_str().unwrap_or_default();
+	let error = scrobble_rejected("Last.fm", status.as_u16(), &format!("Error {code}: {message}"));
+
+	// Invalid parameters, unknown methods and refused credentials can't go away on their own.
+	// Authentication failed (4), invalid session key (9), invalid API key (10) and suspended API key (26).
+	match code {
+		3 | 4 | 6 | 7 | 9 | 10 | 26 => Err(Failure::Rejected(error)),
+		_ => Err(Failure::Transient(error)),
+	}
+}
+
+/// Signs the parameters as `md5(k1v1k2v2...secret)`, with the keys in alphabetical order.
+fn sign_lastfm(params: &BTreeMap<&str, String>, secret: &str) -> String {
+	let mut payload = params
+		.iter()
+		.filter(|(k, _)| !matches!(**k, "format" | "callback"))
+		.map(|(k, v)| format!("{k}{v}"))
+		.collect::<String>();
+	payload.push_str(secret);
+
+	format!("{:x}", md5::compute(payload))
+}
+
+fn insert_some<T: serde::Serialize>(map: &mut Map<String, Value>, key: &str, value: &Option<T>) {
+	if let Some(x) = value {
+		map.insert(key.into(), json!(x));
+	}
+}
+
+impl From<Failure> for Error {
+	fn from(value: Failure) -> Self {
+		match value {
+			Failure::Transient(x) | Failure::Rejected(x) => x,
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::collections::BTreeMap;
+
+	use super::sign_lastfm;
+
+	#[test]
+	fn test_sign_lastfm() {
+		let mut params = BTreeMap::<&str, String>::new();
+		params.insert("method", "auth.getMobileSession".to_string());
+		params.insert("api_key", "key".to_string());
+		params.insert("format", "json".to_string());
+
+		// md5("api_keykeymethodauth.getMobileSessionsecret")
+		assert_eq!(sign_lastfm(&params, "secret"), "018322def6bdaf0b7eba8f03ac376100");
+	}
+}
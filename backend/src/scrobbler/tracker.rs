@@ -0,0 +1,200 @@
+use std::time::Instant;
+
+use chrono::{DateTime, Utc};
+
+use crate::models::playback::PlaybackStatus;
+
+/// Tracks shorter than this are never submitted.
+const MIN_DURATION: u64 = 30_000;
+/// Listening for this long is enough to submit a track, regardless of its duration.
+const MAX_THRESHOLD: u64 = 240_000;
+/// Positions before this count as the start of the track, so going back to it starts a new listen.
+const RESTART_POSITION: u64 = 5_000;
+
+#[derive(Debug, PartialEq)]
+pub enum ScrobbleAction {
+	/// The track started playing.
+	NowPlaying { track_id: u64 },
+	/// The track was played for long enough to count as a listen.
+	Listen { track_id: u64, listened_at: DateTime<Utc> },
+}
+
+/// Follows the reported playback, deciding when a track is submitted.
+///
+/// A track counts as a listen once it was played for half of its duration or four minutes, whichever comes first.
+/// Only the time spent playing counts, so pauses and seeks don't bring a track closer to the threshold.
+/// Going back to the start of the track, like when it's repeated, counts as a new listen.
+///
+/// The actions carry the id of their track, as a listen can be completed by the update that moves on to another one.
+#[derive(Debug, Default)]
+pub struct ScrobbleTracker {
+	session: Option<Session>,
+}
+
+#[derive(Debug)]
+struct Session {
+	track_id: u64,
+	started_at: DateTime<Utc>,
+	/// Milliseconds spent playing.
+	listened: u64,
+	/// Last known position in milliseconds, advanced by the time spent playing since it was reported.
+	position: u64,
+	/// Set while the track is playing, to measure the time until the next update.
+	resumed_at: Option<Instant>,
+	/// Length in milliseconds, as last reported.
+	duration: Option<u64>,
+	announced: bool,
+	submitted: bool,
+}
+
+impl ScrobbleTracker {
+	/// Updates the tracker with the reported playback, where `position` and `duration` are in milliseconds.
+	pub fn update(
+		&mut self,
+		track_id: Option<u64>,
+		status: PlaybackStatus,
+		position: u64,
+		duration: Option<u64>,
+		now: Instant,
+	) -> Vec<ScrobbleAction> {
+		let mut actions = Vec::new();
+
+		if let Some(session) = &mut self.session {
+			if let Some(resumed_at) = session.resumed_at.take() {
+				let played = now.duration_since(resumed_at).as_millis() as u64;
+				session.listened += played;
+				session.position += played;
+			}
+
+			let restarted = position < RESTART_POSITION && session.position >= RESTART_POSITION;
+			if Some(session.track_id) != track_id || status == PlaybackStatus::Stopped || restarted {
+				// The time played since the last update can complete the listen of the track that is left.
+				actions.extend(session.take_listen());
+				self.session = None;
+			}
+		}
+
+		let Some(track_id) = track_id.filter(|_| status != PlaybackStatus::Stopped) else {
+			return actions;
+		};
+
+		let session = self.session.get_or_insert_with(|| Session {
+			track_id,
+			started_at: Utc::now(),
+			listened: 0,
+			position,
+			resumed_at: None,
+			duration,
+			announced: false,
+			submitted: false,
+		});
+
+		session.position = position;
+		session.duration = duration;
+		if status == PlaybackStatus::Playing {
+			session.resumed_at = Some(now);
+
+			if !session.announced {
+				session.announced = true;
+				actions.push(ScrobbleAction::NowPlaying { track_id });
+			}
+		}
+
+		actions.extend(session.take_listen());
+		actions
+	}
+}
+
+impl Session {
+	/// Marks the track as submitted once it was played for long enough.
+	fn take_listen(&mut self) -> Option<ScrobbleAction> {
+		let threshold = self
+			.duration
+			.filter(|x| *x >= MIN_DURATION)
+			.map(|x| (x / 2).min(MAX_THRESHOLD));
+
+		if self.submitted || !threshold.is_some_and(|x| self.listened >= x) {
+			return None;
+		}
+
+		self.submitted = true;
+		Some(ScrobbleAction::Listen {
+			track_id: self.track_id,
+			listened_at: self.started_at,
+		})
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::time::{Duration, Instant};
+
+	use crate::{
+		models::playback::PlaybackStatus,
+		scrobbler::tracker::{ScrobbleAction, ScrobbleTracker},
+	};
+
+	#[test]
+	fn test_update() {
+		let mut tracker = ScrobbleTracker::default();
+		let start = Instant::now();
+		let at = |seconds: u64| start + Duration::from_secs(seconds);
+		let duration = Some(60_000);
+
+		let actions = tracker.update(Some(1), PlaybackStatus::Playing, 0, duration, at(0));
+		assert_eq!(actions, vec![ScrobbleAction::NowPlaying { track_id: 1 }]);
+
+		assert!(tracker
+			.update(Some(1), PlaybackStatus::Paused, 20_000, duration, at(20))
+			.is_empty());
+		// Time spent paused doesn't count.
+		assert!(tracker
+			.update(Some(1), PlaybackStatus::Playing, 20_000, duration, at(100))
+			.is_empty());
+
+		let actions = tracker.update(Some(1), PlaybackStatus::Playing, 31_000, duration, at(111));
+		assert!(matches!(actions[..], [ScrobbleAction::Listen { .. }]));
+		assert!(tracker
+			.update(Some(1), PlaybackStatus::Playing, 60_000, duration, at(200))
+			.is_empty());
+
+		// Long tracks are submitted after four minutes.
+		let duration = Some(3_600_000);
+		let actions = tracker.update(Some(2), PlaybackStatus::Playing, 0, duration, at(200));
+		assert_eq!(actions, vec![ScrobbleAction::NowPlaying { track_id: 2 }]);
+
+		let actions = tracker.update(Some(2), PlaybackStatus::Playing, 240_000, duration, at(440));
+		assert!(matches!(actions[..], [ScrobbleAction::Listen { .. }]));
+
+		// Short tracks are never submitted.
+		tracker.update(Some(3), PlaybackStatus::Playing, 0, Some(20_000), at(440));
+		let actions = tracker.update(Some(3), PlaybackStatus::Playing, 20_000, Some(20_000), at(460));
+		assert!(actions.is_empty());
+
+		// Repeating a track counts as a new listen.
+		let duration = Some(60_000);
+		tracker.update(Some(4), PlaybackStatus::Playing, 0, duration, at(500));
+		let actions = tracker.update(Some(4), PlaybackStatus::Playing, 31_000, duration, at(531));
+		assert!(matches!(actions[..], [ScrobbleAction::Listen { .. }]));
+
+		let actions = tracker.update(Some(4), PlaybackStatus::Playing, 0, duration, at(560));
+		assert_eq!(actions, vec![ScrobbleAction::NowPlaying { track_id: 4 }]);
+		let actions = tracker.update(Some(4), PlaybackStatus::Playing, 31_000, duration, at(591));
+		assert!(matches!(actions[..], [ScrobbleAction::Listen { .. }]));
+
+		// Moving on to another track can complete the listen of the previous one.
+		tracker.update(Some(5), PlaybackStatus::Playing, 0, duration, at(600));
+		tracker.update(Some(5), PlaybackStatus::Playing, 20_000, duration, at(620));
+		let actions = tracker.update(Some(6), PlaybackStatus::Playing, 0, duration, at(631));
+		assert!(matches!(
+			actions[..],
+			[
+				ScrobbleAction::Listen { track_id: 5, .. },
+				ScrobbleAction::NowPlaying { track_id: 6 }
+			]
+		));
+
+		let actions = tracker.update(None, PlaybackStatus::Stopped, 0, None, at(662));
+		assert!(matches!(actions[..], [ScrobbleAction::Listen { track_id: 6, .. }]));
+	}
+}
@@ -0,0 +1,396 @@
+use std::collections::{BTreeMap, HashMap};
+
+use {
+	axum::extract::State,
+	bonsaidb::{
+		core::schema::{SerializedCollection, SerializedView},
+		local::AsyncDatabase,
+	},
+	serde_json::json,
+};
+
+use crate::{
+	database::{
+		helpers::paginate,
+		methods,
+		models::{person::Person, playlist::Playlist as StoredPlaylist, release::Release, track::Track},
+		views::{
+			release::{ReleaseByArtistId, ReleaseByName},
+			track::{TrackByReleaseId, TrackByTitle},
+		},
+	},
+	errors::Result,
+	models::tauri::{PageRequest, SortDirection},
+	subsonic::{
+		models::{Album, Artist, ArtistIndex, MusicFolder, Playlist, Resolver, SearchResult},
+		response::{ApiError, ApiResult, Payload, Reply},
+		ApiState, Params,
+	},
+};
+
+/// Articles skipped when indexing artists, so "The Band" is listed under "B".
+const IGNORED_ARTICLES: [&str; 3] = ["The", "A", "An"];
+
+pub async fn ping(params: Params) -> Reply {
+	Reply(params.format, Ok(Payload::empty()))
+}
+
+pub async fn get_license(params: Params) -> Reply {
+	Reply(params.format, Payload::new("license", json!({ "valid": true })))
+}
+
+pub async fn get_extensions(params: Params) -> Reply {
+	Reply(params.format, Payload::new("openSubsonicExtensions", json!([])))
+}
+
+/// Every library is served as a music folder, sharing its id.
+pub async fn get_music_folders(State(state): State<ApiState>, params: Params) -> Reply {
+	let result = async {
+		let folders = methods::library::get_all(&state.database)
+			.await?
+			.into_iter()
+			.map(|(id, x)| MusicFolder { id, name: x.name })
+			.collect::<Vec<_>>();
+
+		Payload::new("musicFolders", json!({ "musicFolder": folders }))
+	};
+
+	Reply(params.format, result.await)
+}
+
+pub async fn get_artists(State(state): State<ApiState>, params: Params) -> Reply {
+	let result = async {
+		let library_id = params.parse::<u64>("musicFolderId")?;
+		let mut indexes = BTreeMap::<String, Vec<(String, Artist)>>::new();
+
+		for (id, person, album_count) in get_album_artists(&state.database, library_id).await? {
+			let sort_name = get_sort_name(&person);
+			let letter = match sort_name.chars().next() {
+				Some(x) if x.is_alphabetic() => x.to_uppercase().to_string(),
+				_ => "#".to_string(),
+			};
+
+			let artist = Artist::new(id, &person, album_count);
+			indexes.entry(letter).or_default().push((sort_name, artist));
+		}
+
+		let index = indexes
+			.into_iter()
+			.map(|(name, mut artists)| {
+				artists.sort_by(|a, b| a.0.cmp(&b.0));
+				ArtistIndex {
+					name,
+					artist: artists.into_iter().map(|(_, x)| x).collect(),
+				}
+			})
+			.collect::<Vec<_>>();
+
+		let ignored_articles = IGNORED_ARTICLES.join(" ");
+		Payload::new(
+			"artists",
+			json!({ "ignoredArticles": ignored_articles, "index": index }),
+		)
+	};
+
+	Reply(params.format, result.await)
+}
+
+pub async fn get_artist(State(state): State<ApiState>, params: Params) -> Reply {
+	let result = async {
+		let id = params.require_id("id")?;
+		let person = Person::get_async(&id, &state.database)
+			.await?
+			.ok_or_else(|| ApiError::not_found("artist"))?
+			.contents;
+
+		let releases = ReleaseByArtistId::entries_async(&state.database)
+			.with_key(&id)
+			.query_with_collection_docs()
+			.await?;
+
+		let mut resolver = Resolver::new(&state.database);
+		let mut albums = Vec::with_capacity(releases.documents.len());
+		for (release_id, doc) in &releases.documents {
+			albums.push(resolver.album(*release_id, &doc.contents).await?);
+		}
+		albums.sort_by_key(|x| x.year);
+
+		let artist = Artist {
+			album: Some(albums),
+			..Artist::new(id, &person, releases.documents.len() as u64)
+		};
+
+		Payload::new("artist", artist)
+	};
+
+	Reply(params.format, result.await)
+}
+
+pub async fn get_album(State(state): State<ApiState>, params: Params) -> Reply {
+	let result = async {
+		let id = params.require_id("id")?;
+		let release = Release::get_async(&id, &state.database)
+			.await?
+			.ok_or_else(|| ApiError::not_found("album"))?
+			.contents;
+
+		let tracks = TrackByReleaseId::entries_async(&state.database)
+			.with_key(&id)
+			.query_with_collection_docs()
+			.await?;
+
+		let mut tracks = tracks
+			.documents
+			.iter()
+			.map(|(id, doc)| (*id, &doc.contents))
+			.collect::<Vec<_>>();
+		tracks.sort_by_key(|(_, x)| (x.disc_number, x.track_number, x.title.clone()));
+
+		let mut resolver = Resolver::new(&state.database);
+		let mut songs = Vec::with_capacity(tracks.len());
+		for (track_id, track) in tracks {
+			songs.push(resolver.song(track_id, track).await?);
+		}
+
+		let album = Album {
+			song_count: Some(songs.len()),
+			song: Some(songs),
+			..resolver.album(id, &release).await?
+		};
+
+		Payload::new("album", album)
+	};
+
+	Reply(params.format, result.await)
+}
+
+pub async fn get_song(State(state): State<ApiState>, params: Params) -> Reply {
+	let result = async {
+		let id = params.require_id("id")?;
+		let track = Track::get_async(&id, &state.database)
+			.await?
+			.ok_or_else(|| ApiError::not_found("song"))?
+			.contents;
+
+		let song = Resolver::new(&state.database).song(id, &track).await?;
+		Payload::new("song", song)
+	};
+
+	Reply(params.format, result.await)
+}
+
+/// Matches the query against the names of artists, albums and songs.
+///
+/// An empty query matches everything, which clients use to sync the whole library page by page.
+pub async fn search3(State(state): State<ApiState>, params: Params) -> Reply {
+	let result = async {
+		let library_id = params.parse::<u64>("musicFolderId")?;
+		let in_library = |x: u64| library_id.map_or(true, |y| x == y);
+
+		let query = params
+			.get("query")
+			.unwrap_or_default()
+			.trim()
+			.trim_matches('"')
+			.to_lowercase();
+		let matches = |x: &str| query.is_empty() || x.to_lowercase().contains(&query);
+
+		let page = |count: &str, offset: &str| -> ApiResult<(usize, usize)> {
+			let count = params.parse::<usize>(count)?.unwrap_or(20);
+			let offset = params.parse::<usize>(offset)?.unwrap_or(0);
+			Ok((count, offset))
+		};
+
+		let mut result = SearchResult::default();
+		let mut resolver = Resolver::new(&state.database);
+
+		let (count, offset) = page("artistCount", "artistOffset")?;
+		if count > 0 {
+			result.artist = get_album_artists(&state.database, library_id)
+				.await?
+				.into_iter()
+				.filter(|(_, person, _)| matches(&person.name))
+				.skip(offset)
+				.take(count)
+				.map(|(id, person, album_count)| Artist::new(id, &person, album_count))
+				.collect();
+		}
+
+		// Albums and songs are walked in the order of their names, reading only as many as the page needs.
+		let (count, offset) = page("albumCount", "albumOffset")?;
+		if count > 0 {
+			let filter = |x: &Release| in_library(x.library_id) && matches(&x.name);
+			let releases =
+				paginate::<ReleaseByName, _>(&state.database, page_request(count, offset), None, filter).await?;
+
+			for x in releases.items.into_iter().skip(offset) {
+				result.album.push(resolver.album(x.id, &x.attributes).await?);
+			}
+		}
+
+		let (count, offset) = page("songCount", "songOffset")?;
+		if count > 0 {
+			let filter = |x: &Track| in_library(x.library_id) && matches(&x.title);
+			let tracks =
+				paginate::<TrackByTitle, _>(&state.database, page_request(count, offset), None, filter).await?;
+
+			for x in tracks.items.into_iter().skip(offset) {
+				result.song.push(resolver.song(x.id, &x.attributes).await?);
+			}
+		}
+
+		Payload::new("searchResult3", result)
+	};
+
+	Reply(params.format, result.await)
+}
+
+/// Requests the first `offset + count` entries of a view, as pages can't be started from an offset.
+fn page_request(count: usize, offset: usize) -> PageRequest {
+	PageRequest {
+		cursor: None,
+		direction: SortDirection::Ascending,
+		limit: u32::try_from(offset.saturating_add(count)).unwrap_or(u32::MAX),
+	}
+}
+
+pub async fn get_playlists(State(state): State<ApiState>, params: Params) -> Reply {
+	let result = async {
+		let owner = &state.configuration.username;
+		let playlists = methods::playlist::get_all(&state.database)
+			.await?
+			.into_iter()
+			.map(|(id, x)| Playlist::new(id, &x, owner))
+			.collect::<Vec<_>>();
+
+		Payload::new("playlists", json!({ "playlist": playlists }))
+	};
+
+	Reply(params.format, result.await)
+}
+
+/// Lists the songs of a playlist in its order, skipping the ones that were removed from the library.
+pub async fn get_playlist(State(state): State<ApiState>, params: Params) -> Reply {
+	let result = async {
+		let id = params.require_id("id")?;
+		let playlist = StoredPlaylist::get_async(&id, &state.database)
+			.await?
+			.ok_or_else(|| ApiError::not_found("playlist"))?
+			.contents;
+
+		let mut resolver = Resolver::new(&state.database);
+		let mut songs = Vec::with_capacity(playlist.track_ids.len());
+		for (track_id, track) in methods::playlist::get_tracks(&state.database, id).await? {
+			songs.push(resolver.song(track_id, &track).await?);
+		}
+
+		let playlist = Playlist {
+			song_count: songs.len(),
+			duration: songs.iter().filter_map(|x| x.duration).sum(),
+			entry: Some(songs),
+			..Playlist::new(id, &playlist, &state.configuration.username)
+		};
+
+		Payload::new("playlist", playlist)
+	};
+
+	Reply(params.format, result.await)
+}
+
+/// Resolves the artists credited in at least one release, along with their release count.
+///
+/// Only the releases of the given library are counted, or every release when it's missing.
+/// The artists are ordered by their sort name and then their id, so pages over them are stable.
+async fn get_album_artists(database: &AsyncDatabase, library_id: Option<u64>) -> Result<Vec<(u64, Person, u64)>> {
+	let counts = match library_id {
+		Some(library_id) => {
+			let release_ids = methods::library::get_release_ids(database, library_id).await?;
+
+			let mut counts = HashMap::<u64, u64>::new();
+			for mapping in &ReleaseByArtistId::entries_async(database).query().await? {
+				if release_ids.contains(&mapping.source.id) {
+					*counts.entry(mapping.key).or_default() += 1;
+				}
+			}
+
+			counts
+		}
+		None => ReleaseByArtistId::entries_async(database)
+			.reduce_grouped()
+			.await?
+			.into_iter()
+			.map(|x| (x.key, x.value))
+			.collect(),
+	};
+
+	let ids = counts.keys().copied().collect::<Vec<u64>>();
+	let mut people = Person::get_multiple_async(&ids, database)
+		.await?
+		.into_iter()
+		.map(|doc| (doc.header.id, doc.contents, counts[&doc.header.id]))
+		.collect::<Vec<_>>();
+
+	people.sort_by_cached_key(|(id, person, _)| (get_sort_name(person), *id));
+	Ok(people)
+}
+
+/// Resolves the name used to index an artist, without the leading articles.
+fn get_sort_name(person: &Person) -> String {
+	let name = person.name_sort.as_ref().unwrap_or(&person.name);
+
+	for article in IGNORED_ARTICLES {
+		if let Some(x) = name.strip_prefix(article).and_then(|x| x.strip_prefix(' ')) {
+			return x.to_lowercase();
+		}
+	}
+
+	name.to_lowercase()
+}
+
+#[cfg(test)]
+mod test {
+	use bonsaidb::core::schema::SerializedCollection;
+
+	use crate::{
+		database::{
+			models::{
+				person::{Person, PersonType},
+				release::Release,
+				InlinedArtist,
+			},
+			Database,
+		},
+		errors::Result,
+		subsonic::handlers::get_album_artists,
+	};
+
+	#[tokio::test]
+	async fn test_get_album_artists() -> Result<()> {
+		let db = Database::testing().await?;
+		let dbx = db.0;
+
+		for (id, name) in [(10, "The Zebras"), (11, "Apples"), (12, "Zebras"), (13, "Apples")] {
+			Person::new(name.to_string(), PersonType::Artist)
+				.insert_into_async(&id, &dbx)
+				.await?;
+
+			let release = Release {
+				artists: vec![InlinedArtist {
+					id,
+					..InlinedArtist::unknown()
+				}],
+				..Default::default()
+			};
+			release.insert_into_async(&id, &dbx).await?;
+		}
+
+		let ids = get_album_artists(&dbx, None)
+			.await?
+			.into_iter()
+			.map(|(id, _, _)| id)
+			.collect::<Vec<_>>();
+		assert_eq!(ids, vec![11, 13, 10, 12]);
+
+		Ok(())
+	}
+}
@@ -0,0 +1,283 @@
+use std::{
+	borrow::Cow,
+	net::{Ipv4Addr, SocketAddr, TcpListener},
+	path::PathBuf,
+	str::FromStr,
+	sync::Arc,
+	time::Duration,
+};
+
+use {
+	axum::{
+		async_trait,
+		extract::{FromRequestParts, Query},
+		http::request::Parts,
+		response::{IntoResponse, Response},
+		routing::{get, MethodRouter},
+		Router,
+	},
+	bonsaidb::local::AsyncDatabase,
+	tokio::{sync::oneshot, task::JoinHandle},
+	tracing::{error, info, warn},
+};
+
+use crate::{
+	errors::{Error, Result},
+	models::configuration::ServerConfiguration,
+	subsonic::response::{ApiError, ApiResult, Format, Reply},
+};
+
+mod handlers;
+mod models;
+mod response;
+mod stream;
+
+/// Time given to the ongoing requests, like streams, to finish once the server is asked to stop.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Embedded HTTP server implementing the Subsonic API, so existing clients can browse and stream the library.
+pub struct SubsonicServer {
+	pub configuration: ServerConfiguration,
+	shutdown: oneshot::Sender<()>,
+	handle: JoinHandle<()>,
+}
+
+#[derive(Clone)]
+pub struct ApiState {
+	pub database: AsyncDatabase,
+	pub cover_dir: PathBuf,
+	pub configuration: Arc<ServerConfiguration>,
+}
+
+/// Query parameters of an authenticated request.
+pub struct Params {
+	pub format: Format,
+	pairs: Vec<(String, String)>,
+}
+
+impl SubsonicServer {
+	/// Binds to the given port on every interface, so clients in the same network can reach it.
+	pub fn bind(port: u16) -> Result<TcpListener> {
+		let address = SocketAddr::from((Ipv4Addr::UNSPECIFIED, port));
+		let listener = TcpListener::bind(address)
+			.map_err(|e| Error::from(e).append_message("Failed to bind the Subsonic server"))?;
+		listener.set_nonblocking(true)?;
+
+		Ok(listener)
+	}
+
+	/// Serves the API through a listener from [SubsonicServer::bind].
+	pub fn start(
+		configuration: ServerConfiguration,
+		listener: TcpListener,
+		database: AsyncDatabase,
+		cover_dir: PathBuf,
+	) -> Result<Self> {
+		let address = listener.local_addr()?;
+
+		let state = ApiState {
+			database,
+			cover_dir,
+			configuration: Arc::new(configuration.clone()),
+		};
+
+		let server = axum::Server::from_tcp(listener)
+			.map_err(|e| Error::new("Subsonic: Server error", Cow::Owned(e.to_string())))?
+			.serve(router(state).into_make_service());
+
+		let (shutdown, rx) = oneshot::channel::<()>();
+		let handle = tokio::spawn(async move {
+			let server = server.with_graceful_shutdown(async {
+				rx.await.ok();
+			});
+
+			if let Err(e) = server.await {
+				error!("Subsonic server stopped unexpectedly: {e:#?}");
+			}
+		});
+
+		info!("Serving the Subsonic API on {address}");
+
+		Ok(Self {
+			configuration,
+			shutdown,
+			handle,
+		})
+	}
+
+	/// Stops accepting requests, waiting up to [SHUTDOWN_TIMEOUT] for the ongoing ones to finish.
+	pub async fn stop(mut self) -> Result<()> {
+		let _ = self.shutdown.send(());
+
+		if tokio::time::timeout(SHUTDOWN_TIMEOUT, &mut self.handle).await.is_err() {
+			warn!("Subsonic server didn't stop within {SHUTDOWN_TIMEOUT:?}, dropping the ongoing requests");
+			self.handle.abort();
+		}
+
+		info!("Stopped the Subsonic server");
+
+		Ok(())
+	}
+}
+
+/// Every endpoint is reachable both with and without the `.view` suffix, as older clients still add it.
+fn router(state: ApiState) -> Router {
+	let endpoints: [(&str, MethodRouter<ApiState>); 14] = [
+		("ping", get(handlers::ping).post(handlers::ping)),
+		("getLicense", get(handlers::get_license).post(handlers::get_license)),
+		(
+			"getMusicFolders",
+			get(handlers::get_music_folders).post(handlers::get_music_folders),
+		),
+		("getArtists", get(handlers::get_artists).post(handlers::get_artists)),
+		("getArtist", get(handlers::get_artist).post(handlers::get_artist)),
+		("getAlbum", get(handlers::get_album).post(handlers::get_album)),
+		("getSong", get(handlers::get_song).post(handlers::get_song)),
+		("search3", get(handlers::search3).post(handlers::search3)),
+		(
+			"getPlaylists",
+			get(handlers::get_playlists).post(handlers::get_playlists),
+		),
+		("getPlaylist", get(handlers::get_playlist).post(handlers::get_playlist)),
+		("stream", get(stream::stream).post(stream::stream)),
+		("download", get(stream::download).post(stream::download)),
+		("getCoverArt", get(stream::get_cover_art).post(stream::get_cover_art)),
+		(
+			"getOpenSubsonicExtensions",
+			get(handlers::get_extensions).post(handlers::get_extensions),
+		),
+	];
+
+	let mut router = Router::new();
+	for (name, route) in endpoints {
+		router = router
+			.route(&format!("/rest/{name}"), route.clone())
+			.route(&format!("/rest/{name}.view"), route);
+	}
+
+	router.with_state(state)
+}
+
+#[async_trait]
+impl FromRequestParts<ApiState> for Params {
+	type Rejection = Response;
+
+	async fn from_request_parts(parts: &mut Parts, state: &ApiState) -> std::result::Result<Self, Self::Rejection> {
+		let pairs = Query::<Vec<(String, String)>>::from_request_parts(parts, state)
+			.await
+			.map(|x| x.0)
+			.unwrap_or_default();
+
+		let format = pairs.iter().find(|(k, _)| k == "f").map(|(_, v)| v.as_str());
+		let params = Params {
+			format: Format::from_param(format),
+			pairs,
+		};
+
+		match params.authenticate(&state.configuration) {
+			Ok(()) => Ok(params),
+			Err(e) => Err(Reply(params.format, Err(e)).into_response()),
+		}
+	}
+}
+
+impl Params {
+	pub fn get(&self, key: &str) -> Option<&str> {
+		self.pairs.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+	}
+
+	pub fn require(&self, key: &str) -> ApiResult<&str> {
+		self.get(key).ok_or_else(|| ApiError::missing_parameter(key))
+	}
+
+	/// Parses an optional parameter, failing if it's given but invalid.
+	pub fn parse<T: FromStr>(&self, key: &str) -> ApiResult<Option<T>> {
+		match self.get(key) {
+			Some(x) => x.parse::<T>().map(Some).map_err(|_| ApiError::invalid_parameter(key)),
+			None => Ok(None),
+		}
+	}
+
+	/// Parses a required id parameter, as every id given out by this server is numeric.
+	pub fn require_id(&self, key: &str) -> ApiResult<u64> {
+		self.require(key)?
+			.parse::<u64>()
+			.map_err(|_| ApiError::invalid_parameter(key))
+	}
+
+	/// Checks the credentials, given either as a password (`p`) or a salted token (`t`, `s`).
+	///
+	/// Passwords may be hex encoded with an `enc:` prefix.
+	fn authenticate(&self, configuration: &ServerConfiguration) -> ApiResult<()> {
+		let username = self.require("u")?;
+
+		let valid = match (self.get("p"), self.get("t"), self.get("s")) {
+			(Some(password), _, _) => decode_password(password).is_some_and(|x| x == configuration.password),
+			(None, Some(token), Some(salt)) => {
+				let expected = md5::compute(format!("{}{salt}", configuration.password));
+				token.eq_ignore_ascii_case(&format!("{expected:x}"))
+			}
+			_ => return Err(ApiError::missing_parameter("p")),
+		};
+
+		if username != configuration.username || !valid {
+			return Err(ApiError::wrong_credentials());
+		}
+
+		Ok(())
+	}
+}
+
+fn decode_password(password: &str) -> Option<String> {
+	let Some(hex) = password.strip_prefix("enc:") else {
+		return Some(password.to_string());
+	};
+
+	let bytes = (0..hex.len())
+		.step_by(2)
+		.map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+		.collect::<Option<Vec<u8>>>()?;
+
+	String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod test {
+	use crate::{
+		models::configuration::ServerConfiguration,
+		subsonic::{response::Format, Params},
+	};
+
+	fn params(pairs: &[(&str, &str)]) -> Params {
+		Params {
+			format: Format::Xml,
+			pairs: pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+		}
+	}
+
+	#[test]
+	fn test_authenticate() {
+		let configuration = ServerConfiguration {
+			username: "melody".to_string(),
+			password: "sesame".to_string(),
+			..Default::default()
+		};
+
+		// Example from the API documentation, md5("sesamec19b2d")
+		let token = params(&[
+			("u", "melody"),
+			("t", "26719a1196d2a940705a59634eb18eab"),
+			("s", "c19b2d"),
+		]);
+		assert!(token.authenticate(&configuration).is_ok());
+
+		let password = params(&[("u", "melody"), ("p", "enc:736573616d65")]);
+		assert!(password.authenticate(&configuration).is_ok());
+
+		let wrong = params(&[("u", "melody"), ("p", "open")]);
+		assert_eq!(wrong.authenticate(&configuration).unwrap_err().code, 40);
+
+		let missing = params(&[("u", "melody")]);
+		assert_eq!(missing.authenticate(&configuration).unwrap_err().code, 10);
+	}
+}
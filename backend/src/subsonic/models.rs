@@ -0,0 +1,275 @@
+use std::{collections::HashMap, path::Path};
+
+use {
+	bonsaidb::{core::schema::SerializedCollection, local::AsyncDatabase},
+	chrono::{DateTime, Utc},
+	serde::Serialize,
+};
+
+use crate::{
+	database::models::{
+		person::Person, playlist::Playlist as StoredPlaylist, release::Release, track::Track, InlinedArtist,
+	},
+	errors::Result,
+};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MusicFolder {
+	pub id: u64,
+	pub name: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtistIndex {
+	pub name: String,
+	pub artist: Vec<Artist>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Artist {
+	pub id: String,
+	pub name: String,
+	pub album_count: u64,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub cover_art: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub album: Option<Vec<Album>>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Album {
+	pub id: String,
+	pub name: String,
+	pub artist: String,
+	pub artist_id: String,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub cover_art: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub year: Option<i32>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub song_count: Option<usize>,
+	pub created: DateTime<Utc>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub song: Option<Vec<Song>>,
+}
+
+/// Named `Child` in the Subsonic schema, only ever used for tracks here.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Song {
+	pub id: String,
+	pub parent: String,
+	pub is_dir: bool,
+	pub title: String,
+	pub album: String,
+	pub album_id: String,
+	pub artist: String,
+	pub artist_id: String,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub track: Option<u32>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub disc_number: Option<u32>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub year: Option<i32>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub cover_art: Option<String>,
+	/// Only known for the tracks split from a CUE sheet.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub duration: Option<u64>,
+	pub suffix: String,
+	pub content_type: &'static str,
+	pub path: String,
+	#[serde(rename = "type")]
+	pub type_: &'static str,
+	pub created: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Playlist {
+	pub id: String,
+	pub name: String,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub comment: Option<String>,
+	pub owner: String,
+	pub public: bool,
+	pub song_count: usize,
+	/// Sum of the known song durations in seconds.
+	pub duration: u64,
+	pub created: DateTime<Utc>,
+	pub changed: DateTime<Utc>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub entry: Option<Vec<Song>>,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResult {
+	pub artist: Vec<Artist>,
+	pub album: Vec<Album>,
+	pub song: Vec<Song>,
+}
+
+impl Artist {
+	pub fn new(id: u64, person: &Person, album_count: u64) -> Self {
+		Self {
+			id: id.to_string(),
+			name: person.name.clone(),
+			album_count,
+			cover_art: first_cover_id(&person.cover_ids),
+			album: None,
+		}
+	}
+}
+
+impl Playlist {
+	/// Playlists are owned by the only user of the server.
+	pub fn new(id: u64, playlist: &StoredPlaylist, owner: &str) -> Self {
+		Self {
+			id: id.to_string(),
+			name: playlist.name.clone(),
+			comment: playlist.description.clone(),
+			owner: owner.to_string(),
+			public: false,
+			song_count: playlist.track_ids.len(),
+			duration: 0,
+			created: playlist.date_added,
+			changed: playlist.date_modified,
+			entry: None,
+		}
+	}
+}
+
+/// Resolves the artist names and releases referenced by entries, caching the ones already read.
+pub struct Resolver<'a> {
+	database: &'a AsyncDatabase,
+	people: HashMap<u64, Option<Person>>,
+	releases: HashMap<u64, Option<Release>>,
+}
+
+impl<'a> Resolver<'a> {
+	pub fn new(database: &'a AsyncDatabase) -> Self {
+		Self {
+			database,
+			people: HashMap::new(),
+			releases: HashMap::new(),
+		}
+	}
+
+	pub async fn person(&mut self, id: u64) -> Result<Option<&Person>> {
+		if !self.people.contains_key(&id) {
+			let person = Person::get_async(&id, self.database).await?.map(|x| x.contents);
+			self.people.insert(id, person);
+		}
+
+		Ok(self.people[&id].as_ref())
+	}
+
+	pub async fn release(&mut self, id: u64) -> Result<Option<&Release>> {
+		if !self.releases.contains_key(&id) {
+			let release = Release::get_async(&id, self.database).await?.map(|x| x.contents);
+			self.releases.insert(id, release);
+		}
+
+		Ok(self.releases[&id].as_ref())
+	}
+
+	/// Joins the credited names of the artists, like "A feat. B".
+	pub async fn artist_name(&mut self, artists: &[InlinedArtist]) -> Result<String> {
+		let mut name = String::new();
+
+		for (i, artist) in artists.iter().enumerate() {
+			match &artist.credited_as {
+				Some(x) => name.push_str(x),
+				None => name.push_str(self.person(artist.id).await?.map_or("", |x| x.name.as_str())),
+			}
+
+			if i + 1 < artists.len() {
+				name.push_str(artist.join.as_deref().unwrap_or(", "));
+			}
+		}
+
+		Ok(name)
+	}
+
+	pub async fn album(&mut self, id: u64, release: &Release) -> Result<Album> {
+		Ok(Album {
+			id: id.to_string(),
+			name: release.name.clone(),
+			artist: self.artist_name(&release.artists).await?,
+			artist_id: first_artist_id(&release.artists),
+			cover_art: first_cover_id(&release.cover_ids),
+			year: release.get_year(),
+			song_count: None,
+			created: release.date_added,
+			song: None,
+		})
+	}
+
+	pub async fn song(&mut self, id: u64, track: &Track) -> Result<Song> {
+		let artist = self.artist_name(&track.artists).await?;
+		let release = self.release(track.release_id).await?;
+
+		let album = release.map(|x| x.name.clone()).unwrap_or_default();
+		let year = release.and_then(|x| x.get_year());
+		let cover_art = first_cover_id(&track.cover_ids).or_else(|| release.and_then(|x| first_cover_id(&x.cover_ids)));
+
+		let suffix = Path::new(&track.path)
+			.extension()
+			.map(|x| x.to_string_lossy().to_lowercase())
+			.unwrap_or_default();
+
+		Ok(Song {
+			id: id.to_string(),
+			parent: track.release_id.to_string(),
+			is_dir: false,
+			title: track.title.clone(),
+			album,
+			album_id: track.release_id.to_string(),
+			artist,
+			artist_id: first_artist_id(&track.artists),
+			track: track.track_number,
+			disc_number: track.disc_number,
+			year,
+			cover_art,
+			duration: track.segment.and_then(|x| Some((x.end? - x.start) / 1000)),
+			content_type: content_type(&suffix),
+			suffix,
+			path: track.path.clone(),
+			type_: "music",
+			created: track.date_added,
+		})
+	}
+}
+
+pub fn content_type(suffix: &str) -> &'static str {
+	match suffix {
+		"mp3" => "audio/mpeg",
+		"flac" => "audio/flac",
+		"ogg" | "oga" | "opus" => "audio/ogg",
+		"wav" => "audio/wav",
+		"aac" => "audio/aac",
+		"m4a" | "m4b" | "alac" => "audio/mp4",
+		"aif" | "aiff" | "aifc" => "audio/aiff",
+		"wv" => "audio/x-wavpack",
+		"ape" => "audio/x-ape",
+		"dsf" | "dff" => "audio/x-dsd",
+		"tta" => "audio/x-tta",
+		"wma" => "audio/x-ms-wma",
+		"mka" => "audio/x-matroska",
+		"mpc" => "audio/x-musepack",
+		_ => "application/octet-stream",
+	}
+}
+
+fn first_artist_id(artists: &[InlinedArtist]) -> String {
+	artists.first().map(|x| x.id.to_string()).unwrap_or_default()
+}
+
+fn first_cover_id(cover_ids: &Option<Vec<u64>>) -> Option<String> {
+	cover_ids.as_ref()?.first().map(|x| x.to_string())
+}
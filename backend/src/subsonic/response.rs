@@ -0,0 +1,236 @@
+use std::borrow::Cow;
+
+use {
+	axum::{
+		http::{header, StatusCode},
+		response::{IntoResponse, Response},
+	},
+	serde::Serialize,
+	serde_json::{json, Map, Value},
+	tracing::error,
+};
+
+use crate::errors::Error;
+
+/// Version of the Subsonic API this server follows.
+pub const API_VERSION: &str = "1.16.1";
+const XML_NAMESPACE: &str = "http://subsonic.org/restapi";
+
+pub type ApiResult<T> = std::result::Result<T, ApiError>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+	Xml,
+	Json,
+}
+
+/// Error returned inside of the response envelope, following the codes of the Subsonic API.
+#[derive(Debug)]
+pub struct ApiError {
+	pub code: u32,
+	pub message: Cow<'static, str>,
+}
+
+/// Element nested in the `subsonic-response` envelope, like `album` in `getAlbum`.
+#[derive(Debug)]
+pub struct Payload(Option<(&'static str, Value)>);
+
+/// Renders a [Payload] or an [ApiError] in the format the client asked for.
+pub struct Reply(pub Format, pub ApiResult<Payload>);
+
+impl Format {
+	/// Resolves the `f` parameter, where XML is the default and JSONP is served as JSON.
+	pub fn from_param(value: Option<&str>) -> Self {
+		match value {
+			Some("json" | "jsonp") => Self::Json,
+			_ => Self::Xml,
+		}
+	}
+}
+
+impl ApiError {
+	pub fn missing_parameter(name: &str) -> Self {
+		Self {
+			code: 10,
+			message: Cow::Owned(format!("Required parameter '{name}' is missing")),
+		}
+	}
+
+	pub fn invalid_parameter(name: &str) -> Self {
+		Self {
+			code: 10,
+			message: Cow::Owned(format!("Parameter '{name}' is invalid")),
+		}
+	}
+
+	pub fn wrong_credentials() -> Self {
+		Self {
+			code: 40,
+			message: Cow::Borrowed("Wrong username or password"),
+		}
+	}
+
+	pub fn not_found(entity: &str) -> Self {
+		Self {
+			code: 70,
+			message: Cow::Owned(format!("The requested {entity} was not found")),
+		}
+	}
+}
+
+impl<E: Into<Error>> From<E> for ApiError {
+	fn from(value: E) -> Self {
+		let value = value.into();
+		error!("Subsonic request failed: {value:#?}");
+
+		Self {
+			code: 0,
+			message: Cow::Owned(value.to_string()),
+		}
+	}
+}
+
+impl Payload {
+	pub fn empty() -> Self {
+		Self(None)
+	}
+
+	pub fn new(name: &'static str, value: impl Serialize) -> ApiResult<Self> {
+		let value = serde_json::to_value(value)?;
+		Ok(Self(Some((name, value))))
+	}
+}
+
+impl IntoResponse for Reply {
+	fn into_response(self) -> Response {
+		let Reply(format, result) = self;
+
+		let mut body = Map::new();
+		body.insert("status".into(), json!(if result.is_ok() { "ok" } else { "failed" }));
+		body.insert("version".into(), json!(API_VERSION));
+		body.insert("type".into(), json!("melody"));
+		body.insert("serverVersion".into(), json!(env!("CARGO_PKG_VERSION")));
+		body.insert("openSubsonic".into(), json!(true));
+
+		match result {
+			Ok(Payload(Some((name, value)))) => {
+				body.insert(name.into(), value);
+			}
+			Ok(Payload(None)) => {}
+			Err(e) => {
+				body.insert("error".into(), json!({ "code": e.code, "message": e.message }));
+			}
+		}
+
+		// Errors are reported inside the envelope, as the clients don't look at the status code.
+		match format {
+			Format::Json => {
+				let body = json!({ "subsonic-response": body });
+				(StatusCode::OK, axum::Json(body)).into_response()
+			}
+			Format::Xml => {
+				body.insert("xmlns".into(), json!(XML_NAMESPACE));
+
+				let mut xml = String::from(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+				write_element(&mut xml, "subsonic-response", &body);
+
+				(StatusCode::OK, [(header::CONTENT_TYPE, "text/xml; charset=utf-8")], xml).into_response()
+			}
+		}
+	}
+}
+
+/// Writes a JSON object as an XML element, following the conventions of the Subsonic schema.
+///
+/// Scalars become attributes, objects become child elements and arrays become repeated child elements.
+fn write_element(out: &mut String, name: &str, object: &Map<String, Value>) {
+	out.push('<');
+	out.push_str(name);
+
+	for (key, value) in object {
+		let value = match value {
+			Value::String(x) => Cow::Borrowed(x.as_str()),
+			Value::Bool(_) | Value::Number(_) => Cow::Owned(value.to_string()),
+			_ => continue,
+		};
+
+		out.push_str(&format!(r#" {key}="{}""#, escape(&value)));
+	}
+
+	let children = object
+		.iter()
+		.filter(|(_, x)| x.is_object() || x.is_array())
+		.collect::<Vec<_>>();
+
+	if children.is_empty() {
+		out.push_str("/>");
+		return;
+	}
+
+	out.push('>');
+
+	for (key, value) in children {
+		match value {
+			Value::Object(x) => write_element(out, key, x),
+			Value::Array(items) => {
+				for item in items {
+					match item {
+						Value::Object(x) => write_element(out, key, x),
+						Value::Null => {}
+						x => out.push_str(&format!("<{key}>{}</{key}>", escape(&x.to_string()))),
+					}
+				}
+			}
+			_ => unreachable!(),
+		}
+	}
+
+	out.push_str(&format!("</{name}>"));
+}
+
+fn escape(value: &str) -> String {
+	let mut escaped = String::with_capacity(value.len());
+
+	for c in value.chars() {
+		match c {
+			'&' => escaped.push_str("&amp;"),
+			'<' => escaped.push_str("&lt;"),
+			'>' => escaped.push_str("&gt;"),
+			'"' => escaped.push_str("&quot;"),
+			'\'' => escaped.push_str("&apos;"),
+			x => escaped.push(x),
+		}
+	}
+
+	escaped
+}
+
+#[cfg(test)]
+mod test {
+	use serde_json::json;
+
+	use super::write_element;
+
+	#[test]
+	fn test_write_element() {
+		let value = json!({
+			"id": "1",
+			"name": "Rock & Roll",
+			"songCount": 2,
+			"song": [{ "id": "2", "title": "<Intro>" }, { "id": "3", "title": "Outro" }],
+			"coverArt": null,
+		});
+
+		let mut xml = String::new();
+		write_element(&mut xml, "album", value.as_object().unwrap());
+
+		assert_eq!(
+			xml,
+			concat!(
+				r#"<album id="1" name="Rock &amp; Roll" songCount="2">"#,
+				r#"<song id="2" title="&lt;Intro&gt;"/><song id="3" title="Outro"/>"#,
+				"</album>"
+			)
+		);
+	}
+}
@@ -0,0 +1,336 @@
+use std::{
+	path::Path,
+	pin::Pin,
+	process::Stdio,
+	task::{Context, Poll},
+};
+
+use {
+	axum::{
+		body::{Body, Bytes, StreamBody},
+		extract::State,
+		http::{header, Request},
+		response::{IntoResponse, Response},
+	},
+	bonsaidb::core::schema::SerializedCollection,
+	futures::Stream,
+	tokio::process::{Child, ChildStdout, Command},
+	tokio_util::io::ReaderStream,
+	tower::ServiceExt,
+	tower_http::services::ServeFile,
+};
+
+use crate::{
+	database::models::{cover::Cover, track::Track},
+	errors::Error,
+	models::{
+		configuration::{ServerConfiguration, TranscodeFormat},
+		tauri::cover::DisplayCover,
+	},
+	subsonic::{
+		response::{ApiError, ApiResult, Reply},
+		ApiState, Params,
+	},
+};
+
+const LOSSLESS_SUFFIXES: [&str; 10] = ["flac", "wav", "aif", "aiff", "aifc", "ape", "wv", "tta", "dsf", "dff"];
+
+/// How a track is sent to the client.
+#[derive(Debug, PartialEq)]
+enum Output {
+	Original,
+	Transcode {
+		format: TranscodeFormat,
+		bitrate: u32,
+		/// Offsets in milliseconds.
+		start: u64,
+		end: Option<u64>,
+	},
+}
+
+/// Keeps the `ffmpeg` process alive for as long as its output is being sent, killing it once the body is dropped.
+struct TranscodeStream {
+	inner: ReaderStream<ChildStdout>,
+	_child: Child,
+}
+
+impl Stream for TranscodeStream {
+	type Item = std::io::Result<Bytes>;
+
+	fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		Pin::new(&mut self.inner).poll_next(cx)
+	}
+}
+
+/// Serves the original file with range support, or a transcode of it.
+///
+/// Transcodes are made when the client asks for another format, limits the bitrate of a lossless file,
+/// seeks through `timeOffset`, or when the track is a segment of a larger file.
+pub async fn stream(State(state): State<ApiState>, params: Params, request: Request<Body>) -> Response {
+	let result = async {
+		let track = get_track(&state, &params).await?;
+		let output = resolve_output(&params, &state.configuration, &track)?;
+
+		match output {
+			Output::Original => serve_file(&track.path, request).await,
+			Output::Transcode {
+				format,
+				bitrate,
+				start,
+				end,
+			} => transcode(
+				&state.configuration.ffmpeg_path,
+				&track.path,
+				format,
+				bitrate,
+				start,
+				end,
+			),
+		}
+	};
+
+	match result.await {
+		Ok(x) => x,
+		Err(e) => Reply(params.format, Err(e)).into_response(),
+	}
+}
+
+/// Serves the original file, even if the track is a segment of it.
+pub async fn download(State(state): State<ApiState>, params: Params, request: Request<Body>) -> Response {
+	let result = async {
+		let track = get_track(&state, &params).await?;
+		serve_file(&track.path, request).await
+	};
+
+	match result.await {
+		Ok(x) => x,
+		Err(e) => Reply(params.format, Err(e)).into_response(),
+	}
+}
+
+/// Serves a cover by its id, as stored after the scan.
+///
+/// Covers larger than 512px are only kept as thumbnails, so the `size` parameter is not followed.
+pub async fn get_cover_art(State(state): State<ApiState>, params: Params) -> Response {
+	let result = async {
+		let id = params.require_id("id")?;
+		let cover = Cover::get_async(&id, &state.database)
+			.await?
+			.ok_or_else(|| ApiError::not_found("cover"))?
+			.contents;
+
+		let path = DisplayCover::from_cover(cover, &state.cover_dir).path;
+		let bytes = tokio::fs::read(&path).await?;
+
+		// Thumbnails are always written as PNGs, regardless of their extension.
+		let content_type = match image::guess_format(&bytes) {
+			Ok(image::ImageFormat::Png) => "image/png",
+			Ok(image::ImageFormat::Jpeg) => "image/jpeg",
+			Ok(image::ImageFormat::WebP) => "image/webp",
+			_ => "application/octet-stream",
+		};
+
+		Ok::<_, ApiError>(([(header::CONTENT_TYPE, content_type)], bytes).into_response())
+	};
+
+	match result.await {
+		Ok(x) => x,
+		Err(e) => Reply(params.format, Err(e)).into_response(),
+	}
+}
+
+async fn get_track(state: &ApiState, params: &Params) -> ApiResult<Track> {
+	let id = params.require_id("id")?;
+	let track = Track::get_async(&id, &state.database)
+		.await?
+		.ok_or_else(|| ApiError::not_found("song"))?
+		.contents;
+
+	Ok(track)
+}
+
+fn resolve_output(params: &Params, configuration: &ServerConfiguration, track: &Track) -> ApiResult<Output> {
+	let suffix = Path::new(&track.path)
+		.extension()
+		.map(|x| x.to_string_lossy().to_lowercase())
+		.unwrap_or_default();
+
+	let requested = params.get("format");
+	let max_bit_rate = params.parse::<u32>("maxBitRate")?.filter(|x| *x > 0);
+	let time_offset = params.parse::<u64>("timeOffset")?.unwrap_or(0);
+
+	let format = match requested {
+		Some("mp3") => Some(TranscodeFormat::Mp3),
+		Some("opus" | "ogg") => Some(TranscodeFormat::Opus),
+		Some("aac" | "m4a") => Some(TranscodeFormat::Aac),
+		_ => None,
+	};
+
+	let wants_other_format = format.is_some() && requested != Some(suffix.as_str());
+	let wants_lower_bitrate =
+		requested != Some("raw") && max_bit_rate.is_some() && LOSSLESS_SUFFIXES.contains(&suffix.as_str());
+
+	if track.segment.is_none() && time_offset == 0 && !wants_other_format && !wants_lower_bitrate {
+		return Ok(Output::Original);
+	}
+
+	let segment_start = track.segment.map_or(0, |x| x.start);
+	let end = track.segment.and_then(|x| x.end);
+
+	// The offset is given in seconds by the client, so it has to be checked before being added to the segment.
+	let start = time_offset
+		.checked_mul(1000)
+		.and_then(|x| x.checked_add(segment_start))
+		.ok_or_else(|| ApiError::invalid_parameter("timeOffset"))?;
+
+	Ok(Output::Transcode {
+		format: format.unwrap_or(configuration.transcode_format),
+		bitrate: max_bit_rate.unwrap_or(configuration.transcode_bitrate).clamp(32, 320),
+		start: end.map_or(start, |x| start.min(x)),
+		end,
+	})
+}
+
+async fn serve_file(path: &str, request: Request<Body>) -> ApiResult<Response> {
+	if !Path::new(path).exists() {
+		return Err(ApiError::not_found("file"));
+	}
+
+	let response = ServeFile::new(path).oneshot(request).await.unwrap();
+	Ok(response.into_response())
+}
+
+fn transcode(
+	ffmpeg_path: &str,
+	path: &str,
+	format: TranscodeFormat,
+	bitrate: u32,
+	start: u64,
+	end: Option<u64>,
+) -> ApiResult<Response> {
+	let (codec, container, content_type) = match format {
+		TranscodeFormat::Mp3 => ("libmp3lame", "mp3", "audio/mpeg"),
+		TranscodeFormat::Opus => ("libopus", "ogg", "audio/ogg"),
+		TranscodeFormat::Aac => ("aac", "adts", "audio/aac"),
+	};
+
+	let mut command = Command::new(ffmpeg_path);
+	command
+		.args(["-v", "error", "-nostdin"])
+		.args(["-ss", &format_seconds(start)])
+		.arg("-i")
+		.arg(path);
+
+	if let Some(end) = end {
+		command.args(["-t", &format_seconds(end.saturating_sub(start))]);
+	}
+
+	command
+		.args(["-map", "0:a:0", "-vn", "-c:a", codec])
+		.args(["-b:a", &format!("{bitrate}k")])
+		.args(["-f", container, "pipe:1"])
+		.stdin(Stdio::null())
+		.stdout(Stdio::piped())
+		.stderr(Stdio::null())
+		.kill_on_drop(true);
+
+	let mut child = command
+		.spawn()
+		.map_err(|e| Error::from(e).append_message("Failed to start ffmpeg for transcoding"))?;
+	let stdout = child.stdout.take().unwrap();
+
+	let body = StreamBody::new(TranscodeStream {
+		inner: ReaderStream::new(stdout),
+		_child: child,
+	});
+
+	Ok(([(header::CONTENT_TYPE, content_type)], body).into_response())
+}
+
+fn format_seconds(millis: u64) -> String {
+	format!("{}.{:03}", millis / 1000, millis % 1000)
+}
+
+#[cfg(test)]
+mod test {
+	use crate::{
+		database::models::track::{Track, TrackSegment},
+		models::configuration::{ServerConfiguration, TranscodeFormat},
+		subsonic::{
+			response::Format,
+			stream::{resolve_output, Output},
+			Params,
+		},
+	};
+
+	fn params(pairs: &[(&str, &str)]) -> Params {
+		Params {
+			format: Format::Xml,
+			pairs: pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+		}
+	}
+
+	#[test]
+	fn test_resolve_output() {
+		let configuration = ServerConfiguration::default();
+		let track = Track::default();
+
+		let output = resolve_output(&params(&[]), &configuration, &track).unwrap();
+		assert_eq!(output, Output::Original);
+
+		let output = resolve_output(&params(&[("format", "flac")]), &configuration, &track).unwrap();
+		assert_eq!(output, Output::Original);
+
+		let output = resolve_output(&params(&[("maxBitRate", "128")]), &configuration, &track).unwrap();
+		assert_eq!(
+			output,
+			Output::Transcode {
+				format: TranscodeFormat::Mp3,
+				bitrate: 128,
+				start: 0,
+				end: None,
+			}
+		);
+
+		let track = Track {
+			segment: Some(TrackSegment {
+				start: 60_000,
+				end: Some(120_000),
+			}),
+			..Default::default()
+		};
+
+		let output = resolve_output(
+			&params(&[("format", "opus"), ("timeOffset", "10")]),
+			&configuration,
+			&track,
+		);
+		assert_eq!(
+			output.unwrap(),
+			Output::Transcode {
+				format: TranscodeFormat::Opus,
+				bitrate: 192,
+				start: 70_000,
+				end: Some(120_000),
+			}
+		);
+
+		let output = resolve_output(&params(&[("timeOffset", "600")]), &configuration, &track);
+		assert_eq!(
+			output.unwrap(),
+			Output::Transcode {
+				format: TranscodeFormat::Mp3,
+				bitrate: 192,
+				start: 120_000,
+				end: Some(120_000),
+			}
+		);
+
+		let output = resolve_output(
+			&params(&[("timeOffset", &u64::MAX.to_string())]),
+			&configuration,
+			&track,
+		);
+		assert_eq!(output.unwrap_err().code, 10);
+	}
+}
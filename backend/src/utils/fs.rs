@@ -1,60 +1,132 @@
-use std::{
-	io,
-	path::{Path, PathBuf},
+use std::path::{Path, PathBuf};
+
+use {
+	notify::{Event, RecommendedWatcher, RecursiveMode, Watcher},
+	tracing::error,
 };
 
-use crate::errors::Result;
+use crate::{database::models::person::normalize_person_name, errors::Result, utils::matchers};
 
-pub fn walkdir_sync<M>(path: &Path, match_fn: M) -> Result<Vec<PathBuf>>
-where
-	M: Fn(&Path) -> bool + Copy,
-{
-	fn one_level(path: &Path, matcher: impl Fn(&Path) -> bool) -> io::Result<(Vec<PathBuf>, Vec<PathBuf>)> {
-		let mut dir = std::fs::read_dir(path)?;
-		let mut files = Vec::new();
-		let mut to_visit = Vec::new();
-
-		while let Some(child) = dir.next().transpose()? {
-			if child.metadata()?.is_dir() {
-				to_visit.push(child.path());
-			} else if matcher(&child.path()) {
-				files.push(child.path());
-			}
-		}
+/// Looks for an artist image in the artist folder of a track.
+///
+/// Follows the `artist/release/track` layout, so the directory above the release directory is checked.
+/// The image is only picked when the name of that directory matches `artist_name`, which keeps
+/// compilations and flat layouts from handing the image of a scan root to a random artist.
+pub fn find_artist_image(track_path: &Path, artist_name: &str) -> Option<PathBuf> {
+	let artist_dir = find_release_dir(track_path)?.parent()?.to_path_buf();
+	let dir_name = artist_dir.file_name()?.to_string_lossy();
+
+	if normalize_person_name(&dir_name) != normalize_person_name(artist_name) {
+		return None;
+	}
 
-		Ok((files, to_visit))
+	crate::constants::ARTIST_IMAGE_FILE_NAMES
+		.iter()
+		.map(|name| artist_dir.join(name))
+		.find(|path| path.is_file())
+}
+
+/// Resolves the directory of the release a track belongs to.
+///
+/// Multi-disc releases are usually split into directories like `CD1`, in which case their parent is used instead.
+pub fn find_release_dir(track_path: &Path) -> Option<PathBuf> {
+	let dir = track_path.parent()?;
+
+	match dir.file_name() {
+		Some(name) if matchers::reg::is_disc_dir(&name.to_string_lossy()) => dir.parent().map(Path::to_path_buf),
+		_ => Some(dir.to_path_buf()),
 	}
+}
+
+/// Copies a directory along with everything in it, creating the destination if it doesn't exist.
+pub fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+	std::fs::create_dir_all(to)?;
 
-	let files = one_level(path, match_fn).and_then(|(mut files, mut to_visit)| {
-		while let Some(path) = to_visit.pop() {
-			let (mut new_files, mut new_to_visit) = one_level(path.as_path(), match_fn)?;
+	for entry in std::fs::read_dir(from)? {
+		let entry = entry?;
+		let target = to.join(entry.file_name());
 
-			files.append(&mut new_files);
-			to_visit.append(&mut new_to_visit);
+		if entry.file_type()?.is_dir() {
+			copy_dir(&entry.path(), &target)?;
+		} else {
+			std::fs::copy(entry.path(), target)?;
 		}
+	}
 
-		Ok(files)
+	Ok(())
+}
+
+/// Watches the direct children of a directory, calling `on_change` with the path of every created or modified file.
+///
+/// Editors tend to replace files instead of writing into them, which is why creations are also reported.
+/// The directory stops being watched once the returned watcher is dropped.
+pub fn watch_dir<F>(path: &Path, on_change: F) -> Result<RecommendedWatcher>
+where
+	F: Fn(&Path) + Send + 'static,
+{
+	let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| match event {
+		Ok(event) if event.kind.is_modify() || event.kind.is_create() => {
+			event.paths.iter().for_each(|x| on_change(x));
+		}
+		Ok(_) => {}
+		Err(e) => error!("Directory watcher returned an error: {e:#?}"),
 	})?;
 
-	Ok(files)
+	watcher.watch(path, RecursiveMode::NonRecursive)?;
+
+	Ok(watcher)
 }
 
 #[cfg(test)]
-mod tests {
-	use std::path::Path;
+mod test {
+	use std::path::{Path, PathBuf};
 
-	use crate::errors::Result;
+	use super::{find_artist_image, find_release_dir};
 
 	#[test]
-	fn test_walk_dir_sync() -> Result<()> {
-		let target = Path::new("./target");
-		let paths = super::walkdir_sync(target, |_| true).unwrap();
+	fn test_find_release_dir() {
+		let release_dir = Some(PathBuf::from("/music/Artist/Release"));
 
-		for path in paths {
-			assert!(path.exists(), "Path {} does not exist", path.display());
-			assert!(path.is_file(), "Path {} is not a file", path.display());
-		}
+		assert_eq!(
+			find_release_dir(Path::new("/music/Artist/Release/01.flac")),
+			release_dir
+		);
+		assert_eq!(
+			find_release_dir(Path::new("/music/Artist/Release/CD2/01.flac")),
+			release_dir
+		);
+		assert_eq!(
+			find_release_dir(Path::new("/music/Artist/Release/Disc 1/01.flac")),
+			release_dir
+		);
+		assert_ne!(
+			find_release_dir(Path::new("/music/Artist/Release/Bonus/01.flac")),
+			release_dir
+		);
+	}
+
+	#[test]
+	fn test_find_artist_image() {
+		let root = std::env::temp_dir().join(format!("melody-artist-image-{}", ulid::Ulid::new()));
+		let release_dir = root.join("Some Artist").join("Release");
+		std::fs::create_dir_all(release_dir.join("CD1")).unwrap();
+		std::fs::write(root.join("Some Artist").join("artist.jpg"), b"").unwrap();
+		std::fs::write(root.join("artist.jpg"), b"").unwrap();
+
+		let image = Some(root.join("Some Artist").join("artist.jpg"));
+		assert_eq!(find_artist_image(&release_dir.join("01.flac"), "some  artist"), image);
+		assert_eq!(
+			find_artist_image(&release_dir.join("CD1").join("01.flac"), "Some Artist"),
+			image
+		);
+		assert_eq!(find_artist_image(&release_dir.join("01.flac"), "Various Artists"), None);
+
+		// Flat layouts put the tracks right under the artist folder, or the scan root.
+		assert_eq!(
+			find_artist_image(&root.join("Some Artist").join("01.flac"), "Some Artist"),
+			None
+		);
 
-		Ok(())
+		std::fs::remove_dir_all(root).unwrap();
 	}
 }
@@ -17,6 +17,31 @@ pub mod reg {
 		YYYY.is_match(source)
 	}
 
+	/// Matches directory names like `CD1`, `Disc 2` or `disk-03`, used to split releases into discs.
+	pub fn is_disc_dir(source: &str) -> bool {
+		static REG: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)^(cd|disc|disk)\s*[-_.]?\s*\d+$").unwrap());
+		REG.is_match(source)
+	}
+
+	/// Finds a catalogue number in the name of a work, like `BWV 1007`, `K. 331` or `Op. 27 No. 2`.
+	pub fn find_catalogue_number(source: &str) -> Option<String> {
+		static REG: Lazy<Regex> = Lazy::new(|| {
+			let catalogues = r"BWV|BuxWV|HWV|TWV|RV|WAB|WoO|KV|K\.|Op\.|D\.|S\.|Sz\.|Hob\.\s*[IVXL]+:";
+			Regex::new(&format!(r"\b(?:{catalogues})\s*\d+[a-z]?(?:\s*,?\s*No\.\s*\d+)?")).unwrap()
+		});
+
+		REG.find(source).map(|x| x.as_str().to_string())
+	}
+
+	/// Finds the key in the name of a work, like `D minor` in `Cello Suite No. 1 in D minor`.
+	pub fn find_musical_key(source: &str) -> Option<String> {
+		static REG: Lazy<Regex> =
+			Lazy::new(|| Regex::new(r"\bin ([A-G](?:[- ]?(?:flat|sharp)|♭|♯)?)[- ](?i:(major|minor))\b").unwrap());
+
+		REG.captures(source)
+			.map(|x| format!("{} {}", &x[1], x[2].to_lowercase()))
+	}
+
 	pub fn is_no_and_total(source: &str) -> bool {
 		static REG: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(\d+)\/(\d+)$").unwrap());
 		REG.is_match(source)
@@ -24,12 +49,46 @@ pub mod reg {
 }
 
 pub mod path {
-	use std::path::Path;
+	use std::{collections::HashSet, fs, path::Path};
+
+	use crate::{constants::NON_AUDIO_EXTENSIONS, ffmpeg, models::configuration::ScannerConfiguration};
+
+	/// Files smaller than this can't hold any audio worth probing, like placeholders or leftovers of failed downloads.
+	const MIN_SNIFF_SIZE: u64 = 1024;
+
+	/// Matches audio files by their extension, probing the contents of files with unknown extensions when enabled.
+	#[derive(Debug, Clone)]
+	pub struct AudioMatcher {
+		extensions: HashSet<String>,
+		sniff: bool,
+	}
+
+	impl AudioMatcher {
+		pub fn new(configuration: &ScannerConfiguration) -> Self {
+			let extensions = configuration
+				.audio_extensions
+				.iter()
+				.map(|x| x.trim_start_matches('.').to_lowercase())
+				.collect();
+
+			Self {
+				extensions,
+				sniff: configuration.sniff_unknown,
+			}
+		}
+
+		pub fn matches(&self, path: &Path) -> bool {
+			let extension = path.extension().map(|x| x.to_string_lossy().to_lowercase());
+
+			match extension {
+				Some(x) if self.extensions.contains(&x) => true,
+				Some(x) if NON_AUDIO_EXTENSIONS.contains(&x.as_str()) => false,
+				_ => self.sniff && is_sniffable(path) && ffmpeg::meta::is_audio(path),
+			}
+		}
+	}
 
-	pub fn audio(path: &Path) -> bool {
-		matches!(
-			path.extension(),
-			Some(extension) if crate::constants::SUPPORTED_AUDIO_EXTENSIONS.contains(&extension.to_str().unwrap())
-		)
+	fn is_sniffable(path: &Path) -> bool {
+		fs::metadata(path).is_ok_and(|x| x.len() >= MIN_SNIFF_SIZE)
 	}
 }
@@ -1,2 +1,4 @@
 pub mod fs;
 pub mod matchers;
+pub mod scan;
+pub mod sort;
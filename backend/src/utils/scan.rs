@@ -0,0 +1,332 @@
+use std::{
+	collections::HashSet,
+	fs,
+	path::{Path, PathBuf},
+	rc::Rc,
+};
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+
+use crate::{database::models::library::ScanOptions, errors::Result};
+
+/// Directories containing this file are skipped along with their children.
+const NO_MEDIA_FILE_NAME: &str = ".nomedia";
+/// Lists glob patterns relative to its directory, skipping the whole directory when there are none.
+const IGNORE_FILE_NAME: &str = ".melodyignore";
+
+/// The compiled [ScanOptions] of a scan location.
+pub struct ScanRules {
+	include: Option<GlobSet>,
+	exclude: GlobSet,
+	options: ScanOptions,
+}
+
+/// Patterns read from a `.melodyignore` file, matched against the paths relative to its directory.
+struct IgnoreFile {
+	base: PathBuf,
+	patterns: GlobSet,
+}
+
+impl ScanRules {
+	pub fn new(options: &ScanOptions) -> Result<Self> {
+		let include = match options.include.is_empty() {
+			true => None,
+			false => Some(build_glob_set(&options.include)?),
+		};
+
+		Ok(Self {
+			include,
+			exclude: build_glob_set(&options.exclude)?,
+			options: options.clone(),
+		})
+	}
+
+	/// Walks through a location, collecting the files that pass both the rules and `match_fn`.
+	///
+	/// Symbolic links are only followed when enabled, and directories reached more than once through them are skipped.
+	pub fn walk<M>(&self, root: &Path, match_fn: M) -> Result<Vec<PathBuf>>
+	where
+		M: Fn(&Path) -> bool,
+	{
+		let mut files = Vec::new();
+		let mut visited = HashSet::<PathBuf>::new();
+		let mut to_visit = vec![(root.to_path_buf(), 0_u32, Vec::<Rc<IgnoreFile>>::new())];
+
+		if self.options.follow_symlinks {
+			visited.insert(fs::canonicalize(root)?);
+		}
+
+		while let Some((dir, depth, mut ignore_files)) = to_visit.pop() {
+			if self.options.ignore_files {
+				if dir.join(NO_MEDIA_FILE_NAME).exists() {
+					continue;
+				}
+
+				let ignore_path = dir.join(IGNORE_FILE_NAME);
+				if ignore_path.is_file() {
+					match read_ignore_file(&ignore_path)? {
+						Some(patterns) => ignore_files.push(Rc::new(IgnoreFile {
+							base: dir.clone(),
+							patterns,
+						})),
+						None => continue,
+					}
+				}
+			}
+
+			for child in fs::read_dir(&dir)? {
+				let child = child?;
+				let path = child.path();
+
+				if self.options.ignore_hidden && child.file_name().to_string_lossy().starts_with('.') {
+					continue;
+				}
+
+				let metadata = match child.file_type()?.is_symlink() {
+					false => child.metadata()?,
+					true if self.options.follow_symlinks => match fs::metadata(&path) {
+						Ok(x) => x,
+						// Broken links are skipped, as there's nothing to read behind them.
+						Err(_) => continue,
+					},
+					true => continue,
+				};
+
+				if self.is_excluded(root, &path, &ignore_files) {
+					continue;
+				}
+
+				if metadata.is_dir() {
+					if self.options.max_depth.is_some_and(|x| depth >= x) {
+						continue;
+					}
+
+					if self.options.follow_symlinks && !visited.insert(fs::canonicalize(&path)?) {
+						continue;
+					}
+
+					to_visit.push((path, depth + 1, ignore_files.clone()));
+				} else if self.is_included(root, &path, metadata.len()) && match_fn(&path) {
+					files.push(path);
+				}
+			}
+		}
+
+		Ok(files)
+	}
+
+	fn is_excluded(&self, root: &Path, path: &Path, ignore_files: &[Rc<IgnoreFile>]) -> bool {
+		self.exclude.is_match(relative_path(root, path))
+			|| ignore_files
+				.iter()
+				.any(|x| x.patterns.is_match(relative_path(&x.base, path)))
+	}
+
+	fn is_included(&self, root: &Path, path: &Path, size: u64) -> bool {
+		let size_matches = self.options.min_file_size.map_or(true, |x| size >= x);
+		let pattern_matches = self
+			.include
+			.as_ref()
+			.map_or(true, |x| x.is_match(relative_path(root, path)));
+
+		size_matches && pattern_matches
+	}
+}
+
+/// Compiles glob patterns, where patterns without a separator match the name of a file or directory at any depth.
+///
+/// Matching is case-insensitive, and a leading separator anchors the pattern to the root.
+fn build_glob_set(patterns: &[String]) -> Result<GlobSet> {
+	let mut builder = GlobSetBuilder::new();
+
+	for pattern in patterns {
+		let pattern = pattern.trim().trim_end_matches('/');
+		let pattern = match pattern.strip_prefix('/') {
+			Some(x) => x.to_string(),
+			None if pattern.contains('/') => pattern.to_string(),
+			None => format!("**/{pattern}"),
+		};
+
+		let glob = GlobBuilder::new(&pattern)
+			.case_insensitive(true)
+			.literal_separator(true)
+			.build()?;
+		builder.add(glob);
+	}
+
+	Ok(builder.build()?)
+}
+
+/// Reads the patterns of an ignore file, skipping blank lines and `#` comments.
+///
+/// Returns none if the file has no patterns, which ignores the directory it resides in.
+fn read_ignore_file(path: &Path) -> Result<Option<GlobSet>> {
+	let contents = fs::read_to_string(path)?;
+	let patterns = contents
+		.lines()
+		.map(str::trim)
+		.filter(|x| !x.is_empty() && !x.starts_with('#'))
+		.map(String::from)
+		.collect::<Vec<String>>();
+
+	if patterns.is_empty() {
+		return Ok(None);
+	}
+
+	build_glob_set(&patterns).map(Some)
+}
+
+/// Joins the components of a path relative to `base` with forward slashes, so patterns work the same on every system.
+fn relative_path(base: &Path, path: &Path) -> String {
+	let relative = path.strip_prefix(base).unwrap_or(path);
+	let components = relative
+		.components()
+		.map(|x| x.as_os_str().to_string_lossy())
+		.collect::<Vec<_>>();
+
+	components.join("/")
+}
+
+#[cfg(test)]
+mod test {
+	use std::{fs, path::Path};
+
+	use crate::{
+		database::models::library::ScanOptions,
+		errors::Result,
+		models::configuration::ScannerConfiguration,
+		utils::{matchers::path::AudioMatcher, scan::ScanRules},
+	};
+
+	fn create_file(root: &Path, path: &str, size: usize) {
+		let path = root.join(path);
+		fs::create_dir_all(path.parent().unwrap()).unwrap();
+		fs::write(path, vec![0; size]).unwrap();
+	}
+
+	fn walk(root: &Path, options: ScanOptions) -> Result<Vec<String>> {
+		let rules = ScanRules::new(&options)?;
+		let matcher = AudioMatcher::new(&ScannerConfiguration {
+			sniff_unknown: false,
+			..Default::default()
+		});
+
+		let mut paths = rules
+			.walk(root, |x| matcher.matches(x))?
+			.into_iter()
+			.map(|x| super::relative_path(root, &x))
+			.collect::<Vec<String>>();
+
+		paths.sort();
+		Ok(paths)
+	}
+
+	#[test]
+	fn test_walk_permissive() -> Result<()> {
+		let options = ScanOptions {
+			ignore_hidden: false,
+			ignore_files: false,
+			..Default::default()
+		};
+
+		let target = Path::new("./target");
+		let paths = ScanRules::new(&options)?.walk(target, |_| true)?;
+
+		for path in paths {
+			assert!(path.exists(), "Path {} does not exist", path.display());
+			assert!(path.is_file(), "Path {} is not a file", path.display());
+		}
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_walk() -> Result<()> {
+		let root = std::env::temp_dir().join(format!("melody-scan-{}", ulid::Ulid::new()));
+
+		create_file(&root, "Artist/Release/01 Track.flac", 64);
+		create_file(&root, "Artist/Release/cover.jpg", 64);
+		create_file(&root, "Artist/Release/@eaDir/01 Track.flac", 64);
+		create_file(&root, "Artist/.hidden/01 Track.flac", 64);
+		create_file(&root, "Samples/kick.wav", 8);
+		create_file(&root, "Samples/.nomedia", 0);
+		create_file(&root, "Other/loop.wav", 8);
+		create_file(&root, "Other/Live/01 Track.mp3", 64);
+		create_file(&root, "Other/.melodyignore", 0);
+		create_file(&root, "Mixed/Keep.flac", 64);
+		create_file(&root, "Mixed/Drop.flac", 64);
+		fs::write(root.join("Mixed/.melodyignore"), "# Comment\ndrop.flac\n").unwrap();
+
+		let default = walk(&root, ScanOptions::default())?;
+		assert_eq!(
+			default,
+			vec![
+				"Artist/Release/01 Track.flac",
+				"Artist/Release/@eaDir/01 Track.flac",
+				"Mixed/Keep.flac",
+			]
+		);
+
+		let options = ScanOptions {
+			exclude: vec!["@eaDir".to_string()],
+			ignore_hidden: false,
+			ignore_files: false,
+			min_file_size: Some(16),
+			..Default::default()
+		};
+		let paths = walk(&root, options)?;
+		assert_eq!(
+			paths,
+			vec![
+				"Artist/.hidden/01 Track.flac",
+				"Artist/Release/01 Track.flac",
+				"Mixed/Drop.flac",
+				"Mixed/Keep.flac",
+				"Other/Live/01 Track.mp3",
+			]
+		);
+
+		let options = ScanOptions {
+			include: vec!["/Artist/**/*.FLAC".to_string()],
+			max_depth: Some(2),
+			..Default::default()
+		};
+		assert_eq!(walk(&root, options)?, vec!["Artist/Release/01 Track.flac"]);
+
+		fs::remove_dir_all(&root)?;
+		Ok(())
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn test_walk_symlinks() -> Result<()> {
+		let root = std::env::temp_dir().join(format!("melody-scan-{}", ulid::Ulid::new()));
+
+		create_file(&root, "Artist/Release/01 Track.flac", 64);
+		std::os::unix::fs::symlink(&root, root.join("Artist/Loop")).unwrap();
+		std::os::unix::fs::symlink(root.join("Artist"), root.join("Link")).unwrap();
+
+		let paths = walk(&root, ScanOptions::default())?;
+		assert_eq!(paths, vec!["Artist/Release/01 Track.flac"]);
+
+		let options = ScanOptions {
+			follow_symlinks: true,
+			..Default::default()
+		};
+		let paths = walk(&root, options)?;
+		assert_eq!(paths.len(), 1, "Directories reached twice should be skipped: {paths:?}");
+
+		fs::remove_dir_all(&root)?;
+		Ok(())
+	}
+
+	#[test]
+	fn test_invalid_pattern() {
+		let options = ScanOptions {
+			exclude: vec!["[".to_string()],
+			..Default::default()
+		};
+
+		assert!(ScanRules::new(&options).is_err());
+	}
+}
@@ -0,0 +1,69 @@
+use std::cmp::Ordering;
+
+/// Compares two strings while treating runs of digits as numbers.
+///
+/// ### Example
+/// ```text
+/// "VICL-9" < "VICL-10"
+/// "Disc 2" < "Disc 11"
+/// ```
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+	let mut a = a.chars().peekable();
+	let mut b = b.chars().peekable();
+
+	loop {
+		match (a.peek().copied(), b.peek().copied()) {
+			(None, None) => return Ordering::Equal,
+			(None, Some(_)) => return Ordering::Less,
+			(Some(_), None) => return Ordering::Greater,
+			(Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+				let x = take_number(&mut a);
+				let y = take_number(&mut b);
+
+				// Compare by length first to avoid overflowing on absurdly long numbers.
+				let order = x.len().cmp(&y.len()).then_with(|| x.cmp(&y));
+				if order != Ordering::Equal {
+					return order;
+				}
+			}
+			(Some(x), Some(y)) => {
+				let order = x.to_lowercase().cmp(y.to_lowercase());
+				if order != Ordering::Equal {
+					return order;
+				}
+
+				a.next();
+				b.next();
+			}
+		}
+	}
+}
+
+/// Consumes a run of digits, stripping its leading zeroes.
+fn take_number(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) -> String {
+	let mut number = String::new();
+
+	while let Some(c) = chars.next_if(|c| c.is_ascii_digit()) {
+		if !(number.is_empty() && c == '0') {
+			number.push(c);
+		}
+	}
+
+	number
+}
+
+#[cfg(test)]
+mod tests {
+	use std::cmp::Ordering;
+
+	use super::natural_cmp;
+
+	#[test]
+	fn test_natural_cmp() {
+		assert_eq!(natural_cmp("VICL-9", "VICL-10"), Ordering::Less);
+		assert_eq!(natural_cmp("vicl-10", "VICL-10"), Ordering::Equal);
+		assert_eq!(natural_cmp("VICL-010", "VICL-10"), Ordering::Equal);
+		assert_eq!(natural_cmp("ABC", "ABCD"), Ordering::Less);
+		assert_eq!(natural_cmp("B-1", "A-2"), Ordering::Greater);
+	}
+}